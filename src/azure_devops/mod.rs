@@ -0,0 +1,340 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::Deserialize;
+use tracing::{debug, info};
+
+use crate::core::{PrStatus, ReviewDecision};
+use crate::forge::{Forge, HttpAuth, PrAuthor, PrInfo, http_get};
+
+/// Root of the Azure DevOps Services REST API, followed by the organization
+const API_ROOT: &str = "https://dev.azure.com";
+
+/// REST API version gwm speaks
+const API_VERSION: &str = "7.1";
+
+/// Environment variable with the personal access token unless the config names another,
+/// shared with the Azure CLI's devops extension
+const DEFAULT_TOKEN_ENV: &str = "AZURE_DEVOPS_EXT_PAT";
+
+/// Pull requests per branch to consider, newest first
+const PRS_PER_BRANCH: usize = 10;
+
+/// Settings for Azure DevOps, as the `[azure_devops]` table in the config file
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AzureDevOpsConfig {
+    /// Environment variable holding a personal access token, AZURE_DEVOPS_EXT_PAT when unset
+    pub token_env: Option<String>,
+}
+
+impl AzureDevOpsConfig {
+    fn token_env(&self) -> &str {
+        self.token_env.as_deref().unwrap_or(DEFAULT_TOKEN_ENV)
+    }
+
+    /// Whether the token is set
+    pub fn any_available(&self) -> bool {
+        std::env::var(self.token_env()).is_ok()
+    }
+
+    /// A client for the repository a remote URL points at, or None when it isn't on Azure DevOps
+    pub fn client_for(&self, remote_url: &str) -> Option<Result<AzureDevOpsClient>> {
+        let repo = parse_azure_devops_url(remote_url)?;
+        let token_env = self.token_env();
+        let client = std::env::var(token_env)
+            .map_err(|_| {
+                anyhow!(
+                    "{} environment variable not set (needed for {})",
+                    token_env,
+                    repo.organization
+                )
+            })
+            .map(|token| AzureDevOpsClient { repo, token });
+        Some(client)
+    }
+}
+
+/// A Git repository in an Azure DevOps project
+/// Names stay percent-encoded as they appear in remote URLs, ready for API paths
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AzureDevOpsRepo {
+    pub organization: String,
+    pub project: String,
+    pub repo: String,
+}
+
+/// Parse an Azure DevOps remote URL into organization, project and repository
+/// Handles HTTPS (https://dev.azure.com/org/project/_git/repo), SSH
+/// (git@ssh.dev.azure.com:v3/org/project/repo) and the older visualstudio.com forms of both
+pub fn parse_azure_devops_url(url: &str) -> Option<AzureDevOpsRepo> {
+    let patterns = [
+        r"^https://(?:[^@/]+@)?dev\.azure\.com/([^/]+)/([^/]+)/_git/([^/]+?)/?$",
+        r"^https://(?:[^@/]+@)?([^./]+)\.visualstudio\.com/(?:DefaultCollection/)?([^/]+)/_git/([^/]+?)/?$",
+        r"^(?:ssh://)?[^@/]+@(?:ssh\.dev\.azure\.com|vs-ssh\.visualstudio\.com)(?::22)?[:/]v3/([^/]+)/([^/]+)/([^/]+?)/?$",
+    ];
+    patterns
+        .iter()
+        .filter_map(|pattern| Regex::new(pattern).ok())
+        .find_map(|regex| regex.captures(url))
+        .map(|captures| AzureDevOpsRepo {
+            organization: captures[1].to_string(),
+            project: captures[2].to_string(),
+            repo: captures[3].to_string(),
+        })
+}
+
+/// Client for one repository on Azure DevOps Services, talking to the REST API through curl
+pub struct AzureDevOpsClient {
+    repo: AzureDevOpsRepo,
+    /// Personal access token
+    token: String,
+}
+
+impl AzureDevOpsClient {
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T> {
+        // Personal access tokens go in basic authentication with an empty user name
+        let credentials = format!(":{}", self.token);
+        let body = http_get(url, HttpAuth::Basic(&credentials)).await?;
+        serde_json::from_str(&body).map_err(|e| anyhow!("Unexpected Azure DevOps response: {}", e))
+    }
+
+    /// Web address of the repository, under which pull requests live
+    fn web_url(&self) -> String {
+        format!(
+            "{}/{}/{}/_git/{}",
+            API_ROOT, self.repo.organization, self.repo.project, self.repo.repo
+        )
+    }
+}
+
+impl Forge for AzureDevOpsClient {
+    async fn fetch_prs(
+        &self,
+        branches: &[String],
+        since_timestamp: i64,
+        author: PrAuthor,
+    ) -> Result<Vec<PrInfo>> {
+        let start_time = std::time::Instant::now();
+
+        let viewer = self
+            .get_json::<ConnectionData>(&format!(
+                "{}/{}/_apis/connectionData?api-version={}-preview",
+                API_ROOT, self.repo.organization, API_VERSION
+            ))
+            .await;
+        let viewer = match viewer {
+            Ok(data) => Some(data.authenticated_user.id),
+            Err(e) if author == PrAuthor::Any => {
+                debug!("No Azure DevOps user for the token: {}", e);
+                None
+            }
+            Err(e) => return Err(anyhow!("Failed to look up the Azure DevOps user: {}", e)),
+        };
+
+        // The API filters by one source branch at a time
+        let pages = futures::future::try_join_all(branches.iter().map(|branch| async move {
+            let url = format!(
+                "{}/{}/{}/_apis/git/repositories/{}/pullrequests?searchCriteria.status=all\
+                 &searchCriteria.sourceRefName={}&$top={}&api-version={}",
+                API_ROOT,
+                self.repo.organization,
+                self.repo.project,
+                self.repo.repo,
+                urlencoding::encode(&format!("refs/heads/{}", branch)),
+                PRS_PER_BRANCH,
+                API_VERSION
+            );
+            self.get_json::<PullRequestList>(&url).await
+        }))
+        .await?;
+
+        let web_url = self.web_url();
+        let prs: Vec<PrInfo> = pages
+            .into_iter()
+            .flat_map(|page| page.value)
+            .filter(|pr| pr.keep(viewer.as_deref(), since_timestamp, author))
+            .map(|pr| pr.into_pr_info(&web_url))
+            .collect();
+
+        info!(
+            "Azure DevOps lookup completed in {:?}, found {} PRs for {}/{}",
+            start_time.elapsed(),
+            prs.len(),
+            self.repo.project,
+            self.repo.repo
+        );
+        Ok(prs)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConnectionData {
+    authenticated_user: Identity,
+}
+
+#[derive(Debug, Deserialize)]
+struct Identity {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PullRequestList {
+    value: Vec<PullRequest>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PullRequest {
+    pull_request_id: u64,
+    /// active, completed or abandoned
+    status: String,
+    #[serde(default)]
+    is_draft: bool,
+    creation_date: DateTime<Utc>,
+    closed_date: Option<DateTime<Utc>>,
+    /// e.g. refs/heads/fix-login
+    source_ref_name: String,
+    created_by: Identity,
+    #[serde(default)]
+    reviewers: Vec<Reviewer>,
+    /// Set when the PR comes from a fork
+    fork_source: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Reviewer {
+    /// 10 approved, 5 approved with suggestions, 0 no vote, -5 waiting for author, -10 rejected
+    vote: i32,
+    #[serde(default)]
+    is_required: bool,
+}
+
+impl PullRequest {
+    /// Whether the PR counts for `author`, with the same rules as the GitHub lookup
+    /// Azure DevOps keeps no update time, so anyone's PRs count while open or when closed since
+    fn keep(&self, viewer: Option<&str>, since_timestamp: i64, author: PrAuthor) -> bool {
+        let by_viewer = viewer.is_some_and(|id| id.eq_ignore_ascii_case(&self.created_by.id));
+        match author {
+            PrAuthor::Me => by_viewer && self.creation_date.timestamp() >= since_timestamp,
+            PrAuthor::Any => {
+                let recent = match self.closed_at() {
+                    Some(closed_at) => closed_at >= since_timestamp,
+                    None => true,
+                };
+                (by_viewer || self.fork_source.is_none()) && recent
+            }
+        }
+    }
+
+    /// Active PRs report the zero time, or nothing, as their closing date
+    fn closed_at(&self) -> Option<i64> {
+        self.closed_date
+            .map(|t| t.timestamp())
+            .filter(|&timestamp| timestamp > 0)
+    }
+
+    fn into_pr_info(self, web_url: &str) -> PrInfo {
+        let status = match self.status.as_str() {
+            "completed" => PrStatus::Merged,
+            "active" if self.is_draft => PrStatus::Draft,
+            "active" => PrStatus::Open,
+            _ => PrStatus::Closed,
+        };
+        let review = if self.reviewers.iter().any(|r| r.vote < 0) {
+            Some(ReviewDecision::ChangesRequested)
+        } else if self.reviewers.iter().any(|r| r.vote > 0) {
+            Some(ReviewDecision::Approved)
+        } else if self.reviewers.iter().any(|r| r.is_required) {
+            Some(ReviewDecision::ReviewRequired)
+        } else {
+            None
+        };
+        PrInfo {
+            number: self.pull_request_id,
+            head_branch: self
+                .source_ref_name
+                .strip_prefix("refs/heads/")
+                .unwrap_or(&self.source_ref_name)
+                .to_string(),
+            status,
+            closed_at: self.closed_at(),
+            url: Some(format!("{}/pullrequest/{}", web_url, self.pull_request_id)),
+            review,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_azure_devops_urls() {
+        let expected = AzureDevOpsRepo {
+            organization: "acme".to_string(),
+            project: "Platform".to_string(),
+            repo: "api".to_string(),
+        };
+        for url in [
+            "https://dev.azure.com/acme/Platform/_git/api",
+            "https://acme@dev.azure.com/acme/Platform/_git/api",
+            "git@ssh.dev.azure.com:v3/acme/Platform/api",
+            "ssh://git@ssh.dev.azure.com/v3/acme/Platform/api",
+            "https://acme.visualstudio.com/Platform/_git/api",
+            "https://acme.visualstudio.com/DefaultCollection/Platform/_git/api",
+            "acme@vs-ssh.visualstudio.com:v3/acme/Platform/api",
+        ] {
+            assert_eq!(
+                parse_azure_devops_url(url),
+                Some(expected.clone()),
+                "{}",
+                url
+            );
+        }
+        assert_eq!(parse_azure_devops_url("git@github.com:acme/api.git"), None);
+    }
+
+    #[test]
+    fn reads_pull_requests() {
+        let json = r#"{"value": [
+            {"pullRequestId": 42, "status": "active", "isDraft": false,
+             "creationDate": "2026-10-01T12:00:00.1234567Z", "closedDate": "0001-01-01T00:00:00Z",
+             "sourceRefName": "refs/heads/fix-login", "createdBy": {"id": "ME"},
+             "reviewers": [{"vote": 10}, {"vote": 0, "isRequired": true}]},
+            {"pullRequestId": 41, "status": "abandoned",
+             "creationDate": "2026-09-01T12:00:00Z", "closedDate": "2026-09-02T12:00:00Z",
+             "sourceRefName": "refs/heads/fix-login", "createdBy": {"id": "someone"},
+             "reviewers": [{"vote": -5}], "forkSource": {"name": "refs/heads/fix-login"}}
+        ]}"#;
+        let list: PullRequestList = serde_json::from_str(json).unwrap();
+        let since = 1790812800; // 2026-10-01T00:00:00Z
+
+        assert!(list.value[0].keep(Some("me"), since, PrAuthor::Me));
+        assert!(list.value[0].keep(None, since, PrAuthor::Any));
+        assert!(!list.value[1].keep(Some("someone"), since, PrAuthor::Me));
+        // From someone else's fork, and closed before the oldest worktree
+        assert!(!list.value[1].keep(Some("me"), 0, PrAuthor::Any));
+        assert!(list.value[1].keep(Some("someone"), 0, PrAuthor::Any));
+        assert!(!list.value[1].keep(Some("someone"), since, PrAuthor::Any));
+
+        let web_url = "https://dev.azure.com/acme/Platform/_git/api";
+        let prs: Vec<PrInfo> = list
+            .value
+            .into_iter()
+            .map(|pr| pr.into_pr_info(web_url))
+            .collect();
+        assert_eq!(prs[0].head_branch, "fix-login");
+        assert_eq!(prs[0].status, PrStatus::Open);
+        assert_eq!(prs[0].closed_at, None);
+        assert_eq!(prs[0].review, Some(ReviewDecision::Approved));
+        assert_eq!(
+            prs[0].url.as_deref(),
+            Some("https://dev.azure.com/acme/Platform/_git/api/pullrequest/42")
+        );
+        assert_eq!(prs[1].status, PrStatus::Closed);
+        assert_eq!(prs[1].review, Some(ReviewDecision::ChangesRequested));
+    }
+}
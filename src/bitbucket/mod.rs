@@ -0,0 +1,683 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use tracing::{debug, info};
+
+use crate::core::{PrStatus, ReviewDecision};
+use crate::forge::{Forge, HttpAuth, PrAuthor, PrInfo, http_get};
+
+/// Host of Bitbucket Cloud
+pub const BITBUCKET_CLOUD_HOST: &str = "bitbucket.org";
+
+/// Root of the Bitbucket Cloud REST API
+const CLOUD_API: &str = "https://api.bitbucket.org/2.0";
+
+/// Environment variable with the bitbucket.org token unless the config names another
+const DEFAULT_TOKEN_ENV: &str = "BITBUCKET_TOKEN";
+
+/// Branches looked up per Bitbucket Cloud query, to keep the URL short
+const BRANCHES_PER_QUERY: usize = 20;
+
+/// Pull requests per branch to consider on Bitbucket Server, newest first
+const PRS_PER_BRANCH: usize = 10;
+
+/// Fields of Bitbucket Cloud pull requests gwm reads, so participants come along in the list
+const CLOUD_FIELDS: &str = "next,values.id,values.state,values.draft,values.created_on,\
+values.updated_on,values.source.branch.name,values.source.repository.full_name,\
+values.destination.repository.full_name,values.author.account_id,values.links.html.href,\
+values.participants.state";
+
+/// Settings for Bitbucket, as the `[bitbucket]` table in the config file
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BitbucketConfig {
+    /// Environment variable holding the bitbucket.org token, BITBUCKET_TOKEN when unset
+    pub token_env: Option<String>,
+    /// Self-hosted Bitbucket Server or Data Center instances, as `[[bitbucket.servers]]` tables
+    pub servers: Vec<ServerConfig>,
+}
+
+/// A Bitbucket Server or Data Center instance
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ServerConfig {
+    /// Host name in remote URLs, e.g. "git.example.com"
+    pub host: String,
+    /// Base URL of the web interface, when it isn't https://<host>
+    pub url: Option<String>,
+    /// Environment variable holding an HTTP access token
+    pub token_env: String,
+}
+
+impl BitbucketConfig {
+    fn cloud_token_env(&self) -> &str {
+        self.token_env.as_deref().unwrap_or(DEFAULT_TOKEN_ENV)
+    }
+
+    /// Whether any Bitbucket token is set at all
+    pub fn any_available(&self) -> bool {
+        std::iter::once(self.cloud_token_env())
+            .chain(self.servers.iter().map(|server| server.token_env.as_str()))
+            .any(|name| std::env::var(name).is_ok())
+    }
+
+    /// Parse a remote URL on bitbucket.org or a configured server
+    pub fn parse_url(&self, remote_url: &str) -> Option<BitbucketRepo> {
+        parse_bitbucket_url(remote_url).filter(|repo| {
+            repo.host == BITBUCKET_CLOUD_HOST || self.servers.iter().any(|s| s.host == repo.host)
+        })
+    }
+
+    /// A client for the repository a remote URL points at, or None when it isn't on
+    /// bitbucket.org or a configured server
+    pub fn client_for(&self, remote_url: &str) -> Option<Result<BitbucketClient>> {
+        let repo = self.parse_url(remote_url)?;
+        let (flavor, token_env) = if repo.host == BITBUCKET_CLOUD_HOST {
+            (Flavor::Cloud, self.cloud_token_env())
+        } else {
+            let server = self.servers.iter().find(|s| s.host == repo.host)?;
+            let base_url = server
+                .url
+                .clone()
+                .unwrap_or_else(|| format!("https://{}", server.host));
+            let base_url = base_url.trim_end_matches('/').to_string();
+            (Flavor::Server { base_url }, server.token_env.as_str())
+        };
+
+        let client = std::env::var(token_env)
+            .map_err(|_| {
+                anyhow!(
+                    "{} environment variable not set (needed for {})",
+                    token_env,
+                    repo.host
+                )
+            })
+            .map(|token| BitbucketClient {
+                repo,
+                flavor,
+                token,
+            });
+        Some(client)
+    }
+}
+
+/// A repository on Bitbucket: its workspace (Cloud) or project key (Server), and its slug
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitbucketRepo {
+    pub host: String,
+    pub owner: String,
+    pub slug: String,
+}
+
+/// Parse a remote URL into host, owner and slug
+/// Handles Cloud remotes (git@bitbucket.org:workspace/repo.git, https://user@bitbucket.org/
+/// workspace/repo.git) and Server ones (ssh://git@host:7999/proj/repo.git,
+/// https://host/scm/proj/repo.git, possibly below a context path)
+pub fn parse_bitbucket_url(url: &str) -> Option<BitbucketRepo> {
+    let regex =
+        Regex::new(r"^(?:(?:ssh|https?)://)?(?:[^@/]+@)?([^/:]+)(?::\d+)?[:/](.+?)(?:\.git)?/?$")
+            .ok()?;
+    let captures = regex.captures(url)?;
+    let segments: Vec<&str> = captures[2].split('/').collect();
+    // Server serves HTTPS clones from /scm/<project>/<repo>
+    let (owner, slug) = match segments.iter().position(|&segment| segment == "scm") {
+        Some(index) if segments.len() == index + 3 => (segments[index + 1], segments[index + 2]),
+        _ => match segments.as_slice() {
+            [.., owner, slug] => (*owner, *slug),
+            _ => return None,
+        },
+    };
+    Some(BitbucketRepo {
+        host: captures[1].to_string(),
+        owner: owner.to_string(),
+        slug: slug.to_string(),
+    })
+}
+
+enum Flavor {
+    Cloud,
+    Server {
+        /// Web interface root, below which /rest/api/1.0 lives
+        base_url: String,
+    },
+}
+
+/// Client for one repository on Bitbucket Cloud or Server, talking to the REST API through curl
+pub struct BitbucketClient {
+    repo: BitbucketRepo,
+    flavor: Flavor,
+    /// An access token, or `username:app password` for Cloud
+    token: String,
+}
+
+impl BitbucketClient {
+    async fn get(&self, url: &str) -> Result<String> {
+        let auth = if self.token.contains(':') {
+            HttpAuth::Basic(&self.token)
+        } else {
+            HttpAuth::Bearer(&self.token)
+        };
+        http_get(url, auth).await
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T> {
+        let body = self.get(url).await?;
+        serde_json::from_str(&body).map_err(|e| anyhow!("Unexpected Bitbucket response: {}", e))
+    }
+
+    async fn fetch_cloud_prs(
+        &self,
+        branches: &[String],
+        since_timestamp: i64,
+        author: PrAuthor,
+    ) -> Result<Vec<PrInfo>> {
+        let viewer = match self
+            .get_json::<CloudUser>(&format!("{}/user", CLOUD_API))
+            .await
+        {
+            Ok(user) => user.account_id,
+            // Repository and workspace access tokens don't belong to a user
+            Err(e) if author == PrAuthor::Any => {
+                debug!("No Bitbucket user for the token: {}", e);
+                None
+            }
+            Err(e) => return Err(anyhow!("Failed to look up the Bitbucket user: {}", e)),
+        };
+        let since_date = DateTime::from_timestamp(since_timestamp, 0)
+            .ok_or_else(|| anyhow!("Invalid timestamp: {}", since_timestamp))?
+            .format("%Y-%m-%d")
+            .to_string();
+
+        let mut prs = Vec::new();
+        for chunk in branches.chunks(BRANCHES_PER_QUERY) {
+            let mut next = Some(format!(
+                "{}/repositories/{}/{}/pullrequests?state=OPEN&state=MERGED&state=DECLINED\
+                 &state=SUPERSEDED&sort=-updated_on&pagelen=50&fields={}&q={}",
+                CLOUD_API,
+                self.repo.owner,
+                self.repo.slug,
+                urlencoding::encode(CLOUD_FIELDS),
+                urlencoding::encode(&cloud_query(chunk, &since_date))
+            ));
+            while let Some(url) = next {
+                let page: CloudPage = self.get_json(&url).await?;
+                prs.extend(
+                    page.values
+                        .into_iter()
+                        .filter(|pr| pr.keep(viewer.as_deref(), since_timestamp, author))
+                        .map(CloudPullRequest::into_pr_info),
+                );
+                next = page.next;
+            }
+        }
+        Ok(prs)
+    }
+
+    async fn fetch_server_prs(
+        &self,
+        base_url: &str,
+        branches: &[String],
+        since_timestamp: i64,
+        author: PrAuthor,
+    ) -> Result<Vec<PrInfo>> {
+        let viewer = match self
+            .get(&format!("{}/plugins/servlet/applinks/whoami", base_url))
+            .await
+        {
+            Ok(name) => Some(name.trim().to_string()).filter(|name| !name.is_empty()),
+            Err(e) if author == PrAuthor::Any => {
+                debug!("No Bitbucket user for the token: {}", e);
+                None
+            }
+            Err(e) => return Err(anyhow!("Failed to look up the Bitbucket user: {}", e)),
+        };
+        if author == PrAuthor::Me && viewer.is_none() {
+            return Err(anyhow!("The Bitbucket token doesn't belong to a user"));
+        }
+
+        // Server filters by one source branch at a time
+        let pages = futures::future::try_join_all(branches.iter().map(|branch| async move {
+            let url = format!(
+                "{}/rest/api/1.0/projects/{}/repos/{}/pull-requests?state=ALL&direction=OUTGOING\
+                 &order=NEWEST&limit={}&at={}",
+                base_url,
+                self.repo.owner,
+                self.repo.slug,
+                PRS_PER_BRANCH,
+                urlencoding::encode(&format!("refs/heads/{}", branch))
+            );
+            self.get_json::<ServerPage>(&url).await
+        }))
+        .await?;
+
+        Ok(pages
+            .into_iter()
+            .flat_map(|page| page.values)
+            .filter(|pr| pr.keep(viewer.as_deref(), since_timestamp, author))
+            .map(ServerPullRequest::into_pr_info)
+            .collect())
+    }
+}
+
+impl Forge for BitbucketClient {
+    async fn fetch_prs(
+        &self,
+        branches: &[String],
+        since_timestamp: i64,
+        author: PrAuthor,
+    ) -> Result<Vec<PrInfo>> {
+        let start_time = std::time::Instant::now();
+
+        let prs = match &self.flavor {
+            Flavor::Cloud => {
+                self.fetch_cloud_prs(branches, since_timestamp, author)
+                    .await?
+            }
+            Flavor::Server { base_url } => {
+                self.fetch_server_prs(base_url, branches, since_timestamp, author)
+                    .await?
+            }
+        };
+
+        info!(
+            "Bitbucket lookup completed in {:?}, found {} PRs for {}/{}",
+            start_time.elapsed(),
+            prs.len(),
+            self.repo.owner,
+            self.repo.slug
+        );
+        Ok(prs)
+    }
+}
+
+/// Bitbucket Cloud query for the PRs from `branches` updated since `since_date`
+fn cloud_query(branches: &[String], since_date: &str) -> String {
+    let branches: Vec<String> = branches
+        .iter()
+        .map(|branch| {
+            format!(
+                "source.branch.name = \"{}\"",
+                branch.replace('\\', "\\\\").replace('"', "\\\"")
+            )
+        })
+        .collect();
+    format!(
+        "({}) AND updated_on >= {}",
+        branches.join(" OR "),
+        since_date
+    )
+}
+
+/// PR status from Bitbucket's state name (OPEN, MERGED, DECLINED or SUPERSEDED) and draft flag
+fn pr_status(state: &str, draft: bool) -> PrStatus {
+    match state {
+        "MERGED" => PrStatus::Merged,
+        "OPEN" if draft => PrStatus::Draft,
+        "OPEN" => PrStatus::Open,
+        _ => PrStatus::Closed,
+    }
+}
+
+/// Review decision from the reviewers' states; one request for changes outweighs any approvals
+fn review_decision<'a>(states: impl Iterator<Item = &'a str>) -> Option<ReviewDecision> {
+    let states: Vec<&str> = states.collect();
+    if states
+        .iter()
+        .any(|state| matches!(*state, "changes_requested" | "NEEDS_WORK"))
+    {
+        Some(ReviewDecision::ChangesRequested)
+    } else if states
+        .iter()
+        .any(|state| matches!(*state, "approved" | "APPROVED"))
+    {
+        Some(ReviewDecision::Approved)
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CloudUser {
+    #[serde(default)]
+    account_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CloudPage {
+    values: Vec<CloudPullRequest>,
+    next: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CloudPullRequest {
+    id: u64,
+    state: String,
+    #[serde(default)]
+    draft: bool,
+    created_on: DateTime<Utc>,
+    updated_on: DateTime<Utc>,
+    source: CloudEndpoint,
+    destination: CloudEndpoint,
+    author: Option<CloudUser>,
+    links: CloudLinks,
+    #[serde(default)]
+    participants: Vec<CloudParticipant>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CloudEndpoint {
+    branch: Option<CloudBranch>,
+    /// Missing when the fork the PR came from was deleted
+    repository: Option<CloudRepository>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CloudBranch {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CloudRepository {
+    full_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CloudLinks {
+    html: CloudLink,
+}
+
+#[derive(Debug, Deserialize)]
+struct CloudLink {
+    href: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CloudParticipant {
+    /// "approved", "changes_requested", or null for no review yet
+    state: Option<String>,
+}
+
+impl CloudPullRequest {
+    /// Whether the PR counts for `author`, with the same rules as the GitHub lookup
+    fn keep(&self, viewer: Option<&str>, since_timestamp: i64, author: PrAuthor) -> bool {
+        let by_viewer = viewer.is_some()
+            && self.author.as_ref().and_then(|a| a.account_id.as_deref()) == viewer;
+        let repository = |endpoint: &CloudEndpoint| {
+            endpoint
+                .repository
+                .as_ref()
+                .map(|r| r.full_name.to_lowercase())
+        };
+        let cross_repository = repository(&self.source) != repository(&self.destination);
+        match author {
+            PrAuthor::Me => by_viewer && self.created_on.timestamp() >= since_timestamp,
+            PrAuthor::Any => {
+                (by_viewer || !cross_repository) && self.updated_on.timestamp() >= since_timestamp
+            }
+        }
+    }
+
+    fn into_pr_info(self) -> PrInfo {
+        let status = pr_status(&self.state, self.draft);
+        PrInfo {
+            number: self.id,
+            head_branch: self.source.branch.map(|b| b.name).unwrap_or_default(),
+            // Cloud lists no closing time; a closed PR's last update is usually its closing
+            closed_at: matches!(status, PrStatus::Merged | PrStatus::Closed)
+                .then(|| self.updated_on.timestamp()),
+            url: Some(self.links.html.href),
+            review: review_decision(self.participants.iter().filter_map(|p| p.state.as_deref())),
+            status,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ServerPage {
+    values: Vec<ServerPullRequest>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ServerPullRequest {
+    id: u64,
+    /// OPEN, MERGED or DECLINED
+    state: String,
+    #[serde(default)]
+    draft: bool,
+    /// Milliseconds since the epoch, like the other dates
+    created_date: i64,
+    updated_date: i64,
+    closed_date: Option<i64>,
+    from_ref: ServerRef,
+    to_ref: ServerRef,
+    author: ServerParticipant,
+    #[serde(default)]
+    reviewers: Vec<ServerParticipant>,
+    links: ServerLinks,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ServerRef {
+    display_id: String,
+    repository: ServerRepository,
+}
+
+#[derive(Debug, Deserialize)]
+struct ServerRepository {
+    id: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ServerParticipant {
+    user: ServerUser,
+    /// APPROVED, NEEDS_WORK or UNAPPROVED
+    status: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ServerUser {
+    /// Username, as whoami reports it
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ServerLinks {
+    #[serde(rename = "self", default)]
+    self_links: Vec<CloudLink>,
+}
+
+impl ServerPullRequest {
+    /// Whether the PR counts for `author`, with the same rules as the GitHub lookup
+    fn keep(&self, viewer: Option<&str>, since_timestamp: i64, author: PrAuthor) -> bool {
+        let by_viewer = viewer.is_some_and(|name| name == self.author.user.name);
+        let cross_repository = self.from_ref.repository.id != self.to_ref.repository.id;
+        match author {
+            PrAuthor::Me => by_viewer && self.created_date / 1000 >= since_timestamp,
+            PrAuthor::Any => {
+                (by_viewer || !cross_repository) && self.updated_date / 1000 >= since_timestamp
+            }
+        }
+    }
+
+    fn into_pr_info(self) -> PrInfo {
+        let review = review_decision(self.reviewers.iter().filter_map(|r| r.status.as_deref()))
+            .or((!self.reviewers.is_empty()).then_some(ReviewDecision::ReviewRequired));
+        PrInfo {
+            number: self.id,
+            head_branch: self.from_ref.display_id,
+            status: pr_status(&self.state, self.draft),
+            closed_at: self.closed_date.map(|millis| millis / 1000),
+            url: self
+                .links
+                .self_links
+                .into_iter()
+                .next()
+                .map(|link| link.href),
+            review,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_bitbucket_urls() {
+        let cloud = |owner: &str, slug: &str| BitbucketRepo {
+            host: BITBUCKET_CLOUD_HOST.to_string(),
+            owner: owner.to_string(),
+            slug: slug.to_string(),
+        };
+        let server = |owner: &str, slug: &str| BitbucketRepo {
+            host: "git.example.com".to_string(),
+            owner: owner.to_string(),
+            slug: slug.to_string(),
+        };
+
+        for (url, expected) in [
+            ("git@bitbucket.org:acme/api.git", cloud("acme", "api")),
+            (
+                "https://jml@bitbucket.org/acme/api.git",
+                cloud("acme", "api"),
+            ),
+            ("https://bitbucket.org/acme/api", cloud("acme", "api")),
+            (
+                "ssh://git@git.example.com:7999/proj/api.git",
+                server("proj", "api"),
+            ),
+            (
+                "https://git.example.com/scm/PROJ/api.git",
+                server("PROJ", "api"),
+            ),
+            (
+                "https://jml@git.example.com/bitbucket/scm/PROJ/api.git",
+                server("PROJ", "api"),
+            ),
+        ] {
+            assert_eq!(parse_bitbucket_url(url), Some(expected), "{}", url);
+        }
+        assert_eq!(parse_bitbucket_url("not a url"), None);
+    }
+
+    #[test]
+    fn detects_bitbucket_hosts_from_the_remote() {
+        let config = BitbucketConfig {
+            token_env: None,
+            servers: vec![ServerConfig {
+                host: "git.example.com".to_string(),
+                url: None,
+                token_env: "GWM_TEST_UNSET_BITBUCKET_TOKEN".to_string(),
+            }],
+        };
+
+        assert!(
+            config
+                .client_for("git@bitbucket.org:acme/api.git")
+                .is_some()
+        );
+        let missing_token = config
+            .client_for("ssh://git@git.example.com:7999/proj/api.git")
+            .unwrap();
+        assert!(missing_token.is_err());
+        assert!(config.client_for("git@github.com:acme/api.git").is_none());
+        assert!(
+            config
+                .client_for("git@gitlab.example.com:acme/api.git")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn quotes_branches_in_cloud_queries() {
+        let branches = vec!["fix-login".to_string(), "say-\"hi\"".to_string()];
+        assert_eq!(
+            cloud_query(&branches, "2026-10-01"),
+            r#"(source.branch.name = "fix-login" OR source.branch.name = "say-\"hi\"") AND updated_on >= 2026-10-01"#
+        );
+    }
+
+    #[test]
+    fn reads_cloud_pull_requests() {
+        let json = r#"{"values": [
+            {"id": 7, "state": "OPEN", "draft": true,
+             "created_on": "2026-10-01T12:00:00.000000+00:00",
+             "updated_on": "2026-10-01T12:00:00.000000+00:00",
+             "source": {"branch": {"name": "fix-login"}, "repository": {"full_name": "acme/api"}},
+             "destination": {"branch": {"name": "main"}, "repository": {"full_name": "acme/api"}},
+             "author": {"account_id": "teammate"},
+             "links": {"html": {"href": "https://bitbucket.org/acme/api/pull-requests/7"}},
+             "participants": [{"state": "approved"}, {"state": null}]},
+            {"id": 6, "state": "MERGED",
+             "created_on": "2026-10-01T12:00:00.000000+00:00",
+             "updated_on": "2026-10-01T12:00:00.000000+00:00",
+             "source": {"branch": {"name": "fix-login"}, "repository": {"full_name": "someone/api"}},
+             "destination": {"branch": {"name": "main"}, "repository": {"full_name": "acme/api"}},
+             "author": {"account_id": "someone"},
+             "links": {"html": {"href": "https://bitbucket.org/acme/api/pull-requests/6"}}}
+        ]}"#;
+        let page: CloudPage = serde_json::from_str(json).unwrap();
+        let since = 1790812800; // 2026-10-01T00:00:00Z
+
+        // Someone else's fork may use the same branch name
+        let any: Vec<&CloudPullRequest> = page
+            .values
+            .iter()
+            .filter(|pr| pr.keep(Some("me"), since, PrAuthor::Any))
+            .collect();
+        assert_eq!(any.len(), 1);
+        assert!(
+            !page
+                .values
+                .iter()
+                .any(|pr| pr.keep(Some("me"), since, PrAuthor::Me))
+        );
+        assert!(page.values[0].keep(Some("teammate"), since, PrAuthor::Me));
+        assert!(!page.values[0].keep(Some("teammate"), since + 86400, PrAuthor::Me));
+
+        let prs: Vec<PrInfo> = page
+            .values
+            .into_iter()
+            .map(CloudPullRequest::into_pr_info)
+            .collect();
+        assert_eq!(prs[0].head_branch, "fix-login");
+        assert_eq!(prs[0].status, PrStatus::Draft);
+        assert_eq!(prs[0].closed_at, None);
+        assert_eq!(prs[0].review, Some(ReviewDecision::Approved));
+        assert_eq!(prs[1].status, PrStatus::Merged);
+        assert_eq!(prs[1].closed_at, Some(since + 43200));
+    }
+
+    #[test]
+    fn reads_server_pull_requests() {
+        let json = r#"{"values": [
+            {"id": 12, "state": "DECLINED", "createdDate": 1790856000000,
+             "updatedDate": 1790856000000, "closedDate": 1790856000000,
+             "fromRef": {"displayId": "add-cache", "repository": {"id": 1}},
+             "toRef": {"displayId": "main", "repository": {"id": 1}},
+             "author": {"user": {"name": "jml"}, "status": "UNAPPROVED"},
+             "reviewers": [{"user": {"name": "alex"}, "status": "APPROVED"},
+                           {"user": {"name": "sam"}, "status": "NEEDS_WORK"}],
+             "links": {"self": [{"href": "https://git.example.com/projects/PROJ/repos/api/pull-requests/12"}]}}
+        ]}"#;
+        let page: ServerPage = serde_json::from_str(json).unwrap();
+        let pr = &page.values[0];
+        assert!(pr.keep(Some("jml"), 1790812800, PrAuthor::Me));
+        assert!(!pr.keep(Some("alex"), 1790812800, PrAuthor::Me));
+        assert!(pr.keep(None, 1790812800, PrAuthor::Any));
+
+        let pr = page.values.into_iter().next().unwrap().into_pr_info();
+        assert_eq!(pr.head_branch, "add-cache");
+        assert_eq!(pr.status, PrStatus::Closed);
+        assert_eq!(pr.closed_at, Some(1790856000));
+        assert_eq!(pr.review, Some(ReviewDecision::ChangesRequested));
+        assert_eq!(
+            pr.url.as_deref(),
+            Some("https://git.example.com/projects/PROJ/repos/api/pull-requests/12")
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::config;
+use crate::git::LocalStatus;
+use crate::state::StateFile;
+
+/// Persistent cache of per-worktree status, so repeated `gwm list` runs skip libgit2 status scans
+/// Entries are keyed by worktree path and only reused while HEAD and the index are unchanged
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedStatus {
+    pub head_oid: String,
+    pub index_mtime: i64,
+    pub local_status: LocalStatus,
+    pub commit_timestamp: i64,
+    pub commit_summary: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatusCache {
+    entries: HashMap<String, CachedStatus>,
+    /// Whether entries were inserted since loading, i.e. whether saving would change anything
+    #[serde(skip)]
+    changed: bool,
+}
+
+impl StatusCache {
+    /// Pure lookup: return the cached status only if the fingerprint still matches
+    pub fn get(
+        &self,
+        worktree_path: &str,
+        head_oid: &str,
+        index_mtime: i64,
+    ) -> Option<&CachedStatus> {
+        self.entries
+            .get(worktree_path)
+            .filter(|entry| entry.head_oid == head_oid && entry.index_mtime == index_mtime)
+    }
+
+    pub fn insert(&mut self, worktree_path: &str, status: CachedStatus) {
+        self.entries.insert(worktree_path.to_string(), status);
+        self.changed = true;
+    }
+
+    pub fn is_changed(&self) -> bool {
+        self.changed
+    }
+
+    /// Load the cache, treating a missing or unreadable file as empty
+    pub fn load() -> Self {
+        Self::path()
+            .and_then(|path| StateFile::new(path).load().ok())
+            .unwrap_or_default()
+    }
+
+    /// Merge these entries into the cache file, keeping entries other gwm processes added meanwhile
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path().ok_or_else(|| anyhow!("Could not determine cache directory"))?;
+        StateFile::new(path).update(|on_disk: &mut StatusCache| {
+            on_disk.merge(self);
+        })
+    }
+
+    /// Pure merge: take `other`'s entries and forget worktrees that no longer exist
+    fn merge(&mut self, other: &StatusCache) {
+        self.entries.extend(
+            other
+                .entries
+                .iter()
+                .map(|(path, status)| (path.clone(), status.clone())),
+        );
+        self.entries
+            .retain(|worktree_path, _| Path::new(worktree_path).exists());
+    }
+
+    pub fn path() -> Option<PathBuf> {
+        config::cache_dir().map(|dir| dir.join("status.json"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cached(head_oid: &str, index_mtime: i64) -> CachedStatus {
+        CachedStatus {
+            head_oid: head_oid.to_string(),
+            index_mtime,
+            local_status: LocalStatus::Clean,
+            commit_timestamp: 0,
+            commit_summary: "test commit".to_string(),
+        }
+    }
+
+    #[test]
+    fn hit_requires_matching_head_and_index_mtime() {
+        let mut cache = StatusCache::default();
+        cache.insert("/repos/api/feature", cached("abc123", 100));
+
+        assert!(cache.get("/repos/api/feature", "abc123", 100).is_some());
+        assert!(cache.get("/repos/api/feature", "def456", 100).is_none());
+        assert!(cache.get("/repos/api/feature", "abc123", 101).is_none());
+        assert!(cache.get("/repos/api/other", "abc123", 100).is_none());
+    }
+
+    #[test]
+    fn inserting_marks_the_cache_changed() {
+        let mut cache = StatusCache::default();
+        assert!(!cache.is_changed());
+        cache.insert("/repos/api/feature", cached("abc123", 100));
+        assert!(cache.is_changed());
+    }
+
+    #[test]
+    fn merge_keeps_other_entries_and_drops_removed_worktrees() {
+        let existing = std::env::temp_dir().to_string_lossy().to_string();
+        let mut on_disk = StatusCache::default();
+        on_disk.insert(&existing, cached("abc123", 100));
+        on_disk.insert("/repos/api/removed", cached("abc123", 100));
+
+        let mut ours = StatusCache::default();
+        ours.insert(&existing, cached("def456", 200));
+        on_disk.merge(&ours);
+
+        assert!(on_disk.get(&existing, "def456", 200).is_some());
+        assert!(on_disk.get("/repos/api/removed", "abc123", 100).is_none());
+    }
+}
@@ -1,29 +1,59 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use clap::Args;
+use clap_complete::ArgValueCandidates;
 use futures::future::try_join_all;
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use crate::core::RepoResult;
-use crate::git::{GitRepository, SystemGitClient};
+use crate::commands::{self, completion};
+use gwm::config::{Config, LinkMode};
+use gwm::core::{self, RepoResult};
+use gwm::git::{Backend, GitClient, GitRepository, SystemGitClient};
+use gwm::github::{Credentials, IssueInfo};
+use gwm::hooks;
+use gwm::output;
+use gwm::recent;
+use gwm::say;
+use gwm::scan;
+use gwm::usage;
+use tracing::{error, warn};
 
 #[derive(Args)]
 pub struct AddCommand {
-    /// Repository name
-    repo: String,
+    /// Repository name, or the branch name when --repos is given; leave it out inside a
+    /// repository or one of its worktrees
+    #[arg(add = ArgValueCandidates::new(completion::repo_candidates))]
+    repo: Option<String>,
 
     /// Branch name to create
-    branch: String,
+    #[arg(add = ArgValueCandidates::new(completion::add_branch_candidates))]
+    branch: Option<String>,
 
-    /// Base branch to create from (defaults to main)
-    #[arg(short, long)]
+    /// Create the same branch in several repositories, comma-separated (e.g. "api,web,worker")
+    #[arg(long, value_delimiter = ',', conflicts_with_all = ["branch", "issue"])]
+    repos: Vec<String>,
+
+    /// GitHub issue number to derive the branch name from (requires GITHUB_TOKEN)
+    #[arg(long, conflicts_with = "branch")]
+    issue: Option<u64>,
+
+    /// Base to create from (defaults to main): a local or remote-tracking branch, a tag or a commit
+    #[arg(short, long, add = ArgValueCandidates::new(completion::base_branch_candidates))]
     base_branch: Option<String>,
 
+    /// Check out origin/<branch> as a new local branch tracking it, fetching first if needed
+    #[arg(long, conflicts_with_all = ["base_branch", "issue", "reuse"])]
+    from_remote: bool,
+
     /// Directory to search for repositories (defaults to current directory)
     /// Can also be set via GWM_REPOS_PATH environment variable
     #[arg(short, long, env = "GWM_REPOS_PATH")]
     path: Option<String>,
 
+    /// Wait for repositories on slow or unreachable network mounts instead of skipping them
+    #[arg(long)]
+    include_slow: bool,
+
     /// Show what would be created without actually creating anything
     #[arg(long)]
     dry_run: bool,
@@ -32,17 +62,84 @@ pub struct AddCommand {
     #[arg(long)]
     reuse: bool,
 
+    /// Make the branch track origin/<branch>, even before it is pushed (default from `[add] track`)
+    #[arg(long)]
+    track: bool,
+
+    /// Push the branch to origin and track it, like `git push -u` (default from `[add] push`)
+    #[arg(long)]
+    push: bool,
+
+    /// Shell command to run in the new worktree (e.g. "pnpm install"); overrides the
+    /// repository's `post_add` command, and a failure removes the worktree again
+    #[arg(long, value_name = "COMMAND")]
+    exec: Option<String>,
+
+    /// Don't set up tracking, even if the config file asks for it
+    #[arg(long, conflicts_with_all = ["track", "push"])]
+    no_track: bool,
+
     /// Don't change to the worktree directory after creation
     #[arg(long)]
     no_switch: bool,
+
+    /// Implementation used to create the worktree (overrides the config file)
+    #[arg(long, value_enum, env = "GWM_GIT_BACKEND")]
+    backend: Option<Backend>,
 }
 
 impl AddCommand {
+    /// Create the branch from the default base with every other option at its default, as
+    /// `switch --create` does; `repo` and `branch` are the positional arguments as given
+    pub fn new(
+        repo: Option<String>,
+        branch: Option<String>,
+        path: Option<String>,
+        include_slow: bool,
+    ) -> Self {
+        Self {
+            repo,
+            branch,
+            repos: Vec::new(),
+            issue: None,
+            base_branch: None,
+            from_remote: false,
+            path,
+            include_slow,
+            dry_run: false,
+            reuse: false,
+            track: false,
+            push: false,
+            exec: None,
+            no_track: false,
+            no_switch: false,
+            backend: None,
+        }
+    }
+
     pub async fn execute(&self) -> Result<()> {
         let search_path = self.path.as_deref().unwrap_or(".");
-
-        // Find all repositories
-        let repo_tasks = self.collect_repositories(search_path).await?;
+        let config = Config::load()?;
+        let backend = commands::backend(self.backend, &config)?;
+
+        // With --repos, the only positional argument is the branch
+        let (repo_names, branch, repo_path) = if self.repos.is_empty() {
+            let target = commands::resolve_repo(
+                self.repo.as_deref(),
+                self.branch.as_deref(),
+                self.issue.is_some(),
+            )?;
+            (vec![target.name], target.branch, target.path)
+        } else {
+            (self.repos.clone(), self.repo.clone(), None)
+        };
+
+        // Find all repositories, or just the one containing the current directory
+        let repo_paths = match repo_path {
+            Some(path) => vec![path],
+            None => scan::discover_repositories(Path::new(search_path), self.include_slow)?,
+        };
+        let repo_tasks = Self::collect_repositories(repo_paths);
         let repo_task_results = try_join_all(repo_tasks).await?;
 
         let mut repo_results = Vec::new();
@@ -50,142 +147,451 @@ impl AddCommand {
             repo_results.push(task_result?);
         }
 
-        // Find the target repository
-        let target_repo = self.find_target_repository(&repo_results)?;
+        // Find the target repositories
+        let mut targets = Vec::new();
+        for name in &repo_names {
+            let Some(repo_result) = Self::find_target_repository(&repo_results, name) else {
+                println!("No repository found with name '{}'", name);
+                return Ok(());
+            };
+            targets.push(repo_result);
+        }
 
-        if target_repo.is_none() {
-            println!("No repository found with name '{}'", self.repo);
-            return Ok(());
+        let issue = match self.issue {
+            Some(number) => Some(
+                Self::fetch_issue(&config.github.credentials(), &targets[0].path, number).await?,
+            ),
+            None => None,
+        };
+        let branch = match (branch, &issue) {
+            (Some(branch), _) => branch,
+            (None, Some(issue)) => core::branch_name_for_issue(issue.number, &issue.title),
+            (None, None) => return Err(anyhow!("A branch name or --issue is required")),
+        };
+
+        // Check every repository before creating anything, so a batch isn't left half done
+        let mut worktree_paths = Vec::new();
+        for repo_result in &targets {
+            if Self::branch_exists_in_repo(repo_result, &branch) {
+                println!(
+                    "Branch '{}' already exists as a worktree in repository '{}'",
+                    branch, repo_result.name
+                );
+                return Ok(());
+            }
+
+            // Determine worktree path (sibling directory to repo)
+            let worktree_path = Self::determine_worktree_path(&repo_result.path, &branch);
+
+            if worktree_path.exists() {
+                println!(
+                    "Target directory '{}' already exists",
+                    worktree_path.display()
+                );
+                return Ok(());
+            }
+            worktree_paths.push(worktree_path);
         }
 
-        let repo_result = target_repo.unwrap();
+        for (repo_result, worktree_path) in targets.iter().zip(&worktree_paths) {
+            self.print_plan(&config, repo_result, &branch, issue.as_ref(), worktree_path);
+        }
 
-        // Check if branch already exists in this repo
-        if self.branch_exists_in_repo(repo_result)? {
-            println!(
-                "Branch '{}' already exists as a worktree in repository '{}'",
-                self.branch, self.repo
-            );
+        if self.dry_run {
+            for repo_result in &targets {
+                println!(
+                    "{}DRY RUN: Would create worktree {}/{}",
+                    output::emoji("🔍 "),
+                    repo_result.name,
+                    branch
+                );
+            }
             return Ok(());
         }
 
-        // Determine worktree path (sibling directory to repo)
-        let worktree_path = self.determine_worktree_path(&repo_result.path)?;
-
-        if worktree_path.exists() {
-            println!(
-                "Target directory '{}' already exists",
-                worktree_path.display()
-            );
+        if targets.len() == 1 {
+            self.create(
+                &config,
+                backend,
+                targets[0],
+                &branch,
+                issue.as_ref(),
+                &worktree_paths[0],
+            )?;
+
+            // Change to the worktree directory unless disabled
+            if !self.no_switch {
+                std::env::set_current_dir(&worktree_paths[0])?;
+                say!("📁 Changed to {}", worktree_paths[0].display());
+                recent::record(&targets[0].name, &branch, &worktree_paths[0]);
+            }
             return Ok(());
         }
 
-        println!("Target worktree:");
-        println!("  Repository: {}", repo_result.name);
-        println!("  Branch: {}", self.branch);
-        println!(
-            "  Base branch: {}",
-            self.base_branch.as_deref().unwrap_or("main")
-        );
-        println!("  Path: {}", worktree_path.display());
-        println!();
+        // One repository failing doesn't stop the others; report them all at the end
+        let mut failed = Vec::new();
+        for (repo_result, worktree_path) in targets.iter().zip(&worktree_paths) {
+            if let Err(e) = self.create(&config, backend, repo_result, &branch, None, worktree_path)
+            {
+                error!("{}: {:#}", repo_result.name, e);
+                failed.push(repo_result.name.clone());
+            }
+            say!();
+        }
 
-        if self.dry_run {
-            println!(
-                "🔍 DRY RUN: Would create worktree {}/{}",
-                self.repo, self.branch
+        say!("Results:");
+        for repo_result in &targets {
+            let (mark, outcome) = if failed.contains(&repo_result.name) {
+                ("❌ ", "failed")
+            } else {
+                ("✅ ", "created")
+            };
+            say!(
+                "  {}{}/{}: {}",
+                output::emoji(mark),
+                repo_result.name,
+                branch,
+                outcome
             );
-            return Ok(());
         }
 
-        // Perform the creation
-        let repo = GitRepository::new(repo_result.path.to_str().unwrap(), SystemGitClient)?;
-        println!("🌟 Creating worktree {}/{}", repo_result.name, self.branch);
+        if !failed.is_empty() {
+            return Err(anyhow!(
+                "Failed to create {} of {} worktrees: {}",
+                failed.len(),
+                targets.len(),
+                failed.join(", ")
+            ));
+        }
+        Ok(())
+    }
 
-        repo.add_worktree(
-            &self.branch,
+    fn print_plan(
+        &self,
+        config: &Config,
+        repo_result: &RepoResult,
+        branch: &str,
+        issue: Option<&IssueInfo>,
+        worktree_path: &Path,
+    ) {
+        say!("Target worktree:");
+        say!("  Repository: {}", repo_result.name);
+        say!("  Branch: {}", branch);
+        if let Some(issue) = issue {
+            say!("  Issue: #{} {}", issue.number, issue.title);
+        }
+        if self.from_remote {
+            say!("  Tracking: origin/{}", branch);
+        } else {
+            if self.upstream(config) != Upstream::None {
+                say!("  Upstream: origin/{}", branch);
+            }
+            say!(
+                "  Base branch: {}",
+                self.base_branch.as_deref().unwrap_or("main")
+            );
+        }
+        let copy = config.copy_on_add(&repo_result.name);
+        if !copy.is_empty() {
+            say!("  Copy: {}", copy.join(", "));
+        }
+        let link = config.link_on_add(&repo_result.name);
+        if !link.is_empty() {
+            let mode = match config.link_mode(&repo_result.name) {
+                LinkMode::Symlink => "symlinked",
+                LinkMode::Hardlink => "hard linked",
+            };
+            say!("  Share: {} ({})", link.join(", "), mode);
+        }
+        if let Some(command) = self.setup_command(config, &repo_result.name) {
+            say!("  Setup: {}", command);
+        }
+        say!("  Path: {}", worktree_path.display());
+        say!();
+    }
+
+    /// Create one worktree, then set it up, link the issue and configure tracking
+    fn create(
+        &self,
+        config: &Config,
+        backend: Backend,
+        repo_result: &RepoResult,
+        branch: &str,
+        issue: Option<&IssueInfo>,
+        worktree_path: &Path,
+    ) -> Result<()> {
+        let repo = GitRepository::new(repo_result.path.to_str().unwrap(), backend.client()?)?;
+        say!("🌟 Creating worktree {}/{}", repo_result.name, branch);
+
+        let branch_existed = repo.branch_names(false)?.iter().any(|b| b == branch);
+        if self.from_remote {
+            Self::create_tracking_branch(&repo, branch)?;
+        }
+        let added = repo.add_worktree(
+            branch,
             worktree_path.to_str().unwrap(),
             self.base_branch.as_deref(),
-            self.reuse,
-        )?;
+            self.reuse || self.from_remote,
+        );
+        if let Err(e) = added {
+            // Don't leave behind the branch that was only created for this worktree
+            if self.from_remote {
+                let _ = repo.delete_branch(branch);
+            }
+            return Err(e);
+        }
 
-        println!(
+        say!(
             "✅ Successfully created worktree {}/{}",
-            self.repo, self.branch
+            repo_result.name,
+            branch
         );
 
-        // Change to the worktree directory unless disabled
-        if !self.no_switch {
-            std::env::set_current_dir(&worktree_path)?;
-            println!("📁 Changed to {}", worktree_path.display());
+        let config = &Self::set_up(
+            config,
+            &repo,
+            &repo_result.name,
+            branch,
+            worktree_path,
+            self.exec.as_deref(),
+            branch_existed,
+        )?;
+
+        // Link the branch back to the issue it was created for
+        if let Some(issue) = issue {
+            repo.set_branch_description(branch, &format!("{}\n\n{}", issue.title, issue.url))?;
+            say!("🔗 Linked to {}", issue.url);
         }
+        // A reused branch keeps the upstream it already has
+        let upstream = self.upstream(config);
+        if !self.from_remote && upstream != Upstream::None && !repo.has_upstream(branch) {
+            Self::track(&repo, branch, upstream)?;
+        }
+        usage::record(config, |stats, now| {
+            stats.record_add(&repo_result.name, branch, now)
+        });
 
+        // The new directory is the one result scripts need
+        if output::is_quiet() {
+            println!("{}", worktree_path.display());
+        }
         Ok(())
     }
 
-    /// Find the target repository by name
-    fn find_target_repository<'a>(
-        &self,
-        repo_results: &'a [RepoResult],
-    ) -> Result<Option<&'a RepoResult>> {
-        for repo_result in repo_results {
-            if repo_result.name == self.repo {
-                return Ok(Some(repo_result));
+    /// Prepare a worktree that was just added, for `gwm add` and `gwm serve` alike: apply its
+    /// `.gwm/template.toml`, copy and share the configured files, allow its `.envrc`, add it to
+    /// the directory jumper and run the setup command (`exec`, else the repository's post_add)
+    /// A failed setup removes the worktree again, and its branch unless `branch_existed`
+    /// Returns the configuration with the template applied
+    pub fn set_up<T: GitClient>(
+        config: &Config,
+        repo: &GitRepository<T>,
+        repo_name: &str,
+        branch: &str,
+        worktree_path: &Path,
+        exec: Option<&str>,
+        branch_existed: bool,
+    ) -> Result<Config> {
+        // The new checkout may bring the team's .gwm/template.toml
+        let config = commands::with_template(config, repo_name, worktree_path);
+
+        // Before the setup command, which may need them (e.g. .env for a migration)
+        Self::copy_local_files(&config, repo, repo_name, worktree_path);
+        Self::link_shared_dirs(&config, repo, repo_name, worktree_path);
+        // After copying, which may have brought the .envrc
+        commands::direnv(&config, worktree_path, true);
+        commands::jump(&config, worktree_path, true);
+
+        if let Some(command) = exec.or(config.post_add(repo_name)) {
+            say!("🔧 Running {}", command);
+            let result = hooks::run_setup(command, repo_name, branch, worktree_path);
+            if let Err(e) = result {
+                // Half-set-up worktrees are worse than none, so undo the add
+                let _ = repo.remove_worktree_at(&worktree_path.to_string_lossy());
+                if !branch_existed {
+                    let _ = repo.delete_branch(branch);
+                }
+                return Err(anyhow!("{}; removed worktree {}/{}", e, repo_name, branch));
             }
         }
-        Ok(None)
+        Ok(config)
     }
 
-    /// Check if branch already exists as a worktree in this repo
-    fn branch_exists_in_repo(&self, repo_result: &RepoResult) -> Result<bool> {
-        for worktree in &repo_result.worktrees {
-            if worktree.branch == self.branch {
-                return Ok(true);
+    /// Copy the repository's copy_on_add files from its main worktree into the new one
+    /// Failures only warn: the worktree itself is fine, and the files can be copied by hand
+    fn copy_local_files<T: GitClient>(
+        config: &Config,
+        repo: &GitRepository<T>,
+        repo_name: &str,
+        worktree_path: &Path,
+    ) {
+        let paths = config.copy_on_add(repo_name);
+        if paths.is_empty() {
+            return;
+        }
+        let Some(main) = repo.main_worktree() else {
+            warn!("No main worktree to copy {} from", paths.join(", "));
+            return;
+        };
+        if fs::canonicalize(&main).ok() == fs::canonicalize(worktree_path).ok() {
+            return;
+        }
+        match hooks::copy_files(paths, &main, worktree_path) {
+            Ok(copied) if !copied.is_empty() => {
+                say!("📋 Copied {} from {}", copied.join(", "), main.display())
             }
+            Ok(_) => {}
+            Err(e) => warn!("Could not copy files: {:#}", e),
         }
-        Ok(false)
     }
 
-    /// Determine the path for the new worktree (inside the repo directory)
-    fn determine_worktree_path(&self, repo_path: &Path) -> Result<PathBuf> {
-        Ok(repo_path.join(&self.branch))
+    /// Share the repository's link_on_add directories with the new worktree, from its
+    /// link_from cache or else its main worktree; failures only warn, as for copies
+    fn link_shared_dirs<T: GitClient>(
+        config: &Config,
+        repo: &GitRepository<T>,
+        repo_name: &str,
+        worktree_path: &Path,
+    ) {
+        let paths = config.link_on_add(repo_name);
+        if paths.is_empty() {
+            return;
+        }
+        let cache = config.link_from(repo_name);
+        let Some(from) = cache.clone().or_else(|| repo.main_worktree()) else {
+            warn!("No main worktree to share {} from", paths.join(", "));
+            return;
+        };
+        if fs::canonicalize(&from).ok() == fs::canonicalize(worktree_path).ok() {
+            return;
+        }
+        let mode = config.link_mode(repo_name);
+        match hooks::link_dirs(paths, &from, worktree_path, mode, cache.is_some()) {
+            Ok(linked) if !linked.is_empty() => {
+                // `node_modules/` in .gitignore doesn't match a symlink, which git would
+                // otherwise report as an untracked file
+                if mode == LinkMode::Symlink {
+                    for path in &linked {
+                        let pattern = format!("/{}", path.trim_end_matches('/'));
+                        if let Err(e) = repo.exclude(&pattern) {
+                            warn!("Could not ignore {}: {}", path, e);
+                        }
+                    }
+                }
+                say!("🔗 Shared {} from {}", linked.join(", "), from.display())
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Could not share directories: {:#}", e),
+        }
     }
 
-    async fn collect_repositories(
-        &self,
-        search_path: &str,
-    ) -> Result<Vec<tokio::task::JoinHandle<Result<RepoResult>>>> {
-        let mut repo_tasks = Vec::new();
-        let entries = fs::read_dir(search_path)?;
+    /// Setup command for a repository's new worktree: --exec, then the repository's post_add
+    fn setup_command<'a>(&'a self, config: &'a Config, repo: &str) -> Option<&'a str> {
+        self.exec.as_deref().or(config.post_add(repo))
+    }
 
-        for entry in entries {
-            let entry = entry?;
-            let path = entry.path();
+    /// How the new branch should track origin, from the flags and then the config file
+    fn upstream(&self, config: &Config) -> Upstream {
+        if self.no_track {
+            Upstream::None
+        } else if self.push || config.add.push {
+            Upstream::Push
+        } else if self.track || config.add.track {
+            Upstream::Track
+        } else {
+            Upstream::None
+        }
+    }
 
-            if !path.is_dir() {
-                continue;
+    fn track<T: GitClient>(
+        repo: &GitRepository<T>,
+        branch: &str,
+        upstream: Upstream,
+    ) -> Result<()> {
+        if upstream == Upstream::Push {
+            match repo.push_upstream(branch, "origin") {
+                Ok(()) => {
+                    say!("⬆️  Pushed and tracking origin/{}", branch);
+                    return Ok(());
+                }
+                // The worktree is fine, so track the branch anyway and let the user push later
+                Err(e) => warn!("Could not push: {}", e),
             }
+        }
+        repo.set_upstream(branch, "origin")?;
+        say!("🔗 Tracking origin/{}", branch);
+        Ok(())
+    }
 
-            let git_path = path.join(".git");
-            if !git_path.exists() {
-                continue;
-            }
+    /// Create the local branch for --from-remote, fetching if origin doesn't have it yet
+    fn create_tracking_branch<T: GitClient>(repo: &GitRepository<T>, branch: &str) -> Result<()> {
+        let remote_branch = format!("origin/{}", branch);
+        if !repo.has_remote_branch(&remote_branch) {
+            say!("📡 Fetching to find {}", remote_branch);
+            repo.fetch_remotes()?;
+        }
+        if !repo.has_remote_branch(&remote_branch) {
+            return Err(anyhow!("Branch '{}' not found on origin", branch));
+        }
+        repo.create_tracking_branch(branch, &remote_branch)?;
+        say!("🔗 Tracking {}", remote_branch);
+        Ok(())
+    }
 
-            let path_str = path.to_str().unwrap().to_string();
+    /// Find the target repository by name
+    fn find_target_repository<'a>(
+        repo_results: &'a [RepoResult],
+        name: &str,
+    ) -> Option<&'a RepoResult> {
+        repo_results
+            .iter()
+            .find(|repo_result| repo_result.name == name)
+    }
 
-            let task = tokio::spawn(async move { Self::process_repository(path_str).await });
-            repo_tasks.push(task);
-        }
+    /// Check if branch already exists as a worktree in this repo
+    fn branch_exists_in_repo(repo_result: &RepoResult, branch: &str) -> bool {
+        repo_result
+            .worktrees
+            .iter()
+            .any(|worktree| worktree.branch == branch)
+    }
+
+    /// Determine the path for the new worktree (inside the repo directory)
+    fn determine_worktree_path(repo_path: &Path, branch: &str) -> PathBuf {
+        repo_path.join(branch)
+    }
+
+    /// Look up the issue on the repository's GitHub remote
+    async fn fetch_issue(
+        credentials: &Credentials,
+        repo_path: &Path,
+        number: u64,
+    ) -> Result<IssueInfo> {
+        let repo = GitRepository::new(repo_path.to_str().unwrap(), SystemGitClient)?;
+        let remote_url = repo
+            .get_upstream_remote_url()?
+            .ok_or_else(|| anyhow!("No upstream or origin remote found"))?;
+        let github_repo = credentials.parse_url(&remote_url)?;
+        let github_client = credentials.client_for(&github_repo)?;
+
+        github_client.fetch_issue(&github_repo, number).await
+    }
 
-        Ok(repo_tasks)
+    fn collect_repositories(
+        repo_paths: Vec<PathBuf>,
+    ) -> Vec<tokio::task::JoinHandle<Result<RepoResult>>> {
+        repo_paths
+            .into_iter()
+            .map(|path| {
+                let path_str = path.to_string_lossy().to_string();
+                tokio::task::spawn_blocking(move || Self::process_repository(path_str))
+            })
+            .collect()
     }
 
-    async fn process_repository(repo_path: String) -> Result<RepoResult> {
-        let repo_name = Path::new(&repo_path)
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("unknown")
-            .to_string();
+    /// Runs on a blocking thread, since every step goes through libgit2
+    fn process_repository(repo_path: String) -> Result<RepoResult> {
+        let repo_name = scan::repo_name(Path::new(&repo_path));
 
         let repo = GitRepository::new(&repo_path, SystemGitClient)?;
 
@@ -203,17 +609,10 @@ impl AddCommand {
 
         let worktree_results = worktrees
             .into_iter()
-            .map(|worktree| {
-                crate::core::WorktreeResult {
-                    branch: worktree.branch.clone(),
-                    status: crate::core::WorktreeStatus {
-                        local_status: crate::git::LocalStatus::Clean, // Placeholder
-                        commit_timestamp: 0,                          // Placeholder
-                        directory_mtime: 0,                           // Placeholder
-                        commit_summary: "<placeholder>".to_string(),  // Placeholder
-                        pr_status: None, // No PR status for add command
-                    },
-                }
+            .map(|worktree| gwm::core::WorktreeResult {
+                branch: worktree.branch.clone(),
+                path: worktree.path.clone().into(),
+                status: gwm::core::WorktreeStatus::default(),
             })
             .collect();
 
@@ -224,3 +623,13 @@ impl AddCommand {
         })
     }
 }
+
+/// Upstream set up for a newly added branch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Upstream {
+    None,
+    /// Track origin/<branch> without pushing
+    Track,
+    /// Push with `git push -u`
+    Push,
+}
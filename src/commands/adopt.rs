@@ -0,0 +1,218 @@
+use anyhow::{Result, anyhow};
+use clap::Args;
+use clap_complete::ArgValueCandidates;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::commands::completion;
+use gwm::config;
+use gwm::git::{GitRepository, SystemGitClient};
+use gwm::say;
+use gwm::scan;
+use gwm::trash;
+
+#[derive(Args)]
+pub struct AdoptCommand {
+    /// Checkout made outside gwm, e.g. with `git clone`
+    dir: PathBuf,
+
+    /// Repository to add it to; by default the one with the same remote URL
+    #[arg(long, add = ArgValueCandidates::new(completion::repo_candidates))]
+    repo: Option<String>,
+
+    /// Directory to search for repositories (defaults to current directory)
+    /// Can also be set via GWM_REPOS_PATH environment variable
+    #[arg(short, long, env = "GWM_REPOS_PATH")]
+    path: Option<String>,
+
+    /// Wait for repositories on slow or unreachable network mounts instead of skipping them
+    #[arg(long)]
+    include_slow: bool,
+}
+
+impl AdoptCommand {
+    pub async fn execute(&self) -> Result<()> {
+        let dir = fs::canonicalize(&self.dir)
+            .map_err(|e| anyhow!("Cannot adopt '{}': {}", self.dir.display(), e))?;
+        let dot_git = dir.join(".git");
+        let search_path = Path::new(self.path.as_deref().unwrap_or("."));
+        let repo_paths = scan::discover_repositories(search_path, self.include_slow)?;
+
+        if dot_git.is_file() {
+            // Already a linked worktree: nothing to do if it belongs to a known repository
+            return match scan::repository_containing(&dir) {
+                Some(repo_path)
+                    if repo_paths
+                        .iter()
+                        .any(|path| fs::canonicalize(path).ok().as_ref() == Some(&repo_path)) =>
+                {
+                    say!(
+                        "{} is already a worktree of {}",
+                        dir.display(),
+                        scan::repo_name(&repo_path)
+                    );
+                    Ok(())
+                }
+                _ => Err(anyhow!(
+                    "{} is a worktree of a repository gwm doesn't manage",
+                    dir.display()
+                )),
+            };
+        }
+        if !dot_git.is_dir() {
+            return Err(anyhow!("{} is not a git checkout", dir.display()));
+        }
+
+        let checkout = GitRepository::new(&dir.to_string_lossy(), SystemGitClient)?;
+        let branch = checkout.head_branch().ok_or_else(|| {
+            anyhow!(
+                "{} has no branch checked out; check one out before adopting it",
+                dir.display()
+            )
+        })?;
+        if let Some(operation) = checkout.get_operation(&dir.to_string_lossy()) {
+            return Err(anyhow!(
+                "{} has a {} in progress; finish or abort it first",
+                dir.display(),
+                operation
+            ));
+        }
+        // Their git directories would be left pointing into the one moved aside
+        for nested in ["worktrees", "modules"] {
+            if has_entries(&dot_git.join(nested)) {
+                return Err(anyhow!(
+                    "{} has its own {}, which can't be carried over",
+                    dir.display(),
+                    nested
+                ));
+            }
+        }
+
+        let repo_path = self.target_repository(&checkout, &repo_paths)?;
+        let repo_name = scan::repo_name(&repo_path);
+        let repo = GitRepository::new(&repo_path.to_string_lossy(), SystemGitClient)?;
+        if let Some(existing) = repo
+            .list_all_worktrees()?
+            .into_iter()
+            .find(|worktree| worktree.branch == branch)
+        {
+            return Err(anyhow!(
+                "Branch '{}' of {} is already checked out at {}",
+                branch,
+                repo_name,
+                existing.path
+            ));
+        }
+
+        repo.fetch_branch_from(&dir, &branch)?;
+        if !repo.has_upstream(&branch) && repo.has_remote_branch(&format!("origin/{}", branch)) {
+            repo.set_upstream(&branch, "origin")?;
+        }
+
+        // The clone's own git directory is kept, with its other branches and stashes
+        let backup = backup_path(&repo_name, &branch)?;
+        trash::move_dir(&dot_git, &backup)?;
+        if let Err(e) = repo.attach_worktree(&dir, &branch) {
+            let _ = trash::move_dir(&backup, &dot_git);
+            return Err(e);
+        }
+
+        say!(
+            "✅ Adopted {} as worktree {}/{}",
+            dir.display(),
+            repo_name,
+            branch
+        );
+        say!(
+            "Its previous .git directory is kept at {}",
+            backup.display()
+        );
+        Ok(())
+    }
+
+    /// Repository named with --repo, or else the one whose remote the checkout was cloned from
+    fn target_repository(
+        &self,
+        checkout: &GitRepository<SystemGitClient>,
+        repo_paths: &[PathBuf],
+    ) -> Result<PathBuf> {
+        if let Some(name) = &self.repo {
+            return repo_paths
+                .iter()
+                .find(|path| scan::repo_name(path) == *name)
+                .cloned()
+                .ok_or_else(|| anyhow!("No repository found with name '{}'", name));
+        }
+
+        let url = checkout
+            .get_upstream_remote_url()?
+            .ok_or_else(|| anyhow!("The checkout has no remote; pass --repo"))?;
+        let matches: Vec<&PathBuf> = repo_paths
+            .iter()
+            .filter(|path| {
+                GitRepository::new(&path.to_string_lossy(), SystemGitClient)
+                    .and_then(|repo| repo.get_upstream_remote_url())
+                    .ok()
+                    .flatten()
+                    .is_some_and(|other| same_remote(&url, &other))
+            })
+            .collect();
+        match matches.as_slice() {
+            [path] => Ok(path.to_path_buf()),
+            [] => Err(anyhow!("No repository has remote {}; pass --repo", url)),
+            _ => Err(anyhow!(
+                "Several repositories have remote {}; pass --repo",
+                url
+            )),
+        }
+    }
+}
+
+/// Where the clone's git directory goes: `<data>/adopted/<repo>/<branch>-<timestamp>`
+fn backup_path(repo_name: &str, branch: &str) -> Result<PathBuf> {
+    let adopted_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    config::data_dir()
+        .map(|dir| {
+            dir.join("adopted")
+                .join(repo_name)
+                .join(format!("{}-{}", branch, adopted_at))
+        })
+        .ok_or_else(|| anyhow!("Could not determine the gwm data directory"))
+}
+
+fn has_entries(dir: &Path) -> bool {
+    fs::read_dir(dir).is_ok_and(|mut entries| entries.next().is_some())
+}
+
+/// Pure function comparing remote URLs, ignoring a trailing slash or `.git`
+fn same_remote(a: &str, b: &str) -> bool {
+    fn normalize(url: &str) -> &str {
+        let url = url.trim_end_matches('/');
+        url.strip_suffix(".git").unwrap_or(url)
+    }
+    normalize(a) == normalize(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remotes_match_with_or_without_git_suffix() {
+        assert!(same_remote(
+            "git@github.com:owner/repo.git",
+            "git@github.com:owner/repo"
+        ));
+        assert!(same_remote(
+            "https://github.com/owner/repo/",
+            "https://github.com/owner/repo.git"
+        ));
+        assert!(!same_remote(
+            "https://github.com/owner/repo",
+            "https://github.com/owner/repo-fork"
+        ));
+    }
+}
@@ -1,6 +1,6 @@
-use crate::git::{GitRepository, SystemGitClient};
 use anyhow::Result;
 use clap::Args;
+use gwm::git::{GitRepository, SystemGitClient};
 use std::path::Path;
 
 #[derive(Args)]
@@ -13,13 +13,22 @@ pub struct CompleteBranchesCommand {
     /// Can also be set via GWM_REPOS_PATH environment variable
     #[arg(short, long, env = "GWM_REPOS_PATH")]
     path: Option<String>,
+
+    /// List every local and remote-tracking branch, not just those with worktrees
+    #[arg(long)]
+    all_branches: bool,
 }
 
 impl CompleteBranchesCommand {
     pub async fn execute(&self) -> Result<()> {
         let search_path = self.path.as_deref().unwrap_or(".");
 
-        match self.get_branches(search_path, &self.repo) {
+        let branches = if self.all_branches {
+            get_all_branches(search_path, &self.repo, true)
+        } else {
+            get_branches(search_path, &self.repo)
+        };
+        match branches {
             Ok(branches) => {
                 for branch in branches {
                     println!("{}", branch);
@@ -32,30 +41,52 @@ impl CompleteBranchesCommand {
 
         Ok(())
     }
+}
 
-    fn get_branches(&self, search_path: &str, repo_name: &str) -> Result<Vec<String>> {
-        let repo_path = Path::new(search_path).join(repo_name);
+/// List the branches that have worktrees in the named repository, sorted
+pub fn get_branches(search_path: &str, repo_name: &str) -> Result<Vec<String>> {
+    let repo_path = Path::new(search_path).join(repo_name);
 
-        if !repo_path.exists() {
-            return Ok(vec![]);
-        }
+    if !repo_path.exists() {
+        return Ok(vec![]);
+    }
 
-        let git_path = repo_path.join(".git");
-        if !git_path.exists() {
-            return Ok(vec![]);
-        }
+    let git_path = repo_path.join(".git");
+    if !git_path.exists() {
+        return Ok(vec![]);
+    }
 
-        let repo = GitRepository::new(repo_path.to_str().unwrap(), SystemGitClient)?;
+    let repo = GitRepository::new(repo_path.to_str().unwrap(), SystemGitClient)?;
 
-        // Only get branches from bare repos with worktrees
-        if !repo.is_bare().unwrap_or(false) {
-            return Ok(vec![]);
-        }
+    // Only get branches from bare repos with worktrees
+    if !repo.is_bare().unwrap_or(false) {
+        return Ok(vec![]);
+    }
+
+    let worktrees = repo.list_worktrees()?;
+    let mut branch_names: Vec<String> = worktrees
+        .into_iter()
+        .filter(|w| w.detached.is_none())
+        .map(|w| w.branch)
+        .collect();
 
-        let worktrees = repo.list_worktrees()?;
-        let mut branch_names: Vec<String> = worktrees.into_iter().map(|w| w.branch).collect();
+    branch_names.sort();
+    Ok(branch_names)
+}
+
+/// List the local branches of the named repository, followed by its remote-tracking ones
+/// (e.g. "origin/main") when `include_remote` is set
+pub fn get_all_branches(
+    search_path: &str,
+    repo_name: &str,
+    include_remote: bool,
+) -> Result<Vec<String>> {
+    let repo_path = Path::new(search_path).join(repo_name);
 
-        branch_names.sort();
-        Ok(branch_names)
+    if !repo_path.join(".git").exists() {
+        return Ok(vec![]);
     }
+
+    let repo = GitRepository::new(&repo_path.to_string_lossy(), SystemGitClient)?;
+    repo.branch_names(include_remote)
 }
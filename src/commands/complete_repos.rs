@@ -15,7 +15,7 @@ impl CompleteReposCommand {
     pub async fn execute(&self) -> Result<()> {
         let search_path = self.path.as_deref().unwrap_or(".");
 
-        match self.scan_repositories(search_path) {
+        match scan_repositories(search_path) {
             Ok(repos) => {
                 for repo in repos {
                     println!("{}", repo);
@@ -28,30 +28,31 @@ impl CompleteReposCommand {
 
         Ok(())
     }
+}
 
-    fn scan_repositories(&self, search_path: &str) -> Result<Vec<String>> {
-        let mut repo_names = Vec::new();
-        let entries = fs::read_dir(search_path)?;
-
-        for entry in entries {
-            let entry = entry?;
-            let path = entry.path();
+/// List the names of all repositories directly under `search_path`, sorted
+pub fn scan_repositories(search_path: &str) -> Result<Vec<String>> {
+    let mut repo_names = Vec::new();
+    let entries = fs::read_dir(search_path)?;
 
-            if !path.is_dir() {
-                continue;
-            }
+    for entry in entries {
+        let entry = entry?;
+        let path = entry.path();
 
-            let git_path = path.join(".git");
-            if !git_path.exists() {
-                continue;
-            }
+        if !path.is_dir() {
+            continue;
+        }
 
-            if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                repo_names.push(name.to_string());
-            }
+        let git_path = path.join(".git");
+        if !git_path.exists() {
+            continue;
         }
 
-        repo_names.sort();
-        Ok(repo_names)
+        if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+            repo_names.push(name.to_string());
+        }
     }
+
+    repo_names.sort();
+    Ok(repo_names)
 }
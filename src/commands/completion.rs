@@ -1,8 +1,17 @@
-use anyhow::Result;
-use clap::{Args, CommandFactory};
-use clap_complete::{Shell, generate};
+use anyhow::{Result, anyhow};
+use clap::{ArgMatches, Args, CommandFactory};
+use clap_complete::env::Shells;
+use clap_complete::{CompletionCandidate, Shell};
 use std::io;
 
+use crate::commands::complete_branches::{get_all_branches, get_branches};
+use crate::commands::complete_repos::scan_repositories;
+use gwm::config::Config;
+use gwm::recent::RecentWorktrees;
+
+/// Environment variable that switches the binary into dynamic completion mode
+pub const COMPLETE_VAR: &str = "COMPLETE";
+
 #[derive(Args)]
 pub struct CompletionCommand {
     /// The shell to generate completions for
@@ -12,395 +21,216 @@ pub struct CompletionCommand {
 
 impl CompletionCommand {
     pub async fn execute(&self) -> Result<()> {
-        match self.shell {
-            Shell::Bash => self.generate_enhanced_bash_completion().await,
-            Shell::Zsh => self.generate_enhanced_zsh_completion().await,
-            _ => {
-                // For other shells, use the default completion
-                let mut cmd = crate::Cli::command();
-                generate(self.shell, &mut cmd, "gwm", &mut io::stdout());
-                Ok(())
-            }
-        }
-    }
-
-    async fn generate_enhanced_bash_completion(&self) -> Result<()> {
-        // First generate the base completion
-        let mut cmd = crate::Cli::command();
-        let mut output = Vec::new();
-        generate(Shell::Bash, &mut cmd, "gwm", &mut output);
-
-        let base_completion = String::from_utf8(output)?;
-
-        // Add our custom completion functions
-        let enhanced_completion = self.enhance_bash_completion(&base_completion);
-
-        print!("{}", enhanced_completion);
+        // The registration script calls back into `COMPLETE=<shell> gwm -- <words>`, so
+        // candidates are computed by the binary itself at completion time
+        let shell = self.shell.to_string();
+        let shells = Shells::builtins();
+        let completer = shells
+            .completer(&shell)
+            .ok_or_else(|| anyhow!("Completion is not supported for {}", shell))?;
+        completer.write_registration(COMPLETE_VAR, "gwm", "gwm", "gwm", &mut io::stdout())?;
         Ok(())
     }
-
-    async fn generate_enhanced_zsh_completion(&self) -> Result<()> {
-        // Generate the base completion
-        let mut cmd = crate::Cli::command();
-        let mut output = Vec::new();
-        generate(Shell::Zsh, &mut cmd, "gwm", &mut output);
-
-        let base_completion = String::from_utf8(output)?;
-
-        // Add our custom completion functions for zsh
-        let enhanced_completion = self.enhance_zsh_completion(&base_completion);
-
-        print!("{}", enhanced_completion);
-        Ok(())
-    }
-
-    fn enhance_bash_completion(&self, base: &str) -> String {
-        let custom_functions = r#"
-# Enhanced gwm completion with dynamic repository and branch name completion
-
-# Helper functions for dynamic completion
-_gwm_complete_repos() {
-    local path_arg=""
-
-    # Extract path argument if present
-    local i
-    for (( i=0; i < ${#COMP_WORDS[@]}; i++ )); do
-        if [[ "${COMP_WORDS[i]}" == "--path" ]] && [[ $((i+1)) -lt ${#COMP_WORDS[@]} ]]; then
-            path_arg="--path ${COMP_WORDS[i+1]}"
-            break
-        elif [[ "${COMP_WORDS[i]}" == "-p" ]] && [[ $((i+1)) -lt ${#COMP_WORDS[@]} ]]; then
-            path_arg="--path ${COMP_WORDS[i+1]}"
-            break
-        fi
-    done
-
-    # Call gwm to get repository names
-    gwm complete-repos $path_arg 2>/dev/null
-}
-
-_gwm_complete_branches() {
-    local repo="$1"
-    local path_arg=""
-
-    # Extract path argument if present
-    local i
-    for (( i=0; i < ${#COMP_WORDS[@]}; i++ )); do
-        if [[ "${COMP_WORDS[i]}" == "--path" ]] && [[ $((i+1)) -lt ${#COMP_WORDS[@]} ]]; then
-            path_arg="--path ${COMP_WORDS[i+1]}"
-            break
-        elif [[ "${COMP_WORDS[i]}" == "-p" ]] && [[ $((i+1)) -lt ${#COMP_WORDS[@]} ]]; then
-            path_arg="--path ${COMP_WORDS[i+1]}"
-            break
-        fi
-    done
-
-    # Call gwm to get branch names for the specified repository
-    gwm complete-branches "$repo" $path_arg 2>/dev/null
 }
 
-# Get positional argument index for dynamic completion
-_gwm_get_positional_index() {
-    local current_cmd="$1"
-    local positional_count=0
-
-    local i
-    for (( i=2; i < COMP_CWORD; i++ )); do
-        local word="${COMP_WORDS[i]}"
-
-        # Skip the subcommand itself
-        if [[ "$word" == "$current_cmd" ]]; then
-            continue
-        fi
-
-        # Skip flags and their values
-        if [[ "$word" =~ ^- ]]; then
-            case "$word" in
-                --path|-p|--base-branch|-b|--older-than|--newer-than)
-                    # These flags take a value, skip next word too
-                    i=$((i+1))
-                    ;;
-            esac
-        else
-            # This is a positional argument
-            positional_count=$((positional_count+1))
-        fi
-    done
-
-    echo $positional_count
+/// The command line being completed, as passed to `COMPLETE=<shell> gwm -- <words>`
+struct CompletionWords {
+    words: Vec<String>,
 }
 
-# Find repository name from previous arguments
-_gwm_find_repo_arg() {
-    local current_cmd="$1"
-    local repo=""
-    local positional_found=0
-
-    local i
-    for (( i=2; i < COMP_CWORD; i++ )); do
-        local word="${COMP_WORDS[i]}"
+impl CompletionWords {
+    fn from_env() -> Self {
+        let mut words: Vec<String> = std::env::args()
+            .skip_while(|arg| arg != "--")
+            .skip(1)
+            .collect();
+        // Shells that pass the whole line say which word is being completed
+        if let Some(index) = std::env::var("_CLAP_COMPLETE_INDEX")
+            .ok()
+            .and_then(|index| index.parse::<usize>().ok())
+        {
+            words.truncate(index + 1);
+        }
+        Self { words }
+    }
 
-        # Skip the subcommand itself
-        if [[ "$word" == "$current_cmd" ]]; then
-            continue
-        fi
+    /// The words before the one being completed, parsed as far as they go
+    fn subcommand_matches(&self) -> Option<ArgMatches> {
+        let preceding = &self.words[..self.words.len().saturating_sub(1)];
+        let matches = crate::Cli::command()
+            .ignore_errors(true)
+            .try_get_matches_from(preceding)
+            .ok()?;
+        matches.subcommand().map(|(_, matches)| matches.clone())
+    }
 
-        # Skip flags and their values
-        if [[ "$word" =~ ^- ]]; then
-            case "$word" in
-                --path|-p|--base-branch|-b|--older-than|--newer-than)
-                    i=$((i+1))  # Skip the flag value
-                    ;;
-            esac
-        else
-            # This is a positional argument
-            positional_found=$((positional_found+1))
-            if [[ $positional_found -eq 1 ]]; then
-                repo="$word"
-                break
-            fi
-        fi
-    done
+    /// Repository search path from `--path`/`-p`, falling back to GWM_REPOS_PATH
+    fn search_path(&self) -> String {
+        self.subcommand_matches()
+            .and_then(|matches| {
+                matches
+                    .try_get_one::<String>("path")
+                    .ok()
+                    .flatten()
+                    .cloned()
+            })
+            .or_else(|| std::env::var("GWM_REPOS_PATH").ok())
+            .unwrap_or_else(|| ".".to_string())
+    }
 
-    echo "$repo"
+    /// Repository named before the word being completed
+    fn repo(&self) -> Option<String> {
+        self.subcommand_matches()?
+            .try_get_one::<String>("repo")
+            .ok()
+            .flatten()
+            .cloned()
+    }
 }
 
-"#;
-
-        // Replace the gwm__add and gwm__remove sections with enhanced versions
-        let enhanced = base
-            .replace(
-                "        gwm__add)\n            opts=\"-b -p -h --base-branch --path --dry-run --help <REPO> <BRANCH>\"\n            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then\n                COMPREPLY=( $(compgen -W \"${opts}\" -- \"${cur}\") )\n                return 0\n            fi\n            case \"${prev}\" in\n                --base-branch)\n                    COMPREPLY=($(compgen -f \"${cur}\"))\n                    return 0\n                    ;;\n                -b)\n                    COMPREPLY=($(compgen -f \"${cur}\"))\n                    return 0\n                    ;;\n                --path)\n                    COMPREPLY=($(compgen -f \"${cur}\"))\n                    return 0\n                    ;;\n                -p)\n                    COMPREPLY=($(compgen -f \"${cur}\"))\n                    return 0\n                    ;;\n                *)\n                    COMPREPLY=()\n                    ;;\n            esac\n            COMPREPLY=( $(compgen -W \"${opts}\" -- \"${cur}\") )\n            return 0\n            ;;",
-                r#"        gwm__add)
-            opts="-b -p -h --base-branch --path --dry-run --help"
-
-            # Check if we're completing flags
-            if [[ ${cur} == -* ]]; then
-                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
-                return 0
-            fi
-
-            # Handle flag values
-            case "${prev}" in
-                --base-branch|-b|--path|-p)
-                    COMPREPLY=($(compgen -f "${cur}"))
-                    return 0
-                    ;;
-                *)
-                    # Handle positional arguments (repo and branch)
-                    local pos_index=$(_gwm_get_positional_index "add")
-
-                    if [[ $pos_index -eq 1 ]]; then
-                        # First positional: repository name
-                        local repos=$(_gwm_complete_repos)
-                        COMPREPLY=( $(compgen -W "${repos}" -- "${cur}") )
-                        return 0
-                    elif [[ $pos_index -eq 2 ]]; then
-                        # Second positional: branch name (new branch, so no completion)
-                        # Let user type freely since they're creating a new branch
-                        COMPREPLY=()
-                        return 0
-                    fi
-                    ;;
-            esac
-            return 0
-            ;;"#
-            )
-            .replace(
-                "        gwm__remove)\n            opts=\"-p -h --path --dry-run --help <REPO> <BRANCH>\"\n            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then\n                COMPREPLY=( $(compgen -W \"${opts}\" -- \"${cur}\") )\n                return 0\n            fi\n            case \"${prev}\" in\n                --path)\n                    COMPREPLY=($(compgen -f \"${cur}\"))\n                    return 0\n                    ;;\n                -p)\n                    COMPREPLY=($(compgen -f \"${cur}\"))\n                    return 0\n                    ;;\n                *)\n                    COMPREPLY=()\n                    ;;\n            esac\n            COMPREPLY=( $(compgen -W \"${opts}\" -- \"${cur}\") )\n            return 0\n            ;;",
-                r#"        gwm__remove)
-            opts="-p -h --path --dry-run --help"
-
-            # Check if we're completing flags
-            if [[ ${cur} == -* ]]; then
-                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
-                return 0
-            fi
-
-            # Handle flag values
-            case "${prev}" in
-                --path|-p)
-                    COMPREPLY=($(compgen -f "${cur}"))
-                    return 0
-                    ;;
-                *)
-                    # Handle positional arguments (repo and branch)
-                    local pos_index=$(_gwm_get_positional_index "remove")
-
-                    if [[ $pos_index -eq 1 ]]; then
-                        # First positional: repository name
-                        local repos=$(_gwm_complete_repos)
-                        COMPREPLY=( $(compgen -W "${repos}" -- "${cur}") )
-                        return 0
-                    elif [[ $pos_index -eq 2 ]]; then
-                        # Second positional: branch name
-                        local repo=$(_gwm_find_repo_arg "remove")
-                        if [[ -n "$repo" ]]; then
-                            local branches=$(_gwm_complete_branches "$repo")
-                            COMPREPLY=( $(compgen -W "${branches}" -- "${cur}") )
-                            return 0
-                        fi
-                    fi
-                    ;;
-            esac
-            return 0
-            ;;"#
-            );
-
-        format!("{}{}", custom_functions, enhanced)
+/// Dynamic completion candidates for repository name arguments
+pub fn repo_candidates() -> Vec<CompletionCandidate> {
+    let words = CompletionWords::from_env();
+    // `gwm add --repos a,b <branch>` takes the branch where the repository would be
+    if words.subcommand_matches().is_some_and(|matches| {
+        matches
+            .try_get_many::<String>("repos")
+            .is_ok_and(|repos| repos.is_some())
+    }) {
+        return Vec::new();
     }
-
-    fn enhance_zsh_completion(&self, base: &str) -> String {
-        let custom_functions = r#"
-# Enhanced gwm completion with dynamic repository and branch name completion
-
-# Helper functions for dynamic completion in zsh
-_gwm_complete_repos() {
-    local path_arg=""
-
-    # Extract path argument if present
-    local i
-    for (( i=1; i <= ${#words[@]}; i++ )); do
-        if [[ "${words[i]}" == "--path" ]] && [[ $((i+1)) -le ${#words[@]} ]]; then
-            path_arg="--path ${words[i+1]}"
-            break
-        elif [[ "${words[i]}" == "-p" ]] && [[ $((i+1)) -le ${#words[@]} ]]; then
-            path_arg="--path ${words[i+1]}"
-            break
-        fi
-    done
-
-    # Call gwm to get repository names and return as array
-    local repos
-    repos=($(gwm complete-repos $path_arg 2>/dev/null))
-    _describe 'repositories' repos
+    scan_repositories(&words.search_path())
+        .unwrap_or_default()
+        .into_iter()
+        .map(CompletionCandidate::new)
+        .collect()
 }
 
-_gwm_complete_branches() {
-    local repo="$1"
-    local path_arg=""
-
-    # Extract path argument if present
-    local i
-    for (( i=1; i <= ${#words[@]}; i++ )); do
-        if [[ "${words[i]}" == "--path" ]] && [[ $((i+1)) -le ${#words[@]} ]]; then
-            path_arg="--path ${words[i+1]}"
-            break
-        elif [[ "${words[i]}" == "-p" ]] && [[ $((i+1)) -le ${#words[@]} ]]; then
-            path_arg="--path ${words[i+1]}"
-            break
-        fi
-    done
-
-    # Call gwm to get branch names and return as array
-    local branches
-    branches=($(gwm complete-branches "$repo" $path_arg 2>/dev/null))
-    _describe 'branches' branches
+/// Dynamic completion candidates for branch arguments of an already-named repository, the
+/// most recently visited first
+pub fn branch_candidates() -> Vec<CompletionCandidate> {
+    let words = CompletionWords::from_env();
+    let Some(repo) = words.repo() else {
+        return Vec::new();
+    };
+    let mut branches = get_branches(&words.search_path(), &repo).unwrap_or_default();
+    let recent = RecentWorktrees::path()
+        .and_then(|path| RecentWorktrees::load(path).ok())
+        .unwrap_or_default();
+    // Stable, so branches never visited stay sorted after the visited ones
+    branches.sort_by_key(|branch| {
+        recent
+            .visits
+            .iter()
+            .position(|visit| visit.repo == repo && visit.branch == *branch)
+            .unwrap_or(usize::MAX)
+    });
+    branches
+        .into_iter()
+        .enumerate()
+        .map(|(order, branch)| CompletionCandidate::new(branch).display_order(Some(order)))
+        .collect()
 }
 
-# Get positional argument index for dynamic completion
-_gwm_get_positional_index_zsh() {
-    local current_cmd="$1"
-    local positional_count=0
-
-    local i
-    for (( i=2; i <= ${#words[@]}; i++ )); do
-        local word="${words[i]}"
-
-        # Skip the subcommand itself
-        if [[ "$word" == "$current_cmd" ]]; then
-            continue
-        fi
-
-        # Skip flags and their values
-        if [[ "$word" =~ ^- ]]; then
-            case "$word" in
-                --path|-p|--base-branch|-b|--older-than|--newer-than)
-                    # These flags take a value, skip next word too
-                    i=$((i+1))
-                    ;;
-            esac
-        else
-            # This is a positional argument
-            positional_count=$((positional_count+1))
-        fi
-    done
-
-    echo $positional_count
+/// Dynamic completion candidates for the branch of `gwm add`: existing local branches without
+/// a worktree once `--reuse` is given, and origin's branches with `--from-remote`, since new
+/// branch names can't be guessed
+pub fn add_branch_candidates() -> Vec<CompletionCandidate> {
+    let words = CompletionWords::from_env();
+    let Some(matches) = words.subcommand_matches() else {
+        return Vec::new();
+    };
+    let flag = |id: &str| {
+        matches
+            .try_get_one::<bool>(id)
+            .ok()
+            .flatten()
+            .copied()
+            .unwrap_or(false)
+    };
+    let (reuse, from_remote) = (flag("reuse"), flag("from_remote"));
+    let Some(repo) = words.repo().filter(|_| reuse || from_remote) else {
+        return Vec::new();
+    };
+    let search_path = words.search_path();
+    let local = get_all_branches(&search_path, &repo, false).unwrap_or_default();
+    let candidates: Vec<String> = if from_remote {
+        // Remote-tracking branches follow the local ones
+        get_all_branches(&search_path, &repo, true)
+            .unwrap_or_default()
+            .into_iter()
+            .skip(local.len())
+            .filter_map(|branch| branch.strip_prefix("origin/").map(str::to_string))
+            .filter(|branch| !local.contains(branch))
+            .collect()
+    } else {
+        let checked_out = get_branches(&search_path, &repo).unwrap_or_default();
+        local
+            .into_iter()
+            .filter(|branch| !checked_out.contains(branch))
+            .collect()
+    };
+    candidates
+        .into_iter()
+        .map(CompletionCandidate::new)
+        .collect()
 }
 
-# Find repository name from previous arguments
-_gwm_find_repo_arg_zsh() {
-    local current_cmd="$1"
-    local repo=""
-    local positional_found=0
-
-    local i
-    for (( i=2; i <= ${#words[@]}; i++ )); do
-        local word="${words[i]}"
-
-        # Skip the subcommand itself
-        if [[ "$word" == "$current_cmd" ]]; then
-            continue
-        fi
-
-        # Skip flags and their values
-        if [[ "$word" =~ ^- ]]; then
-            case "$word" in
-                --path|-p|--base-branch|-b|--older-than|--newer-than)
-                    i=$((i+1))  # Skip the flag value
-                    ;;
-            esac
-        else
-            # This is a positional argument
-            positional_found=$((positional_found+1))
-            if [[ $positional_found -eq 1 ]]; then
-                repo="$word"
-                break
-            fi
-        fi
-    done
-
-    echo "$repo"
+/// Dynamic completion candidates for `--base-branch`: local branches, then `origin/*` ones
+pub fn base_branch_candidates() -> Vec<CompletionCandidate> {
+    let words = CompletionWords::from_env();
+    let Some(repo) = words.repo() else {
+        return Vec::new();
+    };
+    let search_path = words.search_path();
+    let local = get_all_branches(&search_path, &repo, false).unwrap_or_default();
+    // Remote-tracking branches follow the local ones
+    let remote = get_all_branches(&search_path, &repo, true)
+        .unwrap_or_default()
+        .into_iter()
+        .skip(local.len())
+        .filter(|branch| branch.starts_with("origin/"));
+    local
+        .into_iter()
+        .chain(remote)
+        .map(CompletionCandidate::new)
+        .collect()
 }
 
-"#;
-
-        // For zsh, we need to add custom completion functions
-        // But only apply branch completion to remove command, not add command
-        let mut enhanced = base.to_string();
-
-        // Replace repository completion for both add and remove commands
-        enhanced = enhanced.replace(
-            ":repo -- Repository name:_default",
-            ":repo -- Repository name:_gwm_complete_repos",
-        );
-
-        // Only apply branch completion to remove command
-        // Find the remove command section and replace branch completion there
-        if let Some(remove_start) = enhanced.find("(remove)")
-            && let Some(remove_end) = enhanced[remove_start..].find(";;")
-        {
-            let remove_section_end = remove_start + remove_end + 2;
-            let remove_section = &enhanced[remove_start..remove_section_end];
+/// Dynamic completion candidates for `--preset`: the `[filters.<name>]` tables in the config file
+pub fn preset_candidates() -> Vec<CompletionCandidate> {
+    let mut names: Vec<String> = Config::load()
+        .map(|config| config.filters.into_keys().collect())
+        .unwrap_or_default();
+    names.sort_unstable();
+    names.into_iter().map(CompletionCandidate::new).collect()
+}
 
-            // Replace branch completion only in the remove section
-            let updated_remove = remove_section.replace(
-                ":branch -- Branch name to remove:_default",
-                ":branch -- Branch name to remove:_gwm_complete_branches",
-            );
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            enhanced = format!(
-                "{}{}{}",
-                &enhanced[..remove_start],
-                updated_remove,
-                &enhanced[remove_section_end..]
-            );
+    fn words(args: &[&str]) -> CompletionWords {
+        CompletionWords {
+            words: args.iter().map(|a| a.to_string()).collect(),
         }
+    }
+
+    #[test]
+    fn repo_skips_flag_values_and_current_word() {
+        let words = words(&["gwm", "remove", "--path", "/src", "my-repo", "fea"]);
+        assert_eq!(words.repo().as_deref(), Some("my-repo"));
+    }
 
-        // For add command, we want to keep the default (no completion) for branch name
-        // The add command should already have ":branch -- Branch name to create:_default"
-        // and we leave that as _default (no custom completion)
+    #[test]
+    fn repo_is_unknown_while_it_is_being_completed() {
+        let words = words(&["gwm", "remove", "--path", "/src", "my-re"]);
+        assert_eq!(words.repo(), None);
+    }
 
-        format!("{}{}", custom_functions, enhanced)
+    #[test]
+    fn search_path_reads_path_flag() {
+        let words = words(&["gwm", "remove", "--path=/src", "my-repo", ""]);
+        assert_eq!(words.search_path(), "/src");
     }
 }
@@ -0,0 +1,181 @@
+use anyhow::{Result, anyhow};
+use clap::Args;
+use futures::future::join_all;
+use std::path::Path;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::commands::{
+    self,
+    sync::{FetchOutcome, RetryPolicy, SyncCommand},
+};
+use gwm::config::{Config, SyncConfig};
+use gwm::git::Backend;
+use gwm::notify::{Event, Notification, Notifier};
+use gwm::say;
+use gwm::scan::{self, ScanOptions};
+use gwm::snapshot::Snapshot;
+use gwm::usage::history;
+
+#[derive(Args)]
+pub struct DaemonCommand {
+    /// Directory to search for repositories (defaults to current directory)
+    /// Can also be set via GWM_REPOS_PATH environment variable
+    #[arg(short, long, env = "GWM_REPOS_PATH")]
+    path: Option<String>,
+
+    /// Seconds between refreshes
+    #[arg(long, value_name = "SECONDS", default_value_t = 300, value_parser = clap::value_parser!(u64).range(1..))]
+    interval: u64,
+
+    /// Wait for repositories on slow or unreachable network mounts instead of skipping them
+    #[arg(long)]
+    include_slow: bool,
+
+    /// Don't fetch remotes, only refresh worktree and PR status
+    #[arg(long)]
+    no_fetch: bool,
+
+    /// Disable PR status fetching from GitHub
+    #[arg(long)]
+    no_pr_status: bool,
+
+    /// Implementation used to fetch and read repositories (overrides the config file); gitoxide
+    /// fetches through libgit2
+    #[arg(long, value_enum, env = "GWM_GIT_BACKEND")]
+    backend: Option<Backend>,
+}
+
+impl DaemonCommand {
+    pub async fn execute(&self) -> Result<()> {
+        let search_path = Path::new(self.path.as_deref().unwrap_or("."));
+        let config = Config::load()?;
+        let backend = commands::backend(self.backend, &config)?;
+        let notifier = Notifier::from_config(&config.notify)?;
+        if !self.no_pr_status && !config.forges().any_available() {
+            return Err(anyhow!(
+                "GITHUB_TOKEN environment variable not set, gh is not logged in, and no Bitbucket or Azure DevOps token is set. Set one, log in with `gh auth login`, or pass --no-pr-status to only fetch and refresh local status"
+            ));
+        }
+
+        say!(
+            "Refreshing {} every {}s (stop with Ctrl-C)",
+            search_path.display(),
+            self.interval
+        );
+        loop {
+            // A failed cycle (e.g. no network) is retried at the next interval
+            if let Err(e) = self.refresh(search_path, backend, &config, &notifier).await {
+                warn!("Refresh failed: {:#}", e);
+                notifier.notify(&Notification::new(
+                    Event::Failure,
+                    "gwm daemon: refresh failed",
+                    format!("{:#}", e),
+                ));
+            }
+            tokio::time::sleep(Duration::from_secs(self.interval)).await;
+        }
+    }
+
+    /// Fetch every repository, then rescan and publish the snapshot
+    async fn refresh(
+        &self,
+        search_path: &Path,
+        backend: Backend,
+        config: &Config,
+        notifier: &Notifier,
+    ) -> Result<()> {
+        if !self.no_fetch {
+            self.fetch_all(search_path, backend, &config.sync, notifier)
+                .await?;
+        }
+
+        let repos = scan::scan(
+            search_path,
+            &ScanOptions::new()
+                .pr_status(!self.no_pr_status)
+                .pr_author(config.github.pr_author)
+                .forges(config.forges())
+                .backend(backend)
+                .cache(true)
+                .include_slow(self.include_slow),
+        )
+        .await?;
+        history::record(config, &repos);
+
+        let snapshot = Snapshot {
+            taken_at: chrono::Utc::now().timestamp(),
+            interval_secs: self.interval,
+            repos,
+        };
+        snapshot.save(search_path)?;
+        info!("Snapshot of {} repositories saved", snapshot.repos.len());
+        Ok(())
+    }
+
+    async fn fetch_all(
+        &self,
+        search_path: &Path,
+        backend: Backend,
+        sync: &SyncConfig,
+        notifier: &Notifier,
+    ) -> Result<()> {
+        let policy = RetryPolicy::from_config(sync);
+        let fetches = scan::discover_repositories(search_path, self.include_slow)?
+            .into_iter()
+            .map(|path| {
+                SyncCommand::fetch_with_retries(
+                    path.to_string_lossy().to_string(),
+                    scan::repo_name(&path),
+                    backend,
+                    sync.tags,
+                    policy,
+                    false,
+                )
+            });
+
+        // One unreachable remote shouldn't keep the other repositories from being reported
+        let mut updated = Vec::new();
+        let mut failed = Vec::new();
+        for (repo_name, outcome) in join_all(fetches).await {
+            match outcome {
+                FetchOutcome::Fetched(changes) if !changes.is_empty() => {
+                    updated.push(format!("{} ({})", repo_name, changes.summary()))
+                }
+                FetchOutcome::Fetched(_) => {}
+                FetchOutcome::Failed(error) => {
+                    warn!("Fetching {} failed: {}", repo_name, error);
+                    failed.push(format!("{}: {}", repo_name, error));
+                }
+                FetchOutcome::TimedOut => {
+                    warn!("Fetching {} timed out", repo_name);
+                    failed.push(format!("{}: timed out", repo_name));
+                }
+            }
+        }
+        info!(
+            "Fetched: {} updated, {} failed",
+            updated.len(),
+            failed.len()
+        );
+
+        if !updated.is_empty() {
+            notifier.notify(&Notification::new(
+                Event::Fetched,
+                "gwm daemon: new commits",
+                updated.join("\n"),
+            ));
+        }
+        if !failed.is_empty() {
+            notifier.notify(&Notification::new(
+                Event::Failure,
+                format!(
+                    "gwm daemon: {} repository(ies) failed to fetch",
+                    failed.len()
+                ),
+                failed.join("\n"),
+            ));
+        }
+        Ok(())
+    }
+}
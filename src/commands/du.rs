@@ -0,0 +1,109 @@
+use anyhow::Result;
+use clap::Args;
+use std::path::Path;
+
+use crate::commands;
+use gwm::config::Config;
+use gwm::core::RepoResult;
+use gwm::git::Backend;
+use gwm::output;
+use gwm::output::table::{self, Column, TableOptions, format_size};
+use gwm::say;
+use gwm::scan::{self, ScanOptions};
+
+#[derive(Args)]
+pub struct DuCommand {
+    /// Directory to search for repositories (defaults to current directory)
+    /// Can also be set via GWM_REPOS_PATH environment variable
+    #[arg(short, long, env = "GWM_REPOS_PATH")]
+    path: Option<String>,
+
+    /// Wait for repositories on slow or unreachable network mounts instead of skipping them
+    #[arg(long)]
+    include_slow: bool,
+
+    /// Show only the largest N worktrees
+    #[arg(short = 'n', long)]
+    limit: Option<usize>,
+
+    /// Disable emoji in status output
+    #[arg(long)]
+    no_emoji: bool,
+
+    /// Library used to read worktree status (overrides the config file)
+    #[arg(long, value_enum, env = "GWM_GIT_BACKEND")]
+    backend: Option<Backend>,
+}
+
+impl DuCommand {
+    pub async fn execute(&self) -> Result<()> {
+        let search_path = Path::new(self.path.as_deref().unwrap_or("."));
+        let config = Config::load()?;
+        let backend = commands::backend(self.backend, &config)?;
+
+        // Every worktree is measured on its own blocking task, so large trees add up in parallel
+        let repo_results = scan::scan(
+            search_path,
+            &ScanOptions::new()
+                .pr_status(false)
+                .backend(backend)
+                .include_slow(self.include_slow)
+                .size(true),
+        )
+        .await?;
+
+        // One row per worktree, largest first across all repositories
+        let mut rows: Vec<RepoResult> = repo_results
+            .into_iter()
+            .flat_map(|repo_result| {
+                let (name, path) = (repo_result.name, repo_result.path);
+                repo_result
+                    .worktrees
+                    .into_iter()
+                    .map(move |worktree| RepoResult {
+                        name: name.clone(),
+                        path: path.clone(),
+                        worktrees: vec![worktree],
+                    })
+            })
+            .collect();
+        rows.sort_by_key(|row| std::cmp::Reverse(row.worktrees[0].status.size_bytes));
+        let total: u64 = rows
+            .iter()
+            .filter_map(|row| row.worktrees[0].status.size_bytes)
+            .sum();
+        let count = rows.len();
+        if let Some(limit) = self.limit {
+            rows.truncate(limit);
+        }
+
+        let table_output = table::create_table(
+            &rows,
+            &TableOptions::new()
+                .emoji(!self.no_emoji && !output::is_quiet())
+                .pr_status(false)
+                .columns(vec![
+                    Column::Size,
+                    Column::Repository,
+                    Column::Branch,
+                    Column::Local,
+                    Column::Age,
+                ])
+                .style(config.table.style)
+                .dates(config.table.dates)
+                .widths(config.table.clone()),
+        );
+        output::print_paged(&table_output);
+
+        if count > 0 {
+            say!();
+            say!(
+                "{}{} in {} worktree(s)",
+                output::emoji("💾 "),
+                format_size(total),
+                count
+            );
+        }
+        Ok(())
+    }
+}
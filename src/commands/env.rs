@@ -0,0 +1,65 @@
+use anyhow::Result;
+use clap::Args;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use gwm::cache::StatusCache;
+use gwm::config::Config;
+use gwm::scan;
+
+/// How `gwm env` prints the current worktree
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum EnvFormat {
+    /// "repo@branch (dirty, behind 2)"
+    #[default]
+    Prompt,
+    /// bash/zsh variable assignments for `eval`
+    Shell,
+    /// One line of JSON, with a `schema_version`
+    Json,
+}
+
+#[derive(Args)]
+pub struct EnvCommand {
+    /// Directory to describe (defaults to the current directory)
+    dir: Option<PathBuf>,
+
+    /// Output format; `shell` prints variables for `eval "$(gwm env --format shell)"`
+    #[arg(long, value_enum, default_value_t = EnvFormat::Prompt)]
+    format: EnvFormat,
+
+    /// Recompute the local status instead of reusing the status cache
+    #[arg(long)]
+    no_cache: bool,
+}
+
+impl EnvCommand {
+    /// Print nothing outside gwm repositories, so prompts can show the output as it is
+    pub async fn execute(&self) -> Result<()> {
+        let config = Config::load()?;
+        let dir = match &self.dir {
+            Some(dir) => std::path::absolute(dir)?,
+            None => std::env::current_dir()?,
+        };
+        let cache = (!self.no_cache).then(|| Mutex::new(StatusCache::load()));
+
+        let Some(status) = scan::prompt_status(&dir, config.git.backend, cache.as_ref())? else {
+            return Ok(());
+        };
+
+        // Prompts run this constantly, so only write the cache when it learned something
+        if let Some(cache) = cache {
+            let cache = cache.into_inner().unwrap();
+            if cache.is_changed() {
+                let _ = cache.save();
+            }
+        }
+
+        match self.format {
+            EnvFormat::Prompt => println!("{}", status.text()),
+            EnvFormat::Shell => println!("{}", status.shell_vars()),
+            EnvFormat::Json => println!("{}", status.json()?),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,102 @@
+use anyhow::{Result, anyhow};
+use clap::Args;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use gwm::config::JumpTool;
+use gwm::git::{GitRepository, SystemGitClient};
+use gwm::hooks;
+use gwm::output::shell::quote;
+use gwm::say;
+use gwm::scan;
+
+/// How `gwm export-paths` prints the worktree directories
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// One directory per line
+    #[default]
+    Paths,
+    /// `zoxide add` commands
+    Zoxide,
+    /// `autojump --add` commands
+    Autojump,
+}
+
+#[derive(Args)]
+pub struct ExportPathsCommand {
+    /// Directory to search for repositories (defaults to current directory)
+    /// Can also be set via GWM_REPOS_PATH environment variable
+    #[arg(short, long, env = "GWM_REPOS_PATH")]
+    path: Option<String>,
+
+    /// Wait for repositories on slow or unreachable network mounts instead of skipping them
+    #[arg(long)]
+    include_slow: bool,
+
+    /// Output format; `zoxide` and `autojump` print the commands that add each worktree
+    #[arg(long, value_enum, default_value_t = ExportFormat::Paths)]
+    format: ExportFormat,
+
+    /// Add the worktrees to zoxide's or autojump's database instead of printing the commands
+    #[arg(long)]
+    register: bool,
+}
+
+impl ExportPathsCommand {
+    pub async fn execute(&self) -> Result<()> {
+        let search_path = Path::new(self.path.as_deref().unwrap_or("."));
+        let repo_paths = scan::discover_repositories(search_path, self.include_slow)?;
+        let paths = Self::worktree_paths(&repo_paths)?;
+
+        let (tool, name, command) = match self.format {
+            ExportFormat::Paths if self.register => {
+                return Err(anyhow!("--register needs --format zoxide or autojump"));
+            }
+            ExportFormat::Paths => {
+                for path in &paths {
+                    println!("{}", path.display());
+                }
+                return Ok(());
+            }
+            ExportFormat::Zoxide => (JumpTool::Zoxide, "zoxide", "zoxide add"),
+            ExportFormat::Autojump => (JumpTool::Autojump, "autojump", "autojump --add"),
+        };
+
+        if self.register {
+            hooks::jump_add(tool, &paths)?;
+            say!("✅ Registered {} worktree(s) with {}", paths.len(), name);
+            return Ok(());
+        }
+        for path in &paths {
+            println!("{} {}", command, quote(&path.to_string_lossy()));
+        }
+        Ok(())
+    }
+
+    /// Absolute directories of every existing worktree of the gwm (bare) repositories, main
+    /// and master included
+    fn worktree_paths(repo_paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+        let mut paths = Vec::new();
+        for repo_path in repo_paths {
+            let worktrees = GitRepository::new(&repo_path.to_string_lossy(), SystemGitClient)
+                .and_then(|repo| match repo.is_bare()? {
+                    true => repo.list_all_worktrees(),
+                    false => Ok(Vec::new()),
+                });
+            let worktrees = match worktrees {
+                Ok(worktrees) => worktrees,
+                Err(e) => {
+                    warn!("Skipping {}: {:#}", scan::repo_name(repo_path), e);
+                    continue;
+                }
+            };
+            for worktree in worktrees {
+                let path = std::path::absolute(&worktree.path)?;
+                if path.is_dir() {
+                    paths.push(path);
+                }
+            }
+        }
+        Ok(paths)
+    }
+}
@@ -1,16 +1,24 @@
 use anyhow::{Result, anyhow};
 use clap::Args;
-use futures::future::try_join_all;
-use std::collections::HashMap;
-use std::fs;
-use std::path::{Path, PathBuf};
-
-use crate::core::{
-    PrStatus, RepoResult, WorktreeAnalyzer, WorktreeFilter, WorktreeResult, WorktreeStatus,
-};
-use crate::git::{GitRepository, SystemGitClient};
-use crate::github;
-use crate::output::table;
+use std::path::Path;
+
+use crate::commands::{self, ExitStatus, remove::archive_date};
+use gwm::config::Config;
+use gwm::core::{RepoResult, WorktreeAnalyzer, WorktreeFilter, glob_matches};
+use gwm::git::{Backend, GitRepository, LocalStatus, SystemGitClient};
+use gwm::hooks;
+use gwm::notify::{Event, Notification, Notifier};
+use gwm::output;
+use gwm::output::table::{self, TableOptions, TableStyle};
+use gwm::say;
+use gwm::scan::{self, ScanOptions};
+use gwm::trash::Trash;
+use gwm::usage;
+use tracing::warn;
+
+/// Exit status of `gc --dry-run` when something could be collected
+/// Distinct from 1, which anyhow uses for errors
+pub const EXIT_CANDIDATES_FOUND: i32 = 10;
 
 #[derive(Args)]
 pub struct GcCommand {
@@ -19,66 +27,182 @@ pub struct GcCommand {
     #[arg(short, long, env = "GWM_REPOS_PATH")]
     path: Option<String>,
 
+    /// Wait for repositories on slow or unreachable network mounts instead of skipping them
+    #[arg(long)]
+    include_slow: bool,
+
     /// Show what would be removed without actually removing anything
+    /// Exits with status 10 when there are candidates, so scheduled runs can alert only then
     #[arg(long)]
     dry_run: bool,
 
     /// Disable emoji in status output
     #[arg(long)]
     no_emoji: bool,
+
+    /// Only collect branches matching a glob pattern (e.g., 'jml/*', '*-experiment')
+    #[arg(long, value_name = "GLOB")]
+    branch: Option<String>,
+
+    /// Also collect worktrees whose PR was closed without merging at least this long ago
+    /// (e.g. 30, 30d, 2w)
+    #[arg(long, value_name = "AGE")]
+    closed_older_than: Option<String>,
+
+    /// Also collect worktrees whose branch was squash-merged into the default branch, found by
+    /// patch-id, whatever the state of their PR
+    #[arg(long)]
+    squash_merged: bool,
+
+    /// Keep the local branches of removed worktrees instead of deleting them
+    #[arg(long)]
+    keep_branches: bool,
+
+    /// Also delete each removed branch on origin when its pull request is merged; branches of
+    /// PRs closed without merging are kept there
+    #[arg(long)]
+    delete_remote: bool,
+
+    /// Tag each branch as archive/<branch>/<date> before removing its worktree
+    #[arg(long)]
+    archive: bool,
+
+    /// Don't archive branches, even if the config file asks for it
+    #[arg(long, conflicts_with = "archive")]
+    no_archive: bool,
+
+    /// Move worktree directories to the trash instead of deleting them, so `gwm undo` can restore
+    /// the last one
+    #[arg(long)]
+    trash: bool,
+
+    /// Delete worktree directories, even if the config file asks for the trash
+    #[arg(long, conflicts_with = "trash")]
+    no_trash: bool,
+
+    /// Table border style (overrides the config file)
+    #[arg(long, value_enum)]
+    style: Option<TableStyle>,
+
+    /// Library used to read worktree status (overrides the config file)
+    #[arg(long, value_enum, env = "GWM_GIT_BACKEND")]
+    backend: Option<Backend>,
 }
 
 impl GcCommand {
     pub async fn execute(&self) -> Result<()> {
-        // Validate GITHUB_TOKEN early
-        std::env::var("GITHUB_TOKEN").map_err(|_| {
-            anyhow!(
-                "GITHUB_TOKEN environment variable not set. This is required to check PR merge status for garbage collection.\n\nSet it with: export GITHUB_TOKEN=your_token_here"
-            )
-        })?;
-
         let search_path = self.path.as_deref().unwrap_or(".");
+        let config = Config::load()?;
+        // Validate forge tokens early
+        if !config.forges().any_available() {
+            return Err(anyhow!(
+                "GITHUB_TOKEN environment variable not set. This is required to check PR merge status for garbage collection.\n\nSet it with: export GITHUB_TOKEN=your_token_here\nor log in with: gh auth login\nor, for Bitbucket or Azure DevOps: export BITBUCKET_TOKEN or AZURE_DEVOPS_EXT_PAT"
+            ));
+        }
+        let backend = commands::backend(self.backend, &config)?;
+        let notifier = Notifier::from_config(&config.notify)?;
 
         // Collect repositories with PR status
-        let repo_tasks = self.collect_repositories(search_path).await?;
-        let repo_task_results = try_join_all(repo_tasks).await?;
-
-        let mut repo_results = Vec::new();
-        for task_result in repo_task_results {
-            repo_results.push(task_result?);
-        }
+        let repo_results = scan::scan(
+            Path::new(search_path),
+            &ScanOptions::new()
+                .pr_status(true)
+                .pr_author(config.github.pr_author)
+                .forges(config.forges())
+                .backend(backend)
+                .include_slow(self.include_slow),
+        )
+        .await?;
 
         // Filter for GC candidates
-        let filter = WorktreeFilter::gc_candidates();
-        let candidates = WorktreeAnalyzer::filter_results(&repo_results, &filter);
+        let mut filter = WorktreeFilter::gc_candidates();
+        filter.branch_pattern = self.branch.clone();
+        if let Some(age) = &self.closed_older_than {
+            filter.gc_closed_older_than_days =
+                Some(WorktreeFilter::parse_age_to_days(age).map_err(|e| anyhow!(e))?);
+        }
+        let mut candidates = WorktreeAnalyzer::filter_results(&repo_results, &filter);
+        if self.squash_merged {
+            Self::add_squash_merged(&repo_results, &mut candidates, self.branch.as_deref());
+        }
 
         // Check if any candidates found
         if candidates.is_empty() {
-            println!("No worktrees eligible for garbage collection.");
-            println!("(Looking for worktrees that are clean or missing AND have merged PRs)");
+            say!("No worktrees eligible for garbage collection.");
+            say!("(Looking for worktrees that are clean or missing AND have merged PRs)");
             return Ok(());
         }
 
         // Display candidates
-        let use_emoji = !self.no_emoji;
-        println!("Garbage collection candidates:");
-        let table_output = table::create_table(&candidates, use_emoji, true);
-        println!("{}", table_output);
-        println!();
+        let use_emoji = !self.no_emoji && !output::is_quiet();
+        say!("Garbage collection candidates:");
+        let table_output = table::create_table(
+            &candidates,
+            &TableOptions::new()
+                .emoji(use_emoji)
+                .style(self.style.unwrap_or(config.table.style))
+                .dates(config.table.dates)
+                .widths(config.table.clone()),
+        );
+        say!("{}", table_output);
+        say!();
 
         let total_count: usize = candidates.iter().map(|r| r.worktrees.len()).sum();
+        let names: Vec<String> = candidates
+            .iter()
+            .flat_map(|repo_result| {
+                repo_result
+                    .worktrees
+                    .iter()
+                    .map(|worktree| format!("{}/{}", repo_result.name, worktree.branch))
+            })
+            .collect();
+        let title = if self.dry_run {
+            format!("gwm gc: {} worktree(s) can be removed", total_count)
+        } else {
+            format!("gwm gc: removing {} worktree(s)", total_count)
+        };
+        notifier.notify(&Notification::new(
+            Event::GcCandidates,
+            title,
+            names.join("\n"),
+        ));
 
         // Dry run check
         if self.dry_run {
-            if use_emoji {
-                println!("🔍 DRY RUN: Would remove {} worktree(s)", total_count);
+            if output::is_quiet() {
+                // The table was skipped, so name each candidate instead
+                for name in &names {
+                    println!("Would remove {}", name);
+                }
             } else {
-                println!("DRY RUN: Would remove {} worktree(s)", total_count);
+                let emoji = if use_emoji { "🔍 " } else { "" };
+                let branches = match (self.keep_branches, self.delete_remote) {
+                    (false, false) => " and delete their branches",
+                    (false, true) => {
+                        " and delete their branches, on origin too when their PR is merged"
+                    }
+                    (true, true) => " and delete the branches of merged PRs on origin",
+                    (true, false) => "",
+                };
+                println!(
+                    "{}DRY RUN: Would remove {} worktree(s){}",
+                    emoji, total_count, branches
+                );
             }
-            return Ok(());
+            return Err(ExitStatus(EXIT_CANDIDATES_FOUND).into());
         }
 
         // Perform removal (no confirmation - user intent is clear)
+        let mut removed = Vec::new();
+        let mut skipped = 0;
+        let mut not_archived = 0;
+        let archive = !self.no_archive && (self.archive || config.remove.archive);
+        let trash = if !self.no_trash && (self.trash || config.remove.trash) {
+            Some(Trash::open()?)
+        } else {
+            None
+        };
         for repo_result in &candidates {
             let repo = GitRepository::new(repo_result.path.to_str().unwrap(), SystemGitClient)?;
 
@@ -86,188 +210,157 @@ impl GcCommand {
                 let emoji = if use_emoji { "🗑️  " } else { "" };
                 println!("{}Removing {}/{}", emoji, repo_result.name, worktree.branch);
 
-                repo.remove_worktree(&worktree.branch)?;
+                // Without the tag the branch tip could be lost, so keep the worktree
+                if archive && let Err(e) = repo.archive_branch(&worktree.branch, &archive_date()) {
+                    warn!("Skipping {}/{}: {}", repo_result.name, worktree.branch, e);
+                    not_archived += 1;
+                    continue;
+                }
+
+                // A failed teardown keeps the worktree, since its resources may still be in use
+                let worktree_config =
+                    commands::with_template(&config, &repo_result.name, &worktree.path);
+                if let Some(command) = worktree_config.teardown(&repo_result.name)
+                    && worktree.path.exists()
+                    && let Err(e) = hooks::run_teardown(
+                        command,
+                        &repo_result.name,
+                        &worktree.branch,
+                        &worktree.path,
+                    )
+                {
+                    warn!("Skipping {}/{}: {}", repo_result.name, worktree.branch, e);
+                    notifier.notify(&Notification::new(
+                        Event::Failure,
+                        "gwm gc: teardown failed",
+                        e.to_string(),
+                    ));
+                    skipped += 1;
+                    continue;
+                }
+
+                commands::direnv(&config, &worktree.path, false);
+                let result = match &trash {
+                    Some(trash) if worktree.path.exists() => trash
+                        .remove_worktree(
+                            &repo_result.path,
+                            &repo_result.name,
+                            &worktree.branch,
+                            &worktree.path,
+                        )
+                        .map(|_| ()),
+                    _ => repo.remove_worktree(&worktree.branch),
+                };
+                if let Err(e) = result {
+                    notifier.notify(&Notification::new(
+                        Event::Failure,
+                        "gwm gc: removal failed",
+                        format!("{}/{}: {}", repo_result.name, worktree.branch, e),
+                    ));
+                    return Err(e);
+                }
+                removed.push((repo_result.name.clone(), worktree.branch.clone()));
+                commands::jump(&config, &worktree.path, false);
+
+                // The branch is merged, squash-merged or long abandoned; the backup ref keeps its
+                // commits reachable in case it was still wanted
+                if !self.keep_branches {
+                    match repo.delete_branch_with_backup(&worktree.branch) {
+                        Ok(backup) => {
+                            say!("Deleted branch {} (backup: {})", worktree.branch, backup)
+                        }
+                        Err(e) => warn!("Could not delete branch {}: {}", worktree.branch, e),
+                    }
+                }
+
+                // Unlike the local branch, origin keeps no backup, so only merged work goes
+                if self.delete_remote
+                    && repo.has_remote_branch(&format!("origin/{}", worktree.branch))
+                {
+                    match repo.delete_merged_remote_branch(
+                        &worktree.branch,
+                        "origin",
+                        worktree.status.pr_status.as_ref(),
+                    ) {
+                        Ok(true) => {}
+                        Ok(false) => say!(
+                            "Keeping origin/{}: its pull request is not merged",
+                            worktree.branch
+                        ),
+                        Err(e) => warn!("Could not delete origin/{}: {}", worktree.branch, e),
+                    }
+                }
             }
         }
+        usage::record(&config, |stats, now| stats.record_gc(&removed, now));
 
         let emoji = if use_emoji { "✅ " } else { "" };
-        println!("{}Successfully removed {} worktree(s)", emoji, total_count);
+        say!(
+            "{}Successfully removed {} worktree(s)",
+            emoji,
+            removed.len()
+        );
+        if not_archived > 0 {
+            return Err(anyhow!(
+                "{} worktree(s) kept because their branch could not be archived",
+                not_archived
+            ));
+        }
+        if skipped > 0 {
+            return Err(anyhow!(
+                "{} worktree(s) kept because their teardown command failed; use `gwm remove --force` to remove them anyway",
+                skipped
+            ));
+        }
 
         Ok(())
     }
 
-    async fn collect_repositories(
-        &self,
-        search_path: &str,
-    ) -> Result<Vec<tokio::task::JoinHandle<Result<RepoResult>>>> {
-        let mut repo_tasks = Vec::new();
-        let entries = fs::read_dir(search_path)?;
-
-        for entry in entries {
-            let entry = entry?;
-            let path = entry.path();
-
-            if !path.is_dir() {
+    /// Add clean or missing worktrees, neither locked nor mid-rebase or merge, whose branch landed in the default branch without a
+    /// merged PR, e.g. squash-merged by hand or through a PR from another branch
+    fn add_squash_merged(
+        repo_results: &[RepoResult],
+        candidates: &mut Vec<RepoResult>,
+        branch_pattern: Option<&str>,
+    ) {
+        for repo_result in repo_results {
+            let Ok(repo) = GitRepository::new(&repo_result.path.to_string_lossy(), SystemGitClient)
+            else {
                 continue;
-            }
-
-            let git_path = path.join(".git");
-            if !git_path.exists() {
+            };
+            let Some(into) = repo.base_branch() else {
                 continue;
-            }
-
-            let path_str = path.to_str().unwrap().to_string();
-
-            let task = tokio::spawn(async move { Self::process_repository(path_str).await });
-            repo_tasks.push(task);
-        }
-
-        Ok(repo_tasks)
-    }
-
-    async fn process_repository(repo_path: String) -> Result<RepoResult> {
-        let repo_name = Path::new(&repo_path)
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("unknown")
-            .to_string();
-
-        let repo = GitRepository::new(&repo_path, SystemGitClient)?;
-
-        // Check if it's a bare repository
-        if !repo.is_bare().unwrap_or(false) {
-            return Ok(RepoResult {
-                name: repo_name,
-                path: PathBuf::from(&repo_path),
-                worktrees: Vec::new(),
-            });
-        }
-
-        // Get worktree list for this repo
-        let worktrees = repo.list_worktrees()?;
-
-        if worktrees.is_empty() {
-            return Ok(RepoResult {
-                name: repo_name,
-                path: PathBuf::from(&repo_path),
-                worktrees: Vec::new(),
-            });
-        }
-
-        // Fetch PR data
-        let pr_matches: HashMap<String, PrStatus> =
-            Self::fetch_pr_data_for_repo(&repo_path, &worktrees).await?;
-
-        // Process all worktrees for this repo
-        let mut worktree_results = Vec::new();
-        for worktree in worktrees {
-            // Get all status information
-            let local_status = repo.get_local_status(&worktree.path)?;
-            let commit_timestamp = repo
-                .get_last_commit_timestamp(&worktree.path, &worktree.branch)
-                .unwrap_or(0);
-            let directory_mtime = repo.get_directory_mtime(&worktree.path).unwrap_or(0);
-            let commit_summary = repo
-                .get_commit_summary(&worktree.path, &worktree.branch)
-                .unwrap_or_else(|_| "<no commit>".to_string());
-
-            // Get PR status for this branch
-            let pr_status = pr_matches.get(&worktree.branch).cloned();
-
-            worktree_results.push(WorktreeResult {
-                branch: worktree.branch.clone(),
-                status: WorktreeStatus {
-                    local_status,
-                    commit_timestamp,
-                    directory_mtime,
-                    commit_summary,
-                    pr_status,
-                },
-            });
-        }
+            };
 
-        Ok(RepoResult {
-            name: repo_name,
-            path: PathBuf::from(&repo_path),
-            worktrees: worktree_results,
-        })
-    }
-
-    async fn fetch_pr_data_for_repo(
-        repo_path: &str,
-        worktrees: &[crate::git::WorktreeInfo],
-    ) -> Result<HashMap<String, PrStatus>> {
-        // Validate GITHUB_TOKEN is present
-        std::env::var("GITHUB_TOKEN")
-            .map_err(|_| anyhow!("GITHUB_TOKEN environment variable not set"))?;
-
-        // Create a new repo instance for this async context
-        let repo = GitRepository::new(repo_path, SystemGitClient)?;
-
-        // Get upstream remote URL
-        let remote_url = repo
-            .get_upstream_remote_url()?
-            .ok_or_else(|| anyhow!("No upstream or origin remote found"))?;
-
-        // Parse GitHub repo from URL
-        let github_repo = github::parse_github_url(&remote_url)?;
-
-        eprintln!(
-            "[PR Fetch] Processing repository: {} ({})",
-            Path::new(repo_path).file_name().unwrap().to_string_lossy(),
-            remote_url
-        );
-
-        // Determine the earliest worktree creation time
-        let since_timestamp = Self::get_earliest_worktree_time(repo_path, worktrees).await?;
-
-        let since_date = chrono::DateTime::from_timestamp(since_timestamp, 0)
-            .map(|dt| dt.format("%Y-%m-%d").to_string())
-            .unwrap_or_else(|| "unknown".to_string());
-
-        eprintln!("[PR Fetch] Looking for PRs created since: {}", since_date);
-
-        // Create GitHub client
-        let github_client = octocrab::Octocrab::builder()
-            .personal_token(std::env::var("GITHUB_TOKEN")?)
-            .build()?;
-
-        // Fetch PRs for this repository
-        let prs = github::fetch_prs_for_repo(&github_client, &github_repo, since_timestamp).await?;
-
-        // Extract branch names from worktrees
-        let branch_names: Vec<String> = worktrees.iter().map(|wt| wt.branch.clone()).collect();
-
-        // Match worktrees to PRs
-        let matches = github::match_worktrees_to_prs(&branch_names, &prs);
-        eprintln!("[PR Fetch] Matched {} worktrees to PRs\n", matches.len());
-
-        Ok(matches)
-    }
-
-    async fn get_earliest_worktree_time(
-        repo_path: &str,
-        worktrees: &[crate::git::WorktreeInfo],
-    ) -> Result<i64> {
-        let repo = GitRepository::new(repo_path, SystemGitClient)?;
-        let mut earliest_time: Option<i64> = None;
-
-        for worktree in worktrees {
-            if let Ok(Some(birth_time)) = repo.get_worktree_birth_time(&worktree.path) {
-                earliest_time = Some(match earliest_time {
-                    None => birth_time,
-                    Some(current) => current.min(birth_time),
+            for worktree in &repo_result.worktrees {
+                let eligible = matches!(
+                    worktree.status.local_status,
+                    LocalStatus::Clean | LocalStatus::Missing
+                ) && worktree.status.locked.is_none()
+                    && worktree.status.operation.is_none()
+                    && branch_pattern.is_none_or(|p| glob_matches(p, &worktree.branch));
+                let already = candidates.iter().any(|c| {
+                    c.name == repo_result.name
+                        && c.worktrees.iter().any(|w| w.branch == worktree.branch)
                 });
+                if !eligible
+                    || already
+                    || !repo
+                        .is_merged_into(&worktree.branch, &into)
+                        .unwrap_or(false)
+                {
+                    continue;
+                }
+
+                match candidates.iter_mut().find(|c| c.name == repo_result.name) {
+                    Some(candidate) => candidate.worktrees.push(worktree.clone()),
+                    None => candidates.push(RepoResult {
+                        name: repo_result.name.clone(),
+                        path: repo_result.path.clone(),
+                        worktrees: vec![worktree.clone()],
+                    }),
+                }
             }
         }
-
-        // If we have a birth time, use it; otherwise fall back to 1 week ago
-        Ok(earliest_time.unwrap_or_else(|| {
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs() as i64
-                - (7 * 24 * 60 * 60)
-        }))
     }
 }
@@ -0,0 +1,174 @@
+use anyhow::{Result, anyhow};
+use clap::Args;
+use clap_complete::ArgValueCandidates;
+use std::path::Path;
+
+use crate::commands::{self, completion};
+use gwm::azure_devops;
+use gwm::config::Config;
+use gwm::git::{Backend, GitRepository, SystemGitClient};
+use gwm::output::table::{self, Column, TableOptions, format_size};
+use gwm::output::{self, Tone};
+use gwm::scan::{self, ScanOptions, dir_size};
+
+#[derive(Args)]
+pub struct InfoCommand {
+    /// Repository name
+    #[arg(add = ArgValueCandidates::new(completion::repo_candidates))]
+    repo: String,
+
+    /// Directory to search for repositories (defaults to current directory)
+    /// Can also be set via GWM_REPOS_PATH environment variable
+    #[arg(short, long, env = "GWM_REPOS_PATH")]
+    path: Option<String>,
+
+    /// Wait for repositories on slow or unreachable network mounts instead of skipping them
+    #[arg(long)]
+    include_slow: bool,
+
+    /// Disable emoji in status output
+    #[arg(long)]
+    no_emoji: bool,
+
+    /// Disable PR status fetching from GitHub (skipped anyway without GITHUB_TOKEN)
+    #[arg(long)]
+    no_pr_status: bool,
+
+    /// Library used to read worktree status (overrides the config file)
+    #[arg(long, value_enum, env = "GWM_GIT_BACKEND")]
+    backend: Option<Backend>,
+}
+
+impl InfoCommand {
+    pub async fn execute(&self) -> Result<()> {
+        let search_path = self.path.as_deref().unwrap_or(".");
+        let config = Config::load()?;
+        let backend = commands::backend(self.backend, &config)?;
+
+        let repo_path = scan::discover_repositories(Path::new(search_path), self.include_slow)?
+            .into_iter()
+            .find(|path| scan::repo_name(path) == self.repo)
+            .ok_or_else(|| anyhow!("No repository found with name '{}'", self.repo))?;
+
+        let show_pr_status = !self.no_pr_status && config.forges().any_available();
+        let repo_result = scan::scan_repository(
+            &repo_path,
+            &ScanOptions::new()
+                .pr_status(show_pr_status)
+                .pr_author(config.github.pr_author)
+                .forges(config.forges())
+                .backend(backend),
+        )
+        .await?;
+
+        let repo = GitRepository::new(&repo_path.to_string_lossy(), SystemGitClient)?;
+
+        println!("{}", output::paint(&repo_result.name, Tone::Info));
+        println!("  Path:           {}", repo_path.display());
+        println!(
+            "  Default branch: {}",
+            repo.default_branch().as_deref().unwrap_or("-")
+        );
+        println!(
+            "  Last fetch:     {}",
+            repo.last_fetch_time()
+                .map(format_timestamp)
+                .unwrap_or_else(|| "never".to_string())
+        );
+        println!("  Forge:          {}", forge(&repo, &config)?);
+
+        println!();
+        println!("Remotes:");
+        let remotes = repo.remotes()?;
+        if remotes.is_empty() {
+            println!("  (none)");
+        }
+        for (name, url) in remotes {
+            println!("  {:<10} {}", name, url);
+        }
+
+        println!();
+        println!("Worktrees:");
+        let columns = Column::ALL
+            .into_iter()
+            .filter(|column| *column != Column::Repository)
+            .collect();
+        let table_output = table::create_table(
+            std::slice::from_ref(&repo_result),
+            &TableOptions::new()
+                .emoji(!self.no_emoji && !output::is_quiet())
+                .pr_status(show_pr_status)
+                .columns(columns)
+                .style(config.table.style)
+                .dates(config.table.dates)
+                .widths(config.table.clone()),
+        );
+        for line in table_output.lines() {
+            println!("  {}", line);
+        }
+
+        println!();
+        println!("Disk usage:");
+        println!(
+            "  {:<24} {}",
+            "repository directory",
+            format_size(dir_size(&repo_path))
+        );
+        for worktree in &repo_result.worktrees {
+            // Worktrees inside the repository directory are already part of its total
+            let note = if worktree.path.starts_with(&repo_path) {
+                " (included above)"
+            } else {
+                ""
+            };
+            println!(
+                "  {:<24} {}{}",
+                worktree.branch,
+                format_size(dir_size(&worktree.path)),
+                note
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// The GitHub, Bitbucket or Azure DevOps repository the upstream (or origin) remote points at
+fn forge(repo: &GitRepository<SystemGitClient>, config: &Config) -> Result<String> {
+    let Some(url) = repo.get_upstream_remote_url()? else {
+        return Ok("none (no upstream or origin remote)".to_string());
+    };
+    if let Some(bitbucket_repo) = config.bitbucket.parse_url(&url) {
+        return Ok(format!(
+            "Bitbucket {}/{} on {}",
+            bitbucket_repo.owner, bitbucket_repo.slug, bitbucket_repo.host
+        ));
+    }
+    if let Some(azure_repo) = azure_devops::parse_azure_devops_url(&url) {
+        return Ok(format!(
+            "Azure DevOps {}/{}/{}",
+            azure_repo.organization, azure_repo.project, azure_repo.repo
+        ));
+    }
+    Ok(match config.github.credentials().parse_url(&url) {
+        Ok(github_repo) => format!(
+            "GitHub {}/{} (https://{}/{}/{})",
+            github_repo.owner,
+            github_repo.repo,
+            github_repo.host,
+            github_repo.owner,
+            github_repo.repo
+        ),
+        Err(_) => "none (remote is not on GitHub, Bitbucket or Azure DevOps)".to_string(),
+    })
+}
+
+fn format_timestamp(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|dt| {
+            dt.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_else(|| "unknown".to_string())
+}
@@ -1,16 +1,40 @@
-use anyhow::{Result, anyhow};
+use anyhow::Result;
 use clap::Args;
-use futures::future::try_join_all;
-use std::collections::HashMap;
-use std::fs;
-use std::path::{Path, PathBuf};
-
-use crate::core::{
-    PrStatus, RepoResult, WorktreeAnalyzer, WorktreeFilter, WorktreeResult, WorktreeStatus,
-};
-use crate::git::{GitRepository, SystemGitClient};
-use crate::github;
-use crate::output::table;
+use clap_complete::ArgValueCandidates;
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::commands::{self, ExitStatus, completion};
+use gwm::config::Config;
+use gwm::core::expr::FilterExpr;
+use gwm::core::{MatchMode, RepoResult, WorktreeAnalyzer, WorktreeFilter};
+use gwm::git::Backend;
+use gwm::github::PrAuthor;
+use gwm::output::table::{self, DateStyle, GroupBy, TableOptions, TableStyle};
+use gwm::output::{self, OutputFormat};
+use gwm::output::{json, shell};
+use gwm::say;
+use gwm::scan::{self, ScanOptions};
+use gwm::snapshot::Snapshot;
+use gwm::usage::history;
+use tracing::debug;
+
+/// Exit status when `--fail-if-matches` or `--fail-if-empty` triggers
+/// Distinct from 1, which anyhow uses for errors
+pub const EXIT_CONDITION_MET: i32 = 10;
+
+/// How often `--watch` looks pull requests up again; local status refreshes every interval
+const PR_REFRESH: Duration = Duration::from_secs(5 * 60);
+
+/// What `list --sort` orders worktrees by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum SortKey {
+    /// Time of the last commit
+    Age,
+    /// Time of the last commit, checkout, reset or staging in the worktree
+    Activity,
+}
 
 #[derive(Args)]
 pub struct ListCommand {
@@ -18,6 +42,14 @@ pub struct ListCommand {
     /// Can also be set via GWM_REPOS_PATH environment variable
     #[arg(short, long, env = "GWM_REPOS_PATH")]
     path: Option<String>,
+
+    /// Wait for repositories on slow or unreachable network mounts instead of skipping them
+    #[arg(long)]
+    include_slow: bool,
+
+    /// Also list main and master worktrees, to check that they are clean and up to date
+    #[arg(long)]
+    all: bool,
     /// Disable emoji in status output
     #[arg(long)]
     no_emoji: bool,
@@ -32,12 +64,20 @@ pub struct ListCommand {
     /// Show only active branches (not-merged, newer than 7 days)
     #[arg(long)]
     active: bool,
-    /// Show only branches needing attention (diverged, behind, or missing)
+    /// Show only branches needing attention (diverged, behind, missing, conflicted or mid-rebase)
     #[arg(long)]
     needs_attention: bool,
     /// Show only stale branches (older than 30 days)
     #[arg(long)]
     stale: bool,
+    /// Use a filter preset defined as `[filters.<NAME>]` in the config file
+    #[arg(
+        long,
+        value_name = "NAME",
+        conflicts_with_all = ["prune_candidates", "active", "needs_attention", "stale"],
+        add = ArgValueCandidates::new(completion::preset_candidates)
+    )]
+    preset: Option<String>,
 
     // Local status filters
     /// Show only branches with dirty working directories
@@ -49,22 +89,243 @@ pub struct ListCommand {
     /// Show only branches with staged changes
     #[arg(long)]
     staged: bool,
+    /// Show only branches with unresolved merge conflicts
+    #[arg(long)]
+    conflicted: bool,
     /// Show only branches with missing worktree directories
     #[arg(long)]
     missing: bool,
 
-    // Age filters
-    /// Show only branches older than the specified time (e.g., 30, 30d, 1w, 2m)
+    // PR status filters
+    /// Show only branches with an open pull request
+    #[arg(long)]
+    pr_open: bool,
+    /// Show only branches with a draft pull request
+    #[arg(long)]
+    pr_draft: bool,
+    /// Show only branches whose pull request is merged
+    #[arg(long)]
+    pr_merged: bool,
+    /// Show only branches without a pull request
+    #[arg(long)]
+    pr_none: bool,
+
+    // Excluded statuses
+    /// Hide branches with dirty working directories
+    #[arg(long, conflicts_with = "dirty")]
+    not_dirty: bool,
+    /// Hide branches with clean working directories
+    #[arg(long, conflicts_with = "clean")]
+    not_clean: bool,
+    /// Hide branches with staged changes
+    #[arg(long, conflicts_with = "staged")]
+    not_staged: bool,
+    /// Hide branches with unresolved merge conflicts
+    #[arg(long, conflicts_with = "conflicted")]
+    not_conflicted: bool,
+    /// Hide branches with missing worktree directories
+    #[arg(long, conflicts_with = "missing")]
+    not_missing: bool,
+    /// Hide branches with an open pull request
+    #[arg(long, conflicts_with = "pr_open")]
+    not_pr_open: bool,
+    /// Hide branches with a draft pull request
+    #[arg(long, conflicts_with = "pr_draft")]
+    not_pr_draft: bool,
+    /// Hide branches whose pull request is merged
+    #[arg(long, conflicts_with = "pr_merged")]
+    not_pr_merged: bool,
+    /// Hide branches without a pull request
+    #[arg(long, conflicts_with = "pr_none")]
+    not_pr_none: bool,
+
+    /// Combine status and age filters: `any` shows worktrees matching at least one, `all` only
+    /// those matching every one (e.g. `--dirty --pr-merged --match all`). By default statuses of
+    /// one kind are alternatives and the kinds must all match
+    #[arg(
+        long = "match",
+        value_enum,
+        value_name = "MODE",
+        conflicts_with_all = ["prune_candidates", "active", "needs_attention", "stale"]
+    )]
+    match_mode: Option<MatchMode>,
+
+    // Age filters, measured from the last commit or later activity in the worktree
+    /// Show only branches not worked on for the specified time (e.g., 30, 30d, 1w, 2m)
     #[arg(long)]
     older_than: Option<String>,
-    /// Show only branches newer than the specified time (e.g., 30, 30d, 1w, 2m)
+    /// Show only branches worked on within the specified time (e.g., 30, 30d, 1w, 2m)
     #[arg(long)]
     newer_than: Option<String>,
+
+    /// Order worktrees within each repository, newest first, by last commit (`age`) or by
+    /// last activity including checkouts and staging (`activity`, which adds a Last Active column)
+    #[arg(long, value_enum, value_name = "KEY")]
+    sort: Option<SortKey>,
+
+    /// Recompute every worktree's status instead of reusing the status cache
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Ask GitHub for PR statuses even when `gwm daemon` has a fresh snapshot
+    #[arg(long)]
+    no_snapshot: bool,
+
+    /// Output format; `shell` prints variables for `eval "$(gwm list --format shell)"`,
+    /// `json` and `ndjson` follow the versioned schema described in the README
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    format: OutputFormat,
+
+    /// Table border style (overrides the config file)
+    #[arg(long, value_enum)]
+    style: Option<TableStyle>,
+
+    /// Show commit times as ages, or as dates in the local timezone (overrides the config file)
+    #[arg(long, value_enum, value_name = "STYLE")]
+    dates: Option<DateStyle>,
+
+    /// Add a column with each branch's added and deleted lines and changed files since it forked
+    /// from the default branch
+    #[arg(long)]
+    diffstat: bool,
+
+    /// Add a column with the disk space each worktree directory uses (see also `gwm du`)
+    #[arg(long)]
+    size: bool,
+
+    /// Add a column with each worktree's full directory
+    #[arg(long)]
+    path_column: bool,
+
+    /// Add a column with the ticket ID in each branch name, e.g. PROJ-123 (see `[table.tickets]`)
+    #[arg(long)]
+    ticket_column: bool,
+
+    /// Print only the absolute path of each matching worktree, one per line, for piping into
+    /// `xargs`, `fzf` or shell loops
+    #[arg(long, conflicts_with_all = ["format", "summary", "watch", "group_by"])]
+    paths: bool,
+
+    /// Show long commit summaries and branch names in full instead of cutting them to fit the
+    /// terminal
+    #[arg(long)]
+    wide: bool,
+
+    /// Exit with status 10 when any worktree matches, e.g. `--needs-attention --fail-if-matches` in CI
+    #[arg(long, conflicts_with = "fail_if_empty")]
+    fail_if_matches: bool,
+
+    /// Exit with status 10 when no worktree matches
+    #[arg(long)]
+    fail_if_empty: bool,
+
+    /// Re-scan and redraw every SECONDS (default 10) until interrupted, emphasizing changed worktrees
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        num_args = 0..=1,
+        default_missing_value = "10",
+        value_parser = clap::value_parser!(u64).range(1..),
+        conflicts_with_all = ["format", "fail_if_matches", "fail_if_empty"]
+    )]
+    watch: Option<u64>,
+
+    /// Print only status counts per repository and overall instead of the worktree table
+    #[arg(long, conflicts_with_all = ["format", "group_by"])]
+    summary: bool,
+
+    /// Print each repository (`repo`) or triage section (`status`) once as a header with its
+    /// worktrees nested underneath
+    #[arg(long, value_enum, default_value_t = GroupBy::None)]
+    group_by: GroupBy,
+
+    /// Whose pull requests to match to branches: only yours, or also teammates' and bots'
+    /// (overrides the config file)
+    #[arg(long, value_enum, value_name = "AUTHOR")]
+    pr_author: Option<PrAuthor>,
+
+    /// Library used to read worktree status (overrides the config file)
+    #[arg(long, value_enum, env = "GWM_GIT_BACKEND")]
+    backend: Option<Backend>,
+
+    // Branch name filter
+    /// Show only branches matching a glob pattern (e.g., 'jml/*', '*-experiment')
+    #[arg(long, value_name = "GLOB")]
+    branch: Option<String>,
+
+    /// Show only branches matching an expression, on top of any other filter
+    /// (e.g. `clean && age > 30d && (merged || no_pr)`)
+    #[arg(long, value_name = "EXPR")]
+    filter: Option<String>,
 }
 
 impl ListCommand {
     /// Build a WorktreeFilter from command line arguments
-    fn build_filter(&self) -> Result<WorktreeFilter> {
+    fn build_filter(&self, config: &Config) -> Result<WorktreeFilter> {
+        let mut filter = self.build_status_filter(config)?;
+        if self.branch.is_some() {
+            filter.branch_pattern = self.branch.clone();
+        }
+        if let Some(text) = &self.filter {
+            let expression: FilterExpr = text
+                .parse()
+                .map_err(|e| anyhow::anyhow!("Invalid --filter expression: {}", e))?;
+            // Both a preset's expression and this one must hold
+            filter.expression = Some(match filter.expression.take() {
+                Some(preset) => FilterExpr::And(Box::new(preset), Box::new(expression)),
+                None => expression,
+            });
+        }
+
+        // PR status filters apply on top of the presets
+        for (flag, value) in [
+            (
+                &mut filter.pr_open,
+                status_flag(self.pr_open, self.not_pr_open),
+            ),
+            (
+                &mut filter.pr_draft,
+                status_flag(self.pr_draft, self.not_pr_draft),
+            ),
+            (
+                &mut filter.pr_merged,
+                status_flag(self.pr_merged, self.not_pr_merged),
+            ),
+            (
+                &mut filter.pr_none,
+                status_flag(self.pr_none, self.not_pr_none),
+            ),
+        ] {
+            if value.is_some() {
+                *flag = value;
+            }
+        }
+        if filter.uses_pr_status() && self.no_pr_status {
+            return Err(anyhow::anyhow!(
+                "PR status filters need PR statuses; drop --no-pr-status"
+            ));
+        }
+
+        // So do excluded local statuses, which only ever narrow the list
+        for (flag, excluded) in [
+            (&mut filter.dirty, self.not_dirty),
+            (&mut filter.clean, self.not_clean),
+            (&mut filter.staged, self.not_staged),
+            (&mut filter.conflicted, self.not_conflicted),
+            (&mut filter.missing, self.not_missing),
+        ] {
+            if excluded {
+                *flag = Some(false);
+            }
+        }
+        if self.match_mode.is_some() {
+            filter.match_mode = self.match_mode;
+        }
+        Ok(filter)
+    }
+
+    /// Build the status and age part of the filter
+    fn build_status_filter(&self, config: &Config) -> Result<WorktreeFilter> {
         // Handle preset filters first (they override individual filters)
         if self.prune_candidates {
             return Ok(WorktreeFilter::prune_candidates());
@@ -78,6 +339,9 @@ impl ListCommand {
         if self.stale {
             return Ok(WorktreeFilter::stale());
         }
+        if let Some(name) = &self.preset {
+            return config.filter_preset(name);
+        }
 
         // Build custom filter from individual flags
         let mut filter = WorktreeFilter::new();
@@ -92,6 +356,9 @@ impl ListCommand {
         if self.staged {
             filter.staged = Some(true);
         }
+        if self.conflicted {
+            filter.conflicted = Some(true);
+        }
         if self.missing {
             filter.missing = Some(true);
         }
@@ -113,54 +380,258 @@ impl ListCommand {
     }
 
     pub async fn execute(&self) -> Result<()> {
-        let search_path = self.path.as_deref().unwrap_or(".");
+        let config = Config::load()?;
 
         // Build filter from command line arguments
-        let filter = self.build_filter()?;
+        let filter = self.build_filter(&config)?;
 
-        // Find all repositories
-        let repo_tasks = self
-            .collect_repositories(search_path, !self.no_pr_status)
-            .await?;
+        let backend = commands::backend(self.backend, &config)?;
 
-        // Process repositories in parallel
-        let repo_task_results = try_join_all(repo_tasks).await?;
+        if let Some(interval) = self.watch {
+            return self.watch(&config, &filter, backend, interval).await;
+        }
 
-        // Unwrap the results from the join handles
-        let mut repo_results = Vec::new();
-        for task_result in repo_task_results {
-            repo_results.push(task_result?);
+        let repo_results = self.scan(&config, backend, !self.no_pr_status).await?;
+        history::record(&config, &repo_results);
+        let filtered_results = self.apply_filter(repo_results, &filter);
+
+        self.display(&config, &filtered_results, HashSet::new())?;
+
+        let match_count: usize = filtered_results.iter().map(|r| r.worktrees.len()).sum();
+        if (self.fail_if_matches && match_count > 0) || (self.fail_if_empty && match_count == 0) {
+            return Err(ExitStatus(EXIT_CONDITION_MET).into());
         }
 
-        // Apply filtering if any filters are active
-        let filtered_results = if self.has_filters() {
-            WorktreeAnalyzer::filter_results(&repo_results, &filter)
+        Ok(())
+    }
+
+    /// Scan every repository, optionally looking up pull requests
+    async fn scan(
+        &self,
+        config: &Config,
+        backend: Backend,
+        pr_status: bool,
+    ) -> Result<Vec<RepoResult>> {
+        let search_path = self.path.as_deref().unwrap_or(".");
+
+        // Take pull request statuses from a fresh `gwm daemon` snapshot instead of asking GitHub
+        let snapshot = if pr_status && !self.no_snapshot {
+            Snapshot::load(Path::new(search_path))
+                .filter(|snapshot| snapshot.is_fresh(chrono::Utc::now().timestamp()))
+        } else {
+            None
+        };
+
+        let mut repo_results = scan::scan(
+            Path::new(search_path),
+            &ScanOptions::new()
+                .pr_status(pr_status && snapshot.is_none())
+                .pr_author(self.pr_author.unwrap_or(config.github.pr_author))
+                .forges(config.forges())
+                .backend(backend)
+                .cache(!self.no_cache)
+                .include_slow(self.include_slow)
+                .include_main(self.all)
+                .diffstat(self.diffstat)
+                .size(self.size),
+        )
+        .await?;
+
+        if let Some(snapshot) = snapshot {
+            debug!(
+                "Using PR statuses from the daemon snapshot taken at {}",
+                snapshot.taken_at
+            );
+            WorktreeAnalyzer::carry_over_pr_statuses(&snapshot.repos, &mut repo_results);
+        }
+
+        Ok(repo_results)
+    }
+
+    /// Apply filtering if any filters are active
+    fn apply_filter(
+        &self,
+        repo_results: Vec<RepoResult>,
+        filter: &WorktreeFilter,
+    ) -> Vec<RepoResult> {
+        let mut repo_results = if self.has_filters() {
+            WorktreeAnalyzer::filter_results(&repo_results, filter)
         } else {
             repo_results
         };
+        if let Some(sort) = self.sort {
+            for repo_result in &mut repo_results {
+                repo_result.worktrees.sort_by_key(|worktree| {
+                    std::cmp::Reverse(match sort {
+                        SortKey::Age => worktree.status.commit_timestamp,
+                        SortKey::Activity => worktree.status.last_activity(),
+                    })
+                });
+            }
+        }
+        repo_results
+    }
+
+    /// Redraw the results every `interval` seconds until interrupted, emphasizing changes
+    /// Pull requests are looked up only every PR_REFRESH, to stay within GitHub's rate limits
+    async fn watch(
+        &self,
+        config: &Config,
+        filter: &WorktreeFilter,
+        backend: Backend,
+        interval: u64,
+    ) -> Result<()> {
+        let mut previous: Option<Vec<RepoResult>> = None;
+        let mut last_pr_fetch: Option<Instant> = None;
+
+        loop {
+            let fetch_prs = !self.no_pr_status
+                && last_pr_fetch.is_none_or(|fetched| fetched.elapsed() >= PR_REFRESH);
+            let scanned = self.scan(config, backend, fetch_prs).await;
+
+            // Clear the screen and move the cursor home
+            print!("\x1b[2J\x1b[H");
+            say!(
+                "Every {}s: gwm list    {}",
+                interval,
+                chrono::Local::now().format("%H:%M:%S")
+            );
+            say!();
+
+            match scanned {
+                Ok(mut repo_results) => {
+                    if fetch_prs {
+                        last_pr_fetch = Some(Instant::now());
+                    } else if let Some(previous) = &previous {
+                        WorktreeAnalyzer::carry_over_pr_statuses(previous, &mut repo_results);
+                    }
+                    let changed = previous
+                        .as_ref()
+                        .map(|previous| {
+                            WorktreeAnalyzer::changed_worktrees(previous, &repo_results)
+                        })
+                        .unwrap_or_default();
+
+                    let filtered_results = self.apply_filter(repo_results.clone(), filter);
+                    self.display(config, &filtered_results, changed.clone())?;
+
+                    if !changed.is_empty() {
+                        let mut changed: Vec<String> = changed.into_iter().collect();
+                        changed.sort();
+                        say!();
+                        say!("Changed since last refresh: {}", changed.join(", "));
+                    }
+                    previous = Some(repo_results);
+                }
+                // Keep watching through transient failures such as a network blip
+                Err(e) => println!("Refresh failed: {:#}", e),
+            }
+
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+        }
+    }
+
+    /// Print the results in the chosen format
+    fn display(
+        &self,
+        config: &Config,
+        filtered_results: &[RepoResult],
+        highlight: HashSet<String>,
+    ) -> Result<()> {
+        if self.paths {
+            for worktree in filtered_results.iter().flat_map(|r| &r.worktrees) {
+                let path =
+                    std::path::absolute(&worktree.path).unwrap_or_else(|_| worktree.path.clone());
+                println!("{}", path.display());
+            }
+            return Ok(());
+        }
+
+        match self.format {
+            OutputFormat::Shell => {
+                println!("{}", shell::create_shell_vars(filtered_results));
+                return Ok(());
+            }
+            OutputFormat::Json => {
+                println!("{}", json::create_json(filtered_results)?);
+                return Ok(());
+            }
+            OutputFormat::Ndjson => {
+                let lines = json::create_ndjson(filtered_results)?;
+                if !lines.is_empty() {
+                    println!("{}", lines);
+                }
+                return Ok(());
+            }
+            OutputFormat::Table => {}
+        }
 
         // Use pure functional core to analyze results
         let (total_wip, repos_with_wip, _status_counters, _wip_branches) =
-            WorktreeAnalyzer::analyze(&filtered_results);
+            WorktreeAnalyzer::analyze(filtered_results);
+
+        if self.summary {
+            let counters = WorktreeAnalyzer::counters_by_repo(filtered_results);
+            if !counters.is_empty() || !output::is_quiet() {
+                output::print_paged(&table::create_summary_table(
+                    &counters,
+                    !self.no_pr_status,
+                    self.style.unwrap_or(config.table.style),
+                ));
+            }
+            if self.has_filters() && !counters.is_empty() {
+                say!();
+                say!("Filters applied: {}", self.describe_filters());
+            }
+            return Ok(());
+        }
 
         // Display results as table
-        let use_emoji = !self.no_emoji;
+        let use_emoji = !self.no_emoji && !output::is_quiet();
         let show_pr_status = !self.no_pr_status;
-        let table_output = table::create_table(&filtered_results, use_emoji, show_pr_status);
-        println!("{}", table_output);
+        let table_output = table::create_table(
+            filtered_results,
+            &TableOptions::new()
+                .emoji(use_emoji)
+                .pr_status(show_pr_status)
+                .style(self.style.unwrap_or(config.table.style))
+                .group_by(self.group_by)
+                .highlight(highlight)
+                .dates(self.dates.unwrap_or(config.table.dates))
+                .diffstat(self.diffstat)
+                .size(self.size)
+                .activity(self.sort == Some(SortKey::Activity))
+                .path(self.path_column)
+                .ticket(self.ticket_column || config.table.tickets.show)
+                .max_width(if self.wide {
+                    None
+                } else {
+                    output::terminal_width()
+                })
+                .widths(config.table.clone()),
+        );
+        // In quiet mode an empty result prints nothing at all
+        if total_wip > 0 || !output::is_quiet() {
+            // `--watch` redraws the screen itself
+            if self.watch.is_some() {
+                println!("{}", table_output);
+            } else {
+                output::print_paged(&table_output);
+            }
+        }
 
         // Simple summary
         if total_wip > 0 {
-            println!();
-            println!("Total WIP branches: {}", total_wip);
-            println!("Repositories with WIP: {}", repos_with_wip);
+            say!();
+            say!("Total WIP branches: {}", total_wip);
+            say!("Repositories with WIP: {}", repos_with_wip);
 
             // Show active filters if any
             if self.has_filters() {
-                println!("Filters applied: {}", self.describe_filters());
+                say!("Filters applied: {}", self.describe_filters());
             }
         } else if self.has_filters() {
-            println!("No branches match the specified filters.");
+            say!("No branches match the specified filters.");
         }
 
         Ok(())
@@ -172,12 +643,33 @@ impl ListCommand {
             || self.active
             || self.needs_attention
             || self.stale
+            || self.preset.is_some()
             || self.dirty
             || self.clean
             || self.staged
+            || self.conflicted
             || self.missing
+            || self.not_dirty
+            || self.not_clean
+            || self.not_staged
+            || self.not_conflicted
+            || self.not_missing
+            || self.has_pr_filters()
             || self.older_than.is_some()
             || self.newer_than.is_some()
+            || self.branch.is_some()
+            || self.filter.is_some()
+    }
+
+    fn has_pr_filters(&self) -> bool {
+        self.pr_open
+            || self.pr_draft
+            || self.pr_merged
+            || self.pr_none
+            || self.not_pr_open
+            || self.not_pr_draft
+            || self.not_pr_merged
+            || self.not_pr_none
     }
 
     /// Describe active filters for user feedback
@@ -197,6 +689,9 @@ impl ListCommand {
         if self.stale {
             filters.push("stale".to_string());
         }
+        if let Some(name) = &self.preset {
+            filters.push(format!("preset={}", name));
+        }
 
         // Individual filters
         if self.dirty {
@@ -208,9 +703,39 @@ impl ListCommand {
         if self.staged {
             filters.push("staged".to_string());
         }
+        if self.conflicted {
+            filters.push("conflicted".to_string());
+        }
         if self.missing {
             filters.push("missing".to_string());
         }
+        if self.pr_open {
+            filters.push("pr-open".to_string());
+        }
+        if self.pr_draft {
+            filters.push("pr-draft".to_string());
+        }
+        if self.pr_merged {
+            filters.push("pr-merged".to_string());
+        }
+        if self.pr_none {
+            filters.push("pr-none".to_string());
+        }
+        for (excluded, name) in [
+            (self.not_dirty, "not-dirty"),
+            (self.not_clean, "not-clean"),
+            (self.not_staged, "not-staged"),
+            (self.not_conflicted, "not-conflicted"),
+            (self.not_missing, "not-missing"),
+            (self.not_pr_open, "not-pr-open"),
+            (self.not_pr_draft, "not-pr-draft"),
+            (self.not_pr_merged, "not-pr-merged"),
+            (self.not_pr_none, "not-pr-none"),
+        ] {
+            if excluded {
+                filters.push(name.to_string());
+            }
+        }
 
         if let Some(age) = &self.older_than {
             filters.push(format!("older-than-{}", age));
@@ -218,189 +743,27 @@ impl ListCommand {
         if let Some(age) = &self.newer_than {
             filters.push(format!("newer-than-{}", age));
         }
-
-        filters.join(", ")
-    }
-
-    async fn collect_repositories(
-        &self,
-        search_path: &str,
-        fetch_pr_status: bool,
-    ) -> Result<Vec<tokio::task::JoinHandle<Result<RepoResult>>>> {
-        let mut repo_tasks = Vec::new();
-        let entries = fs::read_dir(search_path)?;
-
-        for entry in entries {
-            let entry = entry?;
-            let path = entry.path();
-
-            if !path.is_dir() {
-                continue;
-            }
-
-            let git_path = path.join(".git");
-            if !git_path.exists() {
-                continue;
-            }
-
-            let path_str = path.to_str().unwrap().to_string();
-
-            let task =
-                tokio::spawn(
-                    async move { Self::process_repository(path_str, fetch_pr_status).await },
-                );
-            repo_tasks.push(task);
-        }
-
-        Ok(repo_tasks)
-    }
-
-    async fn process_repository(repo_path: String, fetch_pr_status: bool) -> Result<RepoResult> {
-        let repo_name = Path::new(&repo_path)
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("unknown")
-            .to_string();
-
-        let repo = GitRepository::new(&repo_path, SystemGitClient)?;
-
-        // Check if it's a bare repository
-        if !repo.is_bare().unwrap_or(false) {
-            return Ok(RepoResult {
-                name: repo_name,
-                path: PathBuf::from(&repo_path),
-                worktrees: Vec::new(),
-            });
+        if let Some(pattern) = &self.branch {
+            filters.push(format!("branch={}", pattern));
         }
-
-        // Get worktree list for this repo
-        let worktrees = repo.list_worktrees()?;
-
-        if worktrees.is_empty() {
-            return Ok(RepoResult {
-                name: repo_name,
-                path: PathBuf::from(&repo_path),
-                worktrees: Vec::new(),
-            });
+        if let Some(expression) = &self.filter {
+            filters.push(format!("filter={}", expression));
         }
-
-        // Fetch PR data if requested
-        let pr_matches: HashMap<String, PrStatus> = if fetch_pr_status {
-            Self::fetch_pr_data_for_repo(&repo_path, &worktrees).await?
-        } else {
-            HashMap::new()
-        };
-
-        // Process all worktrees for this repo
-        let mut worktree_results = Vec::new();
-        for worktree in worktrees {
-            // Get all status information
-            let local_status = repo.get_local_status(&worktree.path)?;
-            let commit_timestamp = repo
-                .get_last_commit_timestamp(&worktree.path, &worktree.branch)
-                .unwrap_or(0);
-            let directory_mtime = repo.get_directory_mtime(&worktree.path).unwrap_or(0);
-            let commit_summary = repo
-                .get_commit_summary(&worktree.path, &worktree.branch)
-                .unwrap_or_else(|_| "<no commit>".to_string());
-
-            // Get PR status for this branch
-            let pr_status = pr_matches.get(&worktree.branch).cloned();
-
-            worktree_results.push(WorktreeResult {
-                branch: worktree.branch.clone(),
-                status: WorktreeStatus {
-                    local_status,
-                    commit_timestamp,
-                    directory_mtime,
-                    commit_summary,
-                    pr_status,
-                },
-            });
+        match self.match_mode {
+            Some(MatchMode::Any) => filters.push("matching any".to_string()),
+            Some(MatchMode::All) => filters.push("matching all".to_string()),
+            None => {}
         }
 
-        Ok(RepoResult {
-            name: repo_name,
-            path: PathBuf::from(&repo_path),
-            worktrees: worktree_results,
-        })
-    }
-
-    async fn fetch_pr_data_for_repo(
-        repo_path: &str,
-        worktrees: &[crate::git::WorktreeInfo],
-    ) -> Result<HashMap<String, PrStatus>> {
-        // Validate GITHUB_TOKEN is present
-        std::env::var("GITHUB_TOKEN")
-            .map_err(|_| anyhow!("GITHUB_TOKEN environment variable not set"))?;
-
-        // Create a new repo instance for this async context
-        let repo = GitRepository::new(repo_path, SystemGitClient)?;
-
-        // Get upstream remote URL
-        let remote_url = repo
-            .get_upstream_remote_url()?
-            .ok_or_else(|| anyhow!("No upstream or origin remote found"))?;
-
-        // Parse GitHub repo from URL
-        let github_repo = github::parse_github_url(&remote_url)?;
-
-        eprintln!(
-            "[PR Fetch] Processing repository: {} ({})",
-            Path::new(repo_path).file_name().unwrap().to_string_lossy(),
-            remote_url
-        );
-
-        // Determine the earliest worktree creation time
-        let since_timestamp = Self::get_earliest_worktree_time(repo_path, worktrees).await?;
-
-        let since_date = chrono::DateTime::from_timestamp(since_timestamp, 0)
-            .map(|dt| dt.format("%Y-%m-%d").to_string())
-            .unwrap_or_else(|| "unknown".to_string());
-
-        eprintln!("[PR Fetch] Looking for PRs created since: {}", since_date);
-
-        // Create GitHub client
-        let github_client = octocrab::Octocrab::builder()
-            .personal_token(std::env::var("GITHUB_TOKEN")?)
-            .build()?;
-
-        // Fetch PRs for this repository
-        let prs = github::fetch_prs_for_repo(&github_client, &github_repo, since_timestamp).await?;
-
-        // Extract branch names from worktrees
-        let branch_names: Vec<String> = worktrees.iter().map(|wt| wt.branch.clone()).collect();
-
-        // Match worktrees to PRs
-        let matches = github::match_worktrees_to_prs(&branch_names, &prs);
-        eprintln!("[PR Fetch] Matched {} worktrees to PRs\n", matches.len());
-
-        Ok(matches)
+        filters.join(", ")
     }
+}
 
-    async fn get_earliest_worktree_time(
-        repo_path: &str,
-        worktrees: &[crate::git::WorktreeInfo],
-    ) -> Result<i64> {
-        let repo = GitRepository::new(repo_path, SystemGitClient)?;
-        let mut earliest_time: Option<i64> = None;
-
-        for worktree in worktrees {
-            if let Ok(Some(birth_time)) = repo.get_worktree_birth_time(&worktree.path) {
-                earliest_time = Some(match earliest_time {
-                    None => birth_time,
-                    Some(current) => current.min(birth_time),
-                });
-            }
-        }
-
-        // If we have a birth time, use it; otherwise fall back to 1 week ago
-        Ok(earliest_time.unwrap_or_else(|| {
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs() as i64
-                - (7 * 24 * 60 * 60)
-        }))
+/// A filter field from a flag asking for a status and one excluding it
+fn status_flag(requested: bool, excluded: bool) -> Option<bool> {
+    match (requested, excluded) {
+        (true, _) => Some(true),
+        (_, true) => Some(false),
+        _ => None,
     }
 }
@@ -0,0 +1,56 @@
+use anyhow::{Result, anyhow};
+use clap::Args;
+use clap_complete::ArgValueCandidates;
+
+use crate::commands::{self, completion};
+use gwm::say;
+
+#[derive(Args)]
+pub struct LockCommand {
+    /// Repository name; leave it out inside a repository or one of its worktrees
+    #[arg(add = ArgValueCandidates::new(completion::repo_candidates))]
+    repo: Option<String>,
+
+    /// Branch (or worktree directory name) to lock
+    #[arg(add = ArgValueCandidates::new(completion::branch_candidates))]
+    branch: Option<String>,
+
+    /// Why the worktree is locked, shown by `list` and when removal is refused
+    #[arg(long, value_name = "MESSAGE")]
+    reason: Option<String>,
+
+    /// Directory to search for repositories (defaults to current directory)
+    /// Can also be set via GWM_REPOS_PATH environment variable
+    #[arg(short, long, env = "GWM_REPOS_PATH")]
+    path: Option<String>,
+
+    /// Wait for repositories on slow or unreachable network mounts instead of skipping them
+    #[arg(long)]
+    include_slow: bool,
+}
+
+impl LockCommand {
+    pub async fn execute(&self) -> Result<()> {
+        let target = commands::resolve_repo(self.repo.as_deref(), self.branch.as_deref(), false)?;
+        let (repo, worktree_path) =
+            commands::find_worktree(&target, self.path.as_deref(), self.include_slow)?;
+        let path = worktree_path.to_string_lossy();
+
+        if let Some(reason) = repo.worktree_lock(&path) {
+            let reason = if reason.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", reason)
+            };
+            return Err(anyhow!(
+                "Worktree at {} is already locked{}",
+                worktree_path.display(),
+                reason
+            ));
+        }
+
+        repo.lock_worktree(&path, self.reason.as_deref())?;
+        say!("🔒 Locked worktree at {}", worktree_path.display());
+        Ok(())
+    }
+}
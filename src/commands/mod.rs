@@ -1,9 +1,266 @@
 pub mod add;
+pub mod adopt;
 pub mod complete_branches;
 pub mod complete_repos;
 pub mod completion;
+pub mod daemon;
+pub mod du;
+pub mod env;
+pub mod export_paths;
 pub mod gc;
+pub mod info;
 pub mod list;
+pub mod lock;
+pub mod prune_metadata;
+pub mod recent;
 pub mod remove;
+pub mod repair;
+pub mod report;
+#[cfg(unix)]
+pub mod serve;
+pub mod stats;
 pub mod switch;
 pub mod sync;
+pub mod undo;
+pub mod unlock;
+
+use anyhow::{Result, anyhow};
+use std::path::{Path, PathBuf};
+
+use gwm::config::{Config, Template};
+use gwm::git::{Backend, GitRepository, LocalStatus, SystemGitClient};
+use gwm::hooks;
+use gwm::say;
+use gwm::scan;
+use tracing::warn;
+
+/// Error a command returns to end gwm with a specific exit status, having printed its output
+/// `main` exits with the status instead of reporting an error
+#[derive(Debug)]
+pub struct ExitStatus(pub i32);
+
+impl std::fmt::Display for ExitStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "exit status {}", self.0)
+    }
+}
+
+impl std::error::Error for ExitStatus {}
+
+/// The backend chosen with `--backend`, or else the configured one, failing early if it isn't
+/// available in this build
+pub fn backend(flag: Option<Backend>, config: &Config) -> Result<Backend> {
+    let backend = flag.unwrap_or(config.git.backend);
+    backend.client()?;
+    Ok(backend)
+}
+
+/// Repository and branch named by a command's `[REPO] [BRANCH]` arguments
+pub struct RepoTarget {
+    pub name: String,
+    pub branch: Option<String>,
+    /// Set when the repository was inferred from the current directory
+    pub path: Option<PathBuf>,
+}
+
+/// Resolve `[REPO] [BRANCH]` where the repository may be left out: a lone argument is the branch
+/// (unless `lone_is_repo`), and the repository is the one containing the current directory
+pub fn resolve_repo(
+    first: Option<&str>,
+    second: Option<&str>,
+    lone_is_repo: bool,
+) -> Result<RepoTarget> {
+    match (first, second) {
+        (Some(repo), branch) if branch.is_some() || lone_is_repo => Ok(RepoTarget {
+            name: repo.to_string(),
+            branch: branch.map(str::to_string),
+            path: None,
+        }),
+        (branch, _) => {
+            let path = scan::repository_containing(&std::env::current_dir()?).ok_or_else(|| {
+                anyhow!("Not inside a repository; pass the repository name as the first argument")
+            })?;
+            Ok(RepoTarget {
+                name: scan::repo_name(&path),
+                branch: branch.map(str::to_string),
+                path: Some(path),
+            })
+        }
+    }
+}
+
+/// Find the repository named by `target`, the one inferred from the current directory or
+/// the one with that name under the search path
+pub fn find_repository(
+    target: &RepoTarget,
+    search_path: Option<&str>,
+    include_slow: bool,
+) -> Result<GitRepository<SystemGitClient>> {
+    let repo_path = match &target.path {
+        Some(path) => path.clone(),
+        None => scan::discover_repositories(Path::new(search_path.unwrap_or(".")), include_slow)?
+            .into_iter()
+            .find(|path| scan::repo_name(path) == target.name)
+            .ok_or_else(|| anyhow!("No repository found with name '{}'", target.name))?,
+    };
+    GitRepository::new(&repo_path.to_string_lossy(), SystemGitClient)
+}
+
+/// Directory git recorded for the repository's worktree of the branch, wherever it was created
+/// Falls back to the worktree's directory or name, for detached worktrees
+pub fn worktree_path(
+    repo: &GitRepository<SystemGitClient>,
+    branch: &str,
+) -> Result<Option<PathBuf>> {
+    Ok(repo
+        .list_worktrees()?
+        .into_iter()
+        .find(|worktree| worktree.branch == branch)
+        .map(|worktree| PathBuf::from(worktree.path))
+        .or_else(|| repo.find_registered_worktree(branch)))
+}
+
+/// Find the repository named by `target` and the directory of its worktree for the branch
+pub fn find_worktree(
+    target: &RepoTarget,
+    search_path: Option<&str>,
+    include_slow: bool,
+) -> Result<(GitRepository<SystemGitClient>, PathBuf)> {
+    let branch = target
+        .branch
+        .as_deref()
+        .ok_or_else(|| anyhow!("A branch name is required"))?;
+    let repo = find_repository(target, search_path, include_slow)?;
+    let path = worktree_path(&repo, branch)?.ok_or_else(|| {
+        anyhow!(
+            "Worktree '{}' not found in repository '{}'",
+            branch,
+            target.name
+        )
+    })?;
+    Ok((repo, path))
+}
+
+/// Refuse to remove a locked worktree, one in the middle of a rebase or merge, or one with
+/// unresolved conflicts, unless `force`, which unlocks it first
+/// `force_hint` is how the caller asks for force, e.g. `--force`, for the error messages
+pub fn check_removable(
+    repo: &GitRepository<SystemGitClient>,
+    worktree_path: &Path,
+    force: bool,
+    force_hint: &str,
+) -> Result<()> {
+    let path = worktree_path.to_string_lossy();
+    if let Some(operation) = repo.get_operation(&path) {
+        if !force {
+            return Err(anyhow!(
+                "Worktree at {} is in the middle of an operation ({})\nFinish or abort it, or pass {} to remove it anyway.",
+                worktree_path.display(),
+                operation.to_string().to_lowercase(),
+                force_hint
+            ));
+        }
+        warn!(
+            "Worktree at {} is {}; removing anyway ({})",
+            worktree_path.display(),
+            operation.to_string().to_lowercase(),
+            force_hint
+        );
+    }
+
+    if matches!(repo.get_local_status(&path), Ok(LocalStatus::Conflicted)) {
+        if !force {
+            return Err(anyhow!(
+                "Worktree at {} has unresolved merge conflicts\nResolve them, or pass {} to remove it anyway.",
+                worktree_path.display(),
+                force_hint
+            ));
+        }
+        warn!(
+            "Worktree at {} has unresolved conflicts; removing anyway ({})",
+            worktree_path.display(),
+            force_hint
+        );
+    }
+
+    let Some(reason) = repo.worktree_lock(&path) else {
+        return Ok(());
+    };
+    if !force {
+        let reason = if reason.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", reason)
+        };
+        return Err(anyhow!(
+            "Worktree at {} is locked{}\nUnlock it with `gwm unlock` or pass {} to remove it anyway.",
+            worktree_path.display(),
+            reason,
+            force_hint
+        ));
+    }
+    warn!(
+        "Worktree at {} is locked; removing anyway ({})",
+        worktree_path.display(),
+        force_hint
+    );
+    repo.unlock_worktree(&path)
+}
+
+/// The configuration for a worktree of `repo`, with the `.gwm/template.toml` checked out in it
+/// applied; a broken template is reported and ignored
+pub fn with_template(config: &Config, repo: &str, worktree_path: &Path) -> Config {
+    match Template::load(worktree_path) {
+        Ok(Some(template)) => {
+            if template.has_commands() && !config.trusts_template(repo) {
+                warn!(
+                    "Not running the commands in {} of {}; set trust_template = true under [repos.{}] to allow them",
+                    Template::PATH,
+                    repo,
+                    repo
+                );
+            }
+            config.with_template(repo, &template)
+        }
+        Ok(None) => config.clone(),
+        Err(e) => {
+            warn!("{:#}", e);
+            config.clone()
+        }
+    }
+}
+
+/// With `[add] direnv` on, trust (`allow`) or stop trusting (`deny`) a worktree's `.envrc`;
+/// failures only warn
+pub fn direnv(config: &Config, worktree_path: &Path, allow: bool) {
+    if !config.add.direnv {
+        return;
+    }
+    let result = if allow {
+        hooks::direnv_allow(worktree_path)
+    } else {
+        hooks::direnv_deny(worktree_path)
+    };
+    match result {
+        Ok(true) if allow => say!("🔓 Allowed .envrc with direnv"),
+        Ok(_) => {}
+        Err(e) => warn!("{:#}", e),
+    }
+}
+
+/// With `[add] jump` set, add a new worktree to the directory jumper's database, or drop a
+/// removed one from it; failures only warn
+pub fn jump(config: &Config, worktree_path: &Path, added: bool) {
+    let Some(tool) = config.add.jump else {
+        return;
+    };
+    let worktree_path = std::path::absolute(worktree_path).unwrap_or(worktree_path.to_path_buf());
+    let result = if added {
+        hooks::jump_add(tool, &[worktree_path])
+    } else {
+        hooks::jump_remove(tool, &worktree_path)
+    };
+    if let Err(e) = result {
+        warn!("{:#}", e);
+    }
+}
@@ -0,0 +1,99 @@
+use anyhow::{Result, anyhow};
+use clap::Args;
+use std::path::{Path, PathBuf};
+
+use crate::commands::repair::RepairCommand;
+use gwm::git::{GitRepository, SystemGitClient};
+use gwm::output;
+use gwm::say;
+use gwm::scan;
+
+#[derive(Args)]
+pub struct PruneMetadataCommand {
+    /// Directory to search for repositories (defaults to current directory)
+    /// Can also be set via GWM_REPOS_PATH environment variable
+    #[arg(short, long, env = "GWM_REPOS_PATH")]
+    path: Option<String>,
+
+    /// Wait for repositories on slow or unreachable network mounts instead of skipping them
+    #[arg(long)]
+    include_slow: bool,
+
+    /// Show what would be pruned without deleting anything
+    #[arg(long)]
+    dry_run: bool,
+}
+
+impl PruneMetadataCommand {
+    pub async fn execute(&self) -> Result<()> {
+        let search_path = Path::new(self.path.as_deref().unwrap_or("."));
+        let repo_paths = scan::discover_repositories(search_path, self.include_slow)?;
+
+        // Worktrees that were moved rather than deleted are reconnected instead of pruned
+        if !self.dry_run {
+            for (repo_name, result) in RepairCommand::repair_all(&repo_paths) {
+                for fix in result.unwrap_or_default() {
+                    say!("Repaired {}: {}", repo_name, fix);
+                }
+            }
+        }
+
+        let mut pruned_count = 0;
+        let mut failures = Vec::new();
+        for (repo_name, result) in Self::prune_all(&repo_paths, self.dry_run) {
+            match result {
+                Ok(pruned) => {
+                    for name in &pruned {
+                        if self.dry_run {
+                            println!("Would prune {}/{}", repo_name, name);
+                        } else {
+                            say!("Pruned {}/{}", repo_name, name);
+                        }
+                    }
+                    pruned_count += pruned.len();
+                }
+                Err(e) => failures.push(format!("{}: {}", repo_name, e)),
+            }
+        }
+
+        if pruned_count == 0 {
+            say!("No stale worktree metadata found");
+        } else if self.dry_run {
+            println!(
+                "{}DRY RUN: Would prune {} stale worktree registration(s)",
+                output::emoji("🔍 "),
+                pruned_count
+            );
+        } else {
+            say!("✅ Pruned {} stale worktree registration(s)", pruned_count);
+        }
+
+        if !failures.is_empty() {
+            return Err(anyhow!(
+                "Failed to prune {} repository(ies):\n  {}",
+                failures.len(),
+                failures.join("\n  ")
+            ));
+        }
+        Ok(())
+    }
+
+    /// Prune (or with `dry_run`, only list) the stale worktrees of each repository
+    /// Also run by `sync`, so manually deleted worktree directories don't block `add`
+    pub fn prune_all(repo_paths: &[PathBuf], dry_run: bool) -> Vec<(String, Result<Vec<String>>)> {
+        repo_paths
+            .iter()
+            .map(|repo_path| {
+                let result = GitRepository::new(&repo_path.to_string_lossy(), SystemGitClient)
+                    .and_then(|repo| {
+                        if dry_run {
+                            repo.stale_worktrees()
+                        } else {
+                            repo.prune_worktrees()
+                        }
+                    });
+                (scan::repo_name(repo_path), result)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,49 @@
+use anyhow::Result;
+use clap::Args;
+
+use gwm::output::table::{DateStyle, format_date};
+use gwm::recent::RecentWorktrees;
+use gwm::say;
+
+#[derive(Args)]
+pub struct RecentCommand {
+    /// Show at most this many worktrees
+    #[arg(short = 'n', long, default_value_t = 10)]
+    limit: usize,
+}
+
+impl RecentCommand {
+    pub async fn execute(&self) -> Result<()> {
+        let recent = match RecentWorktrees::path() {
+            Some(path) => RecentWorktrees::load(path)?,
+            None => RecentWorktrees::default(),
+        };
+        // Worktrees removed since the visit are of no use to switch to
+        let visits: Vec<_> = recent
+            .visits
+            .iter()
+            .filter(|visit| visit.path.exists())
+            .take(self.limit)
+            .collect();
+
+        if visits.is_empty() {
+            say!("No recently visited worktrees");
+            return Ok(());
+        }
+
+        let names: Vec<_> = visits
+            .iter()
+            .map(|visit| format!("{}/{}", visit.repo, visit.branch))
+            .collect();
+        let width = names.iter().map(String::len).max().unwrap_or(0);
+        for (name, visit) in names.iter().zip(&visits) {
+            println!(
+                "{:<width$}  {:<10}  {}",
+                name,
+                format_date(visit.visited_at, DateStyle::Relative),
+                visit.path.display()
+            );
+        }
+        Ok(())
+    }
+}
@@ -1,38 +1,108 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use clap::Args;
+use clap_complete::ArgValueCandidates;
 use futures::future::try_join_all;
-use std::fs;
-use std::io::{self, Write};
+use std::io::{self, IsTerminal, Write};
 use std::path::{Path, PathBuf};
 
-use crate::core::{RepoResult, WorktreeResult};
-use crate::git::{GitRepository, SystemGitClient};
-use crate::output::table;
+use crate::commands::{self, completion};
+use gwm::config::Config;
+use gwm::core::{PrStatus, RepoResult, WorktreeResult, glob_matches};
+use gwm::git::{GitRepository, SystemGitClient, WorktreeInfo, is_detached_label};
+use gwm::hooks;
+use gwm::output;
+use gwm::output::table::{self, TableOptions};
+use gwm::say;
+use gwm::scan;
+use gwm::trash::Trash;
+use gwm::usage;
+use tracing::{error, warn};
 
 #[derive(Args)]
 pub struct RemoveCommand {
-    /// Repository name
-    repo: String,
+    /// Repository name; leave it out inside a repository or one of its worktrees
+    #[arg(add = ArgValueCandidates::new(completion::repo_candidates))]
+    repo: Option<String>,
 
-    /// Branch name to remove
-    branch: String,
+    /// Branch names to remove
+    #[arg(value_name = "BRANCH", add = ArgValueCandidates::new(completion::branch_candidates))]
+    branches: Vec<String>,
+
+    /// Also remove every worktree whose branch matches this glob (e.g. "spike/*")
+    #[arg(long, value_name = "PATTERN", conflicts_with = "worktree_path")]
+    branch_glob: Option<String>,
+
+    /// Remove the worktree registered at this directory, regardless of its branch
+    #[arg(long, value_name = "DIR", conflicts_with = "branches")]
+    worktree_path: Option<String>,
 
     /// Directory to search for repositories (defaults to current directory)
     /// Can also be set via GWM_REPOS_PATH environment variable
     #[arg(short, long, env = "GWM_REPOS_PATH")]
     path: Option<String>,
 
+    /// Wait for repositories on slow or unreachable network mounts instead of skipping them
+    #[arg(long)]
+    include_slow: bool,
+
     /// Show what would be removed without actually removing anything
     #[arg(long)]
     dry_run: bool,
+
+    /// Also delete each branch on origin, once its pull request is merged (needs GITHUB_TOKEN)
+    #[arg(long, conflicts_with = "worktree_path")]
+    delete_remote: bool,
+
+    /// Tag each branch as archive/<branch>/<date> before removing its worktree
+    #[arg(long, conflicts_with = "worktree_path")]
+    archive: bool,
+
+    /// Don't archive branches, even if the config file asks for it
+    #[arg(long, conflicts_with = "archive")]
+    no_archive: bool,
+
+    /// Move the worktree directory to the trash instead of deleting it, so `gwm undo` can restore it
+    #[arg(long, conflicts_with = "worktree_path")]
+    trash: bool,
+
+    /// Delete the worktree directory, even if the config file asks for the trash
+    #[arg(long, conflicts_with = "trash")]
+    no_trash: bool,
+
+    /// Remove without asking for confirmation
+    #[arg(short, long)]
+    yes: bool,
+
+    /// Skip confirmation and remove the worktree even if it is locked, mid-rebase or merge, has
+    /// unresolved conflicts, or the repository's teardown command fails
+    #[arg(long)]
+    force: bool,
 }
 
 impl RemoveCommand {
     pub async fn execute(&self) -> Result<()> {
         let search_path = self.path.as_deref().unwrap_or(".");
-
-        // Find all repositories
-        let repo_tasks = self.collect_repositories(search_path).await?;
+        let config = Config::load()?;
+        let target = commands::resolve_repo(
+            self.repo.as_deref(),
+            self.branches.first().map(String::as_str),
+            self.worktree_path.is_some() || self.branch_glob.is_some(),
+        )?;
+        let repo_name = target.name.as_str();
+        // The first branch is taken by resolve_repo, whether or not the repository was named
+        let branches: Vec<String> = target
+            .branch
+            .iter()
+            .chain(self.branches.iter().skip(1))
+            .cloned()
+            .collect();
+
+        // Find all repositories, or just the one containing the current directory
+        let repo_paths = match target.path {
+            Some(ref path) => vec![path.clone()],
+            None => scan::discover_repositories(Path::new(search_path), self.include_slow)?,
+        };
+        let repo_tasks = Self::collect_repositories(repo_paths);
         let repo_task_results = try_join_all(repo_tasks).await?;
 
         let mut repo_results = Vec::new();
@@ -40,114 +110,421 @@ impl RemoveCommand {
             repo_results.push(task_result?);
         }
 
-        // Find the specific target
-        let target = self.find_target_worktree(&repo_results)?;
+        if branches.is_empty() && self.branch_glob.is_none() {
+            return match &self.worktree_path {
+                Some(worktree_path) => {
+                    self.remove_by_path(&repo_results, repo_name, worktree_path, None, &config)
+                }
+                None => Err(anyhow!(
+                    "A branch name, --branch-glob or --worktree-path is required"
+                )),
+            };
+        }
 
-        if target.is_none() {
-            println!("No worktree found for {}/{}", self.repo, self.branch);
+        let Some(repo_result) = repo_results.iter().find(|r| r.name == repo_name) else {
+            println!("Repository '{}' not found", repo_name);
             return Ok(());
-        }
+        };
 
-        let (repo_result, worktree_result) = target.unwrap();
+        // Find the specific targets, each worktree once even if it is named and matched
+        let mut targets: Vec<&WorktreeResult> = Vec::new();
+        let is_target =
+            |targets: &[&WorktreeResult], branch: &str| targets.iter().any(|t| t.branch == branch);
+        for branch in &branches {
+            match repo_result.worktrees.iter().find(|w| &w.branch == branch) {
+                Some(worktree) if !is_target(&targets, branch) => targets.push(worktree),
+                Some(_) => {}
+                None if branches.len() == 1 && self.branch_glob.is_none() => {
+                    // The branch may have been deleted or renamed outside gwm, leaving a
+                    // worktree that can only be found by its name
+                    if Self::find_registered_worktree(&repo_results, repo_name, branch).is_some() {
+                        println!(
+                            "No worktree has branch '{}', but a worktree named '{}' is registered",
+                            branch, branch
+                        );
+                        return self.remove_by_path(
+                            &repo_results,
+                            repo_name,
+                            branch,
+                            Some(branch),
+                            &config,
+                        );
+                    }
+                    println!("No worktree found for {}/{}", repo_name, branch);
+                }
+                None => println!("No worktree found for {}/{}", repo_name, branch),
+            }
+        }
+        if let Some(pattern) = &self.branch_glob {
+            let matches: Vec<_> = repo_result
+                .worktrees
+                .iter()
+                // Detached worktrees have no branch to match; remove them by path
+                .filter(|w| !is_detached_label(&w.branch) && glob_matches(pattern, &w.branch))
+                .collect();
+            if matches.is_empty() {
+                println!("No worktrees in {} match '{}'", repo_name, pattern);
+            }
+            for worktree in matches {
+                if !is_target(&targets, &worktree.branch) {
+                    targets.push(worktree);
+                }
+            }
+        }
+        if targets.is_empty() {
+            return Ok(());
+        }
 
         // Show what we found
-        println!("Target worktree:");
+        say!(
+            "Target worktree{}:",
+            if targets.len() == 1 { "" } else { "s" }
+        );
         let target_repo = RepoResult {
             name: repo_result.name.clone(),
             path: repo_result.path.clone(),
-            worktrees: vec![worktree_result.clone()],
+            worktrees: targets.iter().map(|w| (*w).clone()).collect(),
+        };
+        let table_output = table::create_table(
+            &[target_repo],
+            &TableOptions::new()
+                .pr_status(false)
+                .style(config.table.style)
+                .dates(config.table.dates)
+                .widths(config.table.clone()),
+        );
+        say!("{}", table_output);
+        say!();
+
+        let remote_branches = if self.delete_remote {
+            Self::merged_remote_branches(repo_result, &targets, &config).await?
+        } else {
+            Vec::new()
         };
-        let table_output = table::create_table(&[target_repo], true, false);
-        println!("{}", table_output);
-        println!();
 
         if self.dry_run {
+            for worktree in &targets {
+                if self.archive(&config) {
+                    println!(
+                        "{}DRY RUN: Would tag {} as archive/{}/{}",
+                        output::emoji("🔍 "),
+                        worktree.branch,
+                        worktree.branch,
+                        archive_date()
+                    );
+                }
+                println!(
+                    "{}DRY RUN: Would remove worktree {}/{}",
+                    output::emoji("🔍 "),
+                    repo_name,
+                    worktree.branch
+                );
+            }
+            for branch in &remote_branches {
+                println!(
+                    "{}DRY RUN: Would delete origin/{}",
+                    output::emoji("🔍 "),
+                    branch
+                );
+            }
+            return Ok(());
+        }
+
+        // Ask for confirmation once for the whole set
+        let mut prompt = match targets.as_slice() {
+            [worktree] => format!("Remove worktree {}/{}", repo_name, worktree.branch),
+            _ => format!("Remove {} worktrees from {}", targets.len(), repo_name),
+        };
+        match remote_branches.as_slice() {
+            [] => {}
+            [branch] => prompt.push_str(&format!(" and delete origin/{}", branch)),
+            _ => prompt.push_str(&format!(
+                " and delete {} branches on origin",
+                remote_branches.len()
+            )),
+        }
+        prompt.push('?');
+        if !self.confirm(&prompt)? {
+            println!("Cancelled.");
+            return Ok(());
+        }
+
+        let repo = GitRepository::new(repo_result.path.to_str().unwrap(), SystemGitClient)?;
+        let mut failed = Vec::new();
+        for worktree in &targets {
+            match self.remove_worktree(&config, &repo, repo_result, worktree) {
+                Ok(()) if remote_branches.contains(&worktree.branch) => {
+                    // The worktree is gone either way, so a failed push only warrants a warning
+                    match repo.delete_remote_branch(&worktree.branch, "origin") {
+                        Ok(()) => say!("🧽 Deleted origin/{}", worktree.branch),
+                        Err(e) => warn!("Could not delete origin/{}: {}", worktree.branch, e),
+                    }
+                }
+                Ok(()) => {}
+                Err(e) if targets.len() == 1 => return Err(e),
+                Err(e) => {
+                    error!("Failed to remove {}/{}: {}", repo_name, worktree.branch, e);
+                    failed.push(worktree.branch.as_str());
+                }
+            }
+        }
+        if !failed.is_empty() {
+            return Err(anyhow!(
+                "{} of {} worktrees were not removed: {}",
+                failed.len(),
+                targets.len(),
+                failed.join(", ")
+            ));
+        }
+        Ok(())
+    }
+
+    /// Branches of the targets that exist on origin and whose pull request is merged
+    async fn merged_remote_branches(
+        repo_result: &RepoResult,
+        targets: &[&WorktreeResult],
+        config: &Config,
+    ) -> Result<Vec<String>> {
+        let repo_path = repo_result.path.to_string_lossy();
+        let repo = GitRepository::new(&repo_path, SystemGitClient)?;
+        let on_remote: Vec<WorktreeInfo> = targets
+            .iter()
+            .filter(|w| repo.has_remote_branch(&format!("origin/{}", w.branch)))
+            .map(|w| WorktreeInfo {
+                path: w.path.to_string_lossy().to_string(),
+                branch: w.branch.clone(),
+                detached: None,
+            })
+            .collect();
+        if on_remote.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let prs = scan::fetch_pull_requests(
+            &repo_path,
+            &on_remote,
+            config.github.pr_author,
+            &config.forges(),
+        )
+        .await
+        .map_err(|e| anyhow!("--delete-remote needs the pull request status: {}", e))?;
+        let mut branches = Vec::new();
+        for worktree in on_remote {
+            if matches!(prs.get(&worktree.branch), Some(pr) if pr.status == PrStatus::Merged) {
+                branches.push(worktree.branch);
+            } else {
+                say!(
+                    "Keeping origin/{}: its pull request is not merged",
+                    worktree.branch
+                );
+            }
+        }
+        Ok(branches)
+    }
+
+    /// Run the teardown command and remove one worktree found by its branch
+    fn remove_worktree(
+        &self,
+        config: &Config,
+        repo: &GitRepository<SystemGitClient>,
+        repo_result: &RepoResult,
+        worktree: &WorktreeResult,
+    ) -> Result<()> {
+        commands::check_removable(repo, &worktree.path, self.force, "--force")?;
+        if self.archive(config) {
+            let tag = repo.archive_branch(&worktree.branch, &archive_date())?;
+            say!(
+                "{}Archived {} as {}",
+                output::emoji("🏷️  "),
+                worktree.branch,
+                tag
+            );
+        }
+        self.teardown(config, &repo_result.name, &worktree.branch, &worktree.path)?;
+        commands::direnv(config, &worktree.path, false);
+
+        say!("🗑️  Removing {}/{}", repo_result.name, worktree.branch);
+        if self.trash(config) && worktree.path.exists() {
+            Trash::open()?.remove_worktree(
+                &repo_result.path,
+                &repo_result.name,
+                &worktree.branch,
+                &worktree.path,
+            )?;
+            say!(
+                "✅ Moved worktree {}/{} to the trash; `gwm undo` restores it",
+                repo_result.name,
+                worktree.branch
+            );
+        } else {
+            repo.remove_worktree(&worktree.branch)?;
+            say!(
+                "✅ Successfully removed worktree {}/{}",
+                repo_result.name,
+                worktree.branch
+            );
+        }
+        commands::jump(config, &worktree.path, false);
+        usage::record(config, |stats, now| {
+            stats.record_remove(&repo_result.name, &worktree.branch, now)
+        });
+        Ok(())
+    }
+
+    /// Remove a worktree by directory or name, for when its branch can't be resolved
+    fn remove_by_path(
+        &self,
+        repo_results: &[RepoResult],
+        repo_name: &str,
+        path_or_name: &str,
+        branch: Option<&str>,
+        config: &Config,
+    ) -> Result<()> {
+        let Some((repo_result, worktree_path)) =
+            Self::find_registered_worktree(repo_results, repo_name, path_or_name)
+        else {
             println!(
-                "🔍 DRY RUN: Would remove worktree {}/{}",
-                self.repo, self.branch
+                "No worktree registered at '{}' in repository '{}'",
+                path_or_name, repo_name
             );
             return Ok(());
-        }
+        };
 
-        // Ask for confirmation
-        print!("❓ Remove worktree {}/{}? [y/N]: ", self.repo, self.branch);
-        io::stdout().flush()?;
+        say!("Target worktree:");
+        say!("  Repository: {}", repo_result.name);
+        say!("  Path: {}", worktree_path.display());
+        if !worktree_path.exists() {
+            say!("  (directory is already gone, only the registration remains)");
+        }
+        say!();
 
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
+        if self.dry_run {
+            println!(
+                "{}DRY RUN: Would remove worktree at {}",
+                output::emoji("🔍 "),
+                worktree_path.display()
+            );
+            return Ok(());
+        }
 
-        if !input.trim().to_lowercase().starts_with('y') {
+        if !self.confirm(&format!("Remove worktree at {}?", worktree_path.display()))? {
             println!("Cancelled.");
             return Ok(());
         }
 
-        // Perform the removal
+        let name = branch.unwrap_or_else(|| {
+            worktree_path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default()
+        });
         let repo = GitRepository::new(repo_result.path.to_str().unwrap(), SystemGitClient)?;
-        println!(
-            "🗑️  Removing {}/{}",
-            repo_result.name, worktree_result.branch
-        );
-        repo.remove_worktree(&worktree_result.branch)?;
+        commands::check_removable(&repo, &worktree_path, self.force, "--force")?;
+        self.teardown(config, &repo_result.name, name, &worktree_path)?;
+        commands::direnv(config, &worktree_path, false);
+
+        say!("🗑️  Removing {}", worktree_path.display());
+        repo.remove_worktree_at(&worktree_path.to_string_lossy())?;
 
-        println!(
-            "✅ Successfully removed worktree {}/{}",
-            self.repo, self.branch
+        say!(
+            "✅ Successfully removed worktree at {}",
+            worktree_path.display()
         );
+        commands::jump(config, &worktree_path, false);
+        usage::record(config, |stats, now| {
+            stats.record_remove(&repo_result.name, name, now)
+        });
         Ok(())
     }
 
-    /// Find the specific worktree target
-    fn find_target_worktree<'a>(
+    /// Whether to tag branches before removal, from the flags and then the config file
+    fn archive(&self, config: &Config) -> bool {
+        !self.no_archive && (self.archive || config.remove.archive)
+    }
+
+    /// Whether to keep removed directories in the trash, from the flags and then the config file
+    fn trash(&self, config: &Config) -> bool {
+        !self.no_trash && (self.trash || config.remove.trash)
+    }
+
+    /// Run the repository's teardown command, if configured; a failure blocks removal unless --force
+    fn teardown(
         &self,
-        repo_results: &'a [RepoResult],
-    ) -> Result<Option<(&'a RepoResult, &'a WorktreeResult)>> {
-        for repo_result in repo_results {
-            if repo_result.name == self.repo {
-                for worktree in &repo_result.worktrees {
-                    if worktree.branch == self.branch {
-                        return Ok(Some((repo_result, worktree)));
-                    }
-                }
+        config: &Config,
+        repo_name: &str,
+        branch: &str,
+        worktree_path: &Path,
+    ) -> Result<()> {
+        let config = commands::with_template(config, repo_name, worktree_path);
+        let Some(command) = config.teardown(repo_name) else {
+            return Ok(());
+        };
+        if !worktree_path.exists() {
+            say!("Worktree directory is gone, skipping teardown command");
+            return Ok(());
+        }
+
+        say!("{}Running teardown: {}", output::emoji("🧹 "), command);
+        match hooks::run_teardown(command, repo_name, branch, worktree_path) {
+            Ok(()) => Ok(()),
+            Err(e) if self.force => {
+                warn!("{}; removing anyway (--force)", e);
+                Ok(())
             }
+            Err(e) => Err(anyhow!(
+                "{}\nThe worktree was not removed. Pass --force to remove it anyway.",
+                e
+            )),
         }
-        Ok(None)
     }
 
-    async fn collect_repositories(
-        &self,
-        search_path: &str,
-    ) -> Result<Vec<tokio::task::JoinHandle<Result<RepoResult>>>> {
-        let mut repo_tasks = Vec::new();
-        let entries = fs::read_dir(search_path)?;
-
-        for entry in entries {
-            let entry = entry?;
-            let path = entry.path();
+    /// Find a worktree registered in the target repository by directory or name
+    fn find_registered_worktree<'a>(
+        repo_results: &'a [RepoResult],
+        repo_name: &str,
+        path_or_name: &str,
+    ) -> Option<(&'a RepoResult, PathBuf)> {
+        let repo_result = repo_results.iter().find(|r| r.name == repo_name)?;
+        let repo = GitRepository::new(repo_result.path.to_str()?, SystemGitClient).ok()?;
+        let worktree_path = repo.find_registered_worktree(path_or_name)?;
+        Some((repo_result, worktree_path))
+    }
 
-            if !path.is_dir() {
-                continue;
-            }
+    /// Ask before removing, unless --yes or --force; without a terminal there is no one to ask
+    fn confirm(&self, prompt: &str) -> Result<bool> {
+        if self.yes || self.force {
+            return Ok(true);
+        }
+        if !io::stdin().is_terminal() {
+            return Err(anyhow!(
+                "Refusing to remove without confirmation: stdin is not a terminal. Pass --yes to remove anyway."
+            ));
+        }
 
-            let git_path = path.join(".git");
-            if !git_path.exists() {
-                continue;
-            }
+        print!("{}{} [y/N]: ", output::emoji("❓ "), prompt);
+        io::stdout().flush()?;
 
-            let path_str = path.to_str().unwrap().to_string();
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
 
-            let task = tokio::spawn(async move { Self::process_repository(path_str).await });
-            repo_tasks.push(task);
-        }
+        Ok(input.trim().to_lowercase().starts_with('y'))
+    }
 
-        Ok(repo_tasks)
+    fn collect_repositories(
+        repo_paths: Vec<PathBuf>,
+    ) -> Vec<tokio::task::JoinHandle<Result<RepoResult>>> {
+        repo_paths
+            .into_iter()
+            .map(|path| {
+                let path_str = path.to_string_lossy().to_string();
+                tokio::task::spawn_blocking(move || Self::process_repository(path_str))
+            })
+            .collect()
     }
 
-    async fn process_repository(repo_path: String) -> Result<RepoResult> {
-        let repo_name = Path::new(&repo_path)
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("unknown")
-            .to_string();
+    /// Runs on a blocking thread, since every step goes through libgit2
+    fn process_repository(repo_path: String) -> Result<RepoResult> {
+        let repo_name = scan::repo_name(Path::new(&repo_path));
 
         let repo = GitRepository::new(&repo_path, SystemGitClient)?;
 
@@ -176,13 +553,8 @@ impl RemoveCommand {
         for worktree in worktrees {
             worktree_results.push(WorktreeResult {
                 branch: worktree.branch.clone(),
-                status: crate::core::WorktreeStatus {
-                    local_status: crate::git::LocalStatus::Clean, // Placeholder
-                    commit_timestamp: 0,                          // Placeholder
-                    directory_mtime: 0,                           // Placeholder
-                    commit_summary: "<placeholder>".to_string(),  // Placeholder
-                    pr_status: None,                              // No PR status for remove command
-                },
+                path: worktree.path.clone().into(),
+                status: gwm::core::WorktreeStatus::default(),
             });
         }
 
@@ -193,3 +565,8 @@ impl RemoveCommand {
         })
     }
 }
+
+/// Date used in archive tag names, e.g. "2024-05-01"
+pub fn archive_date() -> String {
+    chrono::Local::now().format("%Y-%m-%d").to_string()
+}
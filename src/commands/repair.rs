@@ -0,0 +1,70 @@
+use anyhow::{Result, anyhow};
+use clap::Args;
+use std::path::{Path, PathBuf};
+
+use gwm::git::{GitRepository, SystemGitClient};
+use gwm::say;
+use gwm::scan;
+
+#[derive(Args)]
+pub struct RepairCommand {
+    /// Directory to search for repositories (defaults to current directory)
+    /// Can also be set via GWM_REPOS_PATH environment variable
+    #[arg(short, long, env = "GWM_REPOS_PATH")]
+    path: Option<String>,
+
+    /// Wait for repositories on slow or unreachable network mounts instead of skipping them
+    #[arg(long)]
+    include_slow: bool,
+}
+
+impl RepairCommand {
+    pub async fn execute(&self) -> Result<()> {
+        let search_path = Path::new(self.path.as_deref().unwrap_or("."));
+        let repo_paths = scan::discover_repositories(search_path, self.include_slow)?;
+
+        let mut repaired_count = 0;
+        let mut failures = Vec::new();
+        for (repo_name, result) in Self::repair_all(&repo_paths) {
+            match result {
+                Ok(repaired) => {
+                    for fix in &repaired {
+                        say!("Repaired {}: {}", repo_name, fix);
+                    }
+                    repaired_count += repaired.len();
+                }
+                Err(e) => failures.push(format!("{}: {}", repo_name, e)),
+            }
+        }
+
+        if repaired_count == 0 {
+            say!("No broken worktree links found");
+        } else {
+            say!("✅ Repaired {} worktree link(s)", repaired_count);
+        }
+
+        if !failures.is_empty() {
+            return Err(anyhow!(
+                "Failed to repair {} repository(ies):\n  {}",
+                failures.len(),
+                failures.join("\n  ")
+            ));
+        }
+        Ok(())
+    }
+
+    /// Reconnect each repository with its worktrees after the repositories directory (or a
+    /// single repository) was moved, so their `.git` files and `.git/worktrees` records point at
+    /// each other again
+    /// Also run by `sync` and `prune-metadata`, which would otherwise prune moved worktrees
+    pub fn repair_all(repo_paths: &[PathBuf]) -> Vec<(String, Result<Vec<String>>)> {
+        repo_paths
+            .iter()
+            .map(|repo_path| {
+                let result = GitRepository::new(&repo_path.to_string_lossy(), SystemGitClient)
+                    .and_then(|repo| repo.repair_worktrees(&scan::worktree_dirs(repo_path)));
+                (scan::repo_name(repo_path), result)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,67 @@
+use anyhow::{Result, anyhow};
+use clap::Args;
+use std::path::{Path, PathBuf};
+
+use crate::commands;
+use gwm::config::Config;
+use gwm::git::Backend;
+use gwm::output::html;
+use gwm::say;
+use gwm::scan::{self, ScanOptions};
+
+#[derive(Args)]
+pub struct ReportCommand {
+    /// Directory to search for repositories (defaults to current directory)
+    /// Can also be set via GWM_REPOS_PATH environment variable
+    #[arg(short, long, env = "GWM_REPOS_PATH")]
+    path: Option<String>,
+
+    /// File to write the HTML page to (defaults to standard output)
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<PathBuf>,
+
+    /// Disable PR status fetching from GitHub
+    #[arg(long)]
+    no_pr_status: bool,
+
+    /// Wait for repositories on slow or unreachable network mounts instead of skipping them
+    #[arg(long)]
+    include_slow: bool,
+
+    /// Library used to read worktree status (overrides the config file)
+    #[arg(long, value_enum, env = "GWM_GIT_BACKEND")]
+    backend: Option<Backend>,
+}
+
+impl ReportCommand {
+    pub async fn execute(&self) -> Result<()> {
+        let search_path = Path::new(self.path.as_deref().unwrap_or("."));
+        let config = Config::load()?;
+        let backend = commands::backend(self.backend, &config)?;
+
+        let repo_results = scan::scan(
+            search_path,
+            &ScanOptions::new()
+                .pr_status(!self.no_pr_status)
+                .pr_author(config.github.pr_author)
+                .forges(config.forges())
+                .backend(backend)
+                .cache(true)
+                .include_slow(self.include_slow),
+        )
+        .await?;
+
+        let generated_at = chrono::Local::now().format("%Y-%m-%d %H:%M").to_string();
+        let page = html::create_report(&repo_results, &generated_at);
+
+        match &self.output {
+            Some(path) => {
+                std::fs::write(path, page)
+                    .map_err(|e| anyhow!("Failed to write '{}': {}", path.display(), e))?;
+                say!("📄 Report written to {}", path.display());
+            }
+            None => print!("{}", page),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,611 @@
+use anyhow::{Result, anyhow};
+use clap::Args;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tracing::{debug, info, warn};
+
+use crate::commands;
+use crate::commands::add::AddCommand;
+use gwm::config::{self, Config};
+use gwm::core::WorktreeAnalyzer;
+use gwm::git::{Backend, GitRepository, SystemGitClient, is_valid_branch_name};
+use gwm::hooks;
+use gwm::output::json::{self, Versioned};
+use gwm::say;
+use gwm::scan::{self, ScanOptions};
+use gwm::snapshot::Snapshot;
+use gwm::usage;
+
+// JSON-RPC 2.0 error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const SERVER_ERROR: i64 = -32000;
+
+#[derive(Args)]
+pub struct ServeCommand {
+    /// Directory to search for repositories (defaults to current directory)
+    /// Can also be set via GWM_REPOS_PATH environment variable
+    #[arg(short, long, env = "GWM_REPOS_PATH")]
+    path: Option<String>,
+
+    /// Socket to listen on (defaults to $XDG_RUNTIME_DIR/gwm.sock, or gwm.sock in the cache directory)
+    #[arg(long, value_name = "PATH", env = "GWM_SOCKET")]
+    socket: Option<PathBuf>,
+
+    /// Wait for repositories on slow or unreachable network mounts instead of skipping them
+    #[arg(long)]
+    include_slow: bool,
+
+    /// Library used to read and change repositories (overrides the config file)
+    #[arg(long, value_enum, env = "GWM_GIT_BACKEND")]
+    backend: Option<Backend>,
+}
+
+/// What every request handler needs, shared by all connections
+struct Server {
+    search_path: PathBuf,
+    include_slow: bool,
+    backend: Backend,
+    config: Config,
+}
+
+#[derive(Deserialize)]
+struct Request {
+    jsonrpc: String,
+    /// Absent for notifications, which get no response
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Deserialize)]
+struct WorktreeParams {
+    repo: String,
+    branch: String,
+    /// Branch to create from, for `add` (defaults to main)
+    base_branch: Option<String>,
+    /// Reuse an existing branch, for `add`
+    #[serde(default)]
+    reuse: bool,
+    /// Remove even if the teardown command fails, for `remove`
+    #[serde(default)]
+    force: bool,
+}
+
+/// A failed request, as a JSON-RPC error code and message
+struct RpcError(i64, String);
+
+impl From<anyhow::Error> for RpcError {
+    fn from(e: anyhow::Error) -> Self {
+        RpcError(SERVER_ERROR, format!("{:#}", e))
+    }
+}
+
+impl ServeCommand {
+    pub async fn execute(&self) -> Result<()> {
+        let config = Config::load()?;
+        let backend = commands::backend(self.backend, &config)?;
+
+        let socket = match &self.socket {
+            Some(socket) => socket.clone(),
+            None => default_socket_path()
+                .ok_or_else(|| anyhow!("Could not determine a socket path; pass --socket"))?,
+        };
+        let listener = bind(&socket).await?;
+
+        let server = Arc::new(Server {
+            search_path: PathBuf::from(self.path.as_deref().unwrap_or(".")),
+            include_slow: self.include_slow,
+            backend,
+            config,
+        });
+        say!("Listening on {} (stop with Ctrl-C)", socket.display());
+        serve(listener, server).await
+    }
+}
+
+/// Where `gwm serve` listens unless told otherwise
+fn default_socket_path() -> Option<PathBuf> {
+    match std::env::var("XDG_RUNTIME_DIR") {
+        Ok(dir) if !dir.is_empty() => Some(PathBuf::from(dir).join("gwm.sock")),
+        _ => config::cache_dir().map(|dir| dir.join("gwm.sock")),
+    }
+}
+
+/// Listen on `socket`, replacing a socket file left behind by a server that is gone
+async fn bind(socket: &Path) -> Result<UnixListener> {
+    if socket.exists() {
+        if UnixStream::connect(socket).await.is_ok() {
+            return Err(anyhow!(
+                "Another gwm serve is already listening on {}",
+                socket.display()
+            ));
+        }
+        std::fs::remove_file(socket)?;
+    }
+    if let Some(parent) = socket.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let listener = UnixListener::bind(socket)
+        .map_err(|e| anyhow!("Failed to listen on '{}': {}", socket.display(), e))?;
+
+    // The socket can add and remove worktrees, so only its owner may connect
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(socket, std::fs::Permissions::from_mode(0o600))?;
+    Ok(listener)
+}
+
+async fn serve(listener: UnixListener, server: Arc<Server>) -> Result<()> {
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let server = server.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, &server).await {
+                warn!("Connection closed: {:#}", e);
+            }
+        });
+    }
+}
+
+/// Answer newline-delimited JSON-RPC requests until the client disconnects
+async fn handle_connection(stream: UnixStream, server: &Server) -> Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = handle_line(&line, server).await {
+            writer.write_all(response.to_string().as_bytes()).await?;
+            writer.write_all(b"\n").await?;
+        }
+    }
+    Ok(())
+}
+
+/// Response to one request line, or None for a notification
+async fn handle_line(line: &str, server: &Server) -> Option<Value> {
+    let request: Request = match serde_json::from_str::<Value>(line) {
+        Err(e) => return Some(error_response(Value::Null, PARSE_ERROR, &e.to_string())),
+        Ok(value) => match serde_json::from_value(value) {
+            Ok(request) => request,
+            Err(e) => {
+                return Some(error_response(Value::Null, INVALID_REQUEST, &e.to_string()));
+            }
+        },
+    };
+    if request.jsonrpc != "2.0" {
+        let id = request.id.unwrap_or(Value::Null);
+        return Some(error_response(
+            id,
+            INVALID_REQUEST,
+            "jsonrpc must be \"2.0\"",
+        ));
+    }
+
+    debug!("Request {}", request.method);
+    let result = dispatch(&request.method, request.params, server).await;
+    let id = request.id?;
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(RpcError(code, message)) => error_response(id, code, &message),
+    })
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+/// Every result carries `schema_version`, like the JSON output of `gwm list`
+async fn dispatch(method: &str, params: Value, server: &Server) -> Result<Value, RpcError> {
+    let result = match method {
+        "version" => json!({ "version": env!("CARGO_PKG_VERSION") }),
+        "list" => {
+            let list = json::WorktreeList {
+                worktrees: json::worktrees(&list(server).await?),
+            };
+            serde_json::to_value(list).map_err(anyhow::Error::from)?
+        }
+        "add" => {
+            let path = add(server, parse_params(params)?).await?;
+            json!({ "path": path })
+        }
+        "remove" => {
+            let path = remove(server, parse_params(params)?).await?;
+            json!({ "path": path })
+        }
+        "switch" => {
+            let params: WorktreeParams = parse_params(params)?;
+            let path = find_worktree(server, params.repo, params.branch).await?;
+            json!({ "path": path })
+        }
+        _ => {
+            return Err(RpcError(
+                METHOD_NOT_FOUND,
+                format!("Unknown method '{}'", method),
+            ));
+        }
+    };
+    Ok(serde_json::to_value(Versioned::new(result)).map_err(anyhow::Error::from)?)
+}
+
+fn parse_params<T: for<'de> Deserialize<'de>>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|e| RpcError(INVALID_PARAMS, e.to_string()))
+}
+
+/// Current worktree status; PR statuses come only from a fresh `gwm daemon` snapshot,
+/// so a request never waits on GitHub
+async fn list(server: &Server) -> Result<Vec<gwm::core::RepoResult>> {
+    let mut repo_results = scan::scan(
+        &server.search_path,
+        &ScanOptions::new()
+            .pr_status(false)
+            .backend(server.backend)
+            .cache(true)
+            .include_slow(server.include_slow),
+    )
+    .await?;
+
+    if let Some(snapshot) = Snapshot::load(&server.search_path)
+        .filter(|snapshot| snapshot.is_fresh(chrono::Utc::now().timestamp()))
+    {
+        WorktreeAnalyzer::carry_over_pr_statuses(&snapshot.repos, &mut repo_results);
+    }
+    Ok(repo_results)
+}
+
+/// The repository directory with that name; reading the search path blocks, so this runs on
+/// the blocking pool like the git work
+async fn find_repository(server: &Server, name: String) -> Result<PathBuf> {
+    let (search_path, include_slow) = (server.search_path.clone(), server.include_slow);
+    tokio::task::spawn_blocking(move || {
+        scan::discover_repositories(&search_path, include_slow)?
+            .into_iter()
+            .find(|path| scan::repo_name(path) == name)
+            .ok_or_else(|| anyhow!("No repository found with name '{}'", name))
+    })
+    .await?
+}
+
+/// The repository directory and the directory of its worktree for the branch
+async fn find_worktree(server: &Server, repo: String, branch: String) -> Result<PathBuf> {
+    let repo_path = find_repository(server, repo.clone()).await?;
+    tokio::task::spawn_blocking(move || {
+        GitRepository::new(&repo_path.to_string_lossy(), SystemGitClient)?
+            .list_worktrees()?
+            .into_iter()
+            .find(|worktree| worktree.branch == branch)
+            .map(|worktree| PathBuf::from(worktree.path))
+            .ok_or_else(|| anyhow!("Worktree '{}' not found in repository '{}'", branch, repo))
+    })
+    .await?
+}
+
+/// Create a worktree the way `gwm add` does, inside the repository directory
+async fn add(server: &Server, params: WorktreeParams) -> Result<PathBuf> {
+    // The branch names the worktree's directory, so it mustn't point anywhere else
+    if !is_valid_branch_name(&params.branch) {
+        return Err(anyhow!("'{}' is not a valid branch name", params.branch));
+    }
+    let repo_path = find_repository(server, params.repo.clone()).await?;
+    let worktree_path = repo_path.join(&params.branch);
+    if worktree_path.exists() {
+        return Err(anyhow!(
+            "Target directory '{}' already exists",
+            worktree_path.display()
+        ));
+    }
+
+    let backend = server.backend;
+    let config = server.config.clone();
+    let path = worktree_path.clone();
+    let (repo_name, branch) = (params.repo.clone(), params.branch.clone());
+    let (base_branch, reuse) = (params.base_branch, params.reuse);
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        let repo = GitRepository::new(&repo_path.to_string_lossy(), backend.client()?)?;
+        let branch_existed = repo.branch_names(false)?.contains(&branch);
+        repo.add_worktree(
+            &branch,
+            &path.to_string_lossy(),
+            base_branch.as_deref(),
+            reuse,
+        )?;
+        // Templates, copies, shared directories, direnv and the setup command, as `gwm add`
+        AddCommand::set_up(
+            &config,
+            &repo,
+            &repo_name,
+            &branch,
+            &path,
+            None,
+            branch_existed,
+        )?;
+        Ok(())
+    })
+    .await
+    .map_err(anyhow::Error::from)??;
+
+    info!("Added worktree {}/{}", params.repo, params.branch);
+    usage::record(&server.config, |stats, now| {
+        stats.record_add(&params.repo, &params.branch, now)
+    });
+    Ok(worktree_path)
+}
+
+/// Remove a worktree the way `gwm remove` does, running the teardown command first
+async fn remove(server: &Server, params: WorktreeParams) -> Result<PathBuf> {
+    let worktree_path = find_worktree(server, params.repo.clone(), params.branch.clone()).await?;
+    let repo_path = find_repository(server, params.repo.clone()).await?;
+
+    // The same checks as `gwm remove`: locks, operations in progress and conflicts
+    let (path, force) = (worktree_path.clone(), params.force);
+    let check_path = repo_path.clone();
+    tokio::task::spawn_blocking(move || {
+        let repo = GitRepository::new(&check_path.to_string_lossy(), SystemGitClient)?;
+        commands::check_removable(&repo, &path, force, "\"force\": true")
+    })
+    .await
+    .map_err(anyhow::Error::from)??;
+
+    let config = commands::with_template(&server.config, &params.repo, &worktree_path);
+    if let Some(command) = config.teardown(&params.repo)
+        && worktree_path.exists()
+    {
+        let command = command.to_string();
+        let (repo, branch, path) = (
+            params.repo.clone(),
+            params.branch.clone(),
+            worktree_path.clone(),
+        );
+        let teardown = tokio::task::spawn_blocking(move || {
+            hooks::run_teardown(&command, &repo, &branch, &path)
+        })
+        .await
+        .map_err(anyhow::Error::from)?;
+        if let Err(e) = teardown {
+            if !params.force {
+                return Err(anyhow!("{}; pass \"force\": true to remove anyway", e));
+            }
+            warn!("{}; removing anyway", e);
+        }
+    }
+
+    commands::direnv(&config, &worktree_path, false);
+    let branch = params.branch.clone();
+    tokio::task::spawn_blocking(move || {
+        GitRepository::new(&repo_path.to_string_lossy(), SystemGitClient)?.remove_worktree(&branch)
+    })
+    .await
+    .map_err(anyhow::Error::from)??;
+
+    commands::jump(&config, &worktree_path, false);
+    info!("Removed worktree {}/{}", params.repo, params.branch);
+    usage::record(&server.config, |stats, now| {
+        stats.record_remove(&params.repo, &params.branch, now)
+    });
+    Ok(worktree_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn answers_requests_over_the_socket() {
+        let dir = TempDir::new().unwrap();
+        let socket = dir.path().join("gwm.sock");
+        let listener = bind(&socket).await.unwrap();
+        let server = Arc::new(Server {
+            search_path: dir.path().to_path_buf(),
+            include_slow: false,
+            backend: Backend::Libgit2,
+            config: Config::default(),
+        });
+        tokio::spawn(serve(listener, server));
+
+        let stream = UnixStream::connect(&socket).await.unwrap();
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        writer
+            .write_all(
+                b"{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"list\"}\n\
+                  {\"jsonrpc\":\"2.0\",\"method\":\"version\"}\n\
+                  {\"jsonrpc\":\"2.0\",\"id\":2,\"method\":\"switch\",\"params\":{}}\n\
+                  {\"jsonrpc\":\"2.0\",\"id\":3,\"method\":\"rebase\"}\n",
+            )
+            .await
+            .unwrap();
+
+        let mut next = async || -> Value {
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap()
+        };
+        let list = next().await;
+        assert_eq!(list["id"], 1);
+        assert_eq!(list["result"]["schema_version"], json::SCHEMA_VERSION);
+        assert_eq!(list["result"]["worktrees"], json!([]));
+        // The version notification got no response
+        assert_eq!(next().await["error"]["code"], INVALID_PARAMS);
+        assert_eq!(next().await["error"]["code"], METHOD_NOT_FOUND);
+
+        assert!(bind(&socket).await.is_err());
+    }
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args([
+                "-c",
+                "user.name=Test User",
+                "-c",
+                "user.email=test@example.com",
+            ])
+            .args(args)
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    /// A gwm layout under `dir`: the bare repository `proj/.git` with a worktree per branch
+    fn server_with_worktrees(dir: &Path, branches: &[&str]) -> Server {
+        let source = dir.join("source");
+        git(dir, &["init", "-q", "-b", "main", "source"]);
+        git(
+            &source,
+            &["commit", "-q", "--allow-empty", "-m", "Initial commit"],
+        );
+        let repos = dir.join("repos");
+        std::fs::create_dir(&repos).unwrap();
+        git(dir, &["clone", "-q", "--bare", "source", "repos/proj/.git"]);
+        for branch in branches {
+            git(
+                &repos.join("proj").join(".git"),
+                &[
+                    "worktree",
+                    "add",
+                    "-q",
+                    "-b",
+                    branch,
+                    &format!("../{}", branch),
+                ],
+            );
+        }
+        Server {
+            search_path: repos,
+            include_slow: false,
+            backend: Backend::Libgit2,
+            config: Config::default(),
+        }
+    }
+
+    fn add_params(branch: &str) -> WorktreeParams {
+        WorktreeParams {
+            repo: "proj".to_string(),
+            branch: branch.to_string(),
+            base_branch: None,
+            reuse: false,
+            force: false,
+        }
+    }
+
+    fn remove_params(branch: &str, force: bool) -> WorktreeParams {
+        WorktreeParams {
+            repo: "proj".to_string(),
+            branch: branch.to_string(),
+            base_branch: None,
+            reuse: false,
+            force,
+        }
+    }
+
+    #[tokio::test]
+    async fn remove_refuses_locked_and_mid_rebase_worktrees() {
+        let dir = TempDir::new().unwrap();
+        let server = server_with_worktrees(dir.path(), &["locked", "rebasing"]);
+        let repo_dir = server.search_path.join("proj");
+        git(&repo_dir.join(".git"), &["worktree", "lock", "../locked"]);
+        // What `git rebase` leaves in the worktree's git directory until it finishes
+        std::fs::create_dir(repo_dir.join(".git/worktrees/rebasing/rebase-merge")).unwrap();
+
+        for branch in ["locked", "rebasing"] {
+            let error = remove(&server, remove_params(branch, false))
+                .await
+                .unwrap_err();
+            assert!(error.to_string().contains("\"force\": true"), "{}", error);
+            assert!(repo_dir.join(branch).exists());
+        }
+
+        remove(&server, remove_params("locked", true))
+            .await
+            .unwrap();
+        assert!(!repo_dir.join("locked").exists());
+    }
+
+    #[tokio::test]
+    async fn add_sets_worktrees_up_like_gwm_add() {
+        let dir = TempDir::new().unwrap();
+        let mut server = server_with_worktrees(dir.path(), &[]);
+        let repo_dir = server.search_path.join("proj");
+        git(
+            &repo_dir.join(".git"),
+            &["worktree", "add", "-q", "../main", "main"],
+        );
+        std::fs::write(repo_dir.join("main/.env"), "TOKEN=1").unwrap();
+        server.config = Config::parse(
+            r#"
+            [repos.proj]
+            copy_on_add = [".env"]
+            post_add = "test -f .env && touch set-up"
+            "#,
+        )
+        .unwrap();
+
+        let path = add(&server, add_params("feature")).await.unwrap();
+        assert!(path.join(".env").exists());
+        assert!(path.join("set-up").exists());
+
+        // A failing setup command takes the worktree and its new branch away again
+        server.config = Config::parse("[repos.proj]\npost_add = \"false\"").unwrap();
+        assert!(add(&server, add_params("broken")).await.is_err());
+        assert!(!repo_dir.join("broken").exists());
+
+        // A branch name can't place the worktree outside the repository
+        let error = add(&server, add_params("../escaped")).await.unwrap_err();
+        assert!(
+            error.to_string().contains("not a valid branch name"),
+            "{}",
+            error
+        );
+        assert!(!server.search_path.join("escaped").exists());
+    }
+
+    #[tokio::test]
+    async fn remove_refuses_worktrees_with_conflicts() {
+        let dir = TempDir::new().unwrap();
+        let server = server_with_worktrees(dir.path(), &["left", "right"]);
+        let (left, right) = (
+            server.search_path.join("proj/left"),
+            server.search_path.join("proj/right"),
+        );
+        for (worktree, content) in [(&left, "left\n"), (&right, "right\n")] {
+            std::fs::write(worktree.join("notes.txt"), content).unwrap();
+            git(worktree, &["add", "notes.txt"]);
+            git(worktree, &["commit", "-q", "-m", content.trim()]);
+        }
+        let merge = std::process::Command::new("git")
+            .arg("-C")
+            .arg(&left)
+            .args(["merge", "--quiet", "right"])
+            .stdout(std::process::Stdio::null())
+            .status()
+            .unwrap();
+        assert!(!merge.success(), "merge should stop for conflicts");
+        // Unresolved paths alone, without the merge in progress, must still block removal
+        std::fs::remove_file(
+            server
+                .search_path
+                .join("proj/.git/worktrees/left/MERGE_HEAD"),
+        )
+        .unwrap();
+
+        let error = remove(&server, remove_params("left", false))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("conflicts"), "{}", error);
+        assert!(left.exists());
+    }
+}
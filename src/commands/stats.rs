@@ -0,0 +1,159 @@
+use anyhow::{Result, anyhow};
+use clap::Args;
+
+use chrono::{DateTime, Local};
+use std::collections::{BTreeMap, BTreeSet};
+
+use gwm::config::Config;
+use gwm::usage::UsageStats;
+use gwm::usage::history::{WipHistory, WipSample};
+
+#[derive(Args)]
+pub struct StatsCommand {
+    /// Show local usage statistics (adds, removes, gc runs, worktree lifetime)
+    #[arg(long)]
+    usage: bool,
+
+    /// Show work-in-progress trends recorded by `list` and the daemon
+    #[arg(long)]
+    history: bool,
+
+    /// Days of history to show
+    #[arg(long, value_name = "DAYS", default_value_t = 30, value_parser = clap::value_parser!(u32).range(1..))]
+    days: u32,
+}
+
+impl StatsCommand {
+    pub async fn execute(&self) -> Result<()> {
+        if !self.usage && !self.history {
+            return Err(anyhow!(
+                "No report selected. Use --usage to show local usage statistics \
+                 or --history to show work-in-progress trends"
+            ));
+        }
+
+        let config = Config::load()?;
+        if !config.usage.enabled {
+            println!("Usage statistics are disabled.");
+            println!("Enable them by adding this to your gwm config file:");
+            println!();
+            println!("  [usage]");
+            println!("  enabled = true");
+            return Ok(());
+        }
+
+        if self.usage {
+            self.show_usage()?;
+        }
+        if self.history {
+            if self.usage {
+                println!();
+            }
+            self.show_history()?;
+        }
+        Ok(())
+    }
+
+    fn show_usage(&self) -> Result<()> {
+        let path = UsageStats::path()
+            .ok_or_else(|| anyhow!("Could not determine the gwm data directory"))?;
+        let stats = UsageStats::load(path.clone())?;
+
+        println!("Usage statistics ({})", path.display());
+        println!("  Worktrees added:   {}", stats.adds);
+        println!("  Worktrees removed: {}", stats.removes);
+        println!(
+            "  gc runs:           {} ({} worktrees collected)",
+            stats.gc_runs, stats.gc_removed
+        );
+        match stats.average_lifetime_secs() {
+            Some(secs) => println!("  Average lifetime:  {}", format_duration(secs)),
+            None => println!("  Average lifetime:  -"),
+        }
+
+        Ok(())
+    }
+
+    fn show_history(&self) -> Result<()> {
+        let path = WipHistory::path()
+            .ok_or_else(|| anyhow!("Could not determine the gwm data directory"))?;
+        let history = WipHistory::load(path.clone())?;
+        let since = chrono::Utc::now().timestamp() - i64::from(self.days) * 24 * 60 * 60;
+        let samples: Vec<&WipSample> = history
+            .samples
+            .iter()
+            .filter(|sample| sample.taken_at >= since)
+            .collect();
+
+        println!(
+            "Work in progress, last {} days ({})",
+            self.days,
+            path.display()
+        );
+        let (Some(first), Some(last)) = (samples.first(), samples.last()) else {
+            println!("  No samples yet: they are recorded each time `gwm list` runs");
+            return Ok(());
+        };
+
+        // The last sample of each day
+        let mut daily: BTreeMap<String, &WipSample> = BTreeMap::new();
+        for sample in &samples {
+            if let Some(time) = DateTime::from_timestamp(sample.taken_at, 0) {
+                let day = time.with_timezone(&Local).format("%Y-%m-%d").to_string();
+                daily.insert(day, sample);
+            }
+        }
+        println!("  {:<12}{:>5}{:>7}  Avg branch age", "Date", "WIP", "Dirty");
+        for (day, sample) in &daily {
+            println!(
+                "  {:<12}{:>5}{:>7}  {}",
+                day,
+                sample.wip(),
+                sample.dirty(),
+                sample
+                    .average_age_secs()
+                    .map_or("-".to_string(), format_duration)
+            );
+        }
+
+        println!();
+        println!("Per repository:");
+        let merges = history.merges_since(since);
+        let weeks = ((last.taken_at - first.taken_at) as f64 / (7 * 24 * 60 * 60) as f64).max(1.0);
+        let repos: BTreeSet<&String> = first
+            .repos
+            .keys()
+            .chain(last.repos.keys())
+            .chain(merges.keys())
+            .collect();
+        for repo in repos {
+            let wip_at = |sample: &WipSample| sample.repos.get(repo).map_or(0, |r| r.wip);
+            let merged = merges.get(repo).copied().unwrap_or(0);
+            println!(
+                "  {}: WIP {} -> {}, {} merged ({:.1}/week)",
+                repo,
+                wip_at(first),
+                wip_at(last),
+                merged,
+                merged as f64 / weeks
+            );
+        }
+
+        Ok(())
+    }
+}
+
+fn format_duration(secs: i64) -> String {
+    let hours = secs / (60 * 60);
+    let days = hours / 24;
+
+    match days {
+        0 if hours == 0 => "< 1 hour".to_string(),
+        0 if hours == 1 => "1 hour".to_string(),
+        0 => format!("{} hours", hours),
+        1 => "1 day".to_string(),
+        n if n < 7 => format!("{} days", n),
+        n if n < 30 => format!("{} weeks", n / 7),
+        n => format!("{} months", n / 30),
+    }
+}
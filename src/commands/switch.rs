@@ -1,167 +1,125 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use clap::Args;
-use futures::future::try_join_all;
-use std::fs;
-use std::path::Path;
+use clap_complete::ArgValueCandidates;
+use std::io::{self, IsTerminal, Write};
 
-use crate::core::RepoResult;
-use crate::git::{GitRepository, SystemGitClient};
+use crate::commands::add::AddCommand;
+use crate::commands::{self, completion};
+use gwm::output;
+use gwm::recent::{self, RecentWorktrees};
+use gwm::say;
 
 #[derive(Args)]
 pub struct SwitchCommand {
-    /// Repository name
-    repo: String,
+    /// Repository name; leave it out inside a repository or one of its worktrees, or pass `-`
+    /// alone to return to the previously visited worktree
+    #[arg(add = ArgValueCandidates::new(completion::repo_candidates))]
+    repo: Option<String>,
 
     /// Branch name to switch to
-    branch: String,
+    #[arg(add = ArgValueCandidates::new(completion::branch_candidates))]
+    branch: Option<String>,
 
     /// Directory to search for repositories (defaults to current directory)
     /// Can also be set via GWM_REPOS_PATH environment variable
     #[arg(short, long, env = "GWM_REPOS_PATH")]
     path: Option<String>,
+
+    /// Wait for repositories on slow or unreachable network mounts instead of skipping them
+    #[arg(long)]
+    include_slow: bool,
+
+    /// Create the worktree with `gwm add` from the default base branch if it doesn't exist
+    #[arg(short, long)]
+    create: bool,
 }
 
 impl SwitchCommand {
     pub async fn execute(&self) -> Result<()> {
-        let search_path = self.path.as_deref().unwrap_or(".");
-
-        // Find all repositories
-        let repo_tasks = self.collect_repositories(search_path).await?;
-        let repo_task_results = try_join_all(repo_tasks).await?;
-
-        let mut repo_results = Vec::new();
-        for task_result in repo_task_results {
-            repo_results.push(task_result?);
+        if self.repo.as_deref() == Some("-") && self.branch.is_none() {
+            return Self::switch_back();
         }
 
-        // Find the target repository
-        let target_repo = self.find_target_repository(&repo_results)?;
-
-        let repo_result = match target_repo {
-            Some(repo) => repo,
-            None => {
-                eprintln!("No repository found with name '{}'", self.repo);
-                std::process::exit(1);
+        let target = commands::resolve_repo(self.repo.as_deref(), self.branch.as_deref(), false)?;
+        let branch = target
+            .branch
+            .as_deref()
+            .ok_or_else(|| anyhow!("A branch name is required"))?;
+        let repo = commands::find_repository(&target, self.path.as_deref(), self.include_slow)?;
+
+        let Some(worktree_path) = commands::worktree_path(&repo, branch)? else {
+            let not_found = format!(
+                "Worktree '{}' not found in repository '{}'",
+                branch, target.name
+            );
+            if !self.create && !Self::confirm_create(&not_found)? {
+                return Err(anyhow!("{}; pass --create to add it", not_found));
             }
+            // `add` changes to the new worktree itself
+            return AddCommand::new(
+                self.repo.clone(),
+                self.branch.clone(),
+                self.path.clone(),
+                self.include_slow,
+            )
+            .execute()
+            .await;
         };
 
-        // Find the target worktree
-        let worktree_path = self.find_worktree_path(repo_result)?;
-
-        match worktree_path {
-            Some(path) => {
-                // Change to the worktree directory
-                std::env::set_current_dir(&path)?;
-                println!("📁 Changed to {}", path.display());
-            }
-            None => {
-                eprintln!(
-                    "Worktree '{}' not found in repository '{}'",
-                    self.branch, self.repo
-                );
-                std::process::exit(1);
-            }
+        if !worktree_path.exists() {
+            return Err(anyhow!(
+                "Worktree directory {} is missing; `gwm prune-metadata` cleans up its registration",
+                worktree_path.display()
+            ));
         }
 
+        // Change to the worktree directory
+        std::env::set_current_dir(&worktree_path)?;
+        say!("📁 Changed to {}", worktree_path.display());
+        recent::record(&target.name, branch, &worktree_path);
         Ok(())
     }
 
-    /// Find the target repository by name
-    fn find_target_repository<'a>(
-        &self,
-        repo_results: &'a [RepoResult],
-    ) -> Result<Option<&'a RepoResult>> {
-        for repo_result in repo_results {
-            if repo_result.name == self.repo {
-                return Ok(Some(repo_result));
-            }
+    /// `switch -`: return to the most recently visited worktree, like `cd -`
+    fn switch_back() -> Result<()> {
+        let recent = match RecentWorktrees::path() {
+            Some(path) => RecentWorktrees::load(path)?,
+            None => RecentWorktrees::default(),
+        };
+        let visit = recent
+            .previous(&std::env::current_dir()?)
+            .ok_or_else(|| anyhow!("No previous worktree to switch back to"))?;
+        if !visit.path.exists() {
+            return Err(anyhow!(
+                "Worktree {}/{} at {} no longer exists",
+                visit.repo,
+                visit.branch,
+                visit.path.display()
+            ));
         }
-        Ok(None)
-    }
 
-    /// Find the path to the worktree for the given branch
-    fn find_worktree_path(&self, repo_result: &RepoResult) -> Result<Option<std::path::PathBuf>> {
-        // Check if this branch exists as a worktree
-        for worktree in &repo_result.worktrees {
-            if worktree.branch == self.branch {
-                // The worktree path is the branch directory inside the repo
-                let worktree_path = repo_result.path.join(&self.branch);
-                if worktree_path.exists() {
-                    return Ok(Some(worktree_path));
-                }
-            }
-        }
-        Ok(None)
+        std::env::set_current_dir(&visit.path)?;
+        say!("📁 Changed to {}", visit.path.display());
+        recent::record(&visit.repo, &visit.branch, &visit.path);
+        Ok(())
     }
 
-    async fn collect_repositories(
-        &self,
-        search_path: &str,
-    ) -> Result<Vec<tokio::task::JoinHandle<Result<RepoResult>>>> {
-        let mut repo_tasks = Vec::new();
-        let entries = fs::read_dir(search_path)?;
-
-        for entry in entries {
-            let entry = entry?;
-            let path = entry.path();
-
-            if !path.is_dir() {
-                continue;
-            }
-
-            let git_path = path.join(".git");
-            if !git_path.exists() {
-                continue;
-            }
-
-            let path_str = path.to_str().unwrap().to_string();
-
-            let task = tokio::spawn(async move { Self::process_repository(path_str).await });
-            repo_tasks.push(task);
+    /// Offer to create the missing worktree; without a terminal there is no one to ask
+    fn confirm_create(not_found: &str) -> Result<bool> {
+        if !io::stdin().is_terminal() {
+            return Ok(false);
         }
 
-        Ok(repo_tasks)
-    }
+        print!(
+            "{}{}. Create it from the default base branch? [y/N]: ",
+            output::emoji("❓ "),
+            not_found
+        );
+        io::stdout().flush()?;
 
-    async fn process_repository(repo_path: String) -> Result<RepoResult> {
-        let repo_name = Path::new(&repo_path)
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("unknown")
-            .to_string();
-
-        let repo = GitRepository::new(&repo_path, SystemGitClient)?;
-
-        // Check if it's a bare repository
-        if !repo.is_bare().unwrap_or(false) {
-            return Ok(RepoResult {
-                name: repo_name,
-                path: std::path::PathBuf::from(&repo_path),
-                worktrees: Vec::new(),
-            });
-        }
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
 
-        // Get worktree list for this repo
-        let worktrees = repo.list_worktrees()?;
-
-        let worktree_results = worktrees
-            .into_iter()
-            .map(|worktree| crate::core::WorktreeResult {
-                branch: worktree.branch.clone(),
-                status: crate::core::WorktreeStatus {
-                    local_status: crate::git::LocalStatus::Clean,
-                    commit_timestamp: 0,
-                    directory_mtime: 0,
-                    commit_summary: "<placeholder>".to_string(),
-                    pr_status: None,
-                },
-            })
-            .collect();
-
-        Ok(RepoResult {
-            name: repo_name,
-            path: std::path::PathBuf::from(&repo_path),
-            worktrees: worktree_results,
-        })
+        Ok(input.trim().to_lowercase().starts_with('y'))
     }
 }
@@ -1,10 +1,52 @@
 use anyhow::Result;
 use clap::Args;
-use futures::future::try_join_all;
-use std::fs;
+use futures::StreamExt;
 use std::path::Path;
+use std::time::Duration;
 
-use crate::git::{GitRepository, SystemGitClient};
+use crate::commands;
+use crate::commands::prune_metadata::PruneMetadataCommand;
+use crate::commands::repair::RepairCommand;
+use gwm::config::{Config, SyncConfig};
+use gwm::git::{Backend, FetchChanges, FetchTags, GitRepository};
+use gwm::notify::{Event, Notification, Notifier};
+use gwm::output::{self, Tone};
+use gwm::say;
+use gwm::scan;
+use tracing::{debug, warn};
+
+/// How fetching one repository ended, after any retries
+pub enum FetchOutcome {
+    Fetched(FetchChanges),
+    Failed(String),
+    /// No attempt succeeded and at least one ran past the timeout
+    TimedOut,
+}
+
+/// Repository name and how its fetch ended
+pub type FetchResult = (String, FetchOutcome);
+
+/// How long each fetch attempt may run and how often a failed one is tried again
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// `None` waits as long as the fetch takes
+    pub timeout: Option<Duration>,
+    pub retries: u32,
+}
+
+impl RetryPolicy {
+    pub fn from_config(config: &SyncConfig) -> Self {
+        Self {
+            timeout: (config.timeout > 0).then(|| Duration::from_secs(config.timeout)),
+            retries: config.retries,
+        }
+    }
+
+    /// Delay before the given retry (counting from 1): 1s, 2s, 4s, ... up to a minute
+    fn backoff(retry: u32) -> Duration {
+        Duration::from_secs(1 << retry.saturating_sub(1).min(6)).min(Duration::from_secs(60))
+    }
+}
 
 #[derive(Args)]
 pub struct SyncCommand {
@@ -12,115 +54,325 @@ pub struct SyncCommand {
     /// Can also be set via GWM_REPOS_PATH environment variable
     #[arg(short, long, env = "GWM_REPOS_PATH")]
     path: Option<String>,
+
+    /// Wait for repositories on slow or unreachable network mounts instead of skipping them
+    #[arg(long)]
+    include_slow: bool,
+
+    /// Fetch every tag on the remotes, not only those on fetched commits
+    #[arg(long)]
+    tags: bool,
+
+    /// Don't fetch any tags
+    #[arg(long, conflicts_with = "tags")]
+    no_tags: bool,
+
+    /// Seconds a repository's fetch may take before it is abandoned, 0 for no limit (overrides the config file)
+    #[arg(long, value_name = "SECONDS")]
+    timeout: Option<u64>,
+
+    /// How many times to retry a failed or timed-out fetch (overrides the config file)
+    #[arg(long, value_name = "N")]
+    retries: Option<u32>,
+
+    /// Implementation used to fetch and update repositories (overrides the config file); gitoxide
+    /// fetches through libgit2
+    #[arg(long, value_enum, env = "GWM_GIT_BACKEND")]
+    backend: Option<Backend>,
+
+    /// Fetch at most N repositories at a time (default: all at once)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    jobs: Option<u32>,
 }
 
 impl SyncCommand {
     pub async fn execute(&self) -> Result<()> {
         let search_path = self.path.as_deref().unwrap_or(".");
+        let config = Config::load()?;
+        let backend = commands::backend(self.backend, &config)?;
+        let notifier = Notifier::from_config(&config.notify)?;
+        let tags = if self.tags {
+            FetchTags::All
+        } else if self.no_tags {
+            FetchTags::None
+        } else {
+            config.sync.tags
+        };
+
+        let policy = RetryPolicy::from_config(&SyncConfig {
+            timeout: self.timeout.unwrap_or(config.sync.timeout),
+            retries: self.retries.unwrap_or(config.sync.retries),
+            ..config.sync.clone()
+        });
+
+        say!("Fetching remotes for all repositories...");
+
+        let repo_paths = scan::discover_repositories(Path::new(search_path), self.include_slow)?;
 
-        println!("Fetching remotes for all repositories...");
+        // Moved worktrees look deleted until they are reconnected, and would be pruned below
+        for (repo_name, result) in RepairCommand::repair_all(&repo_paths) {
+            match result {
+                Ok(repaired) => {
+                    for fix in repaired {
+                        say!("Repaired {}: {}", repo_name, fix);
+                    }
+                }
+                Err(e) => debug!("Could not repair worktrees of {}: {}", repo_name, e),
+            }
+        }
 
-        // Find all repositories and fetch them in parallel
-        let fetch_tasks = self.collect_repositories(search_path).await?;
+        // Worktree directories deleted by hand leave registrations behind that block `add`
+        for (repo_name, result) in PruneMetadataCommand::prune_all(&repo_paths, false) {
+            match result {
+                Ok(pruned) => {
+                    for name in pruned {
+                        say!("Pruned stale worktree metadata {}/{}", repo_name, name);
+                    }
+                }
+                Err(e) => debug!("Could not prune worktrees of {}: {}", repo_name, e),
+            }
+        }
 
-        // Process repositories in parallel
-        let results = try_join_all(fetch_tasks).await?;
+        // Fetch all repositories in parallel, or --jobs at a time
+        let jobs = self.jobs.map_or(repo_paths.len(), |jobs| jobs as usize);
+        let fetches = repo_paths.iter().map(|path| {
+            Self::fetch_with_retries(
+                path.to_string_lossy().to_string(),
+                scan::repo_name(path),
+                backend,
+                tags,
+                policy,
+                true,
+            )
+        });
+        let results: Vec<FetchResult> = futures::stream::iter(fetches)
+            .buffered(jobs.max(1))
+            .collect()
+            .await;
 
-        // Count successes and failures
+        // Count successes, failures and timeouts
         let mut success_count = 0;
-        let mut failure_count = 0;
         let mut failed_repos = Vec::new();
+        let mut timed_out_repos = Vec::new();
+        let mut updated_repos = Vec::new();
 
-        for result in results {
-            match result {
-                Ok(repo_name) => {
+        for (repo_name, outcome) in results {
+            match outcome {
+                FetchOutcome::Fetched(changes) => {
                     success_count += 1;
-                    println!("✓ {}", repo_name);
+                    say!("{} {}", output::paint("✓", Tone::Good), repo_name);
+                    Self::print_changes(&changes);
+                    if !changes.is_empty() {
+                        updated_repos.push(format!("{} ({})", repo_name, changes.summary()));
+                    }
                 }
-                Err((repo_name, error)) => {
-                    failure_count += 1;
+                FetchOutcome::Failed(error) => {
+                    say!("{} {}: {}", output::paint("✗", Tone::Bad), repo_name, error);
                     failed_repos.push((repo_name, error));
-                    println!(
-                        "✗ {}: {}",
-                        failed_repos.last().unwrap().0,
-                        failed_repos.last().unwrap().1
-                    );
+                }
+                FetchOutcome::TimedOut => {
+                    say!("{} {}: timed out", output::paint("✗", Tone::Bad), repo_name);
+                    timed_out_repos.push(repo_name);
                 }
             }
         }
 
-        println!();
-        println!(
-            "Sync complete: {} successful, {} failed",
-            success_count, failure_count
-        );
+        say!();
+        if timed_out_repos.is_empty() {
+            say!(
+                "Sync complete: {} successful, {} failed",
+                success_count,
+                failed_repos.len()
+            );
+        } else {
+            say!(
+                "Sync complete: {} successful, {} failed, {} timed out",
+                success_count,
+                failed_repos.len(),
+                timed_out_repos.len()
+            );
+        }
 
-        if failure_count > 0 {
-            println!("\nFailed repositories:");
-            for (repo_name, error) in failed_repos {
+        if !updated_repos.is_empty() {
+            notifier.notify(&Notification::new(
+                Event::Fetched,
+                "gwm sync: new commits",
+                updated_repos.join("\n"),
+            ));
+        }
+
+        if !failed_repos.is_empty() {
+            say!("\nFailed repositories:");
+            for (repo_name, error) in &failed_repos {
                 println!("  {}: {}", repo_name, error);
             }
         }
+        if !timed_out_repos.is_empty() {
+            say!(
+                "\nTimed out repositories ({}):",
+                Self::describe_attempts(policy)
+            );
+            for repo_name in &timed_out_repos {
+                println!("  {}", repo_name);
+            }
+        }
+        let failures: Vec<String> = failed_repos
+            .iter()
+            .map(|(repo_name, error)| format!("{}: {}", repo_name, error))
+            .chain(
+                timed_out_repos
+                    .iter()
+                    .map(|repo_name| format!("{}: timed out", repo_name)),
+            )
+            .collect();
+        if !failures.is_empty() {
+            notifier.notify(&Notification::new(
+                Event::Failure,
+                format!("gwm sync: {} repository(ies) failed", failures.len()),
+                failures.join("\n"),
+            ));
+        }
 
         Ok(())
     }
 
-    async fn collect_repositories(
-        &self,
-        search_path: &str,
-    ) -> Result<Vec<tokio::task::JoinHandle<Result<String, (String, String)>>>> {
-        let mut fetch_tasks = Vec::new();
-        let entries = fs::read_dir(search_path)?;
+    /// e.g. "no answer within 120s, 3 attempts"
+    fn describe_attempts(policy: RetryPolicy) -> String {
+        let attempts = policy.retries + 1;
+        format!(
+            "no answer within {}s, {} attempt{}",
+            policy.timeout.map_or(0, |timeout| timeout.as_secs()),
+            attempts,
+            if attempts == 1 { "" } else { "s" }
+        )
+    }
 
-        for entry in entries {
-            let entry = entry?;
-            let path = entry.path();
+    /// List what a fetch brought in under the repository's line
+    fn print_changes(changes: &FetchChanges) {
+        for branch in &changes.new_branches {
+            say!("    new branch {}", branch);
+        }
+        for (branch, commits) in &changes.updated_branches {
+            say!(
+                "    {}: {} new commit{}",
+                branch,
+                commits,
+                if *commits == 1 { "" } else { "s" }
+            );
+        }
+        for tag in &changes.new_tags {
+            say!("    new tag {}", tag);
+        }
+    }
 
-            if !path.is_dir() {
-                continue;
+    /// Fetch a repository, giving each attempt `policy.timeout` and retrying with backoff
+    /// Each attempt runs on its own thread; one that times out can't be cancelled, so it is
+    /// left to finish in the background rather than holding up the other repositories
+    pub async fn fetch_with_retries(
+        repo_path: String,
+        repo_name: String,
+        backend: Backend,
+        tags: FetchTags,
+        policy: RetryPolicy,
+        report_pull_failures: bool,
+    ) -> FetchResult {
+        let mut error = None;
+        let mut timed_out = false;
+        for attempt in 0..=policy.retries {
+            if attempt > 0 {
+                let delay = RetryPolicy::backoff(attempt);
+                debug!("Retrying fetch of {} in {:?}", repo_name, delay);
+                tokio::time::sleep(delay).await;
             }
 
-            let git_path = path.join(".git");
-            if !git_path.exists() {
-                continue;
+            let (sender, receiver) = tokio::sync::oneshot::channel();
+            let (path, name) = (repo_path.clone(), repo_name.clone());
+            std::thread::spawn(move || {
+                let _ = sender.send(Self::fetch_repository(
+                    path,
+                    &name,
+                    backend,
+                    tags,
+                    report_pull_failures,
+                ));
+            });
+            let result = match policy.timeout {
+                Some(timeout) => tokio::time::timeout(timeout, receiver).await,
+                None => Ok(receiver.await),
+            };
+            match result {
+                Ok(Ok(Ok(changes))) => return (repo_name, FetchOutcome::Fetched(changes)),
+                Ok(Ok(Err(e))) => error = Some(e),
+                Ok(Err(_)) => error = Some("fetch thread panicked".to_string()),
+                Err(_) => {
+                    debug!("Fetch of {} timed out", repo_name);
+                    timed_out = true;
+                }
             }
-
-            let path_str = path.to_str().unwrap().to_string();
-            let repo_name = Path::new(&path_str)
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("unknown")
-                .to_string();
-
-            let task =
-                tokio::spawn(async move { Self::fetch_repository(path_str, repo_name).await });
-            fetch_tasks.push(task);
         }
 
-        Ok(fetch_tasks)
+        let outcome = match error {
+            Some(e) if !timed_out => FetchOutcome::Failed(e),
+            _ => FetchOutcome::TimedOut,
+        };
+        (repo_name, outcome)
     }
 
-    async fn fetch_repository(
+    /// Blocks, since fetching goes through libgit2's network code
+    /// `report_pull_failures` prints a note when main can't be pulled; otherwise it is only logged
+    fn fetch_repository(
         repo_path: String,
-        repo_name: String,
-    ) -> Result<String, (String, String)> {
-        match GitRepository::new(&repo_path, SystemGitClient) {
+        repo_name: &str,
+        backend: Backend,
+        tags: FetchTags,
+        report_pull_failures: bool,
+    ) -> Result<FetchChanges, String> {
+        let client = backend.client().map_err(|e| e.to_string())?;
+        match GitRepository::new(&repo_path, client) {
             Ok(repo) => {
-                // First fetch all remotes
-                if let Err(e) = repo.fetch_remotes() {
-                    return Err((repo_name, e.to_string()));
-                }
+                // First fetch all remotes, noting which remote branches and tags changed
+                let before = repo.fetched_ref_tips().unwrap_or_default();
+                repo.fetch_remotes_with_tags(tags)
+                    .map_err(|e| e.to_string())?;
+                let changes = repo.fetch_changes(&before).unwrap_or_default();
 
                 // Then pull main branch if we're in the main worktree
                 if let Err(e) = repo.pull_main() {
                     // If pull_main fails (e.g., not on main branch), just log it but don't fail the sync
                     // This allows sync to work for both main worktrees and feature worktrees
-                    eprintln!("  Note: Could not pull main for {}: {}", repo_name, e);
+                    if report_pull_failures {
+                        warn!("Could not pull main for {}: {}", repo_name, e);
+                    } else {
+                        debug!("Could not pull main for {}: {}", repo_name, e);
+                    }
                 }
 
-                Ok(repo_name)
+                Ok(changes)
             }
-            Err(e) => Err((repo_name, e.to_string())),
+            Err(e) => Err(e.to_string()),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_a_minute() {
+        let delays: Vec<u64> = (1..=8)
+            .map(|retry| RetryPolicy::backoff(retry).as_secs())
+            .collect();
+        assert_eq!(delays, vec![1, 2, 4, 8, 16, 32, 60, 60]);
+    }
+
+    #[test]
+    fn zero_timeout_means_no_limit() {
+        let policy = RetryPolicy::from_config(&SyncConfig {
+            timeout: 0,
+            ..SyncConfig::default()
+        });
+        assert_eq!(policy.timeout, None);
+        assert_eq!(policy.retries, 2);
+    }
+}
@@ -0,0 +1,56 @@
+use anyhow::Result;
+use clap::Args;
+
+use crate::commands;
+use gwm::config::Config;
+use gwm::output;
+use gwm::say;
+use gwm::trash::Trash;
+
+#[derive(Args)]
+pub struct UndoCommand {
+    /// Show what would be restored without restoring it
+    #[arg(long)]
+    dry_run: bool,
+}
+
+impl UndoCommand {
+    pub async fn execute(&self) -> Result<()> {
+        let trash = Trash::open()?;
+
+        if self.dry_run {
+            match trash.last()? {
+                Some(entry) => println!(
+                    "{}DRY RUN: Would restore {}/{} to {}",
+                    output::emoji("🔍 "),
+                    entry.repo,
+                    entry.branch,
+                    entry.worktree_path.display()
+                ),
+                None => println!("Nothing to undo: the trash is empty"),
+            }
+            return Ok(());
+        }
+
+        let Some(entry) = trash.restore_last()? else {
+            println!("Nothing to undo: the trash is empty");
+            return Ok(());
+        };
+
+        say!(
+            "✅ Restored worktree {}/{} at {}",
+            entry.repo,
+            entry.branch,
+            entry.worktree_path.display()
+        );
+        // Removal took back direnv's trust in the .envrc and the jumper's entry
+        let config = Config::load()?;
+        commands::direnv(&config, &entry.worktree_path, true);
+        commands::jump(&config, &entry.worktree_path, true);
+        // The restored directory is the one result scripts need
+        if output::is_quiet() {
+            println!("{}", entry.worktree_path.display());
+        }
+        Ok(())
+    }
+}
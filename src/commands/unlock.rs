@@ -0,0 +1,46 @@
+use anyhow::{Result, anyhow};
+use clap::Args;
+use clap_complete::ArgValueCandidates;
+
+use crate::commands::{self, completion};
+use gwm::say;
+
+#[derive(Args)]
+pub struct UnlockCommand {
+    /// Repository name; leave it out inside a repository or one of its worktrees
+    #[arg(add = ArgValueCandidates::new(completion::repo_candidates))]
+    repo: Option<String>,
+
+    /// Branch (or worktree directory name) to unlock
+    #[arg(add = ArgValueCandidates::new(completion::branch_candidates))]
+    branch: Option<String>,
+
+    /// Directory to search for repositories (defaults to current directory)
+    /// Can also be set via GWM_REPOS_PATH environment variable
+    #[arg(short, long, env = "GWM_REPOS_PATH")]
+    path: Option<String>,
+
+    /// Wait for repositories on slow or unreachable network mounts instead of skipping them
+    #[arg(long)]
+    include_slow: bool,
+}
+
+impl UnlockCommand {
+    pub async fn execute(&self) -> Result<()> {
+        let target = commands::resolve_repo(self.repo.as_deref(), self.branch.as_deref(), false)?;
+        let (repo, worktree_path) =
+            commands::find_worktree(&target, self.path.as_deref(), self.include_slow)?;
+        let path = worktree_path.to_string_lossy();
+
+        if repo.worktree_lock(&path).is_none() {
+            return Err(anyhow!(
+                "Worktree at {} is not locked",
+                worktree_path.display()
+            ));
+        }
+
+        repo.unlock_worktree(&path)?;
+        say!("🔓 Unlocked worktree at {}", worktree_path.display());
+        Ok(())
+    }
+}
@@ -0,0 +1,1007 @@
+use anyhow::{Result, anyhow};
+use colored::Color;
+use regex::Regex;
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::azure_devops::AzureDevOpsConfig;
+use crate::bitbucket::BitbucketConfig;
+use crate::core::expr::FilterExpr;
+use crate::core::{MatchMode, WorktreeFilter};
+use crate::forge::Forges;
+use crate::git::{Backend, FetchTags};
+use crate::github::{Credentials, ForgeBackend, PrAuthor, TokenConfig};
+use crate::notify::SinkConfig;
+use crate::output::table::{DateStyle, TableStyle};
+
+/// User configuration loaded from `~/.config/gwm/config.toml`
+/// The location can be overridden with the GWM_CONFIG environment variable
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub table: TableConfig,
+    pub add: AddConfig,
+    pub remove: RemoveConfig,
+    pub sync: SyncConfig,
+    pub usage: UsageConfig,
+    pub git: GitConfig,
+    pub github: GitHubConfig,
+    pub bitbucket: BitbucketConfig,
+    pub azure_devops: AzureDevOpsConfig,
+    /// Where `sync` and `gc` announce what they did, as `[[notify]]` tables
+    pub notify: Vec<SinkConfig>,
+    /// Per-repository settings keyed by repository name
+    pub repos: HashMap<String, RepoConfig>,
+    /// Filters for `gwm list --preset <name>`, as `[filters.<name>]` tables
+    pub filters: HashMap<String, FilterPreset>,
+    /// Default flags per command, as `[defaults.<command>]` tables keyed by flag name (e.g.
+    /// `no_emoji = true`); flags given on the command line win
+    pub defaults: HashMap<String, HashMap<String, FlagDefault>>,
+}
+
+/// A flag's value from `[defaults.<command>]`, or a list for flags that take several
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum FlagDefault {
+    Bool(bool),
+    Integer(i64),
+    String(String),
+    List(Vec<String>),
+}
+
+impl FlagDefault {
+    /// The values as they would be written on the command line
+    pub fn values(&self) -> Vec<String> {
+        match self {
+            FlagDefault::Bool(value) => vec![value.to_string()],
+            FlagDefault::Integer(value) => vec![value.to_string()],
+            FlagDefault::String(value) => vec![value.clone()],
+            FlagDefault::List(values) => values.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TableConfig {
+    /// Per-column settings keyed by column name (e.g. "branch", "last_commit")
+    pub columns: HashMap<String, ColumnConfig>,
+    /// How wide the terminal draws emoji, for aligning the Local column
+    pub emoji_width: EmojiWidth,
+    /// Border style, overridden by `--style`
+    pub style: TableStyle,
+    /// How the Age column shows commit times, overridden by `--dates`
+    pub dates: DateStyle,
+    /// Colors for status values, replacing the built-in ones
+    pub colors: StatusColors,
+    /// Built-in glyphs for statuses, for terminals and fonts that draw emoji poorly
+    pub icon_set: IconSet,
+    /// Glyphs for status values, replacing those of the icon set
+    pub icons: StatusIcons,
+    /// How the Ticket column finds issue keys in branch names
+    pub tickets: TicketConfig,
+}
+
+/// Issue-tracker keys in branch names, for the Ticket column, as `[table.tickets]`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TicketConfig {
+    /// Show the Ticket column without `--ticket-column`
+    pub show: bool,
+    /// Regex matching a ticket ID; its first group, if any, is the ID
+    pub pattern: TicketPattern,
+    /// Link for each ticket, with `{ticket}` standing for its ID, e.g.
+    /// "https://acme.atlassian.net/browse/{ticket}"
+    pub url: Option<String>,
+}
+
+impl TicketConfig {
+    /// The first ticket ID in a branch name, e.g. "PROJ-123" in "feature/PROJ-123-login"
+    pub fn ticket_id(&self, branch: &str) -> Option<String> {
+        let captures = self.pattern.0.captures(branch)?;
+        captures
+            .get(1)
+            .or_else(|| captures.get(0))
+            .map(|ticket| ticket.as_str().to_string())
+    }
+
+    /// The tracker link for a ticket, when a URL is configured
+    pub fn ticket_url(&self, ticket: &str) -> Option<String> {
+        self.url.as_ref().map(|url| url.replace("{ticket}", ticket))
+    }
+}
+
+/// A compiled ticket pattern, by default Jira-style keys like "PROJ-123"
+#[derive(Debug, Clone)]
+pub struct TicketPattern(pub Regex);
+
+impl Default for TicketPattern {
+    fn default() -> Self {
+        TicketPattern(Regex::new(r"[A-Z][A-Z0-9]+-\d+").expect("valid default ticket pattern"))
+    }
+}
+
+impl<'de> Deserialize<'de> for TicketPattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        Regex::new(&pattern).map(TicketPattern).map_err(|e| {
+            serde::de::Error::custom(format!("invalid ticket pattern '{}': {}", pattern, e))
+        })
+    }
+}
+
+/// Glyph per status value; unset statuses keep the icon set's glyph
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StatusIcons {
+    pub clean: Option<String>,
+    pub dirty: Option<String>,
+    pub staged: Option<String>,
+    pub conflicted: Option<String>,
+    pub missing: Option<String>,
+    pub locked: Option<String>,
+}
+
+/// Color per status value; unset statuses keep their default color
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StatusColors {
+    pub clean: Option<ColorName>,
+    pub dirty: Option<ColorName>,
+    pub staged: Option<ColorName>,
+    pub conflicted: Option<ColorName>,
+    pub missing: Option<ColorName>,
+    pub open: Option<ColorName>,
+    pub draft: Option<ColorName>,
+    pub merged: Option<ColorName>,
+    pub closed: Option<ColorName>,
+}
+
+/// A color such as "green" or "bright blue"
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorName(pub Color);
+
+impl<'de> Deserialize<'de> for ColorName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map(ColorName).map_err(|_| {
+            serde::de::Error::custom(format!(
+                "unknown color '{}' (expected e.g. \"green\" or \"bright blue\")",
+                name
+            ))
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColumnConfig {
+    pub max_width: Option<usize>,
+    pub truncate: TruncateStrategy,
+}
+
+/// Where the ellipsis goes when a cell is wider than its column allows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TruncateStrategy {
+    /// Drop the start of the text: "…fix-login-page"
+    Head,
+    /// Drop the end of the text: "PROJ-1234-fix…"
+    #[default]
+    Tail,
+    /// Drop the middle of the text: "PROJ-12…n-page"
+    Middle,
+}
+
+/// Terminal rendering of emoji such as ✅ or ⬆️
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmojiWidth {
+    /// Two columns, as Unicode specifies and most modern terminals do
+    #[default]
+    Wide,
+    /// One column, as in older terminals and some fonts; cells are padded to stay aligned
+    Narrow,
+}
+
+/// Glyphs shown next to statuses in tables
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IconSet {
+    /// Color emoji such as ✅ and 🔧
+    #[default]
+    Emoji,
+    /// Icons from a patched [Nerd Font](https://www.nerdfonts.com)
+    Nerd,
+    /// Plain characters such as `*` and `+`, which every terminal draws one column wide
+    Ascii,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RepoConfig {
+    /// Shell command run inside a worktree before it is removed (e.g. "docker compose down")
+    pub teardown: Option<String>,
+    /// Shell command run inside a new worktree after `gwm add` (e.g. "pnpm install")
+    pub post_add: Option<String>,
+    /// Untracked files or directories copied from the main worktree into new ones
+    /// (e.g. ".env"), relative to the worktree
+    pub copy_on_add: Vec<String>,
+    /// Heavy directories (e.g. "node_modules") shared with new worktrees instead of rebuilt
+    pub link_on_add: Vec<String>,
+    /// How `link_on_add` directories are shared, symlinks by default
+    pub link_mode: Option<LinkMode>,
+    /// Directory holding the shared copies, e.g. "~/.cache/my-project"; the main worktree
+    /// when unset
+    pub link_from: Option<String>,
+    /// Run the commands in the repository's `.gwm/template.toml`, which anyone who can push a
+    /// branch can change
+    pub trust_template: bool,
+}
+
+/// Worktree setup checked into a repository as `.gwm/template.toml`, so a team shares it
+/// Same keys as `[repos.<name>]`, which takes precedence; the lists are combined
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Template {
+    pub copy_on_add: Vec<String>,
+    pub link_on_add: Vec<String>,
+    pub link_mode: Option<LinkMode>,
+    pub post_add: Option<String>,
+    pub teardown: Option<String>,
+}
+
+impl Template {
+    /// Where the template lives, relative to a worktree
+    pub const PATH: &str = ".gwm/template.toml";
+
+    /// The template in a worktree's checkout, `None` when it has none
+    pub fn load(worktree: &Path) -> Result<Option<Self>> {
+        let path = worktree.join(Self::PATH);
+        let Ok(contents) = fs::read_to_string(&path) else {
+            return Ok(None);
+        };
+        toml::from_str(&contents)
+            .map(Some)
+            .map_err(|e| anyhow!("Invalid template '{}': {}", path.display(), e))
+    }
+
+    /// Whether the template has commands, which only run for repositories that trust it
+    pub fn has_commands(&self) -> bool {
+        self.post_add.is_some() || self.teardown.is_some()
+    }
+}
+
+/// How a directory from `link_on_add` is shared with a new worktree
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkMode {
+    /// A symlink to the shared directory: changes in any worktree show up in all of them
+    #[default]
+    Symlink,
+    /// A copy made of hard links, which takes no extra space; replacing a file (as package
+    /// managers do) affects only one worktree, but editing a file in place affects all
+    Hardlink,
+}
+
+/// Directory jumper whose database holds worktree directories, for `z <branch>`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JumpTool {
+    Zoxide,
+    Autojump,
+}
+
+/// A named combination of `list` filters
+/// Statuses are true to ask for them and false to hide them, as `--dirty` and `--not-dirty` do
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FilterPreset {
+    pub dirty: Option<bool>,
+    pub clean: Option<bool>,
+    pub staged: Option<bool>,
+    pub conflicted: Option<bool>,
+    pub missing: Option<bool>,
+    pub pr_open: Option<bool>,
+    pub pr_draft: Option<bool>,
+    pub pr_merged: Option<bool>,
+    pub pr_none: Option<bool>,
+    /// Age such as "30d" or "2w", as `--older-than` takes
+    pub older_than: Option<String>,
+    pub newer_than: Option<String>,
+    /// Branch name glob, as `--branch` takes
+    pub branch: Option<String>,
+    /// "any" or "all", as `--match` takes
+    #[serde(rename = "match")]
+    pub match_mode: Option<MatchMode>,
+    /// Expression, as `--filter` takes
+    pub filter: Option<String>,
+}
+
+impl FilterPreset {
+    /// Build the worktree filter the preset describes
+    pub fn to_filter(&self) -> Result<WorktreeFilter> {
+        let age = |age: &Option<String>, key: &str| {
+            age.as_deref()
+                .map(WorktreeFilter::parse_age_to_days)
+                .transpose()
+                .map_err(|e| anyhow!("Invalid {}: {}", key, e))
+        };
+        let expression = self
+            .filter
+            .as_deref()
+            .map(str::parse::<FilterExpr>)
+            .transpose()
+            .map_err(|e| anyhow!("Invalid filter expression: {}", e))?;
+
+        Ok(WorktreeFilter {
+            dirty: self.dirty,
+            clean: self.clean,
+            staged: self.staged,
+            conflicted: self.conflicted,
+            missing: self.missing,
+            pr_open: self.pr_open,
+            pr_draft: self.pr_draft,
+            pr_merged: self.pr_merged,
+            pr_none: self.pr_none,
+            older_than_days: age(&self.older_than, "older_than")?,
+            newer_than_days: age(&self.newer_than, "newer_than")?,
+            branch_pattern: self.branch.clone(),
+            match_mode: self.match_mode,
+            expression,
+            ..WorktreeFilter::default()
+        })
+    }
+}
+
+/// Defaults for `gwm add`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AddConfig {
+    /// Make new branches track origin/<branch>, as `--track` does
+    pub track: bool,
+    /// Push new branches to origin and track them, as `--push` does
+    pub push: bool,
+    /// Run `direnv allow` in new worktrees with an `.envrc`, and `direnv deny` before removing them
+    pub direnv: bool,
+    /// Add new worktrees to this directory jumper's database, and drop removed ones from it
+    pub jump: Option<JumpTool>,
+}
+
+/// Defaults for `gwm remove` and `gc`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RemoveConfig {
+    /// Tag each branch as archive/<branch>/<date> before removing its worktree, as `--archive` does
+    pub archive: bool,
+    /// Move worktree directories to the trash instead of deleting them, as `--trash` does
+    pub trash: bool,
+}
+
+/// Defaults for `gwm sync` and the daemon's fetches
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SyncConfig {
+    /// Which tags to fetch ("auto", "all" or "none"), overridden by `--tags` and `--no-tags`
+    pub tags: FetchTags,
+    /// Seconds a repository's fetch may take before it is abandoned, overridden by `--timeout`
+    pub timeout: u64,
+    /// How many times a failed or timed-out fetch is retried, overridden by `--retries`
+    pub retries: u32,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            tags: FetchTags::default(),
+            timeout: 120,
+            retries: 2,
+        }
+    }
+}
+
+/// Local usage statistics, never sent anywhere
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UsageConfig {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GitConfig {
+    /// Library used to read worktree status ("libgit2" or "gitoxide")
+    pub backend: Backend,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GitHubConfig {
+    /// Whose pull requests to match to branches ("me" or "any"), overridden by `--pr-author`
+    pub pr_author: PrAuthor,
+    /// Tokens for other hosts or organizations than `GITHUB_TOKEN` covers, as
+    /// `[[github.tokens]]` tables
+    pub tokens: Vec<TokenConfig>,
+    /// How to talk to GitHub ("api" or "gh")
+    pub backend: ForgeBackend,
+}
+
+impl GitHubConfig {
+    pub fn credentials(&self) -> Credentials {
+        Credentials::new(self.tokens.clone()).backend(self.backend)
+    }
+}
+
+impl Config {
+    /// Settings for every forge, to look pull requests up wherever a remote lives
+    pub fn forges(&self) -> Forges {
+        Forges::new(self.github.credentials())
+            .bitbucket(self.bitbucket.clone())
+            .azure_devops(self.azure_devops.clone())
+    }
+
+    /// Load the user configuration, falling back to defaults when no file exists
+    pub fn load() -> Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = fs::read_to_string(&path)
+            .map_err(|e| anyhow!("Failed to read config file '{}': {}", path.display(), e))?;
+        Self::parse(&contents)
+            .map_err(|e| anyhow!("Invalid config file '{}': {}", path.display(), e))
+    }
+
+    /// Pure function to parse configuration from TOML text
+    pub fn parse(contents: &str) -> Result<Self> {
+        Ok(toml::from_str(contents)?)
+    }
+
+    /// Teardown command configured for a repository, if any
+    pub fn teardown(&self, repo: &str) -> Option<&str> {
+        self.repos.get(repo)?.teardown.as_deref()
+    }
+
+    /// Setup command configured for a repository's new worktrees, if any
+    pub fn post_add(&self, repo: &str) -> Option<&str> {
+        self.repos.get(repo)?.post_add.as_deref()
+    }
+
+    /// Files to copy from the main worktree into a repository's new worktrees
+    pub fn copy_on_add(&self, repo: &str) -> &[String] {
+        self.repos
+            .get(repo)
+            .map(|repo| repo.copy_on_add.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Directories to share with a repository's new worktrees
+    pub fn link_on_add(&self, repo: &str) -> &[String] {
+        self.repos
+            .get(repo)
+            .map(|repo| repo.link_on_add.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// How a repository's `link_on_add` directories are shared
+    pub fn link_mode(&self, repo: &str) -> LinkMode {
+        self.repos
+            .get(repo)
+            .and_then(|repo| repo.link_mode)
+            .unwrap_or_default()
+    }
+
+    /// Whether the commands in a repository's `.gwm/template.toml` may run
+    pub fn trusts_template(&self, repo: &str) -> bool {
+        self.repos.get(repo).is_some_and(|repo| repo.trust_template)
+    }
+
+    /// This configuration with a repository's template filling in what its `[repos.<name>]`
+    /// table leaves out; the template's commands only when the repository trusts it
+    pub fn with_template(&self, repo: &str, template: &Template) -> Config {
+        let mut config = self.clone();
+        let settings = config.repos.entry(repo.to_string()).or_default();
+        for (paths, extra) in [
+            (&mut settings.copy_on_add, &template.copy_on_add),
+            (&mut settings.link_on_add, &template.link_on_add),
+        ] {
+            for path in extra {
+                if !paths.contains(path) {
+                    paths.push(path.clone());
+                }
+            }
+        }
+        settings.link_mode = settings.link_mode.or(template.link_mode);
+        if settings.trust_template {
+            settings.post_add = settings.post_add.take().or(template.post_add.clone());
+            settings.teardown = settings.teardown.take().or(template.teardown.clone());
+        }
+        config
+    }
+
+    /// Directory holding a repository's shared directories, with `~` expanded, if configured
+    pub fn link_from(&self, repo: &str) -> Option<PathBuf> {
+        let dir = self.repos.get(repo)?.link_from.as_deref()?;
+        match dir.strip_prefix("~/") {
+            Some(rest) => std::env::var("HOME")
+                .ok()
+                .map(|home| PathBuf::from(home).join(rest)),
+            None => Some(PathBuf::from(dir)),
+        }
+    }
+
+    /// Filter of the `[filters.<name>]` preset
+    pub fn filter_preset(&self, name: &str) -> Result<WorktreeFilter> {
+        let Some(preset) = self.filters.get(name) else {
+            let mut names: Vec<_> = self.filters.keys().map(String::as_str).collect();
+            names.sort_unstable();
+            return Err(if names.is_empty() {
+                anyhow!(
+                    "Unknown filter preset '{}': the config file defines no [filters.<name>] tables",
+                    name
+                )
+            } else {
+                anyhow!(
+                    "Unknown filter preset '{}' (defined: {})",
+                    name,
+                    names.join(", ")
+                )
+            });
+        };
+        preset
+            .to_filter()
+            .map_err(|e| anyhow!("Filter preset '{}': {}", name, e))
+    }
+
+    /// Location of the config file, if one can be determined
+    pub fn path() -> Option<PathBuf> {
+        if let Ok(path) = std::env::var("GWM_CONFIG") {
+            return Some(PathBuf::from(path));
+        }
+        config_dir().map(|dir| dir.join("gwm").join("config.toml"))
+    }
+}
+
+/// Directory for gwm's persistent data ($XDG_DATA_HOME/gwm or ~/.local/share/gwm)
+pub fn data_dir() -> Option<PathBuf> {
+    if let Ok(dir) = std::env::var("XDG_DATA_HOME")
+        && !dir.is_empty()
+    {
+        return Some(PathBuf::from(dir).join("gwm"));
+    }
+    std::env::var("HOME")
+        .ok()
+        .map(|home| PathBuf::from(home).join(".local").join("share").join("gwm"))
+}
+
+/// Directory for disposable cached data ($XDG_CACHE_HOME/gwm or ~/.cache/gwm)
+pub fn cache_dir() -> Option<PathBuf> {
+    if let Ok(dir) = std::env::var("XDG_CACHE_HOME")
+        && !dir.is_empty()
+    {
+        return Some(PathBuf::from(dir).join("gwm"));
+    }
+    std::env::var("HOME")
+        .ok()
+        .map(|home| PathBuf::from(home).join(".cache").join("gwm"))
+}
+
+/// Base directory for user configuration ($XDG_CONFIG_HOME or ~/.config)
+fn config_dir() -> Option<PathBuf> {
+    if let Ok(dir) = std::env::var("XDG_CONFIG_HOME")
+        && !dir.is_empty()
+    {
+        return Some(PathBuf::from(dir));
+    }
+    std::env::var("HOME")
+        .ok()
+        .map(|home| PathBuf::from(home).join(".config"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::{Event, SinkKind};
+
+    #[test]
+    fn empty_config_uses_defaults() {
+        let config = Config::parse("").unwrap();
+        assert!(config.table.columns.is_empty());
+    }
+
+    #[test]
+    fn parses_column_widths() {
+        let config = Config::parse(
+            r#"
+            [table.columns.branch]
+            max_width = 30
+            truncate = "head"
+
+            [table.columns.last_commit]
+            max_width = 50
+            "#,
+        )
+        .unwrap();
+
+        let branch = &config.table.columns["branch"];
+        assert_eq!(branch.max_width, Some(30));
+        assert_eq!(branch.truncate, TruncateStrategy::Head);
+        assert_eq!(
+            config.table.columns["last_commit"].truncate,
+            TruncateStrategy::Tail
+        );
+    }
+
+    #[test]
+    fn extracts_ticket_ids_from_branch_names() {
+        let config = Config::default();
+        let tickets = &config.table.tickets;
+        assert_eq!(
+            tickets.ticket_id("feature/PROJ-123-login").as_deref(),
+            Some("PROJ-123")
+        );
+        assert_eq!(tickets.ticket_id("fix-login-2"), None);
+        assert_eq!(tickets.ticket_url("PROJ-123"), None);
+
+        let config = Config::parse(
+            r#"
+            [table.tickets]
+            pattern = "gh-(\\d+)"
+            url = "https://github.com/acme/api/issues/{ticket}"
+            "#,
+        )
+        .unwrap();
+        let tickets = &config.table.tickets;
+        assert_eq!(tickets.ticket_id("gh-42-flaky-test").as_deref(), Some("42"));
+        assert_eq!(
+            tickets.ticket_url("42").as_deref(),
+            Some("https://github.com/acme/api/issues/42")
+        );
+        assert!(Config::parse("[table.tickets]\npattern = \"(\"").is_err());
+    }
+
+    #[test]
+    fn parses_icon_set_and_overrides() {
+        let config = Config::parse(
+            r#"
+            [table]
+            icon_set = "ascii"
+
+            [table.icons]
+            dirty = "~"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.table.icon_set, IconSet::Ascii);
+        assert_eq!(config.table.icons.dirty.as_deref(), Some("~"));
+        assert_eq!(config.table.icons.clean, None);
+        assert!(Config::parse("[table]\nicon_set = \"fancy\"").is_err());
+    }
+
+    #[test]
+    fn pr_author_defaults_to_me() {
+        assert_eq!(Config::parse("").unwrap().github.backend, ForgeBackend::Api);
+        assert_eq!(Config::parse("").unwrap().github.pr_author, PrAuthor::Me);
+        let config = Config::parse("[github]\npr_author = \"any\"").unwrap();
+        assert_eq!(config.github.pr_author, PrAuthor::Any);
+    }
+
+    #[test]
+    fn parses_emoji_width() {
+        assert_eq!(
+            Config::parse("").unwrap().table.emoji_width,
+            EmojiWidth::Wide
+        );
+        assert_eq!(
+            Config::parse("[table]\nemoji_width = \"narrow\"")
+                .unwrap()
+                .table
+                .emoji_width,
+            EmojiWidth::Narrow
+        );
+    }
+
+    #[test]
+    fn parses_table_style_and_status_colors() {
+        let config = Config::parse(
+            r#"
+            [table]
+            style = "markdown"
+
+            [table.colors]
+            dirty = "bright magenta"
+            merged = "blue"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.table.style, TableStyle::Markdown);
+        assert_eq!(
+            config.table.colors.dirty,
+            Some(ColorName(Color::BrightMagenta))
+        );
+        assert_eq!(config.table.colors.merged, Some(ColorName(Color::Blue)));
+        assert_eq!(config.table.colors.clean, None);
+        assert!(Config::parse("[table.colors]\nclean = \"plaid\"").is_err());
+        assert!(Config::parse("[table.colors]\nahead = \"red\"").is_err());
+    }
+
+    #[test]
+    fn parses_filter_presets() {
+        let config = Config::parse(
+            r#"
+            [filters.weekly-triage]
+            dirty = true
+            staged = true
+            missing = false
+            older_than = "2w"
+            match = "any"
+            filter = "!locked"
+            "#,
+        )
+        .unwrap();
+
+        let filter = config.filter_preset("weekly-triage").unwrap();
+        assert_eq!(filter.dirty, Some(true));
+        assert_eq!(filter.missing, Some(false));
+        assert_eq!(filter.older_than_days, Some(14));
+        assert_eq!(filter.match_mode, Some(MatchMode::Any));
+        assert_eq!(
+            filter.expression,
+            Some(FilterExpr::Not(Box::new(FilterExpr::Locked)))
+        );
+        assert!(
+            config
+                .filter_preset("daily")
+                .unwrap_err()
+                .to_string()
+                .contains("defined: weekly-triage")
+        );
+        assert!(
+            Config::parse(
+                "[filters.x]
+shiny = true"
+            )
+            .is_err()
+        );
+        assert!(
+            Config::parse(
+                "[filters.x]
+older_than = \"soon\""
+            )
+            .unwrap()
+            .filter_preset("x")
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn usage_stats_are_opt_in() {
+        assert!(!Config::parse("").unwrap().usage.enabled);
+        assert!(
+            Config::parse("[usage]\nenabled = true")
+                .unwrap()
+                .usage
+                .enabled
+        );
+    }
+
+    #[test]
+    fn parses_git_backend() {
+        assert_eq!(Config::parse("").unwrap().git.backend, Backend::Libgit2);
+        assert_eq!(
+            Config::parse("[git]\nbackend = \"gitoxide\"")
+                .unwrap()
+                .git
+                .backend,
+            Backend::Gitoxide
+        );
+    }
+
+    #[test]
+    fn parses_notification_sinks() {
+        let config = Config::parse(
+            r#"
+            [[notify]]
+            sink = "desktop"
+
+            [[notify]]
+            sink = "webhook"
+            url = "https://hooks.slack.com/services/T0/B0/x"
+            events = ["gc_candidates", "failure"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.notify.len(), 2);
+        assert_eq!(config.notify[0].sink, SinkKind::Desktop);
+        assert!(config.notify[0].events.is_empty());
+        assert_eq!(
+            config.notify[1].events,
+            vec![Event::GcCandidates, Event::Failure]
+        );
+        assert!(Config::parse("[[notify]]\nsink = \"pager\"").is_err());
+    }
+
+    #[test]
+    fn parses_per_repo_teardown() {
+        let config = Config::parse(
+            r#"
+            [repos.api]
+            teardown = "docker compose down -v"
+            post_add = "make setup"
+            copy_on_add = [".env", ".vscode"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.teardown("api"), Some("docker compose down -v"));
+        assert_eq!(config.teardown("web"), None);
+        assert_eq!(config.post_add("api"), Some("make setup"));
+        assert_eq!(config.copy_on_add("api"), [".env", ".vscode"]);
+        assert!(config.copy_on_add("web").is_empty());
+        assert_eq!(config.link_mode("api"), LinkMode::Symlink);
+    }
+
+    #[test]
+    fn templates_fill_in_repo_settings() {
+        let template: Template = toml::from_str(
+            r#"
+            copy_on_add = [".env"]
+            link_on_add = ["node_modules"]
+            link_mode = "hardlink"
+            post_add = "make bootstrap"
+            teardown = "make clean"
+            "#,
+        )
+        .unwrap();
+        assert!(template.has_commands());
+        let config = Config::parse(
+            r#"
+            [repos.api]
+            copy_on_add = [".vscode", ".env"]
+            teardown = "docker compose down"
+
+            [repos.web]
+            trust_template = true
+            post_add = "pnpm install"
+            "#,
+        )
+        .unwrap();
+
+        // Untrusted: files and links apply, commands don't
+        let api = config.with_template("api", &template);
+        assert_eq!(api.copy_on_add("api"), [".vscode", ".env"]);
+        assert_eq!(api.link_on_add("api"), ["node_modules"]);
+        assert_eq!(api.link_mode("api"), LinkMode::Hardlink);
+        assert_eq!(api.post_add("api"), None);
+        assert_eq!(api.teardown("api"), Some("docker compose down"));
+
+        // Trusted: the config file still wins where it says something
+        let web = config.with_template("web", &template);
+        assert_eq!(web.post_add("web"), Some("pnpm install"));
+        assert_eq!(web.teardown("web"), Some("make clean"));
+        assert_eq!(config.with_template("cli", &template).post_add("cli"), None);
+    }
+
+    #[test]
+    fn parses_shared_directories() {
+        let config = Config::parse(
+            r#"
+            [repos.web]
+            link_on_add = ["node_modules", ".venv"]
+            link_mode = "hardlink"
+            link_from = "/var/cache/web"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.link_on_add("web"), ["node_modules", ".venv"]);
+        assert_eq!(config.link_mode("web"), LinkMode::Hardlink);
+        assert_eq!(
+            config.link_from("web"),
+            Some(PathBuf::from("/var/cache/web"))
+        );
+        assert_eq!(config.link_from("api"), None);
+        assert!(Config::parse("[repos.web]\nlink_mode = \"reflink\"").is_err());
+    }
+
+    #[test]
+    fn parses_command_defaults() {
+        let config = Config::parse(
+            r#"
+            [defaults.list]
+            no_emoji = true
+            older-than = "14d"
+
+            [defaults.sync]
+            retries = 5
+
+            [defaults.add]
+            repos = ["api", "web"]
+            "#,
+        )
+        .unwrap();
+        let list = &config.defaults["list"];
+        assert_eq!(list["no_emoji"].values(), vec!["true"]);
+        assert_eq!(list["older-than"].values(), vec!["14d"]);
+        assert_eq!(config.defaults["sync"]["retries"].values(), vec!["5"]);
+        assert_eq!(config.defaults["add"]["repos"].values(), vec!["api", "web"]);
+        assert!(Config::parse("[defaults.add]\nrepos = { api = 1 }").is_err());
+    }
+
+    #[test]
+    fn parses_add_defaults() {
+        let config = Config::parse(
+            r#"
+            [add]
+            track = true
+            direnv = true
+            jump = "zoxide"
+            "#,
+        )
+        .unwrap();
+        assert!(config.add.track);
+        assert!(!config.add.push);
+        assert!(config.add.direnv);
+        assert_eq!(config.add.jump, Some(JumpTool::Zoxide));
+        assert!(Config::parse("[add]\njump = \"fasd\"").is_err());
+    }
+
+    #[test]
+    fn parses_remove_defaults() {
+        let config = Config::parse(
+            r#"
+            [remove]
+            archive = true
+            "#,
+        )
+        .unwrap();
+        assert!(config.remove.archive);
+        assert!(!config.remove.trash);
+    }
+
+    #[test]
+    fn parses_sync_tags() {
+        let config = Config::parse(
+            r#"
+            [sync]
+            tags = "all"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.sync.tags, FetchTags::All);
+        assert_eq!(config.sync.timeout, 120);
+        assert_eq!(config.sync.retries, 2);
+    }
+
+    #[test]
+    fn parses_sync_timeout_and_retries() {
+        let config = Config::parse(
+            r#"
+            [sync]
+            timeout = 30
+            retries = 0
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.sync.timeout, 30);
+        assert_eq!(config.sync.retries, 0);
+    }
+
+    #[test]
+    fn rejects_unknown_truncate_strategy() {
+        let result = Config::parse(
+            r#"
+            [table.columns.branch]
+            truncate = "sideways"
+            "#,
+        );
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,436 @@
+use std::str::FromStr;
+
+use crate::core::{PrStatus, WorktreeFilter, WorktreeResult, glob_matches};
+use crate::git::LocalStatus;
+
+/// A boolean expression over worktree statuses, for `gwm list --filter`
+/// e.g. `clean && age > 30d && (merged || no_pr)`
+///
+/// Names: `clean`, `dirty`, `staged`, `conflicted`, `missing`; `open`, `draft`, `merged`,
+/// `closed`, `no_pr` (also with a `pr_` prefix); `locked`, `stashed`, `in_progress`
+/// Comparisons: `age`, `activity` and `stashes` with `<`, `<=`, `>`, `>=`, `==`, `!=`,
+/// and `branch == 'glob'` or `branch != 'glob'`
+/// Operators, loosest first: `||`, `&&`, `!`, with parentheses for grouping
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterExpr {
+    Not(Box<FilterExpr>),
+    And(Box<FilterExpr>, Box<FilterExpr>),
+    Or(Box<FilterExpr>, Box<FilterExpr>),
+    Local(LocalStatus),
+    /// None for branches without a pull request
+    Pr(Option<PrStatus>),
+    Locked,
+    Stashed,
+    /// A rebase, merge or other git operation waiting to be continued or aborted
+    InProgress,
+    /// Days since the branch's last commit
+    Age(Comparison, u32),
+    /// Days since the branch was last worked on, including uncommitted work
+    Activity(Comparison, u32),
+    Stashes(Comparison, usize),
+    Branch {
+        pattern: String,
+        negated: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+    NotEqual,
+}
+
+impl Comparison {
+    fn holds<T: PartialOrd>(self, left: T, right: T) -> bool {
+        match self {
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Greater => left > right,
+            Comparison::GreaterOrEqual => left >= right,
+            Comparison::Equal => left == right,
+            Comparison::NotEqual => left != right,
+        }
+    }
+}
+
+impl FilterExpr {
+    /// Pure function to check if a worktree satisfies the expression
+    pub fn matches(&self, worktree: &WorktreeResult, current_timestamp: i64) -> bool {
+        let status = &worktree.status;
+        match self {
+            FilterExpr::Not(inner) => !inner.matches(worktree, current_timestamp),
+            FilterExpr::And(left, right) => {
+                left.matches(worktree, current_timestamp)
+                    && right.matches(worktree, current_timestamp)
+            }
+            FilterExpr::Or(left, right) => {
+                left.matches(worktree, current_timestamp)
+                    || right.matches(worktree, current_timestamp)
+            }
+            FilterExpr::Local(local_status) => status.local_status == *local_status,
+            FilterExpr::Pr(pr_status) => status.pr_status == *pr_status,
+            FilterExpr::Locked => status.locked.is_some(),
+            FilterExpr::Stashed => status.stash_count > 0,
+            FilterExpr::InProgress => status.operation.is_some(),
+            FilterExpr::Age(comparison, days) => days_since(
+                status.commit_timestamp,
+                current_timestamp,
+                *comparison,
+                *days,
+            ),
+            FilterExpr::Activity(comparison, days) => days_since(
+                status.last_activity(),
+                current_timestamp,
+                *comparison,
+                *days,
+            ),
+            FilterExpr::Stashes(comparison, count) => comparison.holds(status.stash_count, *count),
+            FilterExpr::Branch { pattern, negated } => {
+                glob_matches(pattern, &worktree.branch) != *negated
+            }
+        }
+    }
+
+    /// Whether the expression looks at pull requests, which `--no-pr-status` leaves unknown
+    pub fn uses_pr_status(&self) -> bool {
+        match self {
+            FilterExpr::Not(inner) => inner.uses_pr_status(),
+            FilterExpr::And(left, right) | FilterExpr::Or(left, right) => {
+                left.uses_pr_status() || right.uses_pr_status()
+            }
+            FilterExpr::Pr(_) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Compare the whole days since a timestamp; unknown times always pass, as with --older-than
+fn days_since(timestamp: i64, current_timestamp: i64, comparison: Comparison, days: u32) -> bool {
+    timestamp == 0
+        || comparison.holds(
+            (current_timestamp - timestamp) / (24 * 60 * 60),
+            days as i64,
+        )
+}
+
+impl FromStr for FilterExpr {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            position: 0,
+        };
+        let expr = parser.parse_or()?;
+        match parser.tokens.get(parser.position) {
+            None => Ok(expr),
+            Some((offset, token)) => Err(format!("unexpected {} at column {}", token, offset + 1)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+    Compare(Comparison),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "'{}'", word),
+            Token::Quoted(text) => write!(f, "'{}'", text),
+            Token::And => write!(f, "'&&'"),
+            Token::Or => write!(f, "'||'"),
+            Token::Not => write!(f, "'!'"),
+            Token::Open => write!(f, "'('"),
+            Token::Close => write!(f, "')'"),
+            Token::Compare(_) => write!(f, "comparison"),
+        }
+    }
+}
+
+/// Split an expression into tokens, each with its byte offset for error messages
+fn tokenize(text: &str) -> Result<Vec<(usize, Token)>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some(&(offset, c)) = chars.peek() {
+        let next = text[offset + c.len_utf8()..].chars().next();
+        let (token, length) = match (c, next) {
+            (c, _) if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            ('&', Some('&')) => (Token::And, 2),
+            ('|', Some('|')) => (Token::Or, 2),
+            ('!', Some('=')) => (Token::Compare(Comparison::NotEqual), 2),
+            ('=', Some('=')) => (Token::Compare(Comparison::Equal), 2),
+            ('<', Some('=')) => (Token::Compare(Comparison::LessOrEqual), 2),
+            ('>', Some('=')) => (Token::Compare(Comparison::GreaterOrEqual), 2),
+            ('<', _) => (Token::Compare(Comparison::Less), 1),
+            ('>', _) => (Token::Compare(Comparison::Greater), 1),
+            ('!', _) => (Token::Not, 1),
+            ('(', _) => (Token::Open, 1),
+            (')', _) => (Token::Close, 1),
+            ('\'' | '"', _) => {
+                let rest = &text[offset + 1..];
+                let end = rest
+                    .find(c)
+                    .ok_or_else(|| format!("unterminated string at column {}", offset + 1))?;
+                (Token::Quoted(rest[..end].to_string()), end + 2)
+            }
+            (c, _) if is_word_char(c) => {
+                let word: String = text[offset..]
+                    .chars()
+                    .take_while(|&c| is_word_char(c))
+                    .collect();
+                let length = word.len();
+                (Token::Word(word), length)
+            }
+            (c, _) => return Err(format!("unexpected '{}' at column {}", c, offset + 1)),
+        };
+        tokens.push((offset, token));
+        while chars.peek().is_some_and(|&(i, _)| i < offset + length) {
+            chars.next();
+        }
+    }
+    Ok(tokens)
+}
+
+/// Characters of names, numbers, ages and unquoted branch globs
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '/' | '*' | '?')
+}
+
+/// Recursive descent over the tokens, one method per precedence level
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(_, token)| token)
+    }
+
+    fn next(&mut self) -> Result<(usize, Token), String> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .cloned()
+            .ok_or_else(|| "unexpected end of expression".to_string())?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn parse_or(&mut self) -> Result<FilterExpr, String> {
+        let mut expr = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.position += 1;
+            expr = FilterExpr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<FilterExpr, String> {
+        let mut expr = self.parse_unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.position += 1;
+            expr = FilterExpr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<FilterExpr, String> {
+        let (offset, token) = self.next()?;
+        match token {
+            Token::Not => Ok(FilterExpr::Not(Box::new(self.parse_unary()?))),
+            Token::Open => {
+                let expr = self.parse_or()?;
+                match self.next() {
+                    Ok((_, Token::Close)) => Ok(expr),
+                    Ok((offset, token)) => Err(format!(
+                        "expected ')' but found {} at column {}",
+                        token,
+                        offset + 1
+                    )),
+                    Err(_) => Err(format!("unclosed '(' at column {}", offset + 1)),
+                }
+            }
+            Token::Word(name) => self.parse_name(&name, offset),
+            token => Err(format!("unexpected {} at column {}", token, offset + 1)),
+        }
+    }
+
+    /// A status name, or a field followed by a comparison
+    fn parse_name(&mut self, name: &str, offset: usize) -> Result<FilterExpr, String> {
+        let expr = match name {
+            "clean" => FilterExpr::Local(LocalStatus::Clean),
+            "dirty" => FilterExpr::Local(LocalStatus::Dirty),
+            "staged" => FilterExpr::Local(LocalStatus::Staged),
+            "conflicted" => FilterExpr::Local(LocalStatus::Conflicted),
+            "missing" => FilterExpr::Local(LocalStatus::Missing),
+            "open" | "pr_open" => FilterExpr::Pr(Some(PrStatus::Open)),
+            "draft" | "pr_draft" => FilterExpr::Pr(Some(PrStatus::Draft)),
+            "merged" | "pr_merged" => FilterExpr::Pr(Some(PrStatus::Merged)),
+            "closed" | "pr_closed" => FilterExpr::Pr(Some(PrStatus::Closed)),
+            "no_pr" | "pr_none" => FilterExpr::Pr(None),
+            "locked" => FilterExpr::Locked,
+            "stashed" => FilterExpr::Stashed,
+            "in_progress" => FilterExpr::InProgress,
+            "age" | "activity" | "stashes" | "branch" => {
+                return self.parse_comparison(name, offset);
+            }
+            _ => {
+                return Err(format!(
+                    "unknown name '{}' at column {} (expected a status such as clean or merged, \
+                     or age, activity, stashes or branch)",
+                    name,
+                    offset + 1
+                ));
+            }
+        };
+        Ok(expr)
+    }
+
+    fn parse_comparison(&mut self, field: &str, offset: usize) -> Result<FilterExpr, String> {
+        let comparison = match self.next() {
+            Ok((_, Token::Compare(comparison))) => comparison,
+            _ => {
+                return Err(format!(
+                    "expected a comparison after '{}' at column {}",
+                    field,
+                    offset + 1
+                ));
+            }
+        };
+        let (value_offset, value) = match self.next()? {
+            (value_offset, Token::Word(value) | Token::Quoted(value)) => (value_offset, value),
+            (value_offset, token) => {
+                return Err(format!(
+                    "expected a value but found {} at column {}",
+                    token,
+                    value_offset + 1
+                ));
+            }
+        };
+        let invalid = |reason: String| format!("{} at column {}", reason, value_offset + 1);
+
+        match field {
+            "age" => WorktreeFilter::parse_age_to_days(&value)
+                .map(|days| FilterExpr::Age(comparison, days))
+                .map_err(invalid),
+            "activity" => WorktreeFilter::parse_age_to_days(&value)
+                .map(|days| FilterExpr::Activity(comparison, days))
+                .map_err(invalid),
+            "stashes" => value
+                .parse()
+                .map(|count| FilterExpr::Stashes(comparison, count))
+                .map_err(|_| invalid(format!("invalid stash count '{}'", value))),
+            _ => match comparison {
+                Comparison::Equal | Comparison::NotEqual => Ok(FilterExpr::Branch {
+                    pattern: value,
+                    negated: comparison == Comparison::NotEqual,
+                }),
+                _ => Err(format!(
+                    "branch can only be compared with == or != at column {}",
+                    offset + 1
+                )),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::WorktreeStatus;
+    use std::path::PathBuf;
+
+    fn worktree(
+        branch: &str,
+        local_status: LocalStatus,
+        pr_status: Option<PrStatus>,
+    ) -> WorktreeResult {
+        WorktreeResult {
+            branch: branch.to_string(),
+            path: PathBuf::from("/repos/project").join(branch),
+            status: WorktreeStatus {
+                local_status,
+                commit_timestamp: 1,
+                commit_summary: "test commit".to_string(),
+                pr_status,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let expr: FilterExpr = "clean && merged || !dirty".parse().unwrap();
+        assert_eq!(
+            expr,
+            FilterExpr::Or(
+                Box::new(FilterExpr::And(
+                    Box::new(FilterExpr::Local(LocalStatus::Clean)),
+                    Box::new(FilterExpr::Pr(Some(PrStatus::Merged))),
+                )),
+                Box::new(FilterExpr::Not(Box::new(FilterExpr::Local(
+                    LocalStatus::Dirty
+                )))),
+            )
+        );
+    }
+
+    #[test]
+    fn evaluates_statuses_ages_and_branch_globs() {
+        let day = 24 * 60 * 60;
+        let expr: FilterExpr = "clean && age > 30d && (merged || no_pr) && branch != 'main'"
+            .parse()
+            .unwrap();
+
+        let old_merged = worktree("jml/fix", LocalStatus::Clean, Some(PrStatus::Merged));
+        assert!(expr.matches(&old_merged, 40 * day));
+        assert!(!expr.matches(&old_merged, 10 * day));
+        assert!(expr.matches(&worktree("spike", LocalStatus::Clean, None), 40 * day));
+        assert!(!expr.matches(&worktree("main", LocalStatus::Clean, None), 40 * day));
+        assert!(!expr.matches(
+            &worktree("wip", LocalStatus::Dirty, Some(PrStatus::Merged)),
+            40 * day
+        ));
+        assert!(!expr.matches(
+            &worktree("review", LocalStatus::Clean, Some(PrStatus::Open)),
+            40 * day
+        ));
+        assert!(expr.uses_pr_status());
+    }
+
+    #[test]
+    fn reports_where_parsing_failed() {
+        let error = |text: &str| text.parse::<FilterExpr>().unwrap_err();
+        assert_eq!(
+            error("clean && (dirty"),
+            "unclosed '(' at column 10".to_string()
+        );
+        assert!(error("clean && shiny").starts_with("unknown name 'shiny' at column 10"));
+        assert_eq!(
+            error("age > soon"),
+            "Invalid number in age: soon at column 7"
+        );
+        assert_eq!(error("clean dirty"), "unexpected 'dirty' at column 7");
+        assert_eq!(
+            error("branch < 'a'"),
+            "branch can only be compared with == or != at column 1"
+        );
+    }
+}
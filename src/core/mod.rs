@@ -1,11 +1,17 @@
-use crate::git::LocalStatus;
+use crate::git::{DiffStat, LocalStatus, Operation};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
 use std::path::PathBuf;
 
+pub mod expr;
+
+use expr::FilterExpr;
+
 /// Pure functional core for worktree status computation
 /// This module contains no I/O operations - only data transformations and business logic
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum PrStatus {
     Open,
     Draft,
@@ -25,7 +31,26 @@ impl Display for PrStatus {
     }
 }
 
-#[derive(Debug, Clone)]
+/// What a pull request's reviewers decided, when it needs reviews
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ReviewDecision {
+    Approved,
+    ChangesRequested,
+    ReviewRequired,
+}
+
+impl Display for ReviewDecision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            ReviewDecision::Approved => "approved",
+            ReviewDecision::ChangesRequested => "changes requested",
+            ReviewDecision::ReviewRequired => "review required",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct WorktreeStatus {
     pub local_status: LocalStatus,
     pub commit_timestamp: i64,
@@ -33,15 +58,51 @@ pub struct WorktreeStatus {
     pub directory_mtime: i64,
     pub commit_summary: String,
     pub pr_status: Option<PrStatus>,
+    /// When the PR was closed or merged, as a Unix timestamp
+    #[serde(default)]
+    pub pr_closed_at: Option<i64>,
+    /// Link to the PR on GitHub
+    #[serde(default)]
+    pub pr_url: Option<String>,
+    /// Review decision on the PR, when it needs reviews
+    #[serde(default)]
+    pub pr_review: Option<ReviewDecision>,
+    /// Reason given to `git worktree lock` (possibly empty), when the worktree is locked
+    #[serde(default)]
+    pub locked: Option<String>,
+    /// Changes on the branch since it forked from the base branch, when the scan computed them
+    #[serde(default)]
+    pub diffstat: Option<DiffStat>,
+    /// Stash entries made on the branch
+    #[serde(default)]
+    pub stash_count: usize,
+    /// Bytes used by the worktree directory, when the scan added them up
+    #[serde(default)]
+    pub size_bytes: Option<u64>,
+    /// Rebase, merge or other git operation waiting to be continued or aborted
+    #[serde(default)]
+    pub operation: Option<Operation>,
+    /// Unix time of the latest HEAD movement or index update in the worktree, 0 when unknown
+    #[serde(default)]
+    pub activity_timestamp: i64,
+}
+
+impl WorktreeStatus {
+    /// When the branch was last worked on: its last commit, or later activity in the worktree
+    pub fn last_activity(&self) -> i64 {
+        self.commit_timestamp.max(self.activity_timestamp)
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorktreeResult {
     pub branch: String,
+    /// Worktree directory, which may no longer exist when the status is Missing
+    pub path: PathBuf,
     pub status: WorktreeStatus,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RepoResult {
     pub name: String,
     pub path: PathBuf,
@@ -49,12 +110,20 @@ pub struct RepoResult {
 }
 
 /// Status counters for generating summaries
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct StatusCounters {
     // Local status counters
     pub clean: u32,
     pub dirty: u32,
     pub staged: u32,
+    pub conflicted: u32,
+    pub missing: u32,
+
+    // PR status counters
+    pub open: u32,
+    pub draft: u32,
+    pub merged: u32,
+    pub closed: u32,
 }
 
 impl StatusCounters {
@@ -69,7 +138,37 @@ impl StatusCounters {
             LocalStatus::Clean => self.clean += 1,
             LocalStatus::Dirty => self.dirty += 1,
             LocalStatus::Staged => self.staged += 1,
-            LocalStatus::Missing => {}
+            LocalStatus::Conflicted => self.conflicted += 1,
+            LocalStatus::Missing => self.missing += 1,
+        }
+
+        // Update PR status counters
+        match status.pr_status {
+            Some(PrStatus::Open) => self.open += 1,
+            Some(PrStatus::Draft) => self.draft += 1,
+            Some(PrStatus::Merged) => self.merged += 1,
+            Some(PrStatus::Closed) => self.closed += 1,
+            None => {}
+        }
+    }
+
+    /// Number of worktrees counted
+    pub fn total(&self) -> u32 {
+        self.clean + self.dirty + self.staged + self.conflicted + self.missing
+    }
+
+    /// Pure function combining two sets of counters
+    pub fn merge(&self, other: &StatusCounters) -> StatusCounters {
+        StatusCounters {
+            clean: self.clean + other.clean,
+            dirty: self.dirty + other.dirty,
+            staged: self.staged + other.staged,
+            conflicted: self.conflicted + other.conflicted,
+            missing: self.missing + other.missing,
+            open: self.open + other.open,
+            draft: self.draft + other.draft,
+            merged: self.merged + other.merged,
+            closed: self.closed + other.closed,
         }
     }
 }
@@ -99,24 +198,116 @@ impl WorktreeAnalyzer {
 
         (total_wip, repos_with_wip, status_counters, wip_branches)
     }
+
+    /// "repo/branch" keys of worktrees that are new in `current` or whose status differs
+    /// from `previous` - pure function
+    pub fn changed_worktrees(previous: &[RepoResult], current: &[RepoResult]) -> HashSet<String> {
+        let before: HashMap<String, &WorktreeStatus> = Self::statuses_by_key(previous).collect();
+        Self::statuses_by_key(current)
+            .filter(|(key, status)| {
+                before.get(key).is_none_or(|old| {
+                    old.local_status != status.local_status
+                        || old.pr_status != status.pr_status
+                        || old.commit_timestamp != status.commit_timestamp
+                        || old.commit_summary != status.commit_summary
+                })
+            })
+            .map(|(key, _)| key)
+            .collect()
+    }
+
+    /// Fill in PR statuses from an earlier scan, for scans that skipped GitHub - pure function
+    pub fn carry_over_pr_statuses(previous: &[RepoResult], current: &mut [RepoResult]) {
+        let before: HashMap<String, &WorktreeStatus> = Self::statuses_by_key(previous).collect();
+        for repo_result in current.iter_mut() {
+            for worktree in &mut repo_result.worktrees {
+                let key = format!("{}/{}", repo_result.name, worktree.branch);
+                if let Some(old) = before.get(&key) {
+                    worktree.status.pr_status = old.pr_status.clone();
+                    worktree.status.pr_closed_at = old.pr_closed_at;
+                    worktree.status.pr_url = old.pr_url.clone();
+                    worktree.status.pr_review = old.pr_review;
+                }
+            }
+        }
+    }
+
+    fn statuses_by_key(
+        repo_results: &[RepoResult],
+    ) -> impl Iterator<Item = (String, &WorktreeStatus)> {
+        repo_results.iter().flat_map(|repo_result| {
+            repo_result.worktrees.iter().map(|worktree| {
+                (
+                    format!("{}/{}", repo_result.name, worktree.branch),
+                    &worktree.status,
+                )
+            })
+        })
+    }
+
+    /// Status counters per repository with work in progress, in input order - pure function
+    pub fn counters_by_repo(repo_results: &[RepoResult]) -> Vec<(String, StatusCounters)> {
+        repo_results
+            .iter()
+            .filter(|repo_result| !repo_result.worktrees.is_empty())
+            .map(|repo_result| {
+                let mut counters = StatusCounters::new();
+                for worktree in &repo_result.worktrees {
+                    counters.update(&worktree.status);
+                }
+                (repo_result.name.clone(), counters)
+            })
+            .collect()
+    }
+}
+
+/// How individual status and age filters combine, for `list --match`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchMode {
+    /// Show worktrees matching at least one filter
+    Any,
+    /// Show worktrees matching every filter
+    All,
 }
 
 /// Filtering criteria for worktrees
+/// Status fields are `Some(true)` to ask for the status and `Some(false)` to exclude it
 #[derive(Debug, Default)]
 pub struct WorktreeFilter {
     // Local status filters
     pub dirty: Option<bool>,
     pub clean: Option<bool>,
     pub staged: Option<bool>,
+    pub conflicted: Option<bool>,
     pub missing: Option<bool>,
 
     // Age filters
     pub older_than_days: Option<u32>,
     pub newer_than_days: Option<u32>,
 
+    // Branch name glob (e.g. "jml/*", "*-experiment")
+    pub branch_pattern: Option<String>,
+
+    // PR status filters
+    pub pr_open: Option<bool>,
+    pub pr_draft: Option<bool>,
+    pub pr_merged: Option<bool>,
+    pub pr_none: Option<bool>,
+
     // Preset indicators
     pub is_needs_attention: bool,
     pub is_gc_candidate: bool,
+
+    // Also count PRs closed without merging at least this many days ago as gc candidates
+    pub gc_closed_older_than_days: Option<u32>,
+
+    // Without a mode, statuses of one kind are alternatives and the kinds must all match
+    // Presets ignore it; excluded statuses and the branch pattern always apply
+    pub match_mode: Option<MatchMode>,
+
+    // Expression from `--filter`, which applies on top of everything else
+    pub expression: Option<FilterExpr>,
 }
 
 impl WorktreeFilter {
@@ -208,6 +399,27 @@ impl WorktreeFilter {
 
     /// Pure function to check if a worktree matches the filter criteria
     pub fn matches(&self, worktree: &WorktreeResult, current_timestamp: i64) -> bool {
+        // Branch name pattern and filter expression apply on top of every preset
+        if let Some(pattern) = &self.branch_pattern
+            && !glob_matches(pattern, &worktree.branch)
+        {
+            return false;
+        }
+
+        if let Some(expression) = &self.expression
+            && !expression.matches(worktree, current_timestamp)
+        {
+            return false;
+        }
+
+        // So do excluded statuses, and PR status filters unless combined with --match
+        if self.is_excluded(worktree) {
+            return false;
+        }
+        if self.match_mode.is_none() && !self.matches_pr_status(&worktree.status.pr_status) {
+            return false;
+        }
+
         // Handle special preset logic
         if self.is_needs_attention {
             return self.matches_needs_attention(worktree);
@@ -215,7 +427,11 @@ impl WorktreeFilter {
 
         // Handle gc candidates preset
         if self.is_gc_candidate {
-            return self.matches_gc_candidate(worktree);
+            return self.matches_gc_candidate(worktree, current_timestamp);
+        }
+
+        if let Some(mode) = self.match_mode {
+            return self.matches_criteria(mode, worktree, current_timestamp);
         }
 
         // Check local status filters
@@ -224,69 +440,264 @@ impl WorktreeFilter {
         }
 
         // Check age filters
-        if !self.matches_age(worktree.status.commit_timestamp, current_timestamp) {
+        // Age is measured from the last activity, so fresh edits on an old commit aren't stale
+        if !self.matches_age(worktree.status.last_activity(), current_timestamp) {
             return false;
         }
 
         true
     }
 
+    /// Whether the filter looks at pull requests, which `--no-pr-status` leaves unknown
+    pub fn uses_pr_status(&self) -> bool {
+        self.pr_status_flags()
+            .iter()
+            .any(|(_, flag)| flag.is_some())
+            || self
+                .expression
+                .as_ref()
+                .is_some_and(|expression| expression.uses_pr_status())
+    }
+
+    fn local_status_flags(&self) -> [(LocalStatus, Option<bool>); 5] {
+        [
+            (LocalStatus::Dirty, self.dirty),
+            (LocalStatus::Clean, self.clean),
+            (LocalStatus::Staged, self.staged),
+            (LocalStatus::Conflicted, self.conflicted),
+            (LocalStatus::Missing, self.missing),
+        ]
+    }
+
+    /// Closed has no flag, so it never matches a PR status filter
+    fn pr_status_flags(&self) -> [(Option<PrStatus>, Option<bool>); 4] {
+        [
+            (Some(PrStatus::Open), self.pr_open),
+            (Some(PrStatus::Draft), self.pr_draft),
+            (Some(PrStatus::Merged), self.pr_merged),
+            (None, self.pr_none),
+        ]
+    }
+
+    /// Whether the worktree has a status the filter excludes
+    fn is_excluded(&self, worktree: &WorktreeResult) -> bool {
+        let status = &worktree.status;
+        self.local_status_flags()
+            .iter()
+            .any(|(value, flag)| *flag == Some(false) && *value == status.local_status)
+            || self
+                .pr_status_flags()
+                .iter()
+                .any(|(value, flag)| *flag == Some(false) && *value == status.pr_status)
+    }
+
+    /// Check each requested status and age bound on its own, then combine them as `mode` says
+    fn matches_criteria(
+        &self,
+        mode: MatchMode,
+        worktree: &WorktreeResult,
+        current_timestamp: i64,
+    ) -> bool {
+        let status = &worktree.status;
+        let local = self
+            .local_status_flags()
+            .into_iter()
+            .filter(|(_, flag)| *flag == Some(true))
+            .map(|(value, _)| value == status.local_status);
+        let pr = self
+            .pr_status_flags()
+            .into_iter()
+            .filter(|(_, flag)| *flag == Some(true))
+            .map(|(value, _)| value == status.pr_status);
+        let age = self.age_criteria(status.last_activity(), current_timestamp);
+        let criteria: Vec<bool> = local.chain(pr).chain(age).collect();
+
+        match mode {
+            MatchMode::Any => criteria.is_empty() || criteria.contains(&true),
+            MatchMode::All => !criteria.contains(&false),
+        }
+    }
+
     fn matches_needs_attention(&self, worktree: &WorktreeResult) -> bool {
-        matches!(worktree.status.local_status, LocalStatus::Missing)
+        matches!(
+            worktree.status.local_status,
+            LocalStatus::Missing | LocalStatus::Conflicted
+        ) || worktree.status.operation.is_some()
     }
 
-    fn matches_gc_candidate(&self, worktree: &WorktreeResult) -> bool {
+    fn matches_gc_candidate(&self, worktree: &WorktreeResult, current_timestamp: i64) -> bool {
         // Must be Clean OR Missing
         let status_ok = matches!(
             worktree.status.local_status,
             LocalStatus::Clean | LocalStatus::Missing
         );
 
-        // Must be Merged
-        let pr_ok = matches!(worktree.status.pr_status, Some(PrStatus::Merged));
+        // Must be Merged, or Closed long enough ago when asked for
+        let pr_ok = match worktree.status.pr_status {
+            Some(PrStatus::Merged) => true,
+            Some(PrStatus::Closed) => self.gc_closed_older_than_days.is_some_and(|days| {
+                worktree.status.pr_closed_at.is_some_and(|closed_at| {
+                    current_timestamp - closed_at >= days as i64 * 24 * 60 * 60
+                })
+            }),
+            _ => false,
+        };
+
+        // Locked worktrees were pinned on purpose, and one in the middle of a rebase or merge
+        // holds work that isn't committed anywhere yet, so never offer either for collection
+        let untouchable = worktree.status.locked.is_some() || worktree.status.operation.is_some();
 
-        status_ok && pr_ok
+        status_ok && pr_ok && !untouchable
     }
 
     fn matches_local_status(&self, status: &LocalStatus) -> bool {
-        // If no local status filters are specified, pass everything
-        if self.dirty.is_none()
-            && self.clean.is_none()
-            && self.staged.is_none()
-            && self.missing.is_none()
-        {
-            return true;
-        }
+        matches_requested(&self.local_status_flags(), status)
+    }
 
-        // Check if this status matches any of the requested filters
-        match status {
-            LocalStatus::Dirty => self.dirty.unwrap_or(false),
-            LocalStatus::Clean => self.clean.unwrap_or(false),
-            LocalStatus::Staged => self.staged.unwrap_or(false),
-            LocalStatus::Missing => self.missing.unwrap_or(false),
-        }
+    fn matches_pr_status(&self, status: &Option<PrStatus>) -> bool {
+        matches_requested(&self.pr_status_flags(), status)
     }
 
-    fn matches_age(&self, commit_timestamp: i64, current_timestamp: i64) -> bool {
-        if commit_timestamp == 0 {
-            return true; // Unknown age always passes
-        }
+    fn matches_age(&self, active_timestamp: i64, current_timestamp: i64) -> bool {
+        self.age_criteria(active_timestamp, current_timestamp)
+            .all(|matched| matched)
+    }
 
-        let days_old = (current_timestamp - commit_timestamp) / (24 * 60 * 60);
+    /// Whether the last activity passes each age bound that is set, in order
+    fn age_criteria(
+        &self,
+        active_timestamp: i64,
+        current_timestamp: i64,
+    ) -> impl Iterator<Item = bool> {
+        // Unknown age always passes
+        let days_old = (active_timestamp != 0)
+            .then(|| (current_timestamp - active_timestamp) / (24 * 60 * 60));
 
-        if let Some(older_than) = self.older_than_days
-            && days_old < older_than as i64
-        {
+        let older = self
+            .older_than_days
+            .map(|days| days_old.is_none_or(|old| old >= days as i64));
+        let newer = self
+            .newer_than_days
+            .map(|days| days_old.is_none_or(|old| old <= days as i64));
+        older.into_iter().chain(newer)
+    }
+}
+
+/// Pure function checking a value against the statuses asked for, if any
+/// Excluded statuses (`Some(false)`) are handled separately
+fn matches_requested<T: PartialEq>(flags: &[(T, Option<bool>)], value: &T) -> bool {
+    let mut requested = flags
+        .iter()
+        .filter(|(_, flag)| *flag == Some(true))
+        .peekable();
+    requested.peek().is_none() || requested.any(|(wanted, _)| wanted == value)
+}
+
+/// Pure function to match a branch name against a glob pattern
+/// Supports `*` (any run of characters, including `/`) and `?` (any single character)
+pub fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = backtrack {
+            // Let the last `*` absorb one more character and retry
+            p = star_p + 1;
+            t = star_t + 1;
+            backtrack = Some((star_p, star_t + 1));
+        } else {
             return false;
         }
+    }
 
-        if let Some(newer_than) = self.newer_than_days
-            && days_old > newer_than as i64
-        {
-            return false;
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Pure function to derive a branch name from an issue, e.g. "123-fix-login-redirect"
+pub fn branch_name_for_issue(number: u64, title: &str) -> String {
+    const MAX_SLUG_LEN: usize = 50;
+
+    let mut slug = String::new();
+    for c in title.chars().flat_map(|c| c.to_lowercase()) {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
         }
+    }
 
-        true
+    // Cut long titles at a word boundary where possible
+    if slug.len() > MAX_SLUG_LEN {
+        slug.truncate(MAX_SLUG_LEN);
+        if let Some(last_dash) = slug.rfind('-') {
+            slug.truncate(last_dash);
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+
+    if slug.is_empty() {
+        format!("issue-{}", number)
+    } else {
+        format!("{}-{}", number, slug)
+    }
+}
+
+/// Section of the triage layout of `list --group-by status`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriageSection {
+    NeedsAttention,
+    Active,
+    PruneCandidates,
+    Other,
+}
+
+impl TriageSection {
+    /// Every section, in display order
+    pub const ALL: [TriageSection; 4] = [
+        TriageSection::NeedsAttention,
+        TriageSection::Active,
+        TriageSection::PruneCandidates,
+        TriageSection::Other,
+    ];
+
+    /// Pure function placing a worktree in the first section whose preset filter it matches
+    pub fn of(worktree: &WorktreeResult, current_timestamp: i64) -> Self {
+        let presets = [
+            (
+                WorktreeFilter::needs_attention(),
+                TriageSection::NeedsAttention,
+            ),
+            (WorktreeFilter::active(), TriageSection::Active),
+            (
+                WorktreeFilter::prune_candidates(),
+                TriageSection::PruneCandidates,
+            ),
+        ];
+        presets
+            .into_iter()
+            .find(|(filter, _)| filter.matches(worktree, current_timestamp))
+            .map_or(TriageSection::Other, |(_, section)| section)
+    }
+}
+
+impl Display for TriageSection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            TriageSection::NeedsAttention => "Needs attention",
+            TriageSection::Active => "Active",
+            TriageSection::PruneCandidates => "Prune candidates",
+            TriageSection::Other => "Other",
+        };
+        write!(f, "{}", text)
     }
 }
 
@@ -332,16 +743,54 @@ mod tests {
     ) -> WorktreeResult {
         WorktreeResult {
             branch: "test-branch".to_string(),
+            path: PathBuf::from("/repos/project/test-branch"),
             status: WorktreeStatus {
                 local_status,
-                commit_timestamp: 0,
-                directory_mtime: 0,
                 commit_summary: "test commit".to_string(),
                 pr_status,
+                ..Default::default()
             },
         }
     }
 
+    #[test]
+    fn glob_matches_prefix_and_suffix_patterns() {
+        assert!(glob_matches("jml/*", "jml/fix-thing"));
+        assert!(glob_matches("*-experiment", "cache-experiment"));
+        assert!(glob_matches("feat-?", "feat-a"));
+        assert!(!glob_matches("jml/*", "other/fix-thing"));
+        assert!(!glob_matches("feat-?", "feat-ab"));
+    }
+
+    #[test]
+    fn branch_name_for_issue_slugs_title() {
+        assert_eq!(
+            branch_name_for_issue(123, "Fix: login redirect loops (again!)"),
+            "123-fix-login-redirect-loops-again"
+        );
+        assert_eq!(branch_name_for_issue(7, "🚀"), "issue-7");
+    }
+
+    #[test]
+    fn branch_name_for_issue_truncates_long_titles_at_word_boundary() {
+        let branch = branch_name_for_issue(
+            1,
+            "Make the worktree manager handle extremely long issue titles gracefully",
+        );
+        assert_eq!(branch, "1-make-the-worktree-manager-handle-extremely-long");
+    }
+
+    #[test]
+    fn branch_pattern_applies_to_presets() {
+        let mut filter = WorktreeFilter::gc_candidates();
+        filter.branch_pattern = Some("other-*".to_string());
+        let worktree = create_test_worktree(LocalStatus::Clean, Some(PrStatus::Merged));
+        assert!(!filter.matches(&worktree, 0));
+
+        filter.branch_pattern = Some("test-*".to_string());
+        assert!(filter.matches(&worktree, 0));
+    }
+
     #[test]
     fn gc_candidates_filter_matches_clean_and_merged() {
         let filter = WorktreeFilter::gc_candidates();
@@ -391,10 +840,205 @@ mod tests {
         assert!(!filter.matches(&worktree, 0));
     }
 
+    #[test]
+    fn gc_candidates_filter_matches_pr_closed_long_enough_ago() {
+        let filter = WorktreeFilter {
+            gc_closed_older_than_days: Some(30),
+            ..WorktreeFilter::gc_candidates()
+        };
+        let day = 24 * 60 * 60;
+        let mut worktree = create_test_worktree(LocalStatus::Clean, Some(PrStatus::Closed));
+        worktree.status.pr_closed_at = Some(0);
+
+        assert!(filter.matches(&worktree, 30 * day));
+        assert!(!filter.matches(&worktree, 29 * day));
+    }
+
+    #[test]
+    fn pr_status_filters_combine_with_presets() {
+        let filter = WorktreeFilter {
+            pr_merged: Some(true),
+            pr_none: Some(true),
+            ..WorktreeFilter::prune_candidates()
+        };
+        let merged = create_test_worktree(LocalStatus::Clean, Some(PrStatus::Merged));
+        let without_pr = create_test_worktree(LocalStatus::Clean, None);
+        let open = create_test_worktree(LocalStatus::Clean, Some(PrStatus::Open));
+        let dirty_merged = create_test_worktree(LocalStatus::Dirty, Some(PrStatus::Merged));
+
+        assert!(filter.matches(&merged, 0));
+        assert!(filter.matches(&without_pr, 0));
+        assert!(!filter.matches(&open, 0));
+        assert!(!filter.matches(&dirty_merged, 0));
+    }
+
+    #[test]
+    fn match_mode_combines_filters_across_kinds() {
+        let dirty_merged = create_test_worktree(LocalStatus::Dirty, Some(PrStatus::Merged));
+        let dirty_open = create_test_worktree(LocalStatus::Dirty, Some(PrStatus::Open));
+        let clean_merged = create_test_worktree(LocalStatus::Clean, Some(PrStatus::Merged));
+        let clean_open = create_test_worktree(LocalStatus::Clean, Some(PrStatus::Open));
+        let filter = |match_mode| WorktreeFilter {
+            dirty: Some(true),
+            pr_merged: Some(true),
+            match_mode,
+            ..WorktreeFilter::new()
+        };
+
+        let all = filter(Some(MatchMode::All));
+        assert!(all.matches(&dirty_merged, 0));
+        assert!(!all.matches(&dirty_open, 0));
+        assert!(!all.matches(&clean_merged, 0));
+
+        let any = filter(Some(MatchMode::Any));
+        assert!(any.matches(&dirty_open, 0));
+        assert!(any.matches(&clean_merged, 0));
+        assert!(!any.matches(&clean_open, 0));
+
+        // Excluded statuses narrow every mode
+        let any_but_open = WorktreeFilter {
+            pr_open: Some(false),
+            ..any
+        };
+        assert!(!any_but_open.matches(&dirty_open, 0));
+        assert!(any_but_open.matches(&dirty_merged, 0));
+    }
+
+    #[test]
+    fn excluded_statuses_apply_on_their_own() {
+        let filter = WorktreeFilter {
+            clean: Some(false),
+            ..WorktreeFilter::new()
+        };
+        assert!(!filter.matches(&create_test_worktree(LocalStatus::Clean, None), 0));
+        assert!(filter.matches(&create_test_worktree(LocalStatus::Dirty, None), 0));
+    }
+
+    #[test]
+    fn recent_activity_keeps_old_commits_from_looking_stale() {
+        let day = 24 * 60 * 60;
+        let filter = WorktreeFilter::stale();
+        let mut worktree = create_test_worktree(LocalStatus::Dirty, None);
+        worktree.status.commit_timestamp = day;
+        assert!(filter.matches(&worktree, 60 * day));
+
+        worktree.status.activity_timestamp = 58 * day;
+        assert!(!filter.matches(&worktree, 60 * day));
+        assert!(WorktreeFilter::active().matches(&worktree, 60 * day));
+    }
+
+    #[test]
+    fn triage_sections_follow_the_presets_in_order() {
+        let day = 24 * 60 * 60;
+        let now = 100 * day;
+        let worktree = |local_status, commit_timestamp| {
+            let mut worktree = create_test_worktree(local_status, None);
+            worktree.status.commit_timestamp = commit_timestamp;
+            worktree
+        };
+
+        let section = |w: &WorktreeResult| TriageSection::of(w, now);
+        assert_eq!(
+            section(&worktree(LocalStatus::Missing, now - day)),
+            TriageSection::NeedsAttention
+        );
+        assert_eq!(
+            section(&worktree(LocalStatus::Dirty, now - day)),
+            TriageSection::Active
+        );
+        assert_eq!(
+            section(&worktree(LocalStatus::Clean, now - 30 * day)),
+            TriageSection::PruneCandidates
+        );
+        assert_eq!(
+            section(&worktree(LocalStatus::Dirty, now - 30 * day)),
+            TriageSection::Other
+        );
+    }
+
+    #[test]
+    fn gc_candidates_filter_rejects_locked_worktrees() {
+        let filter = WorktreeFilter::gc_candidates();
+        let mut worktree = create_test_worktree(LocalStatus::Clean, Some(PrStatus::Merged));
+        worktree.status.locked = Some("on a USB drive".to_string());
+        assert!(!filter.matches(&worktree, 0));
+    }
+
+    #[test]
+    fn gc_candidates_filter_rejects_worktrees_mid_rebase() {
+        let filter = WorktreeFilter::gc_candidates();
+        let mut worktree = create_test_worktree(LocalStatus::Clean, Some(PrStatus::Merged));
+        worktree.status.operation = Some(Operation::Rebasing);
+        assert!(!filter.matches(&worktree, 0));
+    }
+
     #[test]
     fn gc_candidates_filter_rejects_clean_with_no_pr() {
         let filter = WorktreeFilter::gc_candidates();
         let worktree = create_test_worktree(LocalStatus::Clean, None);
         assert!(!filter.matches(&worktree, 0));
     }
+
+    #[test]
+    fn counters_by_repo_skips_repos_without_wip() {
+        let results = vec![
+            RepoResult {
+                name: "api".to_string(),
+                path: PathBuf::from("/repos/api"),
+                worktrees: vec![
+                    create_test_worktree(LocalStatus::Clean, Some(PrStatus::Merged)),
+                    create_test_worktree(LocalStatus::Dirty, Some(PrStatus::Open)),
+                    create_test_worktree(LocalStatus::Missing, None),
+                ],
+            },
+            RepoResult {
+                name: "web".to_string(),
+                path: PathBuf::from("/repos/web"),
+                worktrees: vec![],
+            },
+        ];
+
+        let counters = WorktreeAnalyzer::counters_by_repo(&results);
+        assert_eq!(counters.len(), 1);
+        let (name, api) = &counters[0];
+        assert_eq!(name, "api");
+        assert_eq!((api.clean, api.dirty, api.missing), (1, 1, 1));
+        assert_eq!((api.open, api.merged), (1, 1));
+        assert_eq!(api.total(), 3);
+        assert_eq!(api.merge(api).total(), 6);
+    }
+
+    #[test]
+    fn changed_worktrees_reports_new_and_modified_entries() {
+        let repo = |worktrees| RepoResult {
+            name: "api".to_string(),
+            path: PathBuf::from("/repos/api"),
+            worktrees,
+        };
+        let mut renamed = create_test_worktree(LocalStatus::Clean, None);
+        renamed.branch = "other-branch".to_string();
+        let previous = vec![repo(vec![create_test_worktree(LocalStatus::Clean, None)])];
+        let current = vec![repo(vec![
+            create_test_worktree(LocalStatus::Dirty, None),
+            renamed,
+        ])];
+
+        let changed = WorktreeAnalyzer::changed_worktrees(&previous, &current);
+        assert_eq!(changed.len(), 2);
+        assert!(changed.contains("api/test-branch"));
+        assert!(changed.contains("api/other-branch"));
+        assert!(WorktreeAnalyzer::changed_worktrees(&current, &current).is_empty());
+
+        let mut rescanned = current.clone();
+        let with_pr = vec![repo(vec![create_test_worktree(
+            LocalStatus::Dirty,
+            Some(PrStatus::Open),
+        )])];
+        WorktreeAnalyzer::carry_over_pr_statuses(&with_pr, &mut rescanned);
+        assert_eq!(
+            rescanned[0].worktrees[0].status.pr_status,
+            Some(PrStatus::Open)
+        );
+        assert_eq!(rescanned[0].worktrees[1].status.pr_status, None);
+    }
 }
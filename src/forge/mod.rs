@@ -0,0 +1,197 @@
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+use std::future::Future;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tracing::debug;
+
+use crate::azure_devops::{AzureDevOpsClient, AzureDevOpsConfig};
+use crate::bitbucket::{BitbucketClient, BitbucketConfig};
+use crate::core::{PrStatus, ReviewDecision};
+use crate::github::{Credentials, GitHubForge};
+
+/// Whose pull requests to look for when matching them to worktrees
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum PrAuthor {
+    /// Only the authenticated user's, found with one search
+    #[default]
+    Me,
+    /// Anyone's, including teammates' and bots', as long as the branch lives in the repository
+    /// itself or the user opened the PR from a fork
+    Any,
+}
+
+/// Represents PR information for matching with worktrees
+#[derive(Debug, Clone)]
+pub struct PrInfo {
+    #[allow(dead_code)]
+    pub number: u64,
+    pub head_branch: String,
+    pub status: PrStatus,
+    /// When the PR was closed or merged, as a Unix timestamp
+    pub closed_at: Option<i64>,
+    /// Link to the PR on its forge
+    pub url: Option<String>,
+    /// Whether reviewers approved or asked for changes, when the PR needs reviews
+    pub review: Option<ReviewDecision>,
+}
+
+/// A code host that knows the pull requests of one repository
+pub trait Forge {
+    /// Fetch the PRs whose head is one of `branches`, by `author`, created (or for anyone's,
+    /// updated) after `since_timestamp`
+    fn fetch_prs(
+        &self,
+        branches: &[String],
+        since_timestamp: i64,
+        author: PrAuthor,
+    ) -> impl Future<Output = Result<Vec<PrInfo>>> + Send;
+}
+
+/// Settings for every forge gwm talks to
+#[derive(Debug, Clone, Default)]
+pub struct Forges {
+    pub github: Credentials,
+    pub bitbucket: BitbucketConfig,
+    pub azure_devops: AzureDevOpsConfig,
+}
+
+impl Forges {
+    pub fn new(github: Credentials) -> Self {
+        Self {
+            github,
+            ..Self::default()
+        }
+    }
+
+    pub fn bitbucket(mut self, bitbucket: BitbucketConfig) -> Self {
+        self.bitbucket = bitbucket;
+        self
+    }
+
+    pub fn azure_devops(mut self, azure_devops: AzureDevOpsConfig) -> Self {
+        self.azure_devops = azure_devops;
+        self
+    }
+
+    /// Whether any forge has a token set, to fail early before scanning
+    pub fn any_available(&self) -> bool {
+        self.bitbucket.any_available()
+            || self.azure_devops.any_available()
+            || self.github.any_available()
+    }
+
+    /// The forge a remote URL points at, with a client for it
+    /// bitbucket.org and configured Bitbucket Server hosts are Bitbucket, dev.azure.com and
+    /// visualstudio.com are Azure DevOps, anything else GitHub can parse is GitHub
+    pub fn detect(&self, remote_url: &str) -> Result<RepoForge> {
+        if let Some(client) = self.bitbucket.client_for(remote_url) {
+            return Ok(RepoForge::Bitbucket(client?));
+        }
+        if let Some(client) = self.azure_devops.client_for(remote_url) {
+            return Ok(RepoForge::AzureDevOps(client?));
+        }
+        let repo = self.github.parse_url(remote_url).map_err(|_| {
+            anyhow!(
+                "Remote {} is not on GitHub, Bitbucket or Azure DevOps",
+                remote_url
+            )
+        })?;
+        let client = self.github.client_for(&repo)?;
+        Ok(RepoForge::GitHub(GitHubForge { client, repo }))
+    }
+}
+
+/// The forge detected for a repository
+pub enum RepoForge {
+    GitHub(GitHubForge),
+    Bitbucket(BitbucketClient),
+    AzureDevOps(AzureDevOpsClient),
+}
+
+impl Forge for RepoForge {
+    async fn fetch_prs(
+        &self,
+        branches: &[String],
+        since_timestamp: i64,
+        author: PrAuthor,
+    ) -> Result<Vec<PrInfo>> {
+        match self {
+            RepoForge::GitHub(forge) => forge.fetch_prs(branches, since_timestamp, author).await,
+            RepoForge::Bitbucket(client) => {
+                client.fetch_prs(branches, since_timestamp, author).await
+            }
+            RepoForge::AzureDevOps(client) => {
+                client.fetch_prs(branches, since_timestamp, author).await
+            }
+        }
+    }
+}
+
+/// How a request authenticates
+pub(crate) enum HttpAuth<'a> {
+    /// An access token, sent as `Authorization: Bearer`
+    Bearer(&'a str),
+    /// `username:password`, sent as basic authentication
+    Basic(&'a str),
+}
+
+/// GET a URL with curl and return the body
+/// The URL and credentials go through stdin rather than argv, which other users can see in `ps`
+pub(crate) async fn http_get(url: &str, auth: HttpAuth<'_>) -> Result<String> {
+    debug!("GET {}", url);
+    let (line, secret) = match auth {
+        HttpAuth::Bearer(token) => (
+            format!("header = \"Authorization: Bearer {}\"", token),
+            token,
+        ),
+        HttpAuth::Basic(credentials) => (format!("user = \"{}\"", credentials), credentials),
+    };
+    // Both end up quoted in curl's config
+    if [url, secret]
+        .iter()
+        .any(|value| value.contains(['"', '\\', '\n']))
+    {
+        return Err(anyhow!("Invalid URL or token for {}", origin(url)));
+    }
+    let config = format!(
+        "url = \"{}\"\n{}\nheader = \"Accept: application/json\"\n",
+        url, line
+    );
+
+    let mut child = Command::new("curl")
+        .args([
+            "--silent",
+            "--show-error",
+            "--fail",
+            "--max-time",
+            "30",
+            "--config",
+            "-",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow!("Failed to run curl: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(config.as_bytes()).await?;
+    }
+    let output = child.wait_with_output().await?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Request to {} failed: {}",
+            origin(url),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Scheme and host of a URL, to name it in errors without the query
+fn origin(url: &str) -> String {
+    url.split('/').take(3).collect::<Vec<_>>().join("/")
+}
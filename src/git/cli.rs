@@ -0,0 +1,252 @@
+use anyhow::{Result, anyhow};
+use git2::Repository;
+use std::path::Path;
+use std::process::{Command, Output};
+
+use super::{FetchTags, GitClient, SystemGitClient};
+
+/// GitClient that shells out to the system `git` binary
+/// Useful where libgit2 falls short: credential helpers, protocol v2 fetches, sparse checkouts
+pub struct CliGitClient;
+
+impl CliGitClient {
+    /// Run git in the repository's working directory (or its gitdir when bare)
+    pub(super) fn git(repo: &Repository, args: &[&str]) -> Result<String> {
+        let dir = repo.workdir().unwrap_or_else(|| repo.path());
+        Self::git_in(dir, args)
+    }
+
+    pub(super) fn git_in(dir: &Path, args: &[&str]) -> Result<String> {
+        let output = Self::run(dir, args)?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Like `git`, but returns what git reported on stderr, for commands that only talk there
+    pub(super) fn git_stderr(repo: &Repository, args: &[&str]) -> Result<String> {
+        let dir = repo.workdir().unwrap_or_else(|| repo.path());
+        let output = Self::run(dir, args)?;
+        Ok(String::from_utf8_lossy(&output.stderr).into_owned())
+    }
+
+    fn run(dir: &Path, args: &[&str]) -> Result<Output> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .output()
+            .map_err(|e| anyhow!("Failed to run git: {}", e))?;
+
+        if !output.status.success() {
+            return Err(anyhow!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(output)
+    }
+
+    fn ref_exists(repo: &Repository, reference: &str) -> bool {
+        Self::git(repo, &["show-ref", "--verify", "--quiet", reference]).is_ok()
+    }
+
+    fn commit_exists(repo: &Repository, revision: &str) -> bool {
+        let commit = format!("{}^{{commit}}", revision);
+        Self::git(repo, &["rev-parse", "--verify", "--quiet", &commit]).is_ok()
+    }
+}
+
+/// Pure function converting `git worktree list --porcelain` into gwm's `path [branch]` lines
+/// The main worktree and worktrees whose directory is gone are left out, as with libgit2
+fn parse_worktree_porcelain(output: &str) -> String {
+    let mut result = String::new();
+
+    // The first block always describes the main worktree (or the bare repository)
+    for block in output.split("\n\n").skip(1) {
+        let mut path = None;
+        let mut head = None;
+        let mut branch = None;
+        let mut detached = false;
+
+        for line in block.lines() {
+            if let Some(value) = line.strip_prefix("worktree ") {
+                path = Some(value);
+            } else if let Some(value) = line.strip_prefix("HEAD ") {
+                head = Some(value);
+            } else if let Some(value) = line.strip_prefix("branch ") {
+                branch = Some(value.strip_prefix("refs/heads/").unwrap_or(value));
+            } else if line == "detached" {
+                detached = true;
+            }
+        }
+
+        let Some(path) = path else {
+            continue;
+        };
+        if !Path::new(path).exists() {
+            continue;
+        }
+
+        let branch = match (branch, head) {
+            (Some(branch), _) => branch.to_string(),
+            (None, Some(head)) if detached => format!("detached {}", head),
+            _ => "unknown".to_string(),
+        };
+        result.push_str(&format!("{} [{}]\n", path, branch));
+    }
+
+    result
+}
+
+impl GitClient for CliGitClient {
+    fn get_config(&self, repo: &Repository, key: &str) -> Result<String> {
+        let value = Self::git(repo, &["config", "--get", key])
+            .map_err(|e| anyhow!("Failed to get config value for '{}': {}", key, e))?;
+        Ok(value.trim_end().to_string())
+    }
+
+    fn set_config(&self, repo: &Repository, key: &str, value: &str) -> Result<()> {
+        Self::git(repo, &["config", key, value])
+            .map_err(|e| anyhow!("Failed to set config value for '{}': {}", key, e))?;
+        Ok(())
+    }
+
+    fn list_worktrees(&self, repo: &Repository) -> Result<String> {
+        let output = Self::git(repo, &["worktree", "list", "--porcelain"])
+            .map_err(|e| anyhow!("Failed to list worktrees: {}", e))?;
+        Ok(parse_worktree_porcelain(&output))
+    }
+
+    fn get_status_porcelain(&self, repo: &Repository) -> Result<String> {
+        Self::git(repo, &["status", "--porcelain"])
+            .map_err(|e| anyhow!("Failed to get repository status: {}", e))
+    }
+
+    fn get_last_commit_timestamp(&self, repo: &Repository, branch: &str) -> Result<i64> {
+        let output = Self::git(repo, &["show", "-s", "--format=%ct", branch, "--"])
+            .map_err(|e| anyhow!("Failed to find branch '{}': {}", branch, e))?;
+        output
+            .trim()
+            .parse()
+            .map_err(|e| anyhow!("Invalid commit timestamp '{}': {}", output.trim(), e))
+    }
+
+    fn get_commit_summary(&self, repo: &Repository, branch: &str) -> Result<String> {
+        let output = Self::git(repo, &["show", "-s", "--format=%s", branch, "--"])
+            .map_err(|e| anyhow!("Failed to find branch '{}': {}", branch, e))?;
+        Ok(output.trim_end().to_string())
+    }
+
+    fn get_directory_mtime(&self, path: &str) -> Result<i64> {
+        SystemGitClient.get_directory_mtime(path)
+    }
+
+    fn remove_worktree(&self, repo: &Repository, worktree_path: &str) -> Result<()> {
+        if Path::new(worktree_path).exists() {
+            // Twice to also remove locked worktrees, matching libgit2's forced prune
+            Self::git(
+                repo,
+                &["worktree", "remove", "--force", "--force", worktree_path],
+            )
+            .map_err(|e| anyhow!("Failed to remove worktree: {}", e))?;
+        } else {
+            // Only the registration is left, which prune cleans up
+            Self::git(repo, &["worktree", "prune"])
+                .map_err(|e| anyhow!("Failed to prune worktree: {}", e))?;
+        }
+        Ok(())
+    }
+
+    fn add_worktree(
+        &self,
+        repo: &Repository,
+        branch: &str,
+        path: &str,
+        base_branch: Option<&str>,
+        reuse_existing_branch: bool,
+    ) -> Result<()> {
+        if Path::new(path).exists() {
+            return Err(anyhow!("Target path '{}' already exists", path));
+        }
+
+        if Self::ref_exists(repo, &format!("refs/heads/{}", branch)) {
+            if !reuse_existing_branch {
+                return Err(anyhow!(
+                    "Branch '{}' already exists. Use --reuse to reuse the existing branch, or choose a different branch name.",
+                    branch
+                ));
+            }
+            Self::git(repo, &["worktree", "add", path, branch])
+                .map_err(|e| anyhow!("Failed to create worktree: {}", e))?;
+            return Ok(());
+        }
+
+        let source_branch = base_branch.unwrap_or("main");
+        let source_ref = if Self::ref_exists(repo, &format!("refs/heads/{}", source_branch)) {
+            source_branch.to_string()
+        } else if Self::ref_exists(repo, &format!("refs/remotes/origin/{}", source_branch)) {
+            format!("origin/{}", source_branch)
+        } else if Self::ref_exists(repo, &format!("refs/remotes/{}", source_branch))
+            || Self::commit_exists(repo, source_branch)
+        {
+            // Already a remote-tracking name (e.g. "origin/release"), a tag or a commit
+            source_branch.to_string()
+        } else {
+            return Err(anyhow!(
+                "Source branch '{}' not found locally or on remote, and is not a tag or commit",
+                source_branch
+            ));
+        };
+
+        Self::git(repo, &["worktree", "add", "-b", branch, path, &source_ref])
+            .map_err(|e| anyhow!("Failed to create worktree: {}", e))?;
+        Ok(())
+    }
+
+    fn fetch_remotes(&self, repo: &Repository, tags: FetchTags) -> Result<()> {
+        let mut args = vec!["fetch", "--all"];
+        match tags {
+            FetchTags::Auto => {}
+            FetchTags::All => args.push("--tags"),
+            FetchTags::None => args.push("--no-tags"),
+        }
+        Self::git(repo, &args).map_err(|e| anyhow!("Failed to fetch remotes: {}", e))?;
+        Ok(())
+    }
+
+    fn pull_main(&self, repo: &Repository) -> Result<()> {
+        let output = Self::git(repo, &["worktree", "list", "--porcelain"])
+            .map_err(|e| anyhow!("Failed to list worktrees: {}", e))?;
+        let main_worktree_path = parse_worktree_porcelain(&output)
+            .lines()
+            .find_map(|line| line.strip_suffix(" [main]").map(str::to_string))
+            .ok_or_else(|| anyhow!("No main worktree found"))?;
+
+        let remote_main = ["upstream", "origin"]
+            .into_iter()
+            .find(|remote| Self::ref_exists(repo, &format!("refs/remotes/{}/main", remote)))
+            .map(|remote| format!("{}/main", remote))
+            .ok_or_else(|| anyhow!("Failed to find upstream/main or origin/main"))?;
+
+        Self::git_in(
+            Path::new(&main_worktree_path),
+            &["merge", "--ff-only", &remote_main],
+        )
+        .map_err(|e| anyhow!("Cannot fast-forward main: {}", e))?;
+        Ok(())
+    }
+
+    fn get_worktree_birth_time(&self, path: &str) -> Result<Option<i64>> {
+        SystemGitClient.get_worktree_birth_time(path)
+    }
+
+    fn get_head_oid(&self, repo: &Repository) -> Result<String> {
+        let output = Self::git(repo, &["rev-parse", "HEAD"])
+            .map_err(|e| anyhow!("Failed to resolve HEAD: {}", e))?;
+        Ok(output.trim().to_string())
+    }
+
+    fn get_index_mtime(&self, repo: &Repository) -> Result<i64> {
+        SystemGitClient.get_index_mtime(repo)
+    }
+}
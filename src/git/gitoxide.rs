@@ -0,0 +1,146 @@
+use anyhow::{Result, anyhow};
+use git2::Repository;
+use gix::diff::index::ChangeRef;
+use gix::status::index_worktree::iter::Summary;
+
+use super::{FetchTags, GitClient, SystemGitClient};
+
+/// GitClient backed by gitoxide for the read-heavy status and commit lookups
+/// Operations that write to the repository or talk to remotes still go through libgit2
+pub struct GixGitClient;
+
+impl GixGitClient {
+    /// Open the gitoxide view of a repository already opened by libgit2
+    fn open(repo: &Repository) -> Result<gix::Repository> {
+        let path = repo.workdir().unwrap_or_else(|| repo.path());
+        gix::open(path).map_err(|e| {
+            anyhow!(
+                "Failed to open repository at '{}' with gitoxide: {}",
+                path.display(),
+                e
+            )
+        })
+    }
+
+    fn find_commit<'repo>(
+        repo: &'repo gix::Repository,
+        branch: &str,
+    ) -> Result<gix::Commit<'repo>> {
+        repo.rev_parse_single(branch)
+            .map_err(|e| anyhow!("Failed to find branch '{}': {}", branch, e))?
+            .object()?
+            .peel_to_commit()
+            .map_err(|_| anyhow!("Object is not a commit"))
+    }
+}
+
+impl GitClient for GixGitClient {
+    fn get_config(&self, repo: &Repository, key: &str) -> Result<String> {
+        SystemGitClient.get_config(repo, key)
+    }
+
+    fn set_config(&self, repo: &Repository, key: &str, value: &str) -> Result<()> {
+        SystemGitClient.set_config(repo, key, value)
+    }
+
+    fn list_worktrees(&self, repo: &Repository) -> Result<String> {
+        SystemGitClient.list_worktrees(repo)
+    }
+
+    fn get_status_porcelain(&self, repo: &Repository) -> Result<String> {
+        let repo = Self::open(repo)?;
+        let statuses = repo
+            .status(gix::progress::Discard)
+            .map_err(|e| anyhow!("Failed to get repository status: {}", e))?
+            .untracked_files(gix::status::UntrackedFiles::Files)
+            .into_iter(None)
+            .map_err(|e| anyhow!("Failed to get repository status: {}", e))?;
+
+        // Produce the same two-column format as the libgit2 client
+        let mut result = String::new();
+        for item in statuses {
+            let item = item.map_err(|e| anyhow!("Failed to get repository status: {}", e))?;
+            match item {
+                gix::status::Item::TreeIndex(change) => {
+                    let index_status = match change {
+                        ChangeRef::Addition { .. } => 'A',
+                        ChangeRef::Deletion { .. } => 'D',
+                        ChangeRef::Modification { .. } => 'M',
+                        ChangeRef::Rewrite { .. } => 'R',
+                    };
+                    result.push_str(&format!("{}  {}\n", index_status, change.location()));
+                }
+                gix::status::Item::IndexWorktree(item) => {
+                    let code = match item.summary() {
+                        Some(Summary::Added) => " ?",
+                        Some(Summary::Removed) => " D",
+                        Some(Summary::TypeChange) => " T",
+                        Some(Summary::Renamed | Summary::Copied) => " R",
+                        Some(Summary::Modified | Summary::IntentToAdd) => " M",
+                        Some(Summary::Conflict) => "UU",
+                        None => continue,
+                    };
+                    result.push_str(&format!("{} {}\n", code, item.rela_path()));
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn get_last_commit_timestamp(&self, repo: &Repository, branch: &str) -> Result<i64> {
+        let repo = Self::open(repo)?;
+        let commit = Self::find_commit(&repo, branch)?;
+        Ok(commit.time()?.seconds)
+    }
+
+    fn get_commit_summary(&self, repo: &Repository, branch: &str) -> Result<String> {
+        let repo = Self::open(repo)?;
+        let commit = Self::find_commit(&repo, branch)?;
+        Ok(commit.message()?.summary().to_string())
+    }
+
+    fn get_directory_mtime(&self, path: &str) -> Result<i64> {
+        SystemGitClient.get_directory_mtime(path)
+    }
+
+    fn remove_worktree(&self, repo: &Repository, worktree_path: &str) -> Result<()> {
+        SystemGitClient.remove_worktree(repo, worktree_path)
+    }
+
+    fn add_worktree(
+        &self,
+        repo: &Repository,
+        branch: &str,
+        path: &str,
+        base_branch: Option<&str>,
+        reuse_existing_branch: bool,
+    ) -> Result<()> {
+        SystemGitClient.add_worktree(repo, branch, path, base_branch, reuse_existing_branch)
+    }
+
+    // Only status and commit lookups use gitoxide; fetching and pulling stay with libgit2
+    fn fetch_remotes(&self, repo: &Repository, tags: FetchTags) -> Result<()> {
+        SystemGitClient.fetch_remotes(repo, tags)
+    }
+
+    fn pull_main(&self, repo: &Repository) -> Result<()> {
+        SystemGitClient.pull_main(repo)
+    }
+
+    fn get_worktree_birth_time(&self, path: &str) -> Result<Option<i64>> {
+        SystemGitClient.get_worktree_birth_time(path)
+    }
+
+    fn get_head_oid(&self, repo: &Repository) -> Result<String> {
+        let repo = Self::open(repo)?;
+        let oid = repo
+            .head_id()
+            .map_err(|e| anyhow!("Failed to resolve HEAD: {}", e))?;
+        Ok(oid.to_string())
+    }
+
+    fn get_index_mtime(&self, repo: &Repository) -> Result<i64> {
+        SystemGitClient.get_index_mtime(repo)
+    }
+}
@@ -0,0 +1,291 @@
+use anyhow::{Result, anyhow};
+use git2::Repository;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use super::{FetchTags, GitClient};
+
+/// GitClient with scripted responses, for unit tests that shouldn't depend on real git state
+/// `GitRepository::new` still opens a repository, but an empty `git init --bare` directory is
+/// enough: every answer comes from the script, and every write is recorded instead of performed
+///
+/// ```
+/// use gwm::git::{GitClient, MockGitClient};
+///
+/// let client = MockGitClient::new()
+///     .config("core.bare", "true")
+///     .worktree("/repos/proj/fix-login", "fix-login")
+///     .fail("fetch_remotes");
+/// assert!(client.calls().is_empty());
+/// ```
+#[derive(Default)]
+pub struct MockGitClient {
+    config: Mutex<HashMap<String, String>>,
+    worktrees: Vec<(String, String)>,
+    status_porcelain: String,
+    commits: HashMap<String, (i64, String)>,
+    head_oid: String,
+    directory_mtime: i64,
+    index_mtime: i64,
+    birth_time: Option<i64>,
+    failures: HashSet<String>,
+    calls: Mutex<Vec<String>>,
+}
+
+impl MockGitClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer `get_config(key)` with `value`
+    pub fn config(self, key: &str, value: &str) -> Self {
+        self.config
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Add a linked worktree to the `list_worktrees` output
+    pub fn worktree(mut self, path: &str, branch: &str) -> Self {
+        self.worktrees.push((path.to_string(), branch.to_string()));
+        self
+    }
+
+    /// Answer `get_status_porcelain` with `git status --porcelain` style output
+    pub fn status(mut self, porcelain: &str) -> Self {
+        self.status_porcelain = porcelain.to_string();
+        self
+    }
+
+    /// Give a branch a last commit
+    pub fn commit(mut self, branch: &str, timestamp: i64, summary: &str) -> Self {
+        self.commits
+            .insert(branch.to_string(), (timestamp, summary.to_string()));
+        self
+    }
+
+    pub fn head_oid(mut self, oid: &str) -> Self {
+        self.head_oid = oid.to_string();
+        self
+    }
+
+    pub fn directory_mtime(mut self, mtime: i64) -> Self {
+        self.directory_mtime = mtime;
+        self
+    }
+
+    pub fn index_mtime(mut self, mtime: i64) -> Self {
+        self.index_mtime = mtime;
+        self
+    }
+
+    pub fn birth_time(mut self, birth_time: Option<i64>) -> Self {
+        self.birth_time = birth_time;
+        self
+    }
+
+    /// Make the named GitClient method (e.g. "fetch_remotes") return an error
+    pub fn fail(mut self, method: &str) -> Self {
+        self.failures.insert(method.to_string());
+        self
+    }
+
+    /// Calls that would have changed the repository, in order, e.g. "remove_worktree /path"
+    pub fn calls(&self) -> Vec<String> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    fn check(&self, method: &str) -> Result<()> {
+        if self.failures.contains(method) {
+            return Err(anyhow!("{} failed (scripted)", method));
+        }
+        Ok(())
+    }
+
+    fn record(&self, method: &str, call: String) -> Result<()> {
+        self.check(method)?;
+        self.calls.lock().unwrap().push(call);
+        Ok(())
+    }
+
+    fn find_commit(&self, branch: &str) -> Result<&(i64, String)> {
+        self.commits
+            .get(branch)
+            .ok_or_else(|| anyhow!("Failed to find branch '{}'", branch))
+    }
+}
+
+impl GitClient for MockGitClient {
+    fn get_config(&self, _repo: &Repository, key: &str) -> Result<String> {
+        self.check("get_config")?;
+        self.config
+            .lock()
+            .unwrap()
+            .get(key)
+            .cloned()
+            .ok_or_else(|| anyhow!("Failed to get config value for '{}'", key))
+    }
+
+    fn set_config(&self, _repo: &Repository, key: &str, value: &str) -> Result<()> {
+        self.record("set_config", format!("set_config {} {}", key, value))?;
+        self.config
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn list_worktrees(&self, _repo: &Repository) -> Result<String> {
+        self.check("list_worktrees")?;
+        Ok(self
+            .worktrees
+            .iter()
+            .map(|(path, branch)| format!("{} [{}]\n", path, branch))
+            .collect())
+    }
+
+    fn get_status_porcelain(&self, _repo: &Repository) -> Result<String> {
+        self.check("get_status_porcelain")?;
+        Ok(self.status_porcelain.clone())
+    }
+
+    fn get_last_commit_timestamp(&self, _repo: &Repository, branch: &str) -> Result<i64> {
+        self.check("get_last_commit_timestamp")?;
+        Ok(self.find_commit(branch)?.0)
+    }
+
+    fn get_commit_summary(&self, _repo: &Repository, branch: &str) -> Result<String> {
+        self.check("get_commit_summary")?;
+        Ok(self.find_commit(branch)?.1.clone())
+    }
+
+    fn get_directory_mtime(&self, _path: &str) -> Result<i64> {
+        self.check("get_directory_mtime")?;
+        Ok(self.directory_mtime)
+    }
+
+    fn remove_worktree(&self, _repo: &Repository, worktree_path: &str) -> Result<()> {
+        self.record(
+            "remove_worktree",
+            format!("remove_worktree {}", worktree_path),
+        )
+    }
+
+    fn add_worktree(
+        &self,
+        _repo: &Repository,
+        branch: &str,
+        path: &str,
+        base_branch: Option<&str>,
+        reuse_existing_branch: bool,
+    ) -> Result<()> {
+        let mut call = format!("add_worktree {} {}", branch, path);
+        if let Some(base_branch) = base_branch {
+            call.push_str(&format!(" --base {}", base_branch));
+        }
+        if reuse_existing_branch {
+            call.push_str(" --reuse");
+        }
+        self.record("add_worktree", call)
+    }
+
+    fn fetch_remotes(&self, _repo: &Repository, tags: FetchTags) -> Result<()> {
+        let call = match tags {
+            FetchTags::Auto => "fetch_remotes",
+            FetchTags::All => "fetch_remotes --tags",
+            FetchTags::None => "fetch_remotes --no-tags",
+        };
+        self.record("fetch_remotes", call.to_string())
+    }
+
+    fn pull_main(&self, _repo: &Repository) -> Result<()> {
+        self.record("pull_main", "pull_main".to_string())
+    }
+
+    fn get_worktree_birth_time(&self, _path: &str) -> Result<Option<i64>> {
+        self.check("get_worktree_birth_time")?;
+        Ok(self.birth_time)
+    }
+
+    fn get_head_oid(&self, _repo: &Repository) -> Result<String> {
+        self.check("get_head_oid")?;
+        Ok(self.head_oid.clone())
+    }
+
+    fn get_index_mtime(&self, _repo: &Repository) -> Result<i64> {
+        self.check("get_index_mtime")?;
+        Ok(self.index_mtime)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::GitRepository;
+    use tempfile::TempDir;
+
+    fn empty_repo() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        Repository::init_bare(dir.path()).unwrap();
+        dir
+    }
+
+    #[test]
+    fn scripted_worktrees_go_through_repository_parsing() {
+        let dir = empty_repo();
+        let client = MockGitClient::new()
+            .config("core.bare", "true")
+            .worktree("/repos/proj/main", "main")
+            .worktree("/repos/proj/fix-login", "fix-login");
+        let repo = GitRepository::new(dir.path().to_str().unwrap(), client).unwrap();
+
+        assert!(repo.is_bare().unwrap());
+        let worktrees = repo.list_worktrees().unwrap();
+        assert_eq!(worktrees.len(), 1);
+        assert_eq!(worktrees[0].branch, "fix-login");
+        assert_eq!(worktrees[0].path, "/repos/proj/fix-login");
+
+        let all = repo.list_all_worktrees().unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].branch, "main");
+    }
+
+    #[test]
+    fn detached_worktrees_are_labelled_and_keep_their_commit() {
+        let dir = empty_repo();
+        let oid = "0123456789abcdef0123456789abcdef01234567";
+        let client =
+            MockGitClient::new().worktree("/repos/proj/bisect", &format!("detached {}", oid));
+        let repo = GitRepository::new(dir.path().to_str().unwrap(), client).unwrap();
+
+        let worktrees = repo.list_worktrees().unwrap();
+        assert_eq!(worktrees[0].branch, "(detached 0123456)");
+        assert_eq!(worktrees[0].detached.as_deref(), Some(oid));
+        assert_eq!(worktrees[0].revision(), oid);
+        assert!(crate::git::is_detached_label(&worktrees[0].branch));
+    }
+
+    #[test]
+    fn writes_are_recorded_and_failures_are_scripted() {
+        let dir = empty_repo();
+        let client = MockGitClient::new()
+            .worktree("/repos/proj/fix-login", "fix-login")
+            .fail("fetch_remotes");
+        let repo = GitRepository::new(dir.path().to_str().unwrap(), client).unwrap();
+
+        repo.remove_worktree("fix-login").unwrap();
+        repo.set_branch_description("fix-login", "Fix login")
+            .unwrap();
+        assert!(repo.fetch_remotes().is_err());
+        assert!(repo.remove_worktree("unknown").is_err());
+
+        assert_eq!(
+            repo.git_client().calls(),
+            vec![
+                "remove_worktree /repos/proj/fix-login",
+                "set_config branch.fix-login.description Fix login",
+            ]
+        );
+    }
+}
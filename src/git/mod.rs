@@ -1,13 +1,34 @@
 use anyhow::{Result, anyhow};
 use git2::build::CheckoutBuilder;
-use git2::{BranchType, Repository, StatusOptions, WorktreeAddOptions, WorktreePruneOptions};
+use git2::{
+    BranchType, Repository, RepositoryState, StatusOptions, WorktreeAddOptions, WorktreeLockStatus,
+    WorktreePruneOptions,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt::Display;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+use crate::core::PrStatus;
+
+mod cli;
+#[cfg(feature = "gitoxide")]
+mod gitoxide;
+#[cfg(any(test, feature = "test-util"))]
+mod mock;
+
+pub use cli::CliGitClient;
+#[cfg(feature = "gitoxide")]
+pub use gitoxide::GixGitClient;
+#[cfg(any(test, feature = "test-util"))]
+pub use mock::MockGitClient;
 
 /// Trait for abstracting Git command operations
 pub trait GitClient {
     fn get_config(&self, repo: &Repository, key: &str) -> Result<String>;
+    fn set_config(&self, repo: &Repository, key: &str, value: &str) -> Result<()>;
     fn list_worktrees(&self, repo: &Repository) -> Result<String>;
     fn get_status_porcelain(&self, repo: &Repository) -> Result<String>;
     fn get_last_commit_timestamp(&self, repo: &Repository, branch: &str) -> Result<i64>;
@@ -22,9 +43,33 @@ pub trait GitClient {
         base_branch: Option<&str>,
         reuse_existing_branch: bool,
     ) -> Result<()>;
-    fn fetch_remotes(&self, repo: &Repository) -> Result<()>;
+    fn fetch_remotes(&self, repo: &Repository, tags: FetchTags) -> Result<()>;
     fn pull_main(&self, repo: &Repository) -> Result<()>;
     fn get_worktree_birth_time(&self, path: &str) -> Result<Option<i64>>;
+    fn get_head_oid(&self, repo: &Repository) -> Result<String>;
+    fn get_index_mtime(&self, repo: &Repository) -> Result<i64>;
+}
+
+/// Revision to create a new branch from: a local branch, the same branch on origin, a
+/// remote-tracking branch (e.g. "origin/release"), or a tag or commit
+fn resolve_base(repo: &Repository, source: &str) -> Option<String> {
+    if repo.find_branch(source, BranchType::Local).is_ok() {
+        Some(source.to_string())
+    } else if repo
+        .find_branch(&format!("origin/{}", source), BranchType::Remote)
+        .is_ok()
+    {
+        Some(format!("origin/{}", source))
+    } else if repo.find_branch(source, BranchType::Remote).is_ok()
+        || repo
+            .revparse_single(source)
+            .and_then(|object| object.peel_to_commit())
+            .is_ok()
+    {
+        Some(source.to_string())
+    } else {
+        None
+    }
 }
 
 /// Default implementation using system git command
@@ -41,6 +86,16 @@ impl GitClient for SystemGitClient {
         Ok(value)
     }
 
+    fn set_config(&self, repo: &Repository, key: &str, value: &str) -> Result<()> {
+        let mut config = repo
+            .config()
+            .map_err(|e| anyhow!("Failed to open git config: {}", e))?;
+        config
+            .set_str(key, value)
+            .map_err(|e| anyhow!("Failed to set config value for '{}': {}", key, e))?;
+        Ok(())
+    }
+
     fn list_worktrees(&self, repo: &Repository) -> Result<String> {
         let worktrees = repo
             .worktrees()
@@ -56,10 +111,14 @@ impl GitClient for SystemGitClient {
                     // Try to get the current branch for this worktree
                     if let Ok(wt_repo) = Repository::open(path) {
                         if let Ok(head) = wt_repo.head() {
-                            if let Some(branch_name) = head.shorthand() {
+                            if head.is_branch()
+                                && let Some(branch_name) = head.shorthand()
+                            {
                                 result.push_str(&format!("{} [{}]\n", path_str, branch_name));
+                            } else if let Some(oid) = head.target() {
+                                result.push_str(&format!("{} [detached {}]\n", path_str, oid));
                             } else {
-                                result.push_str(&format!("{} [detached]\n", path_str));
+                                result.push_str(&format!("{} [unknown]\n", path_str));
                             }
                         } else {
                             result.push_str(&format!("{} [unknown]\n", path_str));
@@ -88,6 +147,12 @@ impl GitClient for SystemGitClient {
             let flags = entry.status();
             let path = entry.path().unwrap_or("<unknown>");
 
+            // Unmerged entries are reported as `UU`, whichever side changed what
+            if flags.contains(git2::Status::CONFLICTED) {
+                result.push_str(&format!("UU {}\n", path));
+                continue;
+            }
+
             let mut status_chars = [' ', ' '];
 
             // Index status (first character)
@@ -161,12 +226,7 @@ impl GitClient for SystemGitClient {
     }
 
     fn remove_worktree(&self, repo: &Repository, worktree_path: &str) -> Result<()> {
-        let worktree_name = std::path::Path::new(worktree_path)
-            .file_name()
-            .and_then(|n| n.to_str())
-            .ok_or_else(|| anyhow!("Invalid worktree path: {}", worktree_path))?;
-
-        if let Ok(worktree) = repo.find_worktree(worktree_name) {
+        if let Some(worktree) = find_worktree_by_path(repo, worktree_path) {
             // Configure prune options equivalent to --force
             let mut prune_opts = WorktreePruneOptions::new();
             prune_opts.valid(true); // Prune even if valid (--force equivalent)
@@ -177,10 +237,11 @@ impl GitClient for SystemGitClient {
                 .map_err(|e| anyhow!("Failed to prune worktree: {}", e))?;
         } else {
             // If worktree not found in git metadata but directory exists, just remove it
-            if std::path::Path::new(worktree_path).exists() {
-                std::fs::remove_dir_all(worktree_path)
-                    .map_err(|e| anyhow!("Failed to remove worktree directory: {}", e))?;
+            if !std::path::Path::new(worktree_path).exists() {
+                return Err(anyhow!("No worktree found at '{}'", worktree_path));
             }
+            std::fs::remove_dir_all(worktree_path)
+                .map_err(|e| anyhow!("Failed to remove worktree directory: {}", e))?;
         }
 
         Ok(())
@@ -240,14 +301,10 @@ impl GitClient for SystemGitClient {
                 .checkout_head(Some(CheckoutBuilder::new().force()))
                 .map_err(|e| anyhow!("Failed to checkout existing branch: {}", e))?;
         } else {
-            // Check if source branch exists before creating worktree
-            if repo.find_branch(source_branch, BranchType::Local).is_err()
-                && repo
-                    .find_branch(&format!("origin/{}", source_branch), BranchType::Remote)
-                    .is_err()
-            {
+            // Check if the source exists before creating worktree
+            if resolve_base(repo, source_branch).is_none() {
                 return Err(anyhow!(
-                    "Source branch '{}' not found locally or on remote",
+                    "Source branch '{}' not found locally or on remote, and is not a tag or commit",
                     source_branch
                 ));
             }
@@ -279,17 +336,7 @@ impl GitClient for SystemGitClient {
             };
 
             // Now resolve the source commit in the worktree repository context
-            let source_branch_ref = if worktree_repo
-                .find_branch(source_branch, BranchType::Local)
-                .is_ok()
-            {
-                source_branch.to_string()
-            } else if worktree_repo
-                .find_branch(&format!("origin/{}", source_branch), BranchType::Remote)
-                .is_ok()
-            {
-                format!("origin/{}", source_branch)
-            } else {
+            let Some(source_branch_ref) = resolve_base(&worktree_repo, source_branch) else {
                 cleanup_worktree();
                 return Err(anyhow!(
                     "Source branch '{}' not found in worktree repository",
@@ -309,16 +356,17 @@ impl GitClient for SystemGitClient {
                     ));
                 }
             };
-            let source_commit = match source_obj.as_commit() {
-                Some(commit) => commit,
-                None => {
+            // Annotated tags point at a tag object, so peel through to the commit
+            let source_commit = match source_obj.peel_to_commit() {
+                Ok(commit) => commit,
+                Err(_) => {
                     cleanup_worktree();
                     return Err(anyhow!("Source reference is not a commit"));
                 }
             };
 
             // Create new branch pointing to source commit
-            if let Err(e) = worktree_repo.branch(branch, source_commit, false) {
+            if let Err(e) = worktree_repo.branch(branch, &source_commit, false) {
                 cleanup_worktree();
                 return Err(anyhow!("Failed to create branch '{}': {}", branch, e));
             }
@@ -354,7 +402,14 @@ impl GitClient for SystemGitClient {
         Ok(())
     }
 
-    fn fetch_remotes(&self, repo: &Repository) -> Result<()> {
+    fn fetch_remotes(&self, repo: &Repository, tags: FetchTags) -> Result<()> {
+        // libgit2's SSH transport ignores GIT_SSH_COMMAND, core.sshCommand and ~/.ssh/config
+        // (ProxyJump, per-host identities), so let git and OpenSSH handle SSH remotes
+        if needs_system_ssh(repo) && system_git_available() {
+            debug!("Fetching {} with system git for ssh", repo.path().display());
+            return CliGitClient.fetch_remotes(repo, tags);
+        }
+
         let remotes = repo
             .remotes()
             .map_err(|e| anyhow!("Failed to get remotes: {}", e))?;
@@ -367,6 +422,11 @@ impl GitClient for SystemGitClient {
 
         let mut fetch_options = git2::FetchOptions::new();
         fetch_options.remote_callbacks(callbacks);
+        fetch_options.download_tags(match tags {
+            FetchTags::Auto => git2::AutotagOption::Auto,
+            FetchTags::All => git2::AutotagOption::All,
+            FetchTags::None => git2::AutotagOption::None,
+        });
 
         for remote_name in remotes.iter().flatten() {
             if let Ok(mut remote) = repo.find_remote(remote_name) {
@@ -478,12 +538,6 @@ impl GitClient for SystemGitClient {
             }
         }
 
-        #[cfg(target_os = "linux")]
-        {
-            // Linux doesn't reliably support birth time, return None
-            return Ok(None);
-        }
-
         #[cfg(target_os = "windows")]
         {
             if let Ok(created) = metadata.created() {
@@ -494,31 +548,409 @@ impl GitClient for SystemGitClient {
             }
         }
 
+        // Linux doesn't reliably support birth time
+        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+        let _ = metadata;
+
         // Fallback: return None if birth time is not available
         Ok(None)
     }
+
+    fn get_head_oid(&self, repo: &Repository) -> Result<String> {
+        let head = repo
+            .head()
+            .map_err(|e| anyhow!("Failed to resolve HEAD: {}", e))?;
+        let oid = head
+            .target()
+            .ok_or_else(|| anyhow!("HEAD does not point at a commit"))?;
+        Ok(oid.to_string())
+    }
+
+    fn get_index_mtime(&self, repo: &Repository) -> Result<i64> {
+        // A worktree's index lives in its private gitdir, not in the working directory
+        // Nanosecond precision so that several index updates within one second are told apart
+        let metadata = fs::metadata(repo.path().join("index"))?;
+        let timestamp = metadata
+            .modified()?
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_err(|e| anyhow!("Failed to get timestamp: {}", e))?;
+        Ok(timestamp.as_nanos() as i64)
+    }
+}
+
+/// Find a registered worktree by its directory
+/// Matching on the directory matters because the name is fixed at creation, and may differ from
+/// the directory's file name
+fn find_worktree_by_path(repo: &Repository, worktree_path: &str) -> Option<git2::Worktree> {
+    let wanted = Path::new(worktree_path);
+    let wanted_canonical = fs::canonicalize(wanted).ok();
+
+    let names = repo.worktrees().ok()?;
+    names.iter().flatten().find_map(|name| {
+        let worktree = repo.find_worktree(name).ok()?;
+        let same_path = worktree.path() == wanted
+            || (wanted_canonical.is_some()
+                && fs::canonicalize(worktree.path()).ok() == wanted_canonical);
+        same_path.then_some(worktree)
+    })
+}
+
+/// Find a registered worktree by its directory, or by its name when given a bare name
+/// A path is never matched by its file name, which could be another worktree's name
+fn find_worktree_by_path_or_name(repo: &Repository, path_or_name: &str) -> Option<git2::Worktree> {
+    find_worktree_by_path(repo, path_or_name).or_else(|| {
+        let mut components = Path::new(path_or_name).components();
+        match (components.next(), components.next()) {
+            (Some(std::path::Component::Normal(_)), None) => repo.find_worktree(path_or_name).ok(),
+            _ => None,
+        }
+    })
+}
+
+/// Whether fetching needs the system ssh: a custom ssh command is configured
+/// or any remote is reached over SSH, where only OpenSSH reads ~/.ssh/config
+fn needs_system_ssh(repo: &Repository) -> bool {
+    if std::env::var_os("GIT_SSH_COMMAND").is_some() || std::env::var_os("GIT_SSH").is_some() {
+        return true;
+    }
+    if repo
+        .config()
+        .and_then(|config| config.get_string("core.sshCommand"))
+        .is_ok()
+    {
+        return true;
+    }
+
+    let Ok(remotes) = repo.remotes() else {
+        return false;
+    };
+    remotes.iter().flatten().any(|name| {
+        repo.find_remote(name)
+            .ok()
+            .and_then(|remote| remote.url().map(is_ssh_url))
+            .unwrap_or(false)
+    })
+}
+
+/// Pure function recognizing SSH remote URLs, both `ssh://` and scp-like `user@host:path`
+fn is_ssh_url(url: &str) -> bool {
+    if let Some((scheme, _)) = url.split_once("://") {
+        return matches!(scheme, "ssh" | "git+ssh" | "ssh+git");
+    }
+    // scp-like syntax has a colon before any slash; local paths (even C:\...) don't
+    match url.find(':') {
+        Some(1) => false,
+        Some(colon) => !url[..colon].contains('/'),
+        None => false,
+    }
+}
+
+fn system_git_available() -> bool {
+    std::process::Command::new("git")
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+/// Implementation used for git operations
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    #[default]
+    Libgit2,
+    /// Faster status and commit lookups on very large repositories (fetching still uses libgit2);
+    /// requires the `gitoxide` build feature
+    Gitoxide,
+    /// The system `git` binary, for credential helpers, protocol v2 fetches and sparse checkouts
+    Cli,
+}
+
+impl Backend {
+    pub fn client(self) -> Result<BackendGitClient> {
+        match self {
+            Backend::Libgit2 => Ok(BackendGitClient::Libgit2(SystemGitClient)),
+            Backend::Cli => Ok(BackendGitClient::Cli(CliGitClient)),
+            #[cfg(feature = "gitoxide")]
+            Backend::Gitoxide => Ok(BackendGitClient::Gitoxide(GixGitClient)),
+            #[cfg(not(feature = "gitoxide"))]
+            Backend::Gitoxide => Err(anyhow!(
+                "gwm was built without gitoxide support (rebuild with --features gitoxide)"
+            )),
+        }
+    }
+}
+
+/// Which tags a fetch downloads
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum FetchTags {
+    /// Tags that point at fetched commits, as plain `git fetch` does
+    #[default]
+    Auto,
+    /// Every tag on the remote
+    All,
+    /// No tags at all
+    None,
+}
+
+/// GitClient for the backend selected at runtime
+pub enum BackendGitClient {
+    Libgit2(SystemGitClient),
+    Cli(CliGitClient),
+    #[cfg(feature = "gitoxide")]
+    Gitoxide(GixGitClient),
+}
+
+impl BackendGitClient {
+    fn inner(&self) -> &dyn GitClient {
+        match self {
+            BackendGitClient::Libgit2(client) => client,
+            BackendGitClient::Cli(client) => client,
+            #[cfg(feature = "gitoxide")]
+            BackendGitClient::Gitoxide(client) => client,
+        }
+    }
+}
+
+impl GitClient for BackendGitClient {
+    fn get_config(&self, repo: &Repository, key: &str) -> Result<String> {
+        self.inner().get_config(repo, key)
+    }
+
+    fn set_config(&self, repo: &Repository, key: &str, value: &str) -> Result<()> {
+        self.inner().set_config(repo, key, value)
+    }
+
+    fn list_worktrees(&self, repo: &Repository) -> Result<String> {
+        self.inner().list_worktrees(repo)
+    }
+
+    fn get_status_porcelain(&self, repo: &Repository) -> Result<String> {
+        self.inner().get_status_porcelain(repo)
+    }
+
+    fn get_last_commit_timestamp(&self, repo: &Repository, branch: &str) -> Result<i64> {
+        self.inner().get_last_commit_timestamp(repo, branch)
+    }
+
+    fn get_commit_summary(&self, repo: &Repository, branch: &str) -> Result<String> {
+        self.inner().get_commit_summary(repo, branch)
+    }
+
+    fn get_directory_mtime(&self, path: &str) -> Result<i64> {
+        self.inner().get_directory_mtime(path)
+    }
+
+    fn remove_worktree(&self, repo: &Repository, worktree_path: &str) -> Result<()> {
+        self.inner().remove_worktree(repo, worktree_path)
+    }
+
+    fn add_worktree(
+        &self,
+        repo: &Repository,
+        branch: &str,
+        path: &str,
+        base_branch: Option<&str>,
+        reuse_existing_branch: bool,
+    ) -> Result<()> {
+        self.inner()
+            .add_worktree(repo, branch, path, base_branch, reuse_existing_branch)
+    }
+
+    fn fetch_remotes(&self, repo: &Repository, tags: FetchTags) -> Result<()> {
+        self.inner().fetch_remotes(repo, tags)
+    }
+
+    fn pull_main(&self, repo: &Repository) -> Result<()> {
+        self.inner().pull_main(repo)
+    }
+
+    fn get_worktree_birth_time(&self, path: &str) -> Result<Option<i64>> {
+        self.inner().get_worktree_birth_time(path)
+    }
+
+    fn get_head_oid(&self, repo: &Repository) -> Result<String> {
+        self.inner().get_head_oid(repo)
+    }
+
+    fn get_index_mtime(&self, repo: &Repository) -> Result<i64> {
+        self.inner().get_index_mtime(repo)
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct WorktreeInfo {
     pub path: String,
+    /// Branch checked out, or for a detached HEAD a "(detached <short id>)" label
     pub branch: String,
+    /// Commit checked out when HEAD is detached
+    pub detached: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+impl WorktreeInfo {
+    /// What the worktree's last commit is looked up by: its branch, or the detached commit
+    pub fn revision(&self) -> &str {
+        self.detached.as_deref().unwrap_or(&self.branch)
+    }
+}
+
+/// Branch a stash entry was made on, from its message: "WIP on main: 1a2b3c4 Fix" or
+/// "On main: custom message"; `None` for stashes made on a detached HEAD
+pub fn stash_branch(message: &str) -> Option<&str> {
+    let rest = message
+        .strip_prefix("WIP on ")
+        .or_else(|| message.strip_prefix("On "))?;
+    let (branch, _) = rest.split_once(": ")?;
+    (branch != "(no branch)").then_some(branch)
+}
+
+/// Whether git accepts `name` as a branch name, which also keeps it from climbing out of the
+/// repository directory when it names a worktree's directory
+pub fn is_valid_branch_name(name: &str) -> bool {
+    git2::Branch::name_is_valid(name).unwrap_or(false)
+}
+
+/// Whether a worktree's branch is the label of a detached HEAD rather than a branch name,
+/// which can never contain a space
+pub fn is_detached_label(branch: &str) -> bool {
+    branch.starts_with("(detached ")
+}
+
+/// What a fetch brought in, from the remote-tracking branches and tags before and after it
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FetchChanges {
+    /// Remote-tracking branches that didn't exist before, e.g. "origin/feature"
+    pub new_branches: Vec<String>,
+    /// Remote-tracking branches that moved, with the number of commits they gained
+    pub updated_branches: Vec<(String, usize)>,
+    pub new_tags: Vec<String>,
+}
+
+impl FetchChanges {
+    /// Compare ref tips (full ref names to object ids) taken before and after a fetch
+    /// `commits(old, new)` counts the commits a moved branch gained
+    pub fn between(
+        before: &HashMap<String, String>,
+        after: &HashMap<String, String>,
+        commits: impl Fn(&str, &str) -> usize,
+    ) -> Self {
+        let mut changes = Self::default();
+        for (name, oid) in after {
+            let old = before.get(name);
+            if let Some(tag) = name.strip_prefix("refs/tags/") {
+                if old.is_none() {
+                    changes.new_tags.push(tag.to_string());
+                }
+            } else if let Some(branch) = name.strip_prefix("refs/remotes/") {
+                match old {
+                    None => changes.new_branches.push(branch.to_string()),
+                    Some(old) if old != oid => changes
+                        .updated_branches
+                        .push((branch.to_string(), commits(old, oid))),
+                    Some(_) => {}
+                }
+            }
+        }
+        changes.new_branches.sort();
+        changes.updated_branches.sort();
+        changes.new_tags.sort();
+        changes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.new_branches.is_empty() && self.updated_branches.is_empty() && self.new_tags.is_empty()
+    }
+
+    /// One-line summary such as "1 new branch, 2 updated branches, 1 new tag"
+    pub fn summary(&self) -> String {
+        let count =
+            |n: usize, what: &str| format!("{} {}{}", n, what, if n == 1 { "" } else { "es" });
+        let mut parts = Vec::new();
+        if !self.new_branches.is_empty() {
+            parts.push(count(self.new_branches.len(), "new branch"));
+        }
+        if !self.updated_branches.is_empty() {
+            parts.push(count(self.updated_branches.len(), "updated branch"));
+        }
+        if !self.new_tags.is_empty() {
+            let n = self.new_tags.len();
+            parts.push(format!("{} new tag{}", n, if n == 1 { "" } else { "s" }));
+        }
+        parts.join(", ")
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub enum LocalStatus {
+    #[default]
     Clean,
     Dirty,
     Staged,
+    /// Unmerged paths left by a merge, rebase or stash pop that stopped for conflicts
+    Conflicted,
     Missing,
 }
 
+/// A git operation left in progress in a worktree, waiting for `--continue` or `--abort`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Operation {
+    Rebasing,
+    Merging,
+    CherryPicking,
+    Reverting,
+    Bisecting,
+    /// `git am`
+    ApplyingPatches,
+}
+
+impl Operation {
+    fn from_state(state: RepositoryState) -> Option<Self> {
+        match state {
+            RepositoryState::Clean => None,
+            RepositoryState::Merge => Some(Operation::Merging),
+            RepositoryState::Revert | RepositoryState::RevertSequence => Some(Operation::Reverting),
+            RepositoryState::CherryPick | RepositoryState::CherryPickSequence => {
+                Some(Operation::CherryPicking)
+            }
+            RepositoryState::Bisect => Some(Operation::Bisecting),
+            RepositoryState::Rebase
+            | RepositoryState::RebaseInteractive
+            | RepositoryState::RebaseMerge
+            | RepositoryState::ApplyMailboxOrRebase => Some(Operation::Rebasing),
+            RepositoryState::ApplyMailbox => Some(Operation::ApplyingPatches),
+        }
+    }
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Operation::Rebasing => "Rebasing",
+            Operation::Merging => "Merging",
+            Operation::CherryPicking => "Cherry-picking",
+            Operation::Reverting => "Reverting",
+            Operation::Bisecting => "Bisecting",
+            Operation::ApplyingPatches => "Applying patches",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// Size of a branch's changes, as `git diff --shortstat` reports it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiffStat {
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
 impl Display for LocalStatus {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let text = match self {
             LocalStatus::Clean => "Clean",
             LocalStatus::Dirty => "Dirty",
             LocalStatus::Staged => "Staged",
+            LocalStatus::Conflicted => "Conflicted",
             LocalStatus::Missing => "Missing",
         };
         write!(f, "{}", text)
@@ -540,6 +972,11 @@ impl<T: GitClient> GitRepository<T> {
         })
     }
 
+    /// The client this repository talks to git through, e.g. to inspect a MockGitClient
+    pub fn git_client(&self) -> &T {
+        &self.git_client
+    }
+
     pub fn is_bare(&self) -> Result<bool> {
         match self.git_client.get_config(&self.repository, "core.bare") {
             Ok(config_value) => Ok(config_value.trim() == "true"),
@@ -547,7 +984,39 @@ impl<T: GitClient> GitRepository<T> {
         }
     }
 
+    /// Work-in-progress worktrees: all except those on main or master
     pub fn list_worktrees(&self) -> Result<Vec<WorktreeInfo>> {
+        Ok(self
+            .registered_worktrees()?
+            .into_iter()
+            .filter(|worktree| worktree.branch != "main" && worktree.branch != "master")
+            .collect())
+    }
+
+    /// Every worktree with a branch checked out, including main and master and the working
+    /// copy of a non-bare repository
+    pub fn list_all_worktrees(&self) -> Result<Vec<WorktreeInfo>> {
+        let mut worktrees = self.registered_worktrees()?;
+        // libgit2 lists only linked worktrees, not the working copy of a non-bare repository
+        if let Some(workdir) = self.repository.workdir()
+            && !worktrees.iter().any(|w| Path::new(&w.path) == workdir)
+            && let Ok(head) = self.repository.head()
+            && head.is_branch()
+            && let Some(branch) = head.shorthand()
+        {
+            worktrees.insert(
+                0,
+                WorktreeInfo {
+                    path: workdir.to_string_lossy().trim_end_matches('/').to_string(),
+                    branch: branch.to_string(),
+                    detached: None,
+                },
+            );
+        }
+        Ok(worktrees)
+    }
+
+    fn registered_worktrees(&self) -> Result<Vec<WorktreeInfo>> {
         let worktrees_output = match self.git_client.list_worktrees(&self.repository) {
             Ok(output) => output,
             Err(_) => return Ok(vec![]),
@@ -572,11 +1041,19 @@ impl<T: GitClient> GitRepository<T> {
             {
                 let branch = line[branch_start + 1..branch_end].to_string();
                 let path = line.split_whitespace().next().unwrap_or("").to_string();
-
-                // Skip main/master branches for WIP detection
-                if branch != "main" && branch != "master" {
-                    worktrees.push(WorktreeInfo { path, branch });
-                }
+                let worktree = match branch.strip_prefix("detached ") {
+                    Some(oid) => WorktreeInfo {
+                        path,
+                        branch: format!("(detached {})", &oid[..oid.len().min(7)]),
+                        detached: Some(oid.to_string()),
+                    },
+                    None => WorktreeInfo {
+                        path,
+                        branch,
+                        detached: None,
+                    },
+                };
+                worktrees.push(worktree);
             }
         }
 
@@ -602,6 +1079,8 @@ impl<T: GitClient> GitRepository<T> {
     fn parse_local_status(status_output: &str) -> LocalStatus {
         if status_output.trim().is_empty() {
             LocalStatus::Clean
+        } else if status_output.lines().any(Self::is_unmerged) {
+            LocalStatus::Conflicted
         } else if status_output.lines().any(|line| {
             line.starts_with('A')
                 || line.starts_with('D')
@@ -614,6 +1093,12 @@ impl<T: GitClient> GitRepository<T> {
         }
     }
 
+    /// Whether a porcelain line is an unmerged path: `DD`, `AA`, or either side `U`
+    fn is_unmerged(line: &str) -> bool {
+        let code = line.get(..2).unwrap_or_default();
+        code == "DD" || code == "AA" || code.contains('U')
+    }
+
     pub fn get_last_commit_timestamp(&self, worktree_path: &str, branch_name: &str) -> Result<i64> {
         let worktree_repo = Repository::open(worktree_path)
             .map_err(|_| anyhow!("Failed to open worktree repository"))?;
@@ -644,6 +1129,57 @@ impl<T: GitClient> GitRepository<T> {
             .remove_worktree(&self.repository, &worktree.path)
     }
 
+    /// Remove a worktree by directory or name without resolving its branch
+    /// Recovery path for worktrees whose branch was deleted or renamed outside gwm
+    pub fn remove_worktree_at(&self, path_or_name: &str) -> Result<()> {
+        let worktree_path = self
+            .find_registered_worktree(path_or_name)
+            .ok_or_else(|| anyhow!("No worktree registered at '{}'", path_or_name))?;
+        self.git_client
+            .remove_worktree(&self.repository, &worktree_path.to_string_lossy())
+    }
+
+    /// Rebase, merge or other operation in progress in the worktree, from the state files
+    /// (`rebase-merge/`, `MERGE_HEAD`, ...) in its git directory
+    pub fn get_operation(&self, worktree_path: &str) -> Option<Operation> {
+        let worktree_repo = Repository::open(worktree_path).ok()?;
+        Operation::from_state(worktree_repo.state())
+    }
+
+    /// Why the worktree at the given directory or name is locked with `git worktree lock`:
+    /// `Some` (empty when no reason was given) if it is locked, `None` if not
+    pub fn worktree_lock(&self, path_or_name: &str) -> Option<String> {
+        let worktree = find_worktree_by_path_or_name(&self.repository, path_or_name)?;
+        match worktree.is_locked().ok()? {
+            WorktreeLockStatus::Locked(reason) => Some(reason.unwrap_or_default()),
+            WorktreeLockStatus::Unlocked => None,
+        }
+    }
+
+    /// Lock a worktree, like `git worktree lock`, so that git and gwm won't remove or prune it
+    pub fn lock_worktree(&self, path_or_name: &str, reason: Option<&str>) -> Result<()> {
+        let worktree = find_worktree_by_path_or_name(&self.repository, path_or_name)
+            .ok_or_else(|| anyhow!("No worktree registered at '{}'", path_or_name))?;
+        worktree
+            .lock(reason)
+            .map_err(|e| anyhow!("Failed to lock worktree '{}': {}", path_or_name, e))
+    }
+
+    pub fn unlock_worktree(&self, path_or_name: &str) -> Result<()> {
+        let worktree = find_worktree_by_path_or_name(&self.repository, path_or_name)
+            .ok_or_else(|| anyhow!("No worktree registered at '{}'", path_or_name))?;
+        worktree
+            .unlock()
+            .map_err(|e| anyhow!("Failed to unlock worktree '{}': {}", path_or_name, e))
+    }
+
+    /// Directory of the registered worktree with the given directory or name, if any
+    /// Also finds worktrees whose directory has already been deleted
+    pub fn find_registered_worktree(&self, path_or_name: &str) -> Option<PathBuf> {
+        find_worktree_by_path_or_name(&self.repository, path_or_name)
+            .map(|worktree| worktree.path().to_path_buf())
+    }
+
     pub fn add_worktree(
         &self,
         branch: &str,
@@ -660,18 +1196,656 @@ impl<T: GitClient> GitRepository<T> {
         )
     }
 
+    /// Add a worktree with a detached HEAD at a commit given as a hex object id
+    pub fn add_detached_worktree(&self, path: &str, oid: &str) -> Result<()> {
+        CliGitClient::git(
+            &self.repository,
+            &["worktree", "add", "--quiet", "--detach", path, oid],
+        )?;
+        Ok(())
+    }
+
     pub fn fetch_remotes(&self) -> Result<()> {
-        self.git_client.fetch_remotes(&self.repository)
+        self.fetch_remotes_with_tags(FetchTags::Auto)
+    }
+
+    pub fn fetch_remotes_with_tags(&self, tags: FetchTags) -> Result<()> {
+        self.git_client.fetch_remotes(&self.repository, tags)
     }
 
     pub fn pull_main(&self) -> Result<()> {
         self.git_client.pull_main(&self.repository)
     }
 
+    pub fn get_head_oid(&self, worktree_path: &str) -> Result<String> {
+        let worktree_repo = Repository::open(worktree_path)
+            .map_err(|_| anyhow!("Failed to open worktree repository"))?;
+        self.git_client.get_head_oid(&worktree_repo)
+    }
+
+    /// The worktree's index file, which lives in its private git directory
+    pub fn index_path(&self, worktree_path: &str) -> Result<PathBuf> {
+        let worktree_repo = Repository::open(worktree_path)
+            .map_err(|_| anyhow!("Failed to open worktree repository"))?;
+        Ok(worktree_repo.path().join("index"))
+    }
+
+    pub fn get_index_mtime(&self, worktree_path: &str) -> Result<i64> {
+        let worktree_repo = Repository::open(worktree_path)
+            .map_err(|_| anyhow!("Failed to open worktree repository"))?;
+        self.git_client.get_index_mtime(&worktree_repo)
+    }
+
+    /// Unix time of the latest HEAD movement (commit, checkout, reset, rebase) or index update
+    /// (staging, or `git status` noticing edited files) in a worktree, if either is known
+    pub fn get_activity_time(&self, worktree_path: &str) -> Option<i64> {
+        let worktree_repo = Repository::open(worktree_path).ok()?;
+        let head_moved = worktree_repo.reflog("HEAD").ok().and_then(|reflog| {
+            reflog
+                .get(0)
+                .map(|entry| entry.committer().when().seconds())
+        });
+        let index_updated = self
+            .git_client
+            .get_index_mtime(&worktree_repo)
+            .ok()
+            .map(|nanos| nanos / 1_000_000_000);
+        head_moved.max(index_updated)
+    }
+
     pub fn get_worktree_birth_time(&self, worktree_path: &str) -> Result<Option<i64>> {
         self.git_client.get_worktree_birth_time(worktree_path)
     }
 
+    /// Set the branch description shown by `git branch --edit-description`
+    pub fn set_branch_description(&self, branch: &str, description: &str) -> Result<()> {
+        self.git_client.set_config(
+            &self.repository,
+            &format!("branch.{}.description", branch),
+            description,
+        )
+    }
+
+    /// Remote-tracking branches and tags with the objects they point at, keyed by full ref
+    /// name, to see what a fetch changed
+    pub fn fetched_ref_tips(&self) -> Result<HashMap<String, String>> {
+        let mut tips = HashMap::new();
+        for glob in ["refs/remotes/*", "refs/tags/*"] {
+            for reference in self.repository.references_glob(glob)? {
+                let reference = reference?;
+                if let (Some(name), Some(oid)) = (reference.name(), reference.target()) {
+                    tips.insert(name.to_string(), oid.to_string());
+                }
+            }
+        }
+        Ok(tips)
+    }
+
+    /// What changed since `before` was taken with `fetched_ref_tips`
+    pub fn fetch_changes(&self, before: &HashMap<String, String>) -> Result<FetchChanges> {
+        let after = self.fetched_ref_tips()?;
+        Ok(FetchChanges::between(before, &after, |old, new| {
+            let (Ok(old), Ok(new)) = (git2::Oid::from_str(old), git2::Oid::from_str(new)) else {
+                return 0;
+            };
+            self.repository
+                .graph_ahead_behind(new, old)
+                .map(|(ahead, _)| ahead)
+                .unwrap_or(0)
+        }))
+    }
+
+    /// Local branch names, followed by remote-tracking ones (e.g. "origin/main") when asked for
+    pub fn branch_names(&self, include_remote: bool) -> Result<Vec<String>> {
+        let mut local = Vec::new();
+        let mut remote = Vec::new();
+        for branch in self.repository.branches(None)? {
+            let (branch, branch_type) = branch?;
+            let Some(name) = branch.name()? else {
+                continue;
+            };
+            match branch_type {
+                BranchType::Local => local.push(name.to_string()),
+                // The symbolic origin/HEAD isn't a branch anyone means to pick
+                BranchType::Remote if include_remote && !name.ends_with("/HEAD") => {
+                    remote.push(name.to_string())
+                }
+                BranchType::Remote => {}
+            }
+        }
+        local.sort();
+        remote.sort();
+        local.extend(remote);
+        Ok(local)
+    }
+
+    /// Whether a remote-tracking branch such as "origin/feature" exists
+    pub fn has_remote_branch(&self, remote_branch: &str) -> bool {
+        self.repository
+            .find_branch(remote_branch, BranchType::Remote)
+            .is_ok()
+    }
+
+    /// Create a local branch at a remote-tracking branch's commit, with it as the upstream
+    pub fn create_tracking_branch(&self, branch: &str, remote_branch: &str) -> Result<()> {
+        if self
+            .repository
+            .find_branch(branch, BranchType::Local)
+            .is_ok()
+        {
+            return Err(anyhow!(
+                "Branch '{}' already exists. Use --reuse to reuse the existing branch.",
+                branch
+            ));
+        }
+        let commit = self
+            .repository
+            .find_branch(remote_branch, BranchType::Remote)
+            .map_err(|e| anyhow!("Failed to find remote branch '{}': {}", remote_branch, e))?
+            .get()
+            .peel_to_commit()?;
+        let mut local = self
+            .repository
+            .branch(branch, &commit, false)
+            .map_err(|e| anyhow!("Failed to create branch '{}': {}", branch, e))?;
+        if let Err(e) = local.set_upstream(Some(remote_branch)) {
+            let _ = local.delete();
+            return Err(anyhow!("Failed to track '{}': {}", remote_branch, e));
+        }
+        Ok(())
+    }
+
+    /// Whether a local branch already has an upstream configured
+    pub fn has_upstream(&self, branch: &str) -> bool {
+        self.git_client
+            .get_config(&self.repository, &format!("branch.{}.remote", branch))
+            .is_ok()
+    }
+
+    /// Commits a local branch is ahead of and behind its upstream, `None` without an upstream
+    /// Compares against the last fetch, so it never touches the network
+    pub fn ahead_behind(&self, branch: &str) -> Option<(usize, usize)> {
+        let local = self
+            .repository
+            .find_branch(branch, BranchType::Local)
+            .ok()?;
+        let upstream = local.upstream().ok()?;
+        self.repository
+            .graph_ahead_behind(local.get().target()?, upstream.get().target()?)
+            .ok()
+    }
+
+    /// Make a branch track the same-named branch on a remote, even before it has been pushed
+    pub fn set_upstream(&self, branch: &str, remote: &str) -> Result<()> {
+        self.git_client.set_config(
+            &self.repository,
+            &format!("branch.{}.remote", branch),
+            remote,
+        )?;
+        self.git_client.set_config(
+            &self.repository,
+            &format!("branch.{}.merge", branch),
+            &format!("refs/heads/{}", branch),
+        )
+    }
+
+    /// Push a branch and track it (`git push -u`), with the system git so credential helpers
+    /// and SSH configuration apply
+    pub fn push_upstream(&self, branch: &str, remote: &str) -> Result<()> {
+        CliGitClient::git(&self.repository, &["push", "--quiet", "-u", remote, branch])?;
+        Ok(())
+    }
+
+    /// Delete a branch on a remote (`git push --delete`), which also drops the remote-tracking branch
+    pub fn delete_remote_branch(&self, branch: &str, remote: &str) -> Result<()> {
+        CliGitClient::git(
+            &self.repository,
+            &["push", "--quiet", remote, "--delete", branch],
+        )?;
+        Ok(())
+    }
+
+    /// Delete a branch on a remote only once its pull request is merged, so work from a PR
+    /// closed without merging survives on the remote
+    /// Returns whether the branch was deleted
+    pub fn delete_merged_remote_branch(
+        &self,
+        branch: &str,
+        remote: &str,
+        pr_status: Option<&PrStatus>,
+    ) -> Result<bool> {
+        if pr_status != Some(&PrStatus::Merged) {
+            return Ok(false);
+        }
+        self.delete_remote_branch(branch, remote)?;
+        Ok(true)
+    }
+
+    /// Commit a local branch points at, as a hex object id
+    pub fn branch_tip(&self, branch: &str) -> Result<String> {
+        let oid = self
+            .repository
+            .find_branch(branch, BranchType::Local)
+            .map_err(|e| anyhow!("Failed to find branch '{}': {}", branch, e))?
+            .get()
+            .peel_to_commit()?
+            .id();
+        Ok(oid.to_string())
+    }
+
+    /// Create a local branch at a commit given as a hex object id
+    pub fn create_branch_at(&self, branch: &str, oid: &str) -> Result<()> {
+        let commit = self
+            .repository
+            .find_commit(git2::Oid::from_str(oid)?)
+            .map_err(|e| anyhow!("Failed to find commit {}: {}", oid, e))?;
+        self.repository
+            .branch(branch, &commit, false)
+            .map_err(|e| anyhow!("Failed to create branch '{}': {}", branch, e))?;
+        Ok(())
+    }
+
+    /// Tag a branch's tip as `archive/<branch>/<date>` so it can be recovered after cleanup,
+    /// adding a counter if the branch was already archived that day at another commit
+    pub fn archive_branch(&self, branch: &str, date: &str) -> Result<String> {
+        let commit = self
+            .repository
+            .find_branch(branch, BranchType::Local)
+            .map_err(|e| anyhow!("Failed to find branch '{}': {}", branch, e))?
+            .get()
+            .peel_to_commit()?;
+        let base = format!("archive/{}/{}", branch, date);
+        let mut counter = 1;
+        loop {
+            let name = match counter {
+                1 => base.clone(),
+                n => format!("{}-{}", base, n),
+            };
+            match self
+                .repository
+                .find_reference(&format!("refs/tags/{}", name))
+            {
+                Ok(tag) if tag.target() == Some(commit.id()) => return Ok(name),
+                Ok(_) => counter += 1,
+                Err(_) => {
+                    self.repository
+                        .tag_lightweight(&name, commit.as_object(), false)
+                        .map_err(|e| anyhow!("Failed to create tag '{}': {}", name, e))?;
+                    return Ok(name);
+                }
+            }
+        }
+    }
+
+    /// Delete a local branch, e.g. one created for a worktree that failed to appear
+    pub fn delete_branch(&self, branch: &str) -> Result<()> {
+        self.repository
+            .find_branch(branch, BranchType::Local)?
+            .delete()
+            .map_err(|e| anyhow!("Failed to delete branch '{}': {}", branch, e))
+    }
+
+    /// Whether a branch's work has landed in `into` (e.g. "origin/main"): the branch is an
+    /// ancestor of it, or a commit on it since they diverged has the same patch-id as the whole
+    /// branch, which is what a squash merge leaves behind
+    pub fn is_merged_into(&self, branch: &str, into: &str) -> Result<bool> {
+        let repo = &self.repository;
+        let branch_commit = repo
+            .find_branch(branch, BranchType::Local)
+            .map_err(|e| anyhow!("Failed to find branch '{}': {}", branch, e))?
+            .get()
+            .peel_to_commit()?;
+        let target = repo
+            .revparse_single(into)
+            .and_then(|object| object.peel_to_commit())
+            .map_err(|e| anyhow!("Failed to find '{}': {}", into, e))?;
+        let base = repo.merge_base(branch_commit.id(), target.id())?;
+        if base == branch_commit.id() {
+            return Ok(true);
+        }
+
+        let branch_diff = repo.diff_tree_to_tree(
+            Some(&repo.find_commit(base)?.tree()?),
+            Some(&branch_commit.tree()?),
+            None,
+        )?;
+        if branch_diff.deltas().len() == 0 {
+            return Ok(false);
+        }
+        let branch_patch_id = branch_diff.patchid(None)?;
+
+        let mut walk = repo.revwalk()?;
+        walk.push(target.id())?;
+        walk.hide(base)?;
+        for oid in walk {
+            let commit = repo.find_commit(oid?)?;
+            if commit.parent_count() != 1 {
+                continue;
+            }
+            let diff = repo.diff_tree_to_tree(
+                Some(&commit.parent(0)?.tree()?),
+                Some(&commit.tree()?),
+                None,
+            )?;
+            if diff.patchid(None)? == branch_patch_id {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Number of stash entries per branch they were made on
+    /// Stashes are shared by all worktrees of a repository, so they are told apart by the
+    /// "WIP on <branch>: ..." or "On <branch>: ..." message git gives them
+    pub fn stash_counts(&self) -> Result<HashMap<String, usize>> {
+        // Listing stashes needs a mutable repository, so open a second handle
+        let mut repo = Repository::open(self.repository.path())?;
+        let mut counts = HashMap::new();
+        repo.stash_foreach(|_, message, _| {
+            if let Some(branch) = stash_branch(message) {
+                *counts.entry(branch.to_string()).or_default() += 1;
+            }
+            true
+        })?;
+        Ok(counts)
+    }
+
+    /// Branch that work is merged into: origin's copy of the default branch when there is one,
+    /// otherwise the local default branch
+    pub fn base_branch(&self) -> Option<String> {
+        let default_branch = self.default_branch()?;
+        let remote_branch = format!("origin/{}", default_branch);
+        if self.has_remote_branch(&remote_branch) {
+            Some(remote_branch)
+        } else {
+            Some(default_branch)
+        }
+    }
+
+    /// Changes committed on `revision` (a branch or commit) since it forked from `base`, like
+    /// `git diff --shortstat base...revision`
+    pub fn diff_stat(&self, revision: &str, base: &str) -> Result<DiffStat> {
+        let repo = &self.repository;
+        let commit = |spec: &str| {
+            repo.revparse_single(spec)
+                .and_then(|object| object.peel_to_commit())
+                .map_err(|e| anyhow!("Failed to find '{}': {}", spec, e))
+        };
+        let head = commit(revision)?;
+        let fork_point = repo.merge_base(head.id(), commit(base)?.id())?;
+        let stats = repo
+            .diff_tree_to_tree(
+                Some(&repo.find_commit(fork_point)?.tree()?),
+                Some(&head.tree()?),
+                None,
+            )?
+            .stats()?;
+        Ok(DiffStat {
+            files_changed: stats.files_changed(),
+            insertions: stats.insertions(),
+            deletions: stats.deletions(),
+        })
+    }
+
+    /// Delete a local branch, first saving its tip as `refs/gwm/backup/<branch>` so it can be
+    /// recovered; returns the backup ref
+    pub fn delete_branch_with_backup(&self, branch: &str) -> Result<String> {
+        let backup = format!("refs/gwm/backup/{}", branch);
+        let oid = self
+            .repository
+            .find_branch(branch, BranchType::Local)
+            .map_err(|e| anyhow!("Failed to find branch '{}': {}", branch, e))?
+            .get()
+            .peel_to_commit()?
+            .id();
+        self.repository
+            .reference(&backup, oid, true, "gwm: backup before deleting branch")
+            .map_err(|e| anyhow!("Failed to create backup ref '{}': {}", backup, e))?;
+        self.delete_branch(branch)?;
+        Ok(backup)
+    }
+
+    /// Names of registered worktrees whose directory is gone, which `prune_worktrees` would
+    /// clean up; locked worktrees are left alone, as `git worktree prune` does
+    pub fn stale_worktrees(&self) -> Result<Vec<String>> {
+        let mut stale = Vec::new();
+        for name in self.repository.worktrees()?.iter().flatten() {
+            if self.repository.find_worktree(name)?.is_prunable(None)? {
+                stale.push(name.to_string());
+            }
+        }
+        stale.sort();
+        Ok(stale)
+    }
+
+    /// Delete the `.git/worktrees/<name>` files of stale worktrees, like `git worktree prune`,
+    /// so their branches can be checked out again; returns the names pruned
+    pub fn prune_worktrees(&self) -> Result<Vec<String>> {
+        let stale = self.stale_worktrees()?;
+        for name in &stale {
+            self.repository
+                .find_worktree(name)?
+                .prune(None)
+                .map_err(|e| anyhow!("Failed to prune worktree '{}': {}", name, e))?;
+        }
+        Ok(stale)
+    }
+
+    /// Reconnect worktrees with the repository after either was moved, like
+    /// `git worktree repair`; `moved` are worktree directories at their new location, while
+    /// worktrees that stayed put are found from the repository's own records
+    /// Returns what git fixed, e.g. ".git file broken: /path/to/worktree"
+    pub fn repair_worktrees(&self, moved: &[PathBuf]) -> Result<Vec<String>> {
+        let paths: Vec<String> = moved
+            .iter()
+            // git would resolve relative paths against the repository
+            .map(|path| {
+                fs::canonicalize(path)
+                    .unwrap_or_else(|_| path.clone())
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+        let mut repaired = self.repair_commondirs()?;
+        // git finds a moved worktree's records by the last component of the path in its `.git`
+        // file, which libgit2 ends with a slash
+        for path in &paths {
+            let dot_git = Path::new(path).join(".git");
+            if let Ok(content) = fs::read_to_string(&dot_git)
+                && let Some(gitdir) = content.trim_end().strip_suffix('/')
+            {
+                fs::write(&dot_git, format!("{}\n", gitdir))?;
+            }
+        }
+        let mut args = vec!["worktree", "repair"];
+        args.extend(paths.iter().map(String::as_str));
+        let report = CliGitClient::git_stderr(&self.repository, &args)?;
+        repaired.extend(
+            report
+                .lines()
+                .filter_map(|line| line.strip_prefix("repair: "))
+                .map(str::to_string),
+        );
+        Ok(repaired)
+    }
+
+    /// libgit2 records the repository's absolute path in each worktree's `commondir`, which
+    /// git can't even read the worktree through once the repository has moved; point them
+    /// back at the repository relatively, as git itself writes them
+    fn repair_commondirs(&self) -> Result<Vec<String>> {
+        let mut repaired = Vec::new();
+        let Ok(entries) = fs::read_dir(self.repository.path().join("worktrees")) else {
+            return Ok(repaired);
+        };
+        let common_dir = fs::canonicalize(self.repository.path())?;
+        for entry in entries.flatten() {
+            let commondir = entry.path().join("commondir");
+            let Ok(recorded) = fs::read_to_string(&commondir) else {
+                continue;
+            };
+            let target = entry.path().join(recorded.trim());
+            if fs::canonicalize(&target).ok().as_ref() != Some(&common_dir) {
+                fs::write(&commondir, "../..\n")?;
+                repaired.push(format!(
+                    "commondir incorrect: worktrees/{}/commondir",
+                    entry.file_name().to_string_lossy()
+                ));
+            }
+        }
+        Ok(repaired)
+    }
+
+    /// Branch checked out in the working copy, `None` when HEAD is detached or unborn
+    pub fn head_branch(&self) -> Option<String> {
+        let head = self.repository.head().ok()?;
+        if !head.is_branch() {
+            return None;
+        }
+        head.shorthand().map(str::to_string)
+    }
+
+    /// Bring a branch over from another repository on disk, e.g. a standalone clone
+    /// Only fast-forwards an existing branch, so commits on either side are never lost
+    pub fn fetch_branch_from(&self, path: &Path, branch: &str) -> Result<()> {
+        let refspec = format!("refs/heads/{0}:refs/heads/{0}", branch);
+        CliGitClient::git(
+            &self.repository,
+            &[
+                "fetch",
+                "--quiet",
+                "--no-tags",
+                &path.to_string_lossy(),
+                &refspec,
+            ],
+        )
+        .map_err(|e| {
+            anyhow!(
+                "Failed to fetch branch '{}' from '{}' (has it diverged?): {}",
+                branch,
+                path.display(),
+                e
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Register a directory without a `.git` as a worktree with the branch checked out, as
+    /// `git worktree add` would but keeping the files in it: differences from the branch show
+    /// up as uncommitted changes
+    pub fn attach_worktree(&self, dir: &Path, branch: &str) -> Result<()> {
+        let dir = fs::canonicalize(dir)?;
+        let base = dir
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| anyhow!("Invalid worktree directory '{}'", dir.display()))?;
+        // Same naming as git: the directory name, numbered when it is taken
+        let worktrees_dir = self.repository.path().join("worktrees");
+        let mut name = base.to_string();
+        let mut suffix = 1;
+        while worktrees_dir.join(&name).exists() {
+            name = format!("{}{}", base, suffix);
+            suffix += 1;
+        }
+
+        let admin_dir = worktrees_dir.join(&name);
+        let dot_git = dir.join(".git");
+        fs::create_dir_all(&admin_dir)?;
+        fs::write(
+            admin_dir.join("HEAD"),
+            format!("ref: refs/heads/{}\n", branch),
+        )?;
+        fs::write(admin_dir.join("commondir"), "../..\n")?;
+        fs::write(admin_dir.join("gitdir"), format!("{}\n", dot_git.display()))?;
+        fs::write(&dot_git, format!("gitdir: {}\n", admin_dir.display()))?;
+
+        // A fresh index from the branch, leaving the files themselves alone
+        if let Err(e) = CliGitClient::git_in(&dir, &["reset", "--quiet"]) {
+            let _ = fs::remove_file(&dot_git);
+            let _ = fs::remove_dir_all(&admin_dir);
+            return Err(anyhow!(
+                "Failed to set up worktree at '{}': {}",
+                dir.display(),
+                e
+            ));
+        }
+        Ok(())
+    }
+
+    /// Every remote with its fetch URL, sorted by name
+    pub fn remotes(&self) -> Result<Vec<(String, String)>> {
+        let mut remotes = Vec::new();
+        for name in self.repository.remotes()?.iter().flatten() {
+            let remote = self.repository.find_remote(name)?;
+            remotes.push((
+                name.to_string(),
+                remote.url().unwrap_or_default().to_string(),
+            ));
+        }
+        remotes.sort();
+        Ok(remotes)
+    }
+
+    /// Branch the remote's HEAD points at (e.g. "main"), falling back to the local HEAD
+    pub fn default_branch(&self) -> Option<String> {
+        let candidates = [
+            ("refs/remotes/upstream/HEAD", "refs/remotes/upstream/"),
+            ("refs/remotes/origin/HEAD", "refs/remotes/origin/"),
+            ("HEAD", "refs/heads/"),
+        ];
+        for (name, prefix) in candidates {
+            if let Ok(reference) = self.repository.find_reference(name)
+                && let Some(target) = reference.symbolic_target()
+                && let Some(branch) = target.strip_prefix(prefix)
+            {
+                return Some(branch.to_string());
+            }
+        }
+        None
+    }
+
+    /// Worktree of the default branch (or of main or master), which holds the local files
+    /// other worktrees are set up from
+    pub fn main_worktree(&self) -> Option<PathBuf> {
+        let worktrees = self.list_all_worktrees().ok()?;
+        let default_branch = self.default_branch();
+        [default_branch.as_deref(), Some("main"), Some("master")]
+            .into_iter()
+            .flatten()
+            .find_map(|branch| worktrees.iter().find(|worktree| worktree.branch == branch))
+            .map(|worktree| PathBuf::from(&worktree.path))
+    }
+
+    /// Make every worktree ignore a path, through the repository's `info/exclude`, unless it
+    /// is there already
+    pub fn exclude(&self, pattern: &str) -> Result<()> {
+        let exclude = self.repository.path().join("info").join("exclude");
+        let existing = fs::read_to_string(&exclude).unwrap_or_default();
+        if existing.lines().any(|line| line.trim() == pattern) {
+            return Ok(());
+        }
+        fs::create_dir_all(exclude.parent().unwrap())?;
+        let separator = if existing.is_empty() || existing.ends_with('\n') {
+            ""
+        } else {
+            "\n"
+        };
+        fs::write(&exclude, format!("{}{}{}\n", existing, separator, pattern))?;
+        Ok(())
+    }
+
+    /// When the repository was last fetched, from the modification time of FETCH_HEAD
+    pub fn last_fetch_time(&self) -> Option<i64> {
+        let modified = fs::metadata(self.repository.path().join("FETCH_HEAD"))
+            .ok()?
+            .modified()
+            .ok()?;
+        let since_epoch = modified.duration_since(std::time::UNIX_EPOCH).ok()?;
+        Some(since_epoch.as_secs() as i64)
+    }
+
+    /// URL of the upstream (or origin) remote as git would fetch from it
+    /// libgit2 applies `url.<base>.insteadOf` rewrites when looking the remote up,
+    /// so aliases like `gh:owner/repo` come back as the URL they expand to
     pub fn get_upstream_remote_url(&self) -> Result<Option<String>> {
         // Try upstream first, then origin
         for remote_name in &["upstream", "origin"] {
@@ -684,3 +1858,98 @@ impl<T: GitClient> GitRepository<T> {
         Ok(None)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_ssh_remote_urls() {
+        assert!(is_ssh_url("git@github.com:owner/repo.git"));
+        assert!(is_ssh_url("ssh://git@github.com:22/owner/repo"));
+        assert!(is_ssh_url("work-github:owner/repo"));
+        assert!(!is_ssh_url("https://github.com/owner/repo.git"));
+        assert!(!is_ssh_url("file:///srv/git/repo.git"));
+        assert!(!is_ssh_url("/srv/git/repo.git"));
+        assert!(!is_ssh_url("./relative/repo:with-colon"));
+        assert!(!is_ssh_url("C:\\src\\repo"));
+    }
+
+    #[test]
+    fn unmerged_paths_win_over_staged_changes() {
+        let parse = GitRepository::<SystemGitClient>::parse_local_status;
+        assert_eq!(
+            parse("M  src/lib.rs\nUU notes.txt\n"),
+            LocalStatus::Conflicted
+        );
+        assert_eq!(parse("AA new.txt\n"), LocalStatus::Conflicted);
+        assert_eq!(parse("DU gone.txt\n"), LocalStatus::Conflicted);
+        assert_eq!(parse("A  added.txt\n"), LocalStatus::Staged);
+        assert_eq!(parse("?? notes.txt\n"), LocalStatus::Dirty);
+    }
+
+    #[test]
+    fn stash_messages_name_their_branch() {
+        assert_eq!(stash_branch("WIP on main: 1a2b3c4 Fix login"), Some("main"));
+        assert_eq!(
+            stash_branch("On jml/feature: try: again"),
+            Some("jml/feature")
+        );
+        assert_eq!(stash_branch("WIP on (no branch): 1a2b3c4 Bisect"), None);
+        assert_eq!(stash_branch("autostash"), None);
+    }
+
+    #[test]
+    fn fetch_changes_sorts_refs_into_new_updated_and_tags() {
+        let tips = |refs: &[(&str, &str)]| -> HashMap<String, String> {
+            refs.iter()
+                .map(|(name, oid)| (name.to_string(), oid.to_string()))
+                .collect()
+        };
+        let before = tips(&[
+            ("refs/remotes/origin/main", "a"),
+            ("refs/remotes/origin/stable", "s"),
+            ("refs/tags/v1", "t1"),
+        ]);
+        let after = tips(&[
+            ("refs/remotes/origin/main", "b"),
+            ("refs/remotes/origin/stable", "s"),
+            ("refs/remotes/origin/feature", "f"),
+            ("refs/tags/v1", "t1"),
+            ("refs/tags/v2", "t2"),
+        ]);
+
+        let changes = FetchChanges::between(&before, &after, |_, _| 3);
+        assert_eq!(changes.new_branches, vec!["origin/feature"]);
+        assert_eq!(
+            changes.updated_branches,
+            vec![("origin/main".to_string(), 3)]
+        );
+        assert_eq!(changes.new_tags, vec!["v2"]);
+        assert_eq!(
+            changes.summary(),
+            "1 new branch, 1 updated branch, 1 new tag"
+        );
+        assert!(FetchChanges::between(&after, &after, |_, _| 0).is_empty());
+    }
+
+    #[test]
+    fn worktrees_are_not_matched_by_the_file_name_of_other_paths() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let repo = Repository::init(dir.path().join("repo")).unwrap();
+        let signature = git2::Signature::now("gwm", "gwm@example.com").unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+        let feature = dir.path().join("feature");
+        repo.worktree("feature", &feature, None).unwrap();
+
+        let elsewhere = dir.path().join("elsewhere/feature");
+        assert!(find_worktree_by_path(&repo, &elsewhere.to_string_lossy()).is_none());
+        assert!(find_worktree_by_path_or_name(&repo, &elsewhere.to_string_lossy()).is_none());
+        assert!(find_worktree_by_path(&repo, &feature.to_string_lossy()).is_some());
+        assert!(find_worktree_by_path_or_name(&repo, "feature").is_some());
+    }
+}
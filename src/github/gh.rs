@@ -0,0 +1,226 @@
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+use tokio::process::Command;
+use tracing::{debug, info};
+
+use super::{GITHUB_HOST, GitHubRepo, IssueInfo, PrAuthor, PrInfo, pr_status, review_decision};
+
+/// Most pull requests `gh pr list` returns per repository
+const PR_LIMIT: &str = "1000";
+
+/// Forge client that shells out to the gh CLI, which brings its own authentication and
+/// GitHub Enterprise support
+pub struct GhClient {
+    /// Token to hand to gh instead of its own login, from `[[github.tokens]]` or GITHUB_TOKEN
+    token: Option<String>,
+}
+
+impl GhClient {
+    pub fn new(token: Option<String>) -> Self {
+        Self { token }
+    }
+
+    async fn gh(&self, repo: &GitHubRepo, args: &[&str]) -> Result<String> {
+        debug!("gh {}", args.join(" "));
+        let mut command = Command::new("gh");
+        command.args(args).stdin(std::process::Stdio::null());
+        if let Some(token) = &self.token {
+            // gh reads enterprise tokens from a variable of their own
+            let variable = if repo.host == GITHUB_HOST {
+                "GH_TOKEN"
+            } else {
+                "GH_ENTERPRISE_TOKEN"
+            };
+            command.env(variable, token);
+        }
+        let output = command
+            .output()
+            .await
+            .map_err(|e| anyhow!("Failed to run gh: {}", e))?;
+
+        if !output.status.success() {
+            return Err(anyhow!(
+                "gh {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Fetch PRs for a repository by `author`, created (or for anyone's, updated) after
+    /// `since_timestamp`, with the same rules as the API client
+    pub async fn fetch_prs(
+        &self,
+        repo: &GitHubRepo,
+        since_timestamp: i64,
+        author: PrAuthor,
+    ) -> Result<Vec<PrInfo>> {
+        let start_time = std::time::Instant::now();
+
+        let since_date = chrono::DateTime::from_timestamp(since_timestamp, 0)
+            .ok_or_else(|| anyhow!("Invalid timestamp: {}", since_timestamp))?
+            .format("%Y-%m-%d");
+        let search = match author {
+            PrAuthor::Me => format!("author:@me created:>={}", since_date),
+            PrAuthor::Any => format!("updated:>={}", since_date),
+        };
+        let json = self
+            .gh(
+                repo,
+                &[
+                    "pr",
+                    "list",
+                    "--repo",
+                    &repo_arg(repo),
+                    "--state",
+                    "all",
+                    "--limit",
+                    PR_LIMIT,
+                    "--search",
+                    &search,
+                    "--json",
+                    "number,headRefName,state,isDraft,closedAt,url,author,isCrossRepository,reviewDecision",
+                ],
+            )
+            .await?;
+        let prs = parse_pr_list(&json)?;
+
+        let prs = match author {
+            PrAuthor::Me => prs,
+            // Someone else's fork may use the same branch name as a local branch
+            PrAuthor::Any => {
+                let login = self
+                    .gh(
+                        repo,
+                        &["api", "--hostname", &repo.host, "user", "--jq", ".login"],
+                    )
+                    .await?;
+                prs.into_iter()
+                    .filter(|pr| !pr.is_cross_repository || pr.author.login == login.trim())
+                    .collect()
+            }
+        };
+        let prs: Vec<PrInfo> = prs.into_iter().map(GhPullRequest::into_pr_info).collect();
+
+        info!(
+            "gh pr list completed in {:?}, found {} PRs for {}/{}",
+            start_time.elapsed(),
+            prs.len(),
+            repo.owner,
+            repo.repo
+        );
+        Ok(prs)
+    }
+
+    /// Fetch a single issue's title and URL
+    pub async fn fetch_issue(&self, repo: &GitHubRepo, number: u64) -> Result<IssueInfo> {
+        let json = self
+            .gh(
+                repo,
+                &[
+                    "issue",
+                    "view",
+                    &number.to_string(),
+                    "--repo",
+                    &repo_arg(repo),
+                    "--json",
+                    "number,title,url",
+                ],
+            )
+            .await
+            .map_err(|e| anyhow!("Failed to fetch issue #{}: {}", number, e))?;
+        let issue: GhIssue = serde_json::from_str(&json)?;
+        Ok(IssueInfo {
+            number: issue.number,
+            title: issue.title,
+            url: issue.url,
+        })
+    }
+}
+
+/// Repository as gh's `--repo` takes it, with the host so enterprise remotes work
+fn repo_arg(repo: &GitHubRepo) -> String {
+    format!("{}/{}/{}", repo.host, repo.owner, repo.repo)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GhPullRequest {
+    number: u64,
+    head_ref_name: String,
+    /// OPEN, CLOSED or MERGED
+    state: String,
+    is_draft: bool,
+    closed_at: Option<chrono::DateTime<chrono::Utc>>,
+    url: String,
+    author: GhAuthor,
+    is_cross_repository: bool,
+    /// APPROVED, CHANGES_REQUESTED or REVIEW_REQUIRED; empty when no review is required
+    #[serde(default)]
+    review_decision: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GhAuthor {
+    login: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GhIssue {
+    number: u64,
+    title: String,
+    url: String,
+}
+
+impl GhPullRequest {
+    fn into_pr_info(self) -> PrInfo {
+        PrInfo {
+            number: self.number,
+            status: pr_status(&self.state, self.is_draft),
+            head_branch: self.head_ref_name,
+            // gh reports open pull requests as closed at the zero time
+            closed_at: self
+                .closed_at
+                .map(|t| t.timestamp())
+                .filter(|&timestamp| timestamp > 0),
+            url: Some(self.url),
+            review: self.review_decision.as_deref().and_then(review_decision),
+        }
+    }
+}
+
+/// Pure function parsing the output of `gh pr list --json`
+fn parse_pr_list(json: &str) -> Result<Vec<GhPullRequest>> {
+    serde_json::from_str(json).map_err(|e| anyhow!("Unexpected gh pr list output: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::PrStatus;
+
+    #[test]
+    fn parses_gh_pr_list_output() {
+        let json = r#"[
+            {"number": 7, "headRefName": "fix-login", "state": "OPEN", "isDraft": true,
+             "closedAt": "0001-01-01T00:00:00Z", "url": "https://github.com/acme/api/pull/7",
+             "author": {"login": "jml"}, "isCrossRepository": false},
+            {"number": 5, "headRefName": "add-cache", "state": "MERGED", "isDraft": false,
+             "closedAt": "2026-10-01T12:00:00Z", "url": "https://github.com/acme/api/pull/5",
+             "author": {"login": "dependabot"}, "isCrossRepository": false}
+        ]"#;
+
+        let prs: Vec<PrInfo> = parse_pr_list(json)
+            .unwrap()
+            .into_iter()
+            .map(GhPullRequest::into_pr_info)
+            .collect();
+        assert_eq!(prs[0].head_branch, "fix-login");
+        assert_eq!(prs[0].status, PrStatus::Draft);
+        assert_eq!(prs[0].closed_at, None);
+        assert_eq!(prs[1].status, PrStatus::Merged);
+        assert_eq!(prs[1].closed_at, Some(1790856000));
+        assert!(parse_pr_list("not json").is_err());
+    }
+}
@@ -1,148 +1,336 @@
 use anyhow::{Result, anyhow};
 use octocrab::Octocrab;
 use regex::Regex;
+use serde::Deserialize;
 use std::collections::HashMap;
+use std::sync::OnceLock;
+use tracing::{debug, info};
 
-use crate::core::PrStatus;
+use crate::core::{PrStatus, ReviewDecision};
+use crate::forge::Forge;
+
+mod gh;
+
+pub use crate::forge::{PrAuthor, PrInfo};
+pub use gh::GhClient;
 
 /// Represents a GitHub repository (owner and name)
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct GitHubRepo {
+    /// "github.com", or a GitHub Enterprise host
+    pub host: String,
     pub owner: String,
     pub repo: String,
 }
 
-/// Represents PR information for matching with worktrees
+/// Represents the parts of a GitHub issue needed to start work on it
 #[derive(Debug, Clone)]
-pub struct PrInfo {
-    #[allow(dead_code)]
+pub struct IssueInfo {
     pub number: u64,
-    pub head_branch: String,
-    pub status: PrStatus,
+    pub title: String,
+    pub url: String,
 }
 
+/// Host of the public GitHub, where `GITHUB_TOKEN` applies
+pub const GITHUB_HOST: &str = "github.com";
+
 /// Parse a GitHub remote URL to extract owner and repo
 /// Handles both SSH (git@github.com:owner/repo.git) and HTTPS (https://github.com/owner/repo.git) formats
 pub fn parse_github_url(url: &str) -> Result<GitHubRepo> {
-    // SSH format: git@github.com:owner/repo.git
-    let ssh_regex = Regex::new(r"git@github\.com:([^/]+)/(.+?)(?:\.git)?$")?;
-    if let Some(captures) = ssh_regex.captures(url) {
-        return Ok(GitHubRepo {
-            owner: captures[1].to_string(),
-            repo: captures[2].to_string(),
-        });
-    }
+    parse_remote_url(url)
+        .filter(|repo| repo.host == GITHUB_HOST)
+        .ok_or_else(|| anyhow!("Failed to parse GitHub URL: {}", url))
+}
 
+/// Host, owner and repository of a remote URL on any host, in the formats `parse_github_url` takes
+fn parse_remote_url(url: &str) -> Option<GitHubRepo> {
+    // SSH URL format, the usual target of url.<base>.insteadOf rewrites:
+    // ssh://git@github.com/owner/repo.git or ssh://git@github.com:22/owner/repo.git
+    // Checked first so a port number isn't mistaken for the owner
+    let ssh_url_regex =
+        Regex::new(r"^ssh://git@([^/:]+)(?::\d+)?/([^/]+)/(.+?)(?:\.git)?$").ok()?;
+    // SSH format: git@github.com:owner/repo.git
+    let ssh_regex = Regex::new(r"git@([^/:]+):([^/]+)/(.+?)(?:\.git)?$").ok()?;
     // HTTPS format: https://github.com/owner/repo.git or https://github.com/owner/repo
-    let https_regex = Regex::new(r"https://github\.com/([^/]+)/(.+?)(?:\.git)?$")?;
-    if let Some(captures) = https_regex.captures(url) {
-        return Ok(GitHubRepo {
-            owner: captures[1].to_string(),
-            repo: captures[2].to_string(),
-        });
+    let https_regex = Regex::new(r"https://([^/:]+)/([^/]+)/(.+?)(?:\.git)?$").ok()?;
+
+    [ssh_url_regex, ssh_regex, https_regex]
+        .iter()
+        .find_map(|regex| regex.captures(url))
+        .map(|captures| GitHubRepo {
+            host: captures[1].to_string(),
+            owner: captures[2].to_string(),
+            repo: captures[3].to_string(),
+        })
+}
+
+/// A token for the repositories on one host, or of one owner there, as a `[[github.tokens]]`
+/// table in the config file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TokenConfig {
+    /// GitHub host, e.g. "github.example.com" for GitHub Enterprise
+    #[serde(default = "default_host")]
+    pub host: String,
+    /// Organization or user the token is for; unset for every owner on the host
+    pub owner: Option<String>,
+    /// Environment variable holding the token, so it stays out of the config file
+    pub token_env: String,
+}
+
+/// The github.com token the gh CLI is logged in with, asked for once per run
+/// gh keeps it in the system keyring or its hosts.yml, and `gh auth token` reads either
+fn gh_token() -> Option<String> {
+    static TOKEN: OnceLock<Option<String>> = OnceLock::new();
+    TOKEN
+        .get_or_init(|| {
+            debug!("Running gh auth token --hostname {}", GITHUB_HOST);
+            let output = std::process::Command::new("gh")
+                .args(["auth", "token", "--hostname", GITHUB_HOST])
+                .stdin(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .output()
+                .ok()?;
+            output
+                .status
+                .success()
+                .then(|| parse_gh_token(&output.stdout))
+                .flatten()
+        })
+        .clone()
+}
+
+/// Pure function reading the token from `gh auth token` output
+fn parse_gh_token(stdout: &[u8]) -> Option<String> {
+    let token = String::from_utf8_lossy(stdout).trim().to_string();
+    (!token.is_empty()).then_some(token)
+}
+
+fn default_host() -> String {
+    GITHUB_HOST.to_string()
+}
+
+/// Implementation used to talk to GitHub
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeBackend {
+    /// The REST API, through octocrab
+    #[default]
+    Api,
+    /// The gh CLI, for its own login and GitHub Enterprise setup
+    Gh,
+}
+
+/// Forge client for the backend selected at runtime
+pub enum ForgeClient {
+    Api(Box<ApiClient>),
+    Gh(GhClient),
+}
+
+/// Octocrab clients for a host's REST and GraphQL endpoints
+/// GitHub Enterprise serves GraphQL from /api/graphql, outside the REST API's /api/v3
+pub struct ApiClient {
+    rest: Octocrab,
+    graphql: Octocrab,
+}
+
+impl ForgeClient {
+    /// Fetch the PRs for `branches` by `author`, created after `since_timestamp`
+    pub async fn fetch_prs(
+        &self,
+        repo: &GitHubRepo,
+        branches: &[String],
+        since_timestamp: i64,
+        author: PrAuthor,
+    ) -> Result<Vec<PrInfo>> {
+        match self {
+            ForgeClient::Api(client) => {
+                fetch_prs_for_branches(client, repo, branches, since_timestamp, author).await
+            }
+            ForgeClient::Gh(client) => client.fetch_prs(repo, since_timestamp, author).await,
+        }
     }
 
-    Err(anyhow!("Failed to parse GitHub URL: {}", url))
+    /// Fetch a single issue's title and URL
+    pub async fn fetch_issue(&self, repo: &GitHubRepo, number: u64) -> Result<IssueInfo> {
+        match self {
+            ForgeClient::Api(client) => fetch_issue(&client.rest, repo, number).await,
+            ForgeClient::Gh(client) => client.fetch_issue(repo, number).await,
+        }
+    }
 }
 
-/// Fetch PRs for a repository created by the authenticated user
-/// Filters by creation date (PRs created after `since_timestamp`)
-/// Uses GitHub Search API for efficient server-side filtering
-pub async fn fetch_prs_for_repo(
-    github_client: &Octocrab,
-    repo: &GitHubRepo,
-    since_timestamp: i64,
-) -> Result<Vec<PrInfo>> {
-    let start_time = std::time::Instant::now();
+/// A GitHub repository with a client for its host
+pub struct GitHubForge {
+    pub client: ForgeClient,
+    pub repo: GitHubRepo,
+}
 
-    // Convert timestamp to date string for search query
-    let since_date = chrono::DateTime::from_timestamp(since_timestamp, 0)
-        .ok_or_else(|| anyhow!("Invalid timestamp: {}", since_timestamp))?;
-    let date_string = since_date.format("%Y-%m-%d").to_string();
+impl Forge for GitHubForge {
+    async fn fetch_prs(
+        &self,
+        branches: &[String],
+        since_timestamp: i64,
+        author: PrAuthor,
+    ) -> Result<Vec<PrInfo>> {
+        self.client
+            .fetch_prs(&self.repo, branches, since_timestamp, author)
+            .await
+    }
+}
 
-    // Build search query: repo:owner/repo is:pr author:@me created:>=date
-    let query = format!(
-        "repo:{}/{} is:pr author:@me created:>={}",
-        repo.owner, repo.repo, date_string
-    );
+/// Tokens to talk to GitHub with, chosen per repository remote, and the backend to use them with
+/// `GITHUB_TOKEN` serves github.com repositories that no configured token matches
+#[derive(Debug, Clone, Default)]
+pub struct Credentials {
+    tokens: Vec<TokenConfig>,
+    backend: ForgeBackend,
+}
 
-    eprintln!("[GitHub API] Searching PRs with query: {}", query);
+impl Credentials {
+    pub fn new(tokens: Vec<TokenConfig>) -> Self {
+        Self {
+            tokens,
+            backend: ForgeBackend::default(),
+        }
+    }
 
-    let mut page = 1u32;
-    let mut all_prs = Vec::new();
+    pub fn backend(mut self, backend: ForgeBackend) -> Self {
+        self.backend = backend;
+        self
+    }
 
-    loop {
-        eprintln!(
-            "[GitHub API] GET /search/issues?q={}&per_page=100&page={}",
-            urlencoding::encode(&query),
-            page
-        );
+    /// Whether any token is set at all, to fail early before scanning
+    pub fn any_available(&self) -> bool {
+        std::iter::once("GITHUB_TOKEN")
+            .chain(self.tokens.iter().map(|token| token.token_env.as_str()))
+            .any(|name| std::env::var(name).is_ok())
+            || gh_token().is_some()
+    }
 
-        let results = github_client
-            .search()
-            .issues_and_pull_requests(&query)
-            .per_page(100)
-            .page(page)
-            .send()
-            .await?;
+    /// Parse a remote URL on github.com or on a host with a configured token
+    /// gh knows the hosts it is logged in to, so with that backend any host will do
+    pub fn parse_url(&self, url: &str) -> Result<GitHubRepo> {
+        parse_remote_url(url)
+            .filter(|repo| {
+                self.backend == ForgeBackend::Gh
+                    || repo.host == GITHUB_HOST
+                    || self.tokens.iter().any(|t| t.host == repo.host)
+            })
+            .ok_or_else(|| anyhow!("Failed to parse GitHub URL: {}", url))
+    }
 
-        let page_size = results.items.len();
+    /// Pure function naming the environment variable with the token for a repository
+    /// A token for the repository's owner wins over one for its whole host
+    pub fn token_env(&self, repo: &GitHubRepo) -> Option<&str> {
+        let on_host = || self.tokens.iter().filter(|t| t.host == repo.host);
+        on_host()
+            .find(|t| {
+                t.owner
+                    .as_ref()
+                    .is_some_and(|owner| owner.eq_ignore_ascii_case(&repo.owner))
+            })
+            .or_else(|| on_host().find(|t| t.owner.is_none()))
+            .map(|t| t.token_env.as_str())
+            .or((repo.host == GITHUB_HOST).then_some("GITHUB_TOKEN"))
+    }
 
-        if page_size == 0 {
-            break;
+    /// A client authenticated for the repository, talking to its host's API
+    pub fn client_for(&self, repo: &GitHubRepo) -> Result<ForgeClient> {
+        if self.backend == ForgeBackend::Gh {
+            // gh falls back on its own login when no token is set
+            let token = self
+                .token_env(repo)
+                .and_then(|name| std::env::var(name).ok());
+            return Ok(ForgeClient::Gh(GhClient::new(token)));
         }
 
-        eprintln!("[GitHub API] Page {} returned {} results", page, page_size);
-
-        let has_more_pages = page_size >= 100;
-
-        for issue in results.items {
-            // The search API returns issues, but we filtered for is:pr
-            // We need to extract PR-specific information
-            if issue.pull_request.is_some() {
-                // Fetch the full PR to get head branch and other details
-                // Note: issue.pull_request only has url/html_url, not the full PR data
-                // We need to extract PR number from the issue and fetch it
-
-                // Issue number is the same as PR number
-                let pr_number = issue.number;
-
-                // Fetch full PR details
-                let pr = github_client
-                    .pulls(&repo.owner, &repo.repo)
-                    .get(pr_number)
-                    .await?;
-
-                // Determine PR status
-                let status = if pr.merged_at.is_some() {
-                    PrStatus::Merged
-                } else if pr.draft.unwrap_or(false) {
-                    PrStatus::Draft
-                } else if pr.state == Some(octocrab::models::IssueState::Open) {
-                    PrStatus::Open
-                } else {
-                    PrStatus::Closed
-                };
-
-                all_prs.push(PrInfo {
-                    number: pr_number,
-                    head_branch: pr.head.ref_field,
-                    status,
-                });
+        let name = self
+            .token_env(repo)
+            .ok_or_else(|| anyhow!("No token configured for {}", repo.host))?;
+        let token = match std::env::var(name) {
+            Ok(token) => token,
+            // Users of the gh CLI are usually logged in already
+            Err(_) if name == "GITHUB_TOKEN" => gh_token().ok_or_else(|| {
+                anyhow!("GITHUB_TOKEN environment variable not set, and `gh auth token` has none")
+            })?,
+            Err(_) => {
+                return Err(anyhow!(
+                    "{} environment variable not set (needed for {}/{})",
+                    name,
+                    repo.host,
+                    repo.owner
+                ));
             }
-        }
+        };
+
+        let client = |base_uri: Option<String>| -> Result<Octocrab> {
+            let builder = Octocrab::builder().personal_token(token.clone());
+            Ok(match base_uri {
+                Some(base_uri) => builder.base_uri(base_uri)?.build()?,
+                None => builder.build()?,
+            })
+        };
+        let (rest, graphql) = if repo.host == GITHUB_HOST {
+            (client(None)?, client(None)?)
+        } else {
+            (
+                client(Some(format!("https://{}/api/v3", repo.host)))?,
+                client(Some(format!("https://{}/api", repo.host)))?,
+            )
+        };
+        Ok(ForgeClient::Api(Box::new(ApiClient { rest, graphql })))
+    }
+}
+
+/// Branches looked up per GraphQL query, to stay well within GitHub's query cost limits
+const BRANCHES_PER_QUERY: usize = 50;
+
+/// Pull requests per branch to consider, newest first
+const PRS_PER_BRANCH: usize = 10;
+
+/// Fetch the PRs whose head is one of `branches`, with one GraphQL query per 50 branches
+/// Only PRs by `author` count, and only those created (or for anyone's, updated) after
+/// `since_timestamp`, so that a branch name reused long after its PR was merged doesn't look
+/// merged
+pub async fn fetch_prs_for_branches(
+    client: &ApiClient,
+    repo: &GitHubRepo,
+    branches: &[String],
+    since_timestamp: i64,
+    author: PrAuthor,
+) -> Result<Vec<PrInfo>> {
+    let start_time = std::time::Instant::now();
 
-        if !has_more_pages {
-            break;
+    let mut all_prs = Vec::new();
+    for chunk in branches.chunks(BRANCHES_PER_QUERY) {
+        let query = branch_prs_query(chunk.len());
+        let mut variables = serde_json::json!({"owner": repo.owner, "name": repo.repo});
+        for (i, branch) in chunk.iter().enumerate() {
+            variables[format!("b{}", i)] = serde_json::json!(branch);
         }
+        debug!(
+            "POST /graphql for {} branches of {}/{}",
+            chunk.len(),
+            repo.owner,
+            repo.repo
+        );
 
-        page += 1;
+        let response: serde_json::Value = client
+            .graphql
+            .graphql(&serde_json::json!({"query": query, "variables": variables}))
+            .await?;
+        all_prs.extend(parse_branch_prs(
+            &response,
+            chunk.len(),
+            since_timestamp,
+            author,
+        )?);
     }
 
-    let elapsed = start_time.elapsed();
-    eprintln!(
-        "[GitHub API] Search completed in {:?}, found {} PRs for {}/{}",
-        elapsed,
+    info!(
+        "GraphQL lookup completed in {:?}, found {} PRs for {}/{}",
+        start_time.elapsed(),
         all_prs.len(),
         repo.owner,
         repo.repo
@@ -151,17 +339,163 @@ pub async fn fetch_prs_for_repo(
     Ok(all_prs)
 }
 
+/// Pure function building a query with one aliased `pullRequests` lookup per branch, `b0`
+/// to `b<count - 1>`, taking the branch names as variables so they need no escaping
+fn branch_prs_query(count: usize) -> String {
+    let variables: String = (0..count).map(|i| format!(", $b{}: String!", i)).collect();
+    let lookups: String = (0..count)
+        .map(|i| {
+            format!(
+                "b{i}: pullRequests(headRefName: $b{i}, first: {PRS_PER_BRANCH}, \
+                 orderBy: {{field: UPDATED_AT, direction: DESC}}) {{ nodes {{ ...pr }} }}\n"
+            )
+        })
+        .collect();
+    format!(
+        "query($owner: String!, $name: String!{variables}) {{\n\
+         viewer {{ login }}\n\
+         repository(owner: $owner, name: $name) {{\n{lookups}}}\n}}\n\
+         fragment pr on PullRequest {{ number headRefName state isDraft closedAt createdAt \
+         updatedAt url reviewDecision isCrossRepository author {{ login }} }}\n"
+    )
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphQlPullRequest {
+    number: u64,
+    head_ref_name: String,
+    /// OPEN, CLOSED or MERGED
+    state: String,
+    is_draft: bool,
+    closed_at: Option<chrono::DateTime<chrono::Utc>>,
+    created_at: chrono::DateTime<chrono::Utc>,
+    updated_at: chrono::DateTime<chrono::Utc>,
+    url: String,
+    review_decision: Option<String>,
+    is_cross_repository: bool,
+    /// Missing for deleted accounts
+    author: Option<GraphQlAuthor>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphQlAuthor {
+    login: String,
+}
+
+/// Pure function reading the PRs out of a `branch_prs_query` response, keeping those by
+/// `author` from after `since_timestamp`, newest first per branch
+fn parse_branch_prs(
+    response: &serde_json::Value,
+    count: usize,
+    since_timestamp: i64,
+    author: PrAuthor,
+) -> Result<Vec<PrInfo>> {
+    if let Some(message) = response["errors"][0]["message"].as_str() {
+        return Err(anyhow!("GitHub GraphQL query failed: {}", message));
+    }
+    let data = &response["data"];
+    let viewer = data["viewer"]["login"].as_str().unwrap_or_default();
+
+    let mut prs = Vec::new();
+    for i in 0..count {
+        let nodes = &data["repository"][format!("b{}", i)]["nodes"];
+        let nodes: Vec<GraphQlPullRequest> = serde_json::from_value(nodes.clone())
+            .map_err(|e| anyhow!("Unexpected GraphQL response: {}", e))?;
+        for pr in nodes {
+            let by_viewer = pr.author.as_ref().is_some_and(|a| a.login == viewer);
+            let keep = match author {
+                PrAuthor::Me => by_viewer && pr.created_at.timestamp() >= since_timestamp,
+                // Someone else's fork may use the same branch name as a local branch
+                PrAuthor::Any => {
+                    (by_viewer || !pr.is_cross_repository)
+                        && pr.updated_at.timestamp() >= since_timestamp
+                }
+            };
+            if keep {
+                prs.push(pr.into_pr_info());
+            }
+        }
+    }
+    Ok(prs)
+}
+
+impl GraphQlPullRequest {
+    fn into_pr_info(self) -> PrInfo {
+        PrInfo {
+            number: self.number,
+            status: pr_status(&self.state, self.is_draft),
+            head_branch: self.head_ref_name,
+            closed_at: self.closed_at.map(|t| t.timestamp()),
+            url: Some(self.url),
+            review: self.review_decision.as_deref().and_then(review_decision),
+        }
+    }
+}
+
+/// PR status from GitHub's state name (OPEN, CLOSED or MERGED) and draft flag
+fn pr_status(state: &str, is_draft: bool) -> PrStatus {
+    match state {
+        "MERGED" => PrStatus::Merged,
+        "OPEN" if is_draft => PrStatus::Draft,
+        "OPEN" => PrStatus::Open,
+        _ => PrStatus::Closed,
+    }
+}
+
+/// Review decision from GitHub's name for it, e.g. CHANGES_REQUESTED
+fn review_decision(name: &str) -> Option<ReviewDecision> {
+    match name {
+        "APPROVED" => Some(ReviewDecision::Approved),
+        "CHANGES_REQUESTED" => Some(ReviewDecision::ChangesRequested),
+        "REVIEW_REQUIRED" => Some(ReviewDecision::ReviewRequired),
+        _ => None,
+    }
+}
+
+/// Fetch a single issue's title and URL
+pub async fn fetch_issue(
+    github_client: &Octocrab,
+    repo: &GitHubRepo,
+    number: u64,
+) -> Result<IssueInfo> {
+    debug!("GET /repos/{}/{}/issues/{}", repo.owner, repo.repo, number);
+
+    let issue = github_client
+        .issues(&repo.owner, &repo.repo)
+        .get(number)
+        .await
+        .map_err(|e| anyhow!("Failed to fetch issue #{}: {}", number, e))?;
+
+    Ok(IssueInfo {
+        number,
+        title: issue.title,
+        url: issue.html_url.to_string(),
+    })
+}
+
 /// Match worktree branches to PRs using exact branch name matching
 pub fn match_worktrees_to_prs(
     worktree_branches: &[String],
     prs: &[PrInfo],
 ) -> HashMap<String, PrStatus> {
+    match_worktrees_to_pr_info(worktree_branches, prs)
+        .into_iter()
+        .map(|(branch, pr)| (branch, pr.status))
+        .collect()
+}
+
+/// Match worktree branches to PRs like `match_worktrees_to_prs`, keeping the whole PR
+pub fn match_worktrees_to_pr_info(
+    worktree_branches: &[String],
+    prs: &[PrInfo],
+) -> HashMap<String, PrInfo> {
     let mut matches = HashMap::new();
 
     for branch in worktree_branches {
         for pr in prs {
             if branch == &pr.head_branch {
-                matches.insert(branch.clone(), pr.status.clone());
+                matches.insert(branch.clone(), pr.clone());
                 break;
             }
         }
@@ -206,6 +540,116 @@ mod tests {
         assert_eq!(repo.repo, "git-worktree-manager");
     }
 
+    #[test]
+    fn parses_ssh_scheme_github_url() {
+        for url in [
+            "ssh://git@github.com/jml/git-worktree-manager.git",
+            "ssh://git@github.com:22/jml/git-worktree-manager",
+        ] {
+            let repo = parse_github_url(url).unwrap();
+            assert_eq!(repo.owner, "jml");
+            assert_eq!(repo.repo, "git-worktree-manager");
+        }
+    }
+
+    #[test]
+    fn other_hosts_need_a_configured_token() {
+        let url = "git@github.example.com:acme/api.git";
+        assert!(parse_github_url(url).is_err());
+        assert!(Credentials::default().parse_url(url).is_err());
+
+        let credentials = Credentials::new(vec![TokenConfig {
+            host: "github.example.com".to_string(),
+            owner: None,
+            token_env: "WORK_TOKEN".to_string(),
+        }]);
+        let repo = credentials.parse_url(url).unwrap();
+        assert_eq!(repo.host, "github.example.com");
+        assert!(
+            Credentials::default()
+                .backend(ForgeBackend::Gh)
+                .parse_url(url)
+                .is_ok()
+        );
+        assert_eq!(repo.owner, "acme");
+        assert_eq!(credentials.token_env(&repo), Some("WORK_TOKEN"));
+    }
+
+    #[test]
+    fn owner_tokens_win_over_host_tokens_and_github_token() {
+        let token = |owner: Option<&str>, env: &str| TokenConfig {
+            host: GITHUB_HOST.to_string(),
+            owner: owner.map(str::to_string),
+            token_env: env.to_string(),
+        };
+        let repo =
+            |owner: &str| parse_github_url(&format!("https://github.com/{}/x", owner)).unwrap();
+
+        let credentials = Credentials::new(vec![token(None, "ANY"), token(Some("Acme"), "ACME")]);
+        assert_eq!(credentials.token_env(&repo("acme")), Some("ACME"));
+        assert_eq!(credentials.token_env(&repo("jml")), Some("ANY"));
+
+        let credentials = Credentials::new(vec![token(Some("acme"), "ACME")]);
+        assert_eq!(credentials.token_env(&repo("jml")), Some("GITHUB_TOKEN"));
+    }
+
+    #[test]
+    fn reads_the_token_gh_prints() {
+        assert_eq!(
+            parse_gh_token(b"gho_abc123\n").as_deref(),
+            Some("gho_abc123")
+        );
+        assert_eq!(parse_gh_token(b"\n"), None);
+    }
+
+    #[test]
+    fn branch_query_looks_each_branch_up_by_variable() {
+        let query = branch_prs_query(2);
+        assert!(query.contains("$b0: String!, $b1: String!"));
+        assert!(query.contains("b1: pullRequests(headRefName: $b1"));
+        assert!(!query.contains("$b2"));
+    }
+
+    #[test]
+    fn branch_prs_keep_the_authors_asked_for() {
+        let pr = |number: u64, login: &str, cross: bool, date: &str| {
+            serde_json::json!({
+                "number": number, "headRefName": "fix-login", "state": "OPEN", "isDraft": false,
+                "closedAt": null, "createdAt": date, "updatedAt": date,
+                "url": format!("https://github.com/acme/api/pull/{}", number),
+                "reviewDecision": "APPROVED", "isCrossRepository": cross,
+                "author": {"login": login}
+            })
+        };
+        let response = serde_json::json!({"data": {
+            "viewer": {"login": "jml"},
+            "repository": {"b0": {"nodes": [
+                pr(3, "teammate", false, "2026-10-05T00:00:00Z"),
+                pr(2, "stranger", true, "2026-10-04T00:00:00Z"),
+                pr(1, "jml", false, "2020-01-01T00:00:00Z"),
+            ]}}
+        }});
+        let since = 1790000000; // September 2026
+        let numbers = |author| -> Vec<u64> {
+            parse_branch_prs(&response, 1, since, author)
+                .unwrap()
+                .iter()
+                .map(|pr| pr.number)
+                .collect()
+        };
+
+        // The old PR from a reused branch name is too old to count
+        assert_eq!(numbers(PrAuthor::Me), Vec::<u64>::new());
+        // Only the teammate's PR; the stranger's comes from a fork
+        assert_eq!(numbers(PrAuthor::Any), [3]);
+
+        let prs = parse_branch_prs(&response, 1, since, PrAuthor::Any).unwrap();
+        assert_eq!(prs[0].review, Some(ReviewDecision::Approved));
+
+        let failed = serde_json::json!({"errors": [{"message": "Bad credentials"}]});
+        assert!(parse_branch_prs(&failed, 1, since, PrAuthor::Me).is_err());
+    }
+
     #[test]
     fn matches_worktrees_to_prs_exact_match() {
         let branches = vec!["feature-1".to_string(), "feature-2".to_string()];
@@ -214,11 +658,17 @@ mod tests {
                 number: 1,
                 head_branch: "feature-1".to_string(),
                 status: PrStatus::Open,
+                closed_at: None,
+                url: None,
+                review: None,
             },
             PrInfo {
                 number: 2,
                 head_branch: "feature-3".to_string(),
                 status: PrStatus::Draft,
+                closed_at: None,
+                url: None,
+                review: None,
             },
         ];
 
@@ -0,0 +1,331 @@
+use anyhow::{Context, Result, anyhow};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+use crate::config::{JumpTool, LinkMode};
+use crate::trash;
+
+/// Run a repository's teardown command before its worktree is removed
+/// The command runs through `sh -c` inside the worktree, with GWM_REPO, GWM_BRANCH and
+/// GWM_WORKTREE_PATH set, and its output goes to stderr so stdout stays clean for scripts
+pub fn run_teardown(command: &str, repo: &str, branch: &str, worktree_path: &Path) -> Result<()> {
+    run("Teardown", command, repo, branch, worktree_path)
+}
+
+/// Run a setup command (e.g. "pnpm install") in a worktree that was just created, the same way
+/// as [`run_teardown`]
+pub fn run_setup(command: &str, repo: &str, branch: &str, worktree_path: &Path) -> Result<()> {
+    run("Setup", command, repo, branch, worktree_path)
+}
+
+/// Copy untracked files such as `.env` from one worktree into a new one, by path relative to
+/// the worktree; entries missing from `from` or already in `to` are skipped
+/// Returns the entries copied
+pub fn copy_files(paths: &[String], from: &Path, to: &Path) -> Result<Vec<String>> {
+    let mut copied = Vec::new();
+    for path in paths {
+        let relative = inside_worktree(path, "copy_on_add")?;
+        let source = from.join(relative);
+        let target = to.join(relative);
+        if fs::symlink_metadata(&source).is_err() || fs::symlink_metadata(&target).is_ok() {
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        trash::copy_path(&source, &target).with_context(|| format!("Failed to copy '{}'", path))?;
+        copied.push(path.clone());
+    }
+    Ok(copied)
+}
+
+/// Share directories such as `node_modules` from `from` (the main worktree or a cache) with a
+/// new worktree, by path relative to the worktree; entries already in `to` are skipped, and
+/// missing ones too unless `create_missing`, which starts them empty for the first install
+/// Returns the entries linked
+pub fn link_dirs(
+    paths: &[String],
+    from: &Path,
+    to: &Path,
+    mode: LinkMode,
+    create_missing: bool,
+) -> Result<Vec<String>> {
+    let mut linked = Vec::new();
+    for path in paths {
+        let relative = inside_worktree(path, "link_on_add")?;
+        let source = from.join(relative);
+        let target = to.join(relative);
+        if fs::symlink_metadata(&target).is_ok() {
+            continue;
+        }
+        if !source.exists() {
+            if !create_missing {
+                continue;
+            }
+            fs::create_dir_all(&source)?;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        match mode {
+            LinkMode::Symlink => symlink_dir(&fs::canonicalize(&source)?, &target),
+            LinkMode::Hardlink => hard_link_tree(&source, &target),
+        }
+        .with_context(|| format!("Failed to link '{}'", path))?;
+        linked.push(path.clone());
+    }
+    Ok(linked)
+}
+
+/// Trust a worktree's `.envrc` with `direnv allow`; `false` when it has none
+pub fn direnv_allow(worktree_path: &Path) -> Result<bool> {
+    direnv("allow", worktree_path)
+}
+
+/// Take back the trust given to a worktree's `.envrc` with `direnv deny`, before the worktree
+/// is removed; `false` when it has none
+pub fn direnv_deny(worktree_path: &Path) -> Result<bool> {
+    direnv("deny", worktree_path)
+}
+
+fn direnv(action: &str, worktree_path: &Path) -> Result<bool> {
+    let envrc = worktree_path.join(".envrc");
+    if !envrc.is_file() {
+        return Ok(false);
+    }
+    // direnv keys its allow list by absolute path
+    let envrc = fs::canonicalize(&envrc)?;
+    let output = Command::new("direnv")
+        .arg(action)
+        .arg(&envrc)
+        .output()
+        .with_context(|| format!("Failed to run direnv {}", action))?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "direnv {} failed: {}",
+            action,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(true)
+}
+
+/// Add directories to a directory jumper's database, so `z <branch>` lands in them
+pub fn jump_add(tool: JumpTool, paths: &[PathBuf]) -> Result<()> {
+    if paths.is_empty() {
+        return Ok(());
+    }
+    match tool {
+        JumpTool::Zoxide => jumper("zoxide", &["add"], paths),
+        JumpTool::Autojump => paths
+            .iter()
+            .try_for_each(|path| jumper("autojump", &["--add"], std::slice::from_ref(path))),
+    }
+}
+
+/// Drop a removed directory from a directory jumper's database
+/// autojump can only purge every directory that no longer exists, so call this after removal
+pub fn jump_remove(tool: JumpTool, path: &Path) -> Result<()> {
+    match tool {
+        JumpTool::Zoxide => match jumper("zoxide", &["remove"], &[path.to_path_buf()]) {
+            // Never visited, or already forgotten
+            Err(e) if e.to_string().contains("not found") => Ok(()),
+            result => result,
+        },
+        JumpTool::Autojump => jumper("autojump", &["--purge"], &[]),
+    }
+}
+
+fn jumper(program: &str, args: &[&str], paths: &[PathBuf]) -> Result<()> {
+    let output = Command::new(program)
+        .args(args)
+        .args(paths)
+        .output()
+        .with_context(|| format!("Failed to run {}", program))?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "{} {} failed: {}",
+            program,
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// A config path as a path relative to the worktree, refusing ones that would leave it
+fn inside_worktree<'a>(path: &'a str, key: &str) -> Result<&'a Path> {
+    let relative = Path::new(path);
+    if relative.is_absolute()
+        || relative
+            .components()
+            .any(|component| component == Component::ParentDir)
+    {
+        return Err(anyhow!(
+            "'{}' is not a path inside the worktree; {} takes relative paths",
+            path,
+            key
+        ));
+    }
+    Ok(relative)
+}
+
+fn symlink_dir(source: &Path, target: &Path) -> Result<()> {
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(source, target)?;
+    #[cfg(windows)]
+    std::os::windows::fs::symlink_dir(source, target)?;
+    Ok(())
+}
+
+/// Recreate a directory tree with every file hard linked to the original
+fn hard_link_tree(from: &Path, to: &Path) -> Result<()> {
+    let file_type = fs::symlink_metadata(from)?.file_type();
+    if file_type.is_dir() {
+        fs::create_dir_all(to)?;
+        for item in fs::read_dir(from)? {
+            let item = item?;
+            hard_link_tree(&item.path(), &to.join(item.file_name()))?;
+        }
+    } else if file_type.is_symlink() {
+        trash::copy_path(from, to)?;
+    } else {
+        fs::hard_link(from, to)?;
+    }
+    Ok(())
+}
+
+fn run(kind: &str, command: &str, repo: &str, branch: &str, worktree_path: &Path) -> Result<()> {
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .current_dir(worktree_path)
+        .env("GWM_REPO", repo)
+        .env("GWM_BRANCH", branch)
+        .env("GWM_WORKTREE_PATH", worktree_path)
+        .stdout(std::io::stderr())
+        .status()
+        .with_context(|| {
+            format!(
+                "Failed to run {} command '{}'",
+                kind.to_lowercase(),
+                command
+            )
+        })?;
+
+    if !status.success() {
+        return Err(anyhow!(
+            "{} command '{}' for {}/{} failed ({})",
+            kind,
+            command,
+            repo,
+            branch,
+            status
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn teardown_runs_in_worktree_with_context() {
+        let dir = TempDir::new().unwrap();
+        run_teardown(
+            "echo \"$GWM_REPO/$GWM_BRANCH\" > teardown.txt",
+            "api",
+            "fix-auth",
+            dir.path(),
+        )
+        .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("teardown.txt")).unwrap(),
+            "api/fix-auth\n"
+        );
+
+        let error = run_teardown("exit 3", "api", "fix-auth", dir.path()).unwrap_err();
+        assert!(error.to_string().contains("api/fix-auth failed"));
+    }
+
+    #[test]
+    fn setup_failure_names_the_command() {
+        let dir = TempDir::new().unwrap();
+        let error = run_setup("false", "web", "new-nav", dir.path()).unwrap_err();
+        assert!(error.to_string().starts_with("Setup command 'false'"));
+    }
+
+    #[test]
+    fn copies_local_files_without_overwriting() {
+        let main = TempDir::new().unwrap();
+        let new = TempDir::new().unwrap();
+        fs::write(main.path().join(".env"), "SECRET=1\n").unwrap();
+        fs::create_dir_all(main.path().join("config").join("local")).unwrap();
+        fs::write(main.path().join("config/local/settings.json"), "{}").unwrap();
+        fs::write(main.path().join("README.md"), "main copy").unwrap();
+        fs::write(new.path().join("README.md"), "checked out").unwrap();
+
+        let paths: Vec<String> = [".env", "config/local", "README.md", ".envrc"]
+            .map(String::from)
+            .to_vec();
+        let copied = copy_files(&paths, main.path(), new.path()).unwrap();
+        assert_eq!(copied, [".env", "config/local"]);
+        assert_eq!(
+            fs::read_to_string(new.path().join(".env")).unwrap(),
+            "SECRET=1\n"
+        );
+        assert!(new.path().join("config/local/settings.json").exists());
+        assert_eq!(
+            fs::read_to_string(new.path().join("README.md")).unwrap(),
+            "checked out"
+        );
+
+        let escape = ["../outside".to_string()];
+        assert!(copy_files(&escape, main.path(), new.path()).is_err());
+    }
+
+    #[test]
+    fn links_shared_directories() {
+        let cache = TempDir::new().unwrap();
+        let new = TempDir::new().unwrap();
+        fs::create_dir_all(cache.path().join("node_modules").join("left-pad")).unwrap();
+        fs::write(cache.path().join("node_modules/left-pad/index.js"), "pad").unwrap();
+
+        let paths = ["node_modules".to_string(), ".venv".to_string()];
+        let linked = link_dirs(&paths, cache.path(), new.path(), LinkMode::Symlink, true).unwrap();
+        assert_eq!(linked, paths);
+        assert!(
+            fs::symlink_metadata(new.path().join("node_modules"))
+                .unwrap()
+                .is_symlink()
+        );
+        assert!(new.path().join("node_modules/left-pad/index.js").exists());
+        // A missing cache entry starts out empty, so the first install fills it for everyone
+        assert!(cache.path().join(".venv").is_dir());
+
+        let other = TempDir::new().unwrap();
+        let linked = link_dirs(
+            &paths,
+            cache.path(),
+            other.path(),
+            LinkMode::Hardlink,
+            false,
+        );
+        assert_eq!(linked.unwrap(), paths);
+        let file = other.path().join("node_modules/left-pad/index.js");
+        assert!(
+            !fs::symlink_metadata(other.path().join("node_modules"))
+                .unwrap()
+                .is_symlink()
+        );
+        assert_eq!(fs::read_to_string(&file).unwrap(), "pad");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            assert_eq!(fs::metadata(&file).unwrap().nlink(), 2);
+        }
+    }
+}
@@ -1,4 +1,57 @@
-// Only expose the modules we need for testing
+//! The worktree status engine behind the `gwm` command line tool
+//!
+//! Other tools (TUIs, editor plugins, prompts) can reuse it to discover repositories
+//! laid out the gwm way and compute the status of their work-in-progress worktrees:
+//!
+//! ```no_run
+//! use std::path::Path;
+//! use gwm::scan::{ScanOptions, scan};
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let repos = scan(Path::new("."), &ScanOptions::new().pr_status(false)).await?;
+//! for repo in repos {
+//!     for worktree in repo.worktrees {
+//!         println!("{} {} {:?}", repo.name, worktree.branch, worktree.status.local_status);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! - [`scan`]: repository discovery and status computation
+//! - [`core`]: result types and filters
+//! - [`git`]: git access through interchangeable backends
+//! - [`forge`]: the code hosts pull requests are looked up on
+//! - [`github`]: pull request lookup and branch matching on GitHub
+//! - [`bitbucket`]: pull request lookup on Bitbucket Cloud and Server
+//! - [`azure_devops`]: pull request lookup on Azure DevOps
+
+pub mod azure_devops;
+pub mod bitbucket;
 pub mod core;
+pub mod forge;
 pub mod git;
 pub mod github;
+pub mod scan;
+
+// Support modules for the gwm binary, not part of the stable API
+#[doc(hidden)]
+pub mod cache;
+#[doc(hidden)]
+pub mod config;
+#[doc(hidden)]
+pub mod hooks;
+#[doc(hidden)]
+pub mod notify;
+#[doc(hidden)]
+pub mod output;
+#[doc(hidden)]
+pub mod recent;
+#[doc(hidden)]
+pub mod snapshot;
+#[doc(hidden)]
+pub mod state;
+#[doc(hidden)]
+pub mod trash;
+#[doc(hidden)]
+pub mod usage;
@@ -1,21 +1,41 @@
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{ArgAction, CommandFactory, FromArgMatches, Parser, Subcommand};
+use clap_complete::CompleteEnv;
+use std::io::IsTerminal;
+use tracing::warn;
+use tracing_subscriber::EnvFilter;
 
 mod commands;
-mod core;
-mod git;
-mod github;
-mod output;
 
+use gwm::config::Config;
+use gwm::output::{self, ColorChoice};
+
+use commands::ExitStatus;
 use commands::add::AddCommand;
+use commands::adopt::AdoptCommand;
 use commands::complete_branches::CompleteBranchesCommand;
 use commands::complete_repos::CompleteReposCommand;
-use commands::completion::CompletionCommand;
+use commands::completion::{COMPLETE_VAR, CompletionCommand};
+use commands::daemon::DaemonCommand;
+use commands::du::DuCommand;
+use commands::env::EnvCommand;
+use commands::export_paths::ExportPathsCommand;
 use commands::gc::GcCommand;
+use commands::info::InfoCommand;
 use commands::list::ListCommand;
+use commands::lock::LockCommand;
+use commands::prune_metadata::PruneMetadataCommand;
+use commands::recent::RecentCommand;
 use commands::remove::RemoveCommand;
+use commands::repair::RepairCommand;
+use commands::report::ReportCommand;
+#[cfg(unix)]
+use commands::serve::ServeCommand;
+use commands::stats::StatsCommand;
 use commands::switch::SwitchCommand;
 use commands::sync::SyncCommand;
+use commands::undo::UndoCommand;
+use commands::unlock::UnlockCommand;
 
 #[derive(Parser)]
 #[command(name = "git-worktree-manager")]
@@ -25,6 +45,24 @@ pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Commands>,
 
+    /// Log progress to stderr; repeat for more detail (-vv GitHub API calls, -vvv everything)
+    ///
+    /// GWM_LOG takes a tracing filter instead, e.g. GWM_LOG=gwm::github=debug
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    pub verbose: u8,
+
+    /// Color output: auto (default) colors terminals unless NO_COLOR is set
+    #[arg(long, value_enum, value_name = "WHEN", global = true, default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+
+    /// Print only essential results and errors, without headers, emoji or progress
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Print long output directly instead of through $PAGER (less -R by default)
+    #[arg(long, global = true)]
+    pub no_pager: bool,
+
     #[command(flatten)]
     pub list: ListCommand,
 }
@@ -37,18 +75,64 @@ pub enum Commands {
     /// Add a new worktree branch
     #[command(name = "add")]
     Add(AddCommand),
+    /// Turn a checkout cloned outside gwm into a worktree of its repository
+    #[command(name = "adopt")]
+    Adopt(AdoptCommand),
     /// Remove a specific worktree branch
     #[command(name = "remove")]
     Remove(RemoveCommand),
     /// Remove worktrees that are clean/missing and have merged PRs
     #[command(name = "gc")]
     Gc(GcCommand),
+    /// Write the status of every worktree as a standalone HTML page
+    #[command(name = "report")]
+    Report(ReportCommand),
+    /// Show one repository in detail: remotes, default branch, worktrees, disk usage
+    #[command(name = "info")]
+    Info(InfoCommand),
+    /// Show the disk space used by each worktree, largest first
+    #[command(name = "du")]
+    Du(DuCommand),
+    /// Print the repository, branch and status of the current worktree for shell prompts
+    #[command(name = "env")]
+    Env(EnvCommand),
+    /// Print every worktree directory, or add them all to zoxide or autojump
+    #[command(name = "export-paths")]
+    ExportPaths(ExportPathsCommand),
     /// Switch to a worktree directory
     #[command(name = "switch")]
     Switch(SwitchCommand),
+    /// List recently visited worktrees, newest first
+    #[command(name = "recent")]
+    Recent(RecentCommand),
+    /// Lock a worktree so that gwm and git won't remove or prune it
+    #[command(name = "lock")]
+    Lock(LockCommand),
+    /// Unlock a worktree locked with `gwm lock` or `git worktree lock`
+    #[command(name = "unlock")]
+    Unlock(UnlockCommand),
+    /// Delete leftover .git/worktrees entries of worktree directories deleted by hand
+    #[command(name = "prune-metadata")]
+    PruneMetadata(PruneMetadataCommand),
+    /// Reconnect worktrees with their repositories after moving the repositories directory
+    #[command(name = "repair")]
+    Repair(RepairCommand),
+    /// Restore the worktree most recently moved to the trash by `remove --trash` or `gc --trash`
+    #[command(name = "undo")]
+    Undo(UndoCommand),
     /// Fetch remotes for all repositories in parallel
     #[command(name = "sync")]
     Sync(SyncCommand),
+    /// Periodically fetch remotes and refresh status in the background, for a fast `list`
+    #[command(name = "daemon")]
+    Daemon(DaemonCommand),
+    /// Answer JSON-RPC requests from editor plugins on a local Unix socket
+    #[cfg(unix)]
+    #[command(name = "serve")]
+    Serve(ServeCommand),
+    /// Show local usage statistics
+    #[command(name = "stats")]
+    Stats(StatsCommand),
     /// Generate shell completions
     #[command(name = "completion")]
     Completion(CompletionCommand),
@@ -62,18 +146,204 @@ pub enum Commands {
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let cli = Cli::parse();
+    // Answer dynamic shell completion requests before any normal output
+    CompleteEnv::with_factory(Cli::command)
+        .var(COMPLETE_VAR)
+        .bin("gwm")
+        .complete();
+
+    // A broken config file mustn't break --help, --version or completion
+    let (config, load_error) = match Config::load() {
+        Ok(config) => (config, None),
+        Err(e) => (
+            Config::default(),
+            Some(format!("Ignoring [defaults]. {:#}", e)),
+        ),
+    };
+    let (command, ignored) = with_config_defaults(Cli::command(), &config);
+    let cli = Cli::from_arg_matches(&command.get_matches()).unwrap_or_else(|e| e.exit());
+    init_logging(cli.verbose, cli.quiet);
+    // Logging needs the parsed flags, so config problems are only reported now
+    for message in load_error.iter().chain(&ignored) {
+        warn!("{}", message);
+    }
+    output::set_quiet(cli.quiet);
+    output::set_color(cli.color);
+    output::set_pager(!cli.no_pager);
 
-    match cli.command {
+    let result = match cli.command {
         Some(Commands::List(cmd)) => cmd.execute().await,
         Some(Commands::Add(cmd)) => cmd.execute().await,
+        Some(Commands::Adopt(cmd)) => cmd.execute().await,
         Some(Commands::Remove(cmd)) => cmd.execute().await,
         Some(Commands::Gc(cmd)) => cmd.execute().await,
+        Some(Commands::Report(cmd)) => cmd.execute().await,
+        Some(Commands::Info(cmd)) => cmd.execute().await,
+        Some(Commands::Du(cmd)) => cmd.execute().await,
+        Some(Commands::Env(cmd)) => cmd.execute().await,
+        Some(Commands::ExportPaths(cmd)) => cmd.execute().await,
         Some(Commands::Switch(cmd)) => cmd.execute().await,
+        Some(Commands::Recent(cmd)) => cmd.execute().await,
+        Some(Commands::Lock(cmd)) => cmd.execute().await,
+        Some(Commands::Unlock(cmd)) => cmd.execute().await,
+        Some(Commands::PruneMetadata(cmd)) => cmd.execute().await,
+        Some(Commands::Repair(cmd)) => cmd.execute().await,
+        Some(Commands::Undo(cmd)) => cmd.execute().await,
         Some(Commands::Sync(cmd)) => cmd.execute().await,
+        Some(Commands::Daemon(cmd)) => cmd.execute().await,
+        #[cfg(unix)]
+        Some(Commands::Serve(cmd)) => cmd.execute().await,
+        Some(Commands::Stats(cmd)) => cmd.execute().await,
         Some(Commands::Completion(cmd)) => cmd.execute().await,
         Some(Commands::CompleteRepos(cmd)) => cmd.execute().await,
         Some(Commands::CompleteBranches(cmd)) => cmd.execute().await,
         None => cli.list.execute().await,
+    };
+    match result {
+        Err(e) => match e.downcast_ref::<ExitStatus>() {
+            Some(ExitStatus(code)) => std::process::exit(*code),
+            None => Err(e),
+        },
+        Ok(()) => Ok(()),
+    }
+}
+
+/// Make `[defaults.<command>]` from the config file the default values of those commands' flags,
+/// so flags given on the command line (or through their environment variables) still win
+/// `list` defaults also apply to plain `gwm`, which lists without a subcommand
+/// On/off flags given a default become `--flag[=true|false]`, so they can still be switched off
+/// Unknown commands and flags are skipped and returned as messages to warn about
+fn with_config_defaults(
+    mut command: clap::Command,
+    config: &Config,
+) -> (clap::Command, Vec<String>) {
+    let mut ignored = Vec::new();
+    for (name, defaults) in &config.defaults {
+        let Some(subcommand) = command.find_subcommand(name) else {
+            ignored.push(format!(
+                "Ignoring [defaults.{}]: there is no `gwm {}` command",
+                name, name
+            ));
+            continue;
+        };
+        let mut flags = Vec::new();
+        for (flag, value) in defaults {
+            let id = flag.replace('-', "_");
+            let Some(arg) = subcommand
+                .get_arguments()
+                .find(|arg| arg.get_id() == id.as_str())
+            else {
+                ignored.push(format!(
+                    "Ignoring '{}' in [defaults.{}]: `gwm {}` has no such flag",
+                    flag, name, name
+                ));
+                continue;
+            };
+            let switch = matches!(arg.get_action(), ArgAction::SetTrue);
+            flags.push((id, value.values(), switch));
+        }
+
+        for (id, values, switch) in flags {
+            let with_default = |arg: clap::Arg| {
+                let arg = arg.default_values(values.clone());
+                match switch {
+                    true => arg
+                        .action(ArgAction::Set)
+                        .num_args(0..=1)
+                        .require_equals(true)
+                        .default_missing_value("true"),
+                    false => arg,
+                }
+            };
+            if name == "list" {
+                command = command.mut_arg(&id, with_default);
+            }
+            command =
+                command.mut_subcommand(name, |subcommand| subcommand.mut_arg(&id, with_default));
+        }
+    }
+    (command, ignored)
+}
+
+/// Send diagnostics to stderr, filtered by GWM_LOG or else by the -v count
+/// Below -vvv only gwm's own events are shown, not those of its dependencies
+fn init_logging(verbose: u8, quiet: bool) {
+    let filter = EnvFilter::try_from_env("GWM_LOG").unwrap_or_else(|_| {
+        EnvFilter::new(match verbose {
+            _ if quiet => "gwm=error",
+            0 => "gwm=warn",
+            1 => "gwm=info",
+            2 => "gwm=debug",
+            _ => "trace",
+        })
+    });
+
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .with_ansi(std::io::stderr().is_terminal())
+        .without_time()
+        .init();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_defaults_apply_until_overridden() {
+        let config =
+            Config::parse("[defaults.sync]\nretries = 5\n[defaults.list]\nall = true").unwrap();
+        let (command, _) = with_config_defaults(Cli::command(), &config);
+
+        let matches = command.clone().get_matches_from(["gwm", "sync"]);
+        let sync = matches.subcommand_matches("sync").unwrap();
+        assert_eq!(sync.get_one::<u32>("retries"), Some(&5));
+
+        let matches = command
+            .clone()
+            .get_matches_from(["gwm", "sync", "--retries", "1"]);
+        let sync = matches.subcommand_matches("sync").unwrap();
+        assert_eq!(sync.get_one::<u32>("retries"), Some(&1));
+
+        let matches = command.clone().get_matches_from(["gwm"]);
+        assert_eq!(matches.get_one::<bool>("all"), Some(&true));
+
+        // On/off flags switched on by default can be switched off again
+        let matches = command.clone().get_matches_from(["gwm", "--all=false"]);
+        assert_eq!(matches.get_one::<bool>("all"), Some(&false));
+        let matches = command.get_matches_from(["gwm", "list", "--all=false"]);
+        let list = matches.subcommand_matches("list").unwrap();
+        assert_eq!(list.get_one::<bool>("all"), Some(&false));
+    }
+
+    #[test]
+    fn unknown_config_defaults_are_skipped() {
+        let config = Config::parse(
+            "[defaults.sync]\nretries = 2\nfrobnicate = true\n[defaults.nonexistent]\nall = true",
+        )
+        .unwrap();
+        let (command, ignored) = with_config_defaults(Cli::command(), &config);
+        assert_eq!(ignored.len(), 2);
+
+        let matches = command.get_matches_from(["gwm", "sync"]);
+        let sync = matches.subcommand_matches("sync").unwrap();
+        assert_eq!(sync.get_one::<u32>("retries"), Some(&2));
+    }
+
+    #[test]
+    fn sync_jobs_can_be_a_config_default() {
+        let config = Config::parse("[defaults.sync]\njobs = 8").unwrap();
+        let (command, _) = with_config_defaults(Cli::command(), &config);
+
+        let matches = command.clone().get_matches_from(["gwm", "sync"]);
+        let sync = matches.subcommand_matches("sync").unwrap();
+        assert_eq!(sync.get_one::<u32>("jobs"), Some(&8));
+
+        assert!(
+            command
+                .try_get_matches_from(["gwm", "sync", "--jobs", "0"])
+                .is_err()
+        );
     }
 }
@@ -0,0 +1,268 @@
+//! Announcements from unattended runs (cron, launchd, systemd timers)
+//!
+//! Sinks are configured as `[[notify]]` tables in the config file; commands build a
+//! [`Notifier`] from them and hand it [`Notification`]s. Delivery failures are logged
+//! and never fail the command that triggered them.
+
+use anyhow::{Context, Result, anyhow};
+use serde::Deserialize;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use tracing::{debug, warn};
+
+/// What happened, so each sink can subscribe to only some kinds of news
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Event {
+    /// `sync` fetched new commits
+    Fetched,
+    /// `gc` found worktrees to collect
+    GcCandidates,
+    /// A repository couldn't be fetched or a worktree couldn't be removed
+    Failure,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub event: Event,
+    pub title: String,
+    pub body: String,
+}
+
+impl Notification {
+    pub fn new(event: Event, title: impl Into<String>, body: impl Into<String>) -> Self {
+        Self {
+            event,
+            title: title.into(),
+            body: body.into(),
+        }
+    }
+}
+
+/// One `[[notify]]` table from the config file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SinkConfig {
+    pub sink: SinkKind,
+    /// Incoming webhook to POST to, for the webhook sink (Slack, Mattermost, Discord's /slack endpoint)
+    pub url: Option<String>,
+    /// Events to deliver; all of them when empty
+    #[serde(default)]
+    pub events: Vec<Event>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SinkKind {
+    /// Print to stdout, even with `--quiet`, so cron mails it
+    Stdout,
+    /// Desktop notification through notify-send (Linux) or osascript (macOS)
+    Desktop,
+    /// JSON `{"text": ...}` POSTed with curl, the Slack incoming webhook format
+    Webhook,
+}
+
+/// Somewhere a notification can be delivered
+pub trait Sink: Send + Sync {
+    fn send(&self, notification: &Notification) -> Result<()>;
+}
+
+pub struct StdoutSink;
+
+impl Sink for StdoutSink {
+    fn send(&self, notification: &Notification) -> Result<()> {
+        println!("{}: {}", notification.title, notification.body);
+        Ok(())
+    }
+}
+
+pub struct DesktopSink;
+
+impl Sink for DesktopSink {
+    fn send(&self, notification: &Notification) -> Result<()> {
+        let mut command = if cfg!(target_os = "macos") {
+            let mut command = Command::new("osascript");
+            command.arg("-e").arg(format!(
+                "display notification {} with title {}",
+                applescript_string(&notification.body),
+                applescript_string(&notification.title)
+            ));
+            command
+        } else {
+            let mut command = Command::new("notify-send");
+            command
+                .arg("--app-name=gwm")
+                .arg(&notification.title)
+                .arg(&notification.body);
+            command
+        };
+        run(&mut command, None)
+    }
+}
+
+pub struct WebhookSink {
+    url: String,
+}
+
+impl Sink for WebhookSink {
+    fn send(&self, notification: &Notification) -> Result<()> {
+        let payload = webhook_payload(notification);
+        // The URL goes through stdin rather than argv, since webhook URLs are secrets
+        // and argv is visible to other users in `ps`
+        let mut command = Command::new("curl");
+        command.args([
+            "--silent",
+            "--show-error",
+            "--fail",
+            "--max-time",
+            "10",
+            "--header",
+            "Content-Type: application/json",
+            "--data-binary",
+            &payload,
+            "--config",
+            "-",
+        ]);
+        run(&mut command, Some(&format!("url = \"{}\"\n", self.url)))
+    }
+}
+
+/// Delivers notifications to every configured sink that wants them
+#[derive(Default)]
+pub struct Notifier {
+    sinks: Vec<(Box<dyn Sink>, Vec<Event>)>,
+}
+
+impl Notifier {
+    /// Build sinks from the config, rejecting incomplete ones up front
+    pub fn from_config(configs: &[SinkConfig]) -> Result<Self> {
+        let mut notifier = Self::default();
+        for config in configs {
+            let sink: Box<dyn Sink> = match config.sink {
+                SinkKind::Stdout => Box::new(StdoutSink),
+                SinkKind::Desktop => Box::new(DesktopSink),
+                SinkKind::Webhook => {
+                    let url = config
+                        .url
+                        .clone()
+                        .ok_or_else(|| anyhow!("Webhook notification sink needs a `url`"))?;
+                    if url.contains(['"', '\\', '\n']) {
+                        return Err(anyhow!("Invalid webhook URL '{}'", url));
+                    }
+                    Box::new(WebhookSink { url })
+                }
+            };
+            notifier = notifier.sink(sink, config.events.clone());
+        }
+        Ok(notifier)
+    }
+
+    /// Add a sink for the given events (all events when empty)
+    pub fn sink(mut self, sink: Box<dyn Sink>, events: Vec<Event>) -> Self {
+        self.sinks.push((sink, events));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sinks.is_empty()
+    }
+
+    pub fn notify(&self, notification: &Notification) {
+        for (sink, events) in &self.sinks {
+            if !events.is_empty() && !events.contains(&notification.event) {
+                continue;
+            }
+            debug!("Sending notification '{}'", notification.title);
+            if let Err(e) = sink.send(notification) {
+                warn!("Failed to send notification: {:#}", e);
+            }
+        }
+    }
+}
+
+/// Slack-compatible webhook body
+fn webhook_payload(notification: &Notification) -> String {
+    serde_json::json!({
+        "text": format!("*{}*\n{}", notification.title, notification.body)
+    })
+    .to_string()
+}
+
+fn applescript_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn run(command: &mut Command, stdin: Option<&str>) -> Result<()> {
+    let program = command.get_program().to_string_lossy().to_string();
+    let mut child = command
+        .stdin(if stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run {}", program))?;
+    if let Some(input) = stdin {
+        child.stdin.take().unwrap().write_all(input.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "{} failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl Sink for Recorder {
+        fn send(&self, notification: &Notification) -> Result<()> {
+            self.0.lock().unwrap().push(notification.title.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn sinks_only_receive_subscribed_events() {
+        let all = Arc::new(Mutex::new(Vec::new()));
+        let failures = Arc::new(Mutex::new(Vec::new()));
+        let notifier = Notifier::default()
+            .sink(Box::new(Recorder(all.clone())), vec![])
+            .sink(Box::new(Recorder(failures.clone())), vec![Event::Failure]);
+
+        notifier.notify(&Notification::new(Event::Fetched, "fetched", ""));
+        notifier.notify(&Notification::new(Event::Failure, "failed", ""));
+
+        assert_eq!(*all.lock().unwrap(), vec!["fetched", "failed"]);
+        assert_eq!(*failures.lock().unwrap(), vec!["failed"]);
+    }
+
+    #[test]
+    fn webhook_sink_requires_url() {
+        let config: SinkConfig = toml::from_str("sink = \"webhook\"").unwrap();
+        assert!(Notifier::from_config(&[config]).is_err());
+        let config: SinkConfig =
+            toml::from_str("sink = \"webhook\"\nurl = \"https://hooks.example/x\"").unwrap();
+        assert!(!Notifier::from_config(&[config]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn payloads_are_escaped() {
+        let notification = Notification::new(Event::Failure, "gwm sync", "proj: \"boom\"");
+        assert_eq!(
+            webhook_payload(&notification),
+            r#"{"text":"*gwm sync*\nproj: \"boom\""}"#
+        );
+        assert_eq!(applescript_string(r#"a "b" \c"#), r#""a \"b\" \\c""#);
+    }
+}
@@ -0,0 +1,150 @@
+use std::fmt::Write;
+
+use crate::core::{PrStatus, RepoResult, WorktreeAnalyzer};
+use crate::git::LocalStatus;
+use crate::output::table::format_age;
+
+const STYLE: &str = "\
+body { font-family: -apple-system, 'Segoe UI', Helvetica, Arial, sans-serif; margin: 2rem; color: #1f2328; }
+h1 { font-size: 1.5rem; margin-bottom: 0.25rem; }
+h2 { font-size: 1.15rem; margin-top: 2rem; }
+.meta { color: #656d76; }
+table { border-collapse: collapse; width: 100%; }
+th, td { text-align: left; padding: 0.35rem 0.75rem; border-bottom: 1px solid #d0d7de; }
+th { background: #f6f8fa; }
+code { font-size: 0.85em; }
+.status { border-radius: 1em; padding: 0.1rem 0.6rem; font-size: 0.85em; white-space: nowrap; }
+.clean, .open { background: #dafbe1; }
+.dirty, .staged, .draft { background: #fff8c5; }
+.conflicted, .missing, .closed { background: #ffebe9; }
+.merged { background: #fbefff; }
+";
+
+/// Render the scan results as one self-contained HTML page (inline CSS, no scripts), for
+/// `gwm report`: a summary, then a table of worktrees per repository with PR links and ages
+pub fn create_report(repo_results: &[RepoResult], generated_at: &str) -> String {
+    let (total_wip, repos_with_wip, counters, _) = WorktreeAnalyzer::analyze(repo_results);
+
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Work in progress</title>\n<style>\n{}</style>\n</head>\n<body>\n\
+         <h1>Work in progress</h1>\n\
+         <p class=\"meta\">{} worktree(s) in {} repository(ies): {} clean, {} dirty, {} staged, \
+         {} conflicted, {} missing &middot; generated {}</p>\n",
+        STYLE,
+        total_wip,
+        repos_with_wip,
+        counters.clean,
+        counters.dirty,
+        counters.staged,
+        counters.conflicted,
+        counters.missing,
+        escape(generated_at)
+    );
+
+    for repo_result in repo_results {
+        if repo_result.worktrees.is_empty() {
+            continue;
+        }
+        let _ = write!(
+            html,
+            "<h2>{}</h2>\n<table>\n<tr><th>Branch</th><th>Local</th><th>PR</th><th>Age</th>\
+             <th>Last commit</th></tr>\n",
+            escape(&repo_result.name)
+        );
+        for worktree in &repo_result.worktrees {
+            let status = &worktree.status;
+            let local = match status.local_status {
+                LocalStatus::Clean => "clean",
+                LocalStatus::Dirty => "dirty",
+                LocalStatus::Staged => "staged",
+                LocalStatus::Conflicted => "conflicted",
+                LocalStatus::Missing => "missing",
+            };
+            let pr = match &status.pr_status {
+                Some(pr_status) => {
+                    let class = match pr_status {
+                        PrStatus::Open => "open",
+                        PrStatus::Draft => "draft",
+                        PrStatus::Merged => "merged",
+                        PrStatus::Closed => "closed",
+                    };
+                    let label = format!("<span class=\"status {}\">{}</span>", class, pr_status);
+                    match &status.pr_url {
+                        Some(url) => format!("<a href=\"{}\">{}</a>", escape(url), label),
+                        None => label,
+                    }
+                }
+                None => "-".to_string(),
+            };
+            let _ = writeln!(
+                html,
+                "<tr><td><code>{}</code></td><td><span class=\"status {}\">{}</span></td>\
+                 <td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape(&worktree.branch),
+                local,
+                status.local_status,
+                pr,
+                format_age(status.commit_timestamp),
+                escape(&status.commit_summary)
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    if total_wip == 0 {
+        html.push_str("<p>No work in progress.</p>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{WorktreeResult, WorktreeStatus};
+    use std::path::PathBuf;
+
+    #[test]
+    fn links_pull_requests_and_escapes_text() {
+        let results = vec![RepoResult {
+            name: "proj".to_string(),
+            path: PathBuf::from("/repos/proj"),
+            worktrees: vec![WorktreeResult {
+                branch: "fix-login".to_string(),
+                path: PathBuf::from("/repos/proj/fix-login"),
+                status: WorktreeStatus {
+                    local_status: LocalStatus::Dirty,
+                    commit_timestamp: 1700000000,
+                    commit_summary: "Handle <script> & \"quotes\"".to_string(),
+                    pr_status: Some(PrStatus::Open),
+                    pr_url: Some("https://github.com/acme/proj/pull/7".to_string()),
+                    ..Default::default()
+                },
+            }],
+        }];
+
+        let html = create_report(&results, "2026-10-17 09:00");
+        assert!(html.contains("<a href=\"https://github.com/acme/proj/pull/7\">"));
+        assert!(html.contains("Handle &lt;script&gt; &amp; &quot;quotes&quot;"));
+        assert!(html.contains("<h2>proj</h2>"));
+        assert!(!html.contains("<script>"));
+    }
+}
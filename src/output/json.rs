@@ -0,0 +1,234 @@
+//! Machine-readable output for `--format json` and `--format ndjson`
+//!
+//! Every JSON document (and every NDJSON line) carries `schema_version`. The compatibility policy:
+//!
+//! - Within a version, fields and enum values are only ever added, so consumers must ignore
+//!   fields they don't know
+//! - Removing or renaming a field, changing its type or meaning, or removing an enum value
+//!   bumps [`SCHEMA_VERSION`]
+//! - Consumers should refuse documents newer than the version they were written against,
+//!   which [`check_schema_version`] does for Rust consumers
+//!
+//! The schema types here are separate from the core result types on purpose, so refactoring
+//! the core never changes the output by accident.
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::core::RepoResult;
+
+/// Version of the JSON schema; see the module docs for when it changes
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// A payload tagged with the schema version it follows
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Versioned<T> {
+    pub schema_version: u32,
+    #[serde(flatten)]
+    pub data: T,
+}
+
+impl<T> Versioned<T> {
+    pub fn new(data: T) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            data,
+        }
+    }
+}
+
+/// Result of `gwm list --format json`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WorktreeList {
+    pub worktrees: Vec<Worktree>,
+}
+
+/// One worktree, as in `gwm list --format json` and each line of `--format ndjson`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Worktree {
+    pub repo: String,
+    pub branch: String,
+    pub path: String,
+    /// "clean", "dirty", "staged", "conflicted" or "missing"
+    pub local_status: String,
+    /// "open", "draft", "merged" or "closed"; null when unknown or not fetched
+    pub pr_status: Option<String>,
+    /// "approved", "changes_requested" or "review_required"; null when the PR needs no review
+    #[serde(default)]
+    pub pr_review: Option<String>,
+    /// Unix time of the branch's last commit
+    pub commit_timestamp: i64,
+    /// Unix time of the last commit, checkout or staging in the worktree
+    #[serde(default)]
+    pub activity_timestamp: i64,
+    pub commit_summary: String,
+    /// Reason given to `git worktree lock`, empty when none was given; null when not locked
+    #[serde(default)]
+    pub locked: Option<String>,
+    /// Changes on the branch since it forked from the base branch; null when not computed
+    #[serde(default)]
+    pub diffstat: Option<Diffstat>,
+    /// Stash entries made on the branch
+    #[serde(default)]
+    pub stash_count: usize,
+    /// Bytes used by the worktree directory; null when not computed
+    #[serde(default)]
+    pub size_bytes: Option<u64>,
+    /// "rebasing", "merging", "cherry_picking", "reverting", "bisecting" or "applying_patches";
+    /// null when no git operation is in progress
+    #[serde(default)]
+    pub operation: Option<String>,
+}
+
+/// Size of a branch's changes, as `git diff --shortstat` reports it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Diffstat {
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+/// Pure function flattening scan results into schema worktrees
+pub fn worktrees(repo_results: &[RepoResult]) -> Vec<Worktree> {
+    repo_results
+        .iter()
+        .flat_map(|repo_result| {
+            repo_result.worktrees.iter().map(|worktree| Worktree {
+                repo: repo_result.name.clone(),
+                branch: worktree.branch.clone(),
+                path: worktree.path.to_string_lossy().to_string(),
+                local_status: worktree.status.local_status.to_string().to_lowercase(),
+                pr_status: worktree
+                    .status
+                    .pr_status
+                    .as_ref()
+                    .map(|status| status.to_string().to_lowercase()),
+                pr_review: worktree
+                    .status
+                    .pr_review
+                    .map(|review| review.to_string().replace(' ', "_")),
+                commit_timestamp: worktree.status.commit_timestamp,
+                activity_timestamp: worktree.status.last_activity(),
+                commit_summary: worktree.status.commit_summary.clone(),
+                locked: worktree.status.locked.clone(),
+                diffstat: worktree.status.diffstat.map(|diffstat| Diffstat {
+                    files_changed: diffstat.files_changed,
+                    insertions: diffstat.insertions,
+                    deletions: diffstat.deletions,
+                }),
+                stash_count: worktree.status.stash_count,
+                size_bytes: worktree.status.size_bytes,
+                operation: worktree.status.operation.map(|operation| {
+                    operation
+                        .to_string()
+                        .to_lowercase()
+                        .replace(['-', ' '], "_")
+                }),
+            })
+        })
+        .collect()
+}
+
+/// One JSON document holding every worktree
+pub fn create_json(repo_results: &[RepoResult]) -> Result<String> {
+    Ok(serde_json::to_string_pretty(&Versioned::new(
+        WorktreeList {
+            worktrees: worktrees(repo_results),
+        },
+    ))?)
+}
+
+/// One JSON object per line and per worktree, for streaming consumers
+pub fn create_ndjson(repo_results: &[RepoResult]) -> Result<String> {
+    let lines = worktrees(repo_results)
+        .into_iter()
+        .map(|worktree| serde_json::to_string(&Versioned::new(worktree)))
+        .collect::<serde_json::Result<Vec<_>>>()?;
+    Ok(lines.join("\n"))
+}
+
+/// Accept documents this build understands and refuse newer ones
+pub fn check_schema_version(found: u32) -> Result<()> {
+    if found > SCHEMA_VERSION {
+        return Err(anyhow!(
+            "gwm output uses schema version {}, but only versions up to {} are supported",
+            found,
+            SCHEMA_VERSION
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{PrStatus, WorktreeResult, WorktreeStatus};
+    use crate::git::{DiffStat, LocalStatus, Operation};
+    use std::path::PathBuf;
+
+    fn results() -> Vec<RepoResult> {
+        vec![RepoResult {
+            name: "proj".to_string(),
+            path: PathBuf::from("/repos/proj"),
+            worktrees: vec![WorktreeResult {
+                branch: "fix-login".to_string(),
+                path: PathBuf::from("/repos/proj/fix-login"),
+                status: WorktreeStatus {
+                    local_status: LocalStatus::Staged,
+                    commit_timestamp: 1700000000,
+                    commit_summary: "Fix login".to_string(),
+                    pr_status: Some(PrStatus::Draft),
+                    locked: Some("on a USB drive".to_string()),
+                    diffstat: Some(DiffStat {
+                        files_changed: 3,
+                        insertions: 40,
+                        deletions: 7,
+                    }),
+                    stash_count: 2,
+                    size_bytes: Some(4096),
+                    operation: Some(Operation::CherryPicking),
+                    ..Default::default()
+                },
+            }],
+        }]
+    }
+
+    #[test]
+    fn every_document_carries_the_schema_version() {
+        let json: serde_json::Value =
+            serde_json::from_str(&create_json(&results()).unwrap()).unwrap();
+        assert_eq!(json["schema_version"], SCHEMA_VERSION);
+        assert_eq!(json["worktrees"][0]["local_status"], "staged");
+        assert_eq!(json["worktrees"][0]["pr_status"], "draft");
+
+        let line: Versioned<Worktree> =
+            serde_json::from_str(&create_ndjson(&results()).unwrap()).unwrap();
+        assert_eq!(line.schema_version, SCHEMA_VERSION);
+        assert_eq!(line.data.branch, "fix-login");
+        assert_eq!(line.data.locked.as_deref(), Some("on a USB drive"));
+        assert_eq!(line.data.diffstat.map(|d| d.insertions), Some(40));
+        assert_eq!(line.data.stash_count, 2);
+        assert_eq!(line.data.size_bytes, Some(4096));
+        assert_eq!(line.data.operation.as_deref(), Some("cherry_picking"));
+    }
+
+    #[test]
+    fn documents_without_the_newer_fields_still_parse() {
+        let line: Versioned<Worktree> = serde_json::from_str(
+            r#"{"schema_version":1,"repo":"proj","branch":"fix-login","path":"/repos/proj/fix-login",
+                "local_status":"clean","pr_status":null,"commit_timestamp":0,"commit_summary":""}"#,
+        )
+        .unwrap();
+        assert_eq!(line.data.locked, None);
+        assert!(line.data.diffstat.is_none());
+        assert_eq!(line.data.stash_count, 0);
+        assert_eq!(line.data.size_bytes, None);
+        assert_eq!(line.data.operation, None);
+    }
+
+    #[test]
+    fn newer_schema_versions_are_refused() {
+        assert!(check_schema_version(SCHEMA_VERSION).is_ok());
+        assert!(check_schema_version(SCHEMA_VERSION + 1).is_err());
+    }
+}
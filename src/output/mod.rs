@@ -1 +1,267 @@
+use colored::{Color, Colorize};
+use std::ffi::OsString;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+pub mod html;
+pub mod json;
+pub mod prompt;
+pub mod shell;
 pub mod table;
+
+/// How `gwm list` prints its results
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable table with a summary
+    #[default]
+    Table,
+    /// bash/zsh variable assignments for `eval`
+    Shell,
+    /// One JSON document, with a `schema_version`
+    Json,
+    /// One JSON object per worktree and line, each with a `schema_version`
+    Ndjson,
+}
+
+/// When to color output, as chosen with `--color`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ColorChoice {
+    /// Color when stdout is a terminal and NO_COLOR isn't set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Meaning of a piece of output, which decides its color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tone {
+    Good,
+    Warning,
+    Bad,
+    Info,
+    Muted,
+    /// Changed since the previous refresh in `list --watch`
+    Changed,
+}
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+static COLOR: AtomicBool = AtomicBool::new(false);
+static HYPERLINKS: AtomicBool = AtomicBool::new(false);
+static PAGER: AtomicBool = AtomicBool::new(false);
+
+/// Keep only essential results and errors on screen, for scripts and cron jobs
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Decide once per process whether output is colored
+pub fn set_color(choice: ColorChoice) {
+    let enabled = color_enabled(
+        choice,
+        std::env::var_os("NO_COLOR"),
+        std::io::stdout().is_terminal(),
+    );
+    COLOR.store(enabled, Ordering::Relaxed);
+    colored::control::set_override(enabled);
+    HYPERLINKS.store(
+        hyperlinks_enabled(
+            enabled,
+            std::env::var_os("FORCE_HYPERLINK"),
+            std::env::var_os("TERM"),
+        ),
+        Ordering::Relaxed,
+    );
+}
+
+/// Let long output go through a pager, unless `--no-pager`
+pub fn set_pager(enabled: bool) {
+    PAGER.store(enabled, Ordering::Relaxed);
+}
+
+/// Print output, through the pager when it is taller than the terminal, as git does
+pub fn print_paged(text: &str) {
+    let fits = || {
+        terminal_size::terminal_size()
+            .is_none_or(|(_, terminal_size::Height(height))| fits_on_screen(text, height))
+    };
+    if PAGER.load(Ordering::Relaxed)
+        && io::stdout().is_terminal()
+        && !fits()
+        && let Some(command) = pager_command(std::env::var_os("PAGER"))
+        && page(text, &command).is_ok()
+    {
+        return;
+    }
+    println!("{}", text);
+}
+
+/// Width of the terminal standard output is drawn on, or None when it isn't a terminal
+pub fn terminal_width() -> Option<usize> {
+    if !io::stdout().is_terminal() {
+        return None;
+    }
+    terminal_size::terminal_size().map(|(terminal_size::Width(width), _)| width as usize)
+}
+
+/// Pure function checking whether text fits on screen with a line to spare for the prompt
+pub fn fits_on_screen(text: &str, height: u16) -> bool {
+    text.lines().count() < height as usize
+}
+
+/// Pure function choosing the pager from $PAGER: `less -R` when unset, none when empty or `cat`
+pub fn pager_command(pager: Option<OsString>) -> Option<Vec<String>> {
+    let pager = match pager {
+        Some(pager) => pager.to_string_lossy().trim().to_string(),
+        None => "less -R".to_string(),
+    };
+    if pager.is_empty() || pager == "cat" {
+        return None;
+    }
+    Some(pager.split_whitespace().map(str::to_string).collect())
+}
+
+/// Pipe text through the pager and wait for the user to quit it
+fn page(text: &str, command: &[String]) -> io::Result<()> {
+    let mut pager = Command::new(&command[0]);
+    pager.args(&command[1..]).stdin(Stdio::piped());
+    // Like git: quit when one screen suffices, keep colors, and leave the output on screen
+    if std::env::var_os("LESS").is_none() {
+        pager.env("LESS", "FRX");
+    }
+    let mut child = pager.spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // The user may quit before reading everything
+        match writeln!(stdin, "{}", text) {
+            Err(e) if e.kind() != io::ErrorKind::BrokenPipe => return Err(e),
+            _ => {}
+        }
+    }
+    child.wait()?;
+    Ok(())
+}
+
+pub fn use_color() -> bool {
+    COLOR.load(Ordering::Relaxed)
+}
+
+/// Pure function resolving `--color`: an explicit choice wins, then a non-empty NO_COLOR
+/// (https://no-color.org), then whether stdout is a terminal
+pub fn color_enabled(choice: ColorChoice, no_color: Option<OsString>, is_terminal: bool) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => no_color.is_none_or(|value| value.is_empty()) && is_terminal,
+    }
+}
+
+/// Pure function deciding whether to print clickable links: FORCE_HYPERLINK=1 or 0 wins, as
+/// other terminal tools honor it, otherwise links go wherever color does, except to dumb terminals
+pub fn hyperlinks_enabled(color: bool, force: Option<OsString>, term: Option<OsString>) -> bool {
+    match force.filter(|value| !value.is_empty()) {
+        Some(value) => value != "0",
+        None => color && term.is_none_or(|term| term != "dumb"),
+    }
+}
+
+/// Make text a link with an OSC 8 escape sequence, or leave it plain when links are off
+/// Terminals without OSC 8 support print just the text
+pub fn hyperlink(text: &str, url: &str) -> String {
+    if !HYPERLINKS.load(Ordering::Relaxed) {
+        return text.to_string();
+    }
+    format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text)
+}
+
+/// Color text by its meaning, or leave it plain when color is off
+pub fn paint(text: &str, tone: Tone) -> String {
+    if !use_color() {
+        return text.to_string();
+    }
+    match tone {
+        Tone::Good => text.green(),
+        Tone::Warning => text.yellow(),
+        Tone::Bad => text.red(),
+        Tone::Info => text.cyan(),
+        Tone::Muted => text.dimmed(),
+        Tone::Changed => text.bold().underline(),
+    }
+    .to_string()
+}
+
+/// Color text with an explicit color, or leave it plain when color is off
+pub fn paint_color(text: &str, color: Color) -> String {
+    if !use_color() {
+        return text.to_string();
+    }
+    text.color(color).to_string()
+}
+
+/// Decorative emoji prefix such as "✅ ", or nothing in quiet mode
+pub fn emoji(symbol: &'static str) -> &'static str {
+    if is_quiet() { "" } else { symbol }
+}
+
+/// `println!` for decorative output (headers, progress, summaries), silenced by `--quiet`
+#[macro_export]
+macro_rules! say {
+    ($($arg:tt)*) => {
+        if !$crate::output::is_quiet() {
+            println!($($arg)*);
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_follows_flag_then_no_color_then_terminal() {
+        assert!(color_enabled(ColorChoice::Always, Some("1".into()), false));
+        assert!(!color_enabled(ColorChoice::Never, None, true));
+        assert!(color_enabled(ColorChoice::Auto, None, true));
+        assert!(!color_enabled(ColorChoice::Auto, None, false));
+        assert!(!color_enabled(ColorChoice::Auto, Some("1".into()), true));
+        assert!(color_enabled(ColorChoice::Auto, Some("".into()), true));
+    }
+
+    #[test]
+    fn hyperlinks_follow_color_unless_forced() {
+        assert!(hyperlinks_enabled(
+            true,
+            None,
+            Some("xterm-256color".into())
+        ));
+        assert!(!hyperlinks_enabled(true, None, Some("dumb".into())));
+        assert!(!hyperlinks_enabled(false, None, None));
+        assert!(hyperlinks_enabled(false, Some("1".into()), None));
+        assert!(!hyperlinks_enabled(true, Some("0".into()), None));
+    }
+
+    #[test]
+    fn pager_defaults_to_less_and_can_be_turned_off() {
+        assert_eq!(
+            pager_command(None),
+            Some(vec!["less".to_string(), "-R".to_string()])
+        );
+        assert_eq!(
+            pager_command(Some("most -s".into())),
+            Some(vec!["most".to_string(), "-s".to_string()])
+        );
+        assert_eq!(pager_command(Some("".into())), None);
+        assert_eq!(pager_command(Some("cat".into())), None);
+        assert!(fits_on_screen("a\nb", 3));
+        assert!(!fits_on_screen("a\nb\nc", 3));
+    }
+
+    #[test]
+    fn paint_leaves_text_plain_without_color() {
+        assert_eq!(paint("Clean", Tone::Good), "Clean");
+    }
+}
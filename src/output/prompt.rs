@@ -0,0 +1,120 @@
+//! Status of the current worktree for shell prompts, as printed by `gwm env`
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::output::json::{SCHEMA_VERSION, Versioned};
+use crate::output::shell::quote;
+
+/// The worktree a directory belongs to, as `gwm env --format json` prints it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptStatus {
+    pub repo: String,
+    pub branch: String,
+    pub path: String,
+    /// "clean", "dirty", "staged", "conflicted" or "missing"
+    pub local_status: String,
+    /// "rebasing", "merging", "cherry-picking", "reverting", "bisecting" or "applying patches";
+    /// null when no operation is in progress
+    pub operation: Option<String>,
+    /// Commits not on the upstream yet; null when the branch has no upstream
+    pub ahead: Option<usize>,
+    /// Commits on the upstream not merged yet; null when the branch has no upstream
+    pub behind: Option<usize>,
+}
+
+impl PromptStatus {
+    /// "repo@branch (dirty, behind 2)", leaving out the parentheses when clean and up to date
+    pub fn text(&self) -> String {
+        let mut details = Vec::new();
+        if self.local_status != "clean" {
+            details.push(self.local_status.clone());
+        }
+        details.extend(self.operation.clone());
+        if let Some(ahead) = self.ahead.filter(|&n| n > 0) {
+            details.push(format!("ahead {}", ahead));
+        }
+        if let Some(behind) = self.behind.filter(|&n| n > 0) {
+            details.push(format!("behind {}", behind));
+        }
+
+        if details.is_empty() {
+            format!("{}@{}", self.repo, self.branch)
+        } else {
+            format!("{}@{} ({})", self.repo, self.branch, details.join(", "))
+        }
+    }
+
+    /// bash/zsh variable assignments for `eval "$(gwm env --format shell)"`, with empty values
+    /// for what is unknown
+    pub fn shell_vars(&self) -> String {
+        let count = |n: Option<usize>| n.map(|n| n.to_string()).unwrap_or_default();
+        [
+            format!("gwm_schema_version={}", SCHEMA_VERSION),
+            format!("gwm_repo={}", quote(&self.repo)),
+            format!("gwm_branch={}", quote(&self.branch)),
+            format!("gwm_path={}", quote(&self.path)),
+            format!("gwm_local={}", quote(&self.local_status)),
+            format!(
+                "gwm_operation={}",
+                quote(self.operation.as_deref().unwrap_or_default())
+            ),
+            format!("gwm_ahead={}", count(self.ahead)),
+            format!("gwm_behind={}", count(self.behind)),
+        ]
+        .join("\n")
+    }
+
+    /// One line of JSON with a `schema_version`
+    pub fn json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&Versioned::new(self))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status() -> PromptStatus {
+        PromptStatus {
+            repo: "proj".to_string(),
+            branch: "fix-login".to_string(),
+            path: "/repos/proj/fix-login".to_string(),
+            local_status: "clean".to_string(),
+            operation: None,
+            ahead: Some(0),
+            behind: Some(0),
+        }
+    }
+
+    #[test]
+    fn text_lists_only_what_needs_attention() {
+        assert_eq!(status().text(), "proj@fix-login");
+
+        let status = PromptStatus {
+            local_status: "dirty".to_string(),
+            operation: Some("rebasing".to_string()),
+            ahead: Some(1),
+            behind: Some(2),
+            ..status()
+        };
+        assert_eq!(
+            status.text(),
+            "proj@fix-login (dirty, rebasing, ahead 1, behind 2)"
+        );
+    }
+
+    #[test]
+    fn shell_vars_leave_unknown_counts_empty() {
+        let status = PromptStatus {
+            branch: "it's".to_string(),
+            ahead: None,
+            behind: None,
+            ..status()
+        };
+        let vars = status.shell_vars();
+        assert!(vars.contains(r"gwm_branch='it'\''s'"));
+        assert!(vars.contains("gwm_ahead=\n"));
+        assert!(vars.ends_with("gwm_behind="));
+    }
+}
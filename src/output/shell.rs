@@ -0,0 +1,112 @@
+use crate::core::RepoResult;
+use crate::output::json::SCHEMA_VERSION;
+
+/// Render worktrees as bash/zsh variable assignments, for `eval "$(gwm list --format shell)"`
+/// Parallel indexed arrays hold one entry per worktree (0-based in bash, 1-based in zsh),
+/// and `gwm_path_by_branch` maps "repo/branch" to the worktree directory
+pub fn create_shell_vars(repo_results: &[RepoResult]) -> String {
+    let mut repos = Vec::new();
+    let mut branches = Vec::new();
+    let mut paths = Vec::new();
+    let mut locals = Vec::new();
+    let mut prs = Vec::new();
+    let mut commit_times = Vec::new();
+    let mut summaries = Vec::new();
+    let mut path_by_branch = Vec::new();
+
+    for repo_result in repo_results {
+        for worktree in &repo_result.worktrees {
+            let path = worktree.path.to_string_lossy();
+            repos.push(quote(&repo_result.name));
+            branches.push(quote(&worktree.branch));
+            paths.push(quote(&path));
+            locals.push(quote(
+                &worktree.status.local_status.to_string().to_lowercase(),
+            ));
+            prs.push(quote(
+                &worktree
+                    .status
+                    .pr_status
+                    .as_ref()
+                    .map(|status| status.to_string().to_lowercase())
+                    .unwrap_or_default(),
+            ));
+            commit_times.push(worktree.status.commit_timestamp.to_string());
+            summaries.push(quote(&worktree.status.commit_summary));
+            path_by_branch.push(format!(
+                "[{}]={}",
+                quote(&format!("{}/{}", repo_result.name, worktree.branch)),
+                quote(&path)
+            ));
+        }
+    }
+
+    [
+        format!("gwm_schema_version={}", SCHEMA_VERSION),
+        format!("gwm_count={}", repos.len()),
+        format!("gwm_repo=({})", repos.join(" ")),
+        format!("gwm_branch=({})", branches.join(" ")),
+        format!("gwm_path=({})", paths.join(" ")),
+        format!("gwm_local=({})", locals.join(" ")),
+        format!("gwm_pr=({})", prs.join(" ")),
+        format!("gwm_commit_time=({})", commit_times.join(" ")),
+        format!("gwm_summary=({})", summaries.join(" ")),
+        // -g keeps the map global when eval'd inside a function
+        "unset gwm_path_by_branch".to_string(),
+        format!(
+            "typeset -gA gwm_path_by_branch=({})",
+            path_by_branch.join(" ")
+        ),
+    ]
+    .join("\n")
+}
+
+/// Single-quote a value so the shell reads it back verbatim
+pub fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{PrStatus, WorktreeResult, WorktreeStatus};
+    use crate::git::LocalStatus;
+    use std::path::PathBuf;
+
+    #[test]
+    fn quote_escapes_single_quotes() {
+        assert_eq!(quote("it's"), r"'it'\''s'");
+        assert_eq!(quote("$(rm -rf ~)"), "'$(rm -rf ~)'");
+    }
+
+    #[test]
+    fn renders_parallel_arrays_and_branch_map() {
+        let results = vec![RepoResult {
+            name: "proj".to_string(),
+            path: PathBuf::from("/repos/proj"),
+            worktrees: vec![WorktreeResult {
+                branch: "fix-login".to_string(),
+                path: PathBuf::from("/repos/proj/fix-login"),
+                status: WorktreeStatus {
+                    local_status: LocalStatus::Dirty,
+                    commit_timestamp: 1700000000,
+                    commit_summary: "Don't log passwords".to_string(),
+                    pr_status: Some(PrStatus::Open),
+                    ..Default::default()
+                },
+            }],
+        }];
+
+        let output = create_shell_vars(&results);
+        assert!(output.starts_with("gwm_schema_version=1\n"));
+        assert!(output.contains("gwm_count=1\n"));
+        assert!(output.contains("gwm_branch=('fix-login')\n"));
+        assert!(output.contains("gwm_local=('dirty')\n"));
+        assert!(output.contains("gwm_pr=('open')\n"));
+        assert!(output.contains("gwm_commit_time=(1700000000)\n"));
+        assert!(output.contains(r"gwm_summary=('Don'\''t log passwords')"));
+        assert!(output.ends_with(
+            "typeset -gA gwm_path_by_branch=(['proj/fix-login']='/repos/proj/fix-login')"
+        ));
+    }
+}
@@ -1,95 +1,611 @@
-use crate::core::{PrStatus, RepoResult, WorktreeResult};
-use crate::git::LocalStatus;
+use crate::config::{ColorName, EmojiWidth, IconSet, StatusColors, TableConfig, TruncateStrategy};
+use crate::core::{
+    PrStatus, RepoResult, ReviewDecision, StatusCounters, TriageSection, WorktreeResult,
+};
+use crate::git::{DiffStat, LocalStatus, is_detached_label};
+use crate::output::{self, Tone};
+use serde::Deserialize;
+use std::collections::HashSet;
 use std::fmt::Display;
+use tabled::Table;
+use tabled::builder::Builder;
 use tabled::settings::Style;
-use tabled::{Table, Tabled};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Narrowest a commit summary, branch or repository gets when squeezing the table into the
+/// terminal
+const MIN_FLEXIBLE_WIDTH: usize = 16;
 
 #[derive(Debug, Clone)]
 pub struct EmojiStatus<T>(pub T);
 
 impl Display for EmojiStatus<LocalStatus> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let emoji = match self.0 {
-            LocalStatus::Clean => "✅",
-            LocalStatus::Dirty => "🔧",
-            LocalStatus::Staged => "📦",
-            LocalStatus::Missing => "❌",
-        };
-        write!(f, "{} {}", emoji, self.0)
-    }
-}
-
-#[derive(Tabled)]
-pub struct TableRow {
-    #[tabled(rename = "Repository")]
-    pub repo: String,
-    #[tabled(rename = "Branch")]
-    pub branch: String,
-    #[tabled(rename = "Local")]
-    pub local_status: String,
-    #[tabled(rename = "PR Status")]
-    pub pr_status: String,
-    #[tabled(rename = "Age")]
-    pub commit_age: String,
-    #[tabled(rename = "Last Commit")]
-    pub commit_summary: String,
-}
-
-#[derive(Tabled)]
-pub struct TableRowWithoutPr {
-    #[tabled(rename = "Repository")]
-    pub repo: String,
-    #[tabled(rename = "Branch")]
-    pub branch: String,
-    #[tabled(rename = "Local")]
-    pub local_status: String,
-    #[tabled(rename = "Age")]
-    pub commit_age: String,
-    #[tabled(rename = "Last Commit")]
-    pub commit_summary: String,
-}
-
-impl TableRow {
-    pub fn from_worktree(repo_name: &str, worktree: &WorktreeResult, use_emoji: bool) -> Self {
-        Self {
-            repo: repo_name.to_string(),
-            branch: worktree.branch.clone(),
-            local_status: if use_emoji {
-                EmojiStatus(worktree.status.local_status.clone()).to_string()
-            } else {
-                worktree.status.local_status.to_string()
+        write!(f, "{} {}", local_glyph(IconSet::Emoji, &self.0), self.0)
+    }
+}
+
+/// Built-in glyph for a local status in an icon set
+fn local_glyph(set: IconSet, status: &LocalStatus) -> &'static str {
+    match (set, status) {
+        (IconSet::Emoji, LocalStatus::Clean) => "✅",
+        (IconSet::Emoji, LocalStatus::Dirty) => "🔧",
+        (IconSet::Emoji, LocalStatus::Staged) => "📦",
+        (IconSet::Emoji, LocalStatus::Conflicted) => "⚔️",
+        (IconSet::Emoji, LocalStatus::Missing) => "❌",
+        (IconSet::Nerd, LocalStatus::Clean) => "\u{f00c}",
+        (IconSet::Nerd, LocalStatus::Dirty) => "\u{f040}",
+        (IconSet::Nerd, LocalStatus::Staged) => "\u{f487}",
+        (IconSet::Nerd, LocalStatus::Conflicted) => "\u{f419}",
+        (IconSet::Nerd, LocalStatus::Missing) => "\u{f00d}",
+        (IconSet::Ascii, LocalStatus::Clean) => "=",
+        (IconSet::Ascii, LocalStatus::Dirty) => "*",
+        (IconSet::Ascii, LocalStatus::Staged) => "+",
+        (IconSet::Ascii, LocalStatus::Conflicted) => "!",
+        (IconSet::Ascii, LocalStatus::Missing) => "x",
+    }
+}
+
+/// Built-in glyph for a locked worktree in an icon set
+fn lock_glyph(set: IconSet) -> &'static str {
+    match set {
+        IconSet::Emoji => "🔒",
+        IconSet::Nerd => "\u{f023}",
+        IconSet::Ascii => "#",
+    }
+}
+
+/// Glyph for a local status: the configured one, else the icon set's
+fn local_icon<'a>(config: &'a TableConfig, status: &LocalStatus) -> &'a str {
+    let icons = &config.icons;
+    let custom = match status {
+        LocalStatus::Clean => &icons.clean,
+        LocalStatus::Dirty => &icons.dirty,
+        LocalStatus::Staged => &icons.staged,
+        LocalStatus::Conflicted => &icons.conflicted,
+        LocalStatus::Missing => &icons.missing,
+    };
+    custom
+        .as_deref()
+        .unwrap_or_else(|| local_glyph(config.icon_set, status))
+}
+
+/// Glyph for a locked worktree: the configured one, else the icon set's
+fn lock_icon(config: &TableConfig) -> &str {
+    config
+        .icons
+        .locked
+        .as_deref()
+        .unwrap_or_else(|| lock_glyph(config.icon_set))
+}
+
+/// A column that can appear in the worktree table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Repository,
+    Branch,
+    /// Only shown when asked for: the issue-tracker key in the branch name
+    Ticket,
+    Local,
+    /// Only shown when some worktree has stash entries
+    Stashes,
+    PrStatus,
+    /// Only shown when asked for, since computing it costs a diff per worktree
+    Diffstat,
+    /// Only shown when asked for, since computing it reads every file's metadata
+    Size,
+    Age,
+    /// Only shown when asked for, next to the Age it usually equals
+    Activity,
+    LastCommit,
+    /// Only shown when asked for, since it repeats the repository and branch at length
+    Path,
+}
+
+impl Column {
+    /// Every column shown by default, in display order
+    pub const ALL: [Column; 6] = [
+        Column::Repository,
+        Column::Branch,
+        Column::Local,
+        Column::PrStatus,
+        Column::Age,
+        Column::LastCommit,
+    ];
+
+    pub fn header(&self) -> &'static str {
+        match self {
+            Column::Repository => "Repository",
+            Column::Branch => "Branch",
+            Column::Ticket => "Ticket",
+            Column::Local => "Local",
+            Column::Stashes => "Stashes",
+            Column::PrStatus => "PR Status",
+            Column::Diffstat => "Changes",
+            Column::Size => "Size",
+            Column::Age => "Age",
+            Column::Activity => "Last Active",
+            Column::LastCommit => "Last Commit",
+            Column::Path => "Path",
+        }
+    }
+
+    /// Name used for this column in the config file
+    pub fn key(&self) -> &'static str {
+        match self {
+            Column::Repository => "repository",
+            Column::Branch => "branch",
+            Column::Ticket => "ticket",
+            Column::Local => "local",
+            Column::Stashes => "stashes",
+            Column::PrStatus => "pr_status",
+            Column::Diffstat => "diffstat",
+            Column::Size => "size",
+            Column::Age => "age",
+            Column::Activity => "activity",
+            Column::LastCommit => "last_commit",
+            Column::Path => "path",
+        }
+    }
+
+    fn cell(&self, repo_name: &str, worktree: &WorktreeResult, options: &TableOptions) -> String {
+        match self {
+            Column::Repository => repo_name.to_string(),
+            Column::Branch => match (&worktree.status.locked, options.use_emoji) {
+                (Some(_), true) => format!("{} {}", lock_icon(&options.widths), worktree.branch),
+                (Some(_), false) => format!("{} (locked)", worktree.branch),
+                (None, _) => worktree.branch.clone(),
             },
-            pr_status: format_pr_status(&worktree.status.pr_status),
-            commit_age: format_age(worktree.status.commit_timestamp),
-            commit_summary: worktree.status.commit_summary.clone(),
+            Column::Ticket => options
+                .widths
+                .tickets
+                .ticket_id(&worktree.branch)
+                .unwrap_or_else(|| "-".to_string()),
+            Column::Local => {
+                let local = if options.use_emoji {
+                    let status = &worktree.status.local_status;
+                    format!("{} {}", local_icon(&options.widths, status), status)
+                } else {
+                    worktree.status.local_status.to_string()
+                };
+                match worktree.status.operation {
+                    Some(operation) => {
+                        format!("{} ({})", local, operation.to_string().to_lowercase())
+                    }
+                    None => local,
+                }
+            }
+            Column::PrStatus => format_pr_status(
+                &worktree.status.pr_status,
+                worktree.status.pr_url.as_deref(),
+                worktree.status.pr_review,
+            ),
+            Column::Stashes => match worktree.status.stash_count {
+                0 => "-".to_string(),
+                count => count.to_string(),
+            },
+            Column::Diffstat => format_diffstat(worktree.status.diffstat),
+            Column::Size => worktree
+                .status
+                .size_bytes
+                .map_or_else(|| "-".to_string(), format_size),
+            Column::Age => format_date(worktree.status.commit_timestamp, options.dates),
+            Column::Activity => format_date(worktree.status.last_activity(), options.dates),
+            Column::LastCommit => worktree.status.commit_summary.clone(),
+            Column::Path => worktree.path.display().to_string(),
         }
     }
+
+    /// Color the cell, for columns whose value carries a status
+    /// A color configured for the status wins over its default tone
+    fn paint(&self, cell: String, worktree: &WorktreeResult, colors: &StatusColors) -> String {
+        let (configured, tone) = match self {
+            Column::Branch if is_detached_label(&worktree.branch) => (None, Tone::Muted),
+            // An unfinished rebase or merge needs attention whatever the files look like
+            Column::Local if worktree.status.operation.is_some() => (colors.dirty, Tone::Warning),
+            Column::Local => match worktree.status.local_status {
+                LocalStatus::Clean => (colors.clean, Tone::Good),
+                LocalStatus::Dirty => (colors.dirty, Tone::Warning),
+                LocalStatus::Staged => (colors.staged, Tone::Info),
+                LocalStatus::Conflicted => (colors.conflicted, Tone::Bad),
+                LocalStatus::Missing => (colors.missing, Tone::Bad),
+            },
+            Column::PrStatus => match worktree.status.pr_status {
+                Some(PrStatus::Open) => (colors.open, Tone::Good),
+                Some(PrStatus::Draft) => (colors.draft, Tone::Muted),
+                Some(PrStatus::Merged) => (colors.merged, Tone::Info),
+                Some(PrStatus::Closed) => (colors.closed, Tone::Bad),
+                None => return cell,
+            },
+            _ => return cell,
+        };
+        match configured {
+            Some(ColorName(color)) => output::paint_color(&cell, color),
+            None => output::paint(&cell, tone),
+        }
+    }
+}
+
+/// Border style for the rendered table
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum TableStyle {
+    #[default]
+    Psql,
+    Rounded,
+    Markdown,
+    Blank,
 }
 
-impl TableRowWithoutPr {
-    pub fn from_worktree(repo_name: &str, worktree: &WorktreeResult, use_emoji: bool) -> Self {
+/// How the Age column shows when a branch last had a commit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum DateStyle {
+    /// How long ago, e.g. "3 days"
+    #[default]
+    Relative,
+    /// Date and time in the local timezone, e.g. "2026-10-14 16:05"
+    Absolute,
+    /// RFC 3339 in the local timezone, e.g. "2026-10-14T16:05:09+02:00"
+    Iso,
+}
+
+/// How rows are arranged
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum GroupBy {
+    /// One table, with the repository named on every row
+    #[default]
+    None,
+    /// A header per repository with its worktrees nested underneath
+    Repo,
+    /// Sections for worktrees needing attention, active ones, prune candidates and the rest
+    Status,
+}
+
+/// Presentation options shared by every command that renders worktrees
+#[derive(Debug, Clone)]
+pub struct TableOptions {
+    use_emoji: bool,
+    show_pr_status: bool,
+    columns: Vec<Column>,
+    style: TableStyle,
+    widths: TableConfig,
+    group_by: GroupBy,
+    highlight: HashSet<String>,
+    dates: DateStyle,
+    show_diffstat: bool,
+    show_size: bool,
+    show_activity: bool,
+    show_path: bool,
+    show_ticket: bool,
+    max_width: Option<usize>,
+}
+
+impl Default for TableOptions {
+    fn default() -> Self {
         Self {
-            repo: repo_name.to_string(),
-            branch: worktree.branch.clone(),
-            local_status: if use_emoji {
-                EmojiStatus(worktree.status.local_status.clone()).to_string()
-            } else {
-                worktree.status.local_status.to_string()
-            },
-            commit_age: format_age(worktree.status.commit_timestamp),
-            commit_summary: worktree.status.commit_summary.clone(),
+            use_emoji: true,
+            show_pr_status: true,
+            columns: Column::ALL.to_vec(),
+            style: TableStyle::default(),
+            widths: TableConfig::default(),
+            group_by: GroupBy::default(),
+            highlight: HashSet::new(),
+            dates: DateStyle::default(),
+            show_diffstat: false,
+            show_size: false,
+            show_activity: false,
+            show_path: false,
+            show_ticket: false,
+            max_width: None,
+        }
+    }
+}
+
+impl TableOptions {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn emoji(mut self, use_emoji: bool) -> Self {
+        self.use_emoji = use_emoji;
+        self
+    }
+
+    /// Show or hide the PR Status column
+    pub fn pr_status(mut self, show_pr_status: bool) -> Self {
+        self.show_pr_status = show_pr_status;
+        self
+    }
+
+    /// Choose which columns to render, in order
+    pub fn columns(mut self, columns: Vec<Column>) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    pub fn style(mut self, style: TableStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Per-column width limits, usually taken from the config file
+    pub fn widths(mut self, widths: TableConfig) -> Self {
+        self.widths = widths;
+        self
+    }
+
+    pub fn group_by(mut self, group_by: GroupBy) -> Self {
+        self.group_by = group_by;
+        self
+    }
+
+    /// Emphasize the branches of these "repo/branch" worktrees
+    pub fn highlight(mut self, highlight: HashSet<String>) -> Self {
+        self.highlight = highlight;
+        self
+    }
+
+    pub fn dates(mut self, dates: DateStyle) -> Self {
+        self.dates = dates;
+        self
+    }
+
+    /// Add the Changes column after the PR status, for results scanned with diffstats
+    pub fn diffstat(mut self, show_diffstat: bool) -> Self {
+        self.show_diffstat = show_diffstat;
+        self
+    }
+
+    /// Add the Size column, for results scanned with sizes
+    pub fn size(mut self, show_size: bool) -> Self {
+        self.show_size = show_size;
+        self
+    }
+
+    /// Add the Last Active column after the Age
+    pub fn activity(mut self, show_activity: bool) -> Self {
+        self.show_activity = show_activity;
+        self
+    }
+
+    /// Add the Path column at the end, with each worktree's full directory
+    pub fn path(mut self, show_path: bool) -> Self {
+        self.show_path = show_path;
+        self
+    }
+
+    /// Add the Ticket column after the branch, with the ticket ID found in each branch name
+    pub fn ticket(mut self, show_ticket: bool) -> Self {
+        self.show_ticket = show_ticket;
+        self
+    }
+
+    /// Squeeze the table into this many terminal columns by cutting long cells
+    pub fn max_width(mut self, max_width: Option<usize>) -> Self {
+        self.max_width = max_width;
+        self
+    }
+
+    fn visible_columns(&self) -> Vec<Column> {
+        let mut columns: Vec<Column> = self
+            .columns
+            .iter()
+            .copied()
+            .filter(|column| self.show_pr_status || *column != Column::PrStatus)
+            // Grouped output names the repository in its header instead
+            .filter(|column| self.group_by != GroupBy::Repo || *column != Column::Repository)
+            .collect();
+        if self.show_ticket {
+            insert_after(&mut columns, Column::Ticket, &[Column::Branch]);
+        }
+        if self.show_diffstat {
+            insert_after(
+                &mut columns,
+                Column::Diffstat,
+                &[Column::PrStatus, Column::Local, Column::Branch],
+            );
+        }
+        if self.show_size {
+            insert_after(
+                &mut columns,
+                Column::Size,
+                &[
+                    Column::Diffstat,
+                    Column::PrStatus,
+                    Column::Local,
+                    Column::Branch,
+                ],
+            );
+        }
+        if self.show_activity {
+            insert_after(
+                &mut columns,
+                Column::Activity,
+                &[Column::Age, Column::PrStatus, Column::Local, Column::Branch],
+            );
+        }
+        if self.show_path && !columns.contains(&Column::Path) {
+            columns.push(Column::Path);
+        }
+        columns
+    }
+}
+
+/// Add an optional column right after the first of `anchors` present, unless already shown
+fn insert_after(columns: &mut Vec<Column>, column: Column, anchors: &[Column]) {
+    if columns.contains(&column) {
+        return;
+    }
+    let index = anchors
+        .iter()
+        .find_map(|anchor| columns.iter().position(|c| c == anchor))
+        .map_or(0, |index| index + 1);
+    columns.insert(index, column);
+}
+
+/// Where a column puts the ellipsis when its text is cut
+/// Paths keep their end by default, since that's where the worktree's own name is
+fn column_strategy(config: &TableConfig, column: &str) -> TruncateStrategy {
+    match config.columns.get(column) {
+        Some(column_config) => column_config.truncate,
+        None if column == Column::Path.key() => TruncateStrategy::Head,
+        None => TruncateStrategy::default(),
+    }
+}
+
+/// Apply the configured width limit for a column, if any
+fn fit_column(config: &TableConfig, column: &str, text: &str) -> String {
+    match config.columns.get(column) {
+        Some(column_config) => match column_config.max_width {
+            Some(max_width) => truncate(text, max_width, column_config.truncate),
+            None => text.to_string(),
+        },
+        None => text.to_string(),
+    }
+}
+
+/// Shorten text to at most `max_width` terminal columns, marking the cut with an ellipsis
+/// Wide characters such as CJK or emoji count as two columns, as they do in the table layout
+pub fn truncate(text: &str, max_width: usize, strategy: TruncateStrategy) -> String {
+    if text.width() <= max_width {
+        return text.to_string();
+    }
+    if max_width == 0 {
+        return String::new();
+    }
+
+    // Leave room for the ellipsis itself
+    let keep = max_width - 1;
+    match strategy {
+        TruncateStrategy::Head => format!("…{}", take_end(text, keep)),
+        TruncateStrategy::Tail => format!("{}…", take_start(text, keep)),
+        TruncateStrategy::Middle => {
+            let head_width = keep.div_ceil(2);
+            format!(
+                "{}…{}",
+                take_start(text, head_width),
+                take_end(text, keep - head_width)
+            )
+        }
+    }
+}
+
+/// Longest prefix of `text` that fits in `width` columns
+fn take_start(text: &str, width: usize) -> &str {
+    let mut used = 0;
+    for (index, c) in text.char_indices() {
+        used += c.width().unwrap_or(0);
+        if used > width {
+            return &text[..index];
+        }
+    }
+    text
+}
+
+/// Longest suffix of `text` that fits in `width` columns
+fn take_end(text: &str, width: usize) -> &str {
+    let mut used = 0;
+    for (index, c) in text.char_indices().rev() {
+        used += c.width().unwrap_or(0);
+        if used > width {
+            return &text[index + c.len_utf8()..];
+        }
+    }
+    text
+}
+
+/// Put a space after every emoji of a rendered table, for terminals that draw emoji one
+/// column wide while the layout reserved two for them
+fn pad_narrow_emoji(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        result.push(c);
+        if chars.peek() == Some(&EMOJI_PRESENTATION) {
+            result.push(EMOJI_PRESENTATION);
+            chars.next();
+            result.push(' ');
+        } else if is_wide_emoji(c) {
+            result.push(' ');
+        }
+    }
+    result
+}
+
+/// Variation selector asking for emoji presentation, as in "⬆️"
+const EMOJI_PRESENTATION: char = '\u{FE0F}';
+
+fn is_wide_emoji(c: char) -> bool {
+    c.width() == Some(2) && matches!(c as u32, 0x2300..=0x2BFF | 0x1F000..=0x1FAFF)
+}
+
+/// The PR status, followed by the PR's number when its URL is known and the review decision
+/// while it is open, e.g. "Open #42, approved"
+fn format_pr_status(
+    pr_status: &Option<PrStatus>,
+    pr_url: Option<&str>,
+    review: Option<ReviewDecision>,
+) -> String {
+    let number = pr_url
+        .and_then(|url| url.rsplit('/').next())
+        .filter(|number| number.parse::<u64>().is_ok());
+    let status = match (pr_status, number) {
+        (Some(status), Some(number)) => format!("{} #{}", status, number),
+        (Some(status), None) => status.to_string(),
+        (None, _) => return "-".to_string(),
+    };
+    match (pr_status, review) {
+        (Some(PrStatus::Open | PrStatus::Draft), Some(review)) => {
+            format!("{}, {}", status, review)
         }
+        _ => status,
     }
 }
 
-fn format_pr_status(pr_status: &Option<PrStatus>) -> String {
-    match pr_status {
-        Some(status) => status.to_string(),
-        None => "-".to_string(),
+/// A branch's changes as the Changes column shows them, e.g. "+120 −30 (4 files)"
+fn format_diffstat(diffstat: Option<DiffStat>) -> String {
+    match diffstat {
+        Some(stat) if stat.files_changed > 0 => format!(
+            "+{} −{} ({} file{})",
+            stat.insertions,
+            stat.deletions,
+            stat.files_changed,
+            if stat.files_changed == 1 { "" } else { "s" }
+        ),
+        _ => "-".to_string(),
     }
 }
 
-fn format_age(timestamp: i64) -> String {
+/// A number of bytes in binary units, e.g. "1.5 KB" or "3.0 GB"
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// A commit time as the Age column shows it
+pub fn format_date(timestamp: i64, style: DateStyle) -> String {
+    let local = chrono::DateTime::from_timestamp(timestamp, 0)
+        .filter(|_| timestamp != 0)
+        .map(|time| time.with_timezone(&chrono::Local));
+    match (style, local) {
+        (DateStyle::Relative, _) => format_age(timestamp),
+        (_, None) => "Unknown".to_string(),
+        (DateStyle::Absolute, Some(time)) => time.format("%Y-%m-%d %H:%M").to_string(),
+        (DateStyle::Iso, Some(time)) => time.to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+    }
+}
+
+pub(crate) fn format_age(timestamp: i64) -> String {
     if timestamp == 0 {
         return "Unknown".to_string();
     }
@@ -111,42 +627,546 @@ fn format_age(timestamp: i64) -> String {
     }
 }
 
-pub fn create_table(repo_results: &[RepoResult], use_emoji: bool, show_pr_status: bool) -> String {
-    if show_pr_status {
-        let mut rows = Vec::new();
-
-        for repo_result in repo_results {
-            for worktree in &repo_result.worktrees {
-                rows.push(TableRow::from_worktree(
-                    &repo_result.name,
-                    worktree,
-                    use_emoji,
-                ));
-            }
+pub fn create_table(repo_results: &[RepoResult], options: &TableOptions) -> String {
+    let mut columns = options.visible_columns();
+    // Forgotten stashes are easy to lose with the worktree, so show them whenever there are any
+    if repo_results
+        .iter()
+        .flat_map(|repo_result| &repo_result.worktrees)
+        .any(|worktree| worktree.status.stash_count > 0)
+    {
+        insert_after(
+            &mut columns,
+            Column::Stashes,
+            &[Column::Local, Column::Branch],
+        );
+    }
+    // Grouped tables are indented under their header
+    let nested_width = options.max_width.map(|width| width.saturating_sub(2));
+    let rendered = match options.group_by {
+        GroupBy::None => render(
+            repo_results.iter().flat_map(|repo_result| {
+                repo_result.worktrees.iter().map(|w| (&repo_result.name, w))
+            }),
+            &columns,
+            options,
+            options.max_width,
+        ),
+        GroupBy::Repo => {
+            let groups: Vec<String> = repo_results
+                .iter()
+                .filter_map(|repo_result| {
+                    let table = render(
+                        repo_result.worktrees.iter().map(|w| (&repo_result.name, w)),
+                        &columns,
+                        options,
+                        nested_width,
+                    )?;
+                    let header = format!(
+                        "{} ({})",
+                        output::paint(&repo_result.name, Tone::Info),
+                        repo_result.worktrees.len()
+                    );
+                    Some(nest(&header, &table))
+                })
+                .collect();
+            (!groups.is_empty()).then(|| groups.join("\n\n"))
+        }
+        GroupBy::Status => {
+            let now = chrono::Utc::now().timestamp();
+            let rows: Vec<_> = repo_results
+                .iter()
+                .flat_map(|repo_result| {
+                    repo_result
+                        .worktrees
+                        .iter()
+                        .map(move |w| (TriageSection::of(w, now), (&repo_result.name, w)))
+                })
+                .collect();
+            let sections: Vec<String> = TriageSection::ALL
+                .iter()
+                .filter_map(|section| {
+                    let section_rows: Vec<_> = rows
+                        .iter()
+                        .filter(|(s, _)| s == section)
+                        .map(|(_, row)| *row)
+                        .collect();
+                    let count = section_rows.len();
+                    let table = render(section_rows.into_iter(), &columns, options, nested_width)?;
+                    let tone = match section {
+                        TriageSection::NeedsAttention => Tone::Bad,
+                        TriageSection::Active => Tone::Good,
+                        TriageSection::PruneCandidates => Tone::Warning,
+                        TriageSection::Other => Tone::Muted,
+                    };
+                    let header =
+                        format!("{} ({})", output::paint(&section.to_string(), tone), count);
+                    Some(nest(&header, &table))
+                })
+                .collect();
+            (!sections.is_empty()).then(|| sections.join("\n\n"))
         }
+    };
+
+    rendered.unwrap_or_else(|| "No work in progress branches found.".to_string())
+}
 
-        if rows.is_empty() {
-            return "No work in progress branches found.".to_string();
+/// A group header with its table indented underneath
+fn nest(header: &str, table: &str) -> String {
+    let body: Vec<String> = table.lines().map(|line| format!("  {}", line)).collect();
+    format!("{}\n{}", header, body.join("\n"))
+}
+
+/// Status counts per repository plus an overall row, for `list --summary`
+/// PR columns appear only when `show_pr_status` is set
+pub fn create_summary_table(
+    counters: &[(String, StatusCounters)],
+    show_pr_status: bool,
+    style: TableStyle,
+) -> String {
+    if counters.is_empty() {
+        return "No work in progress branches found.".to_string();
+    }
+
+    let mut header = vec![
+        "Repository",
+        "Total",
+        "Clean",
+        "Dirty",
+        "Staged",
+        "Conflicted",
+        "Missing",
+    ];
+    if show_pr_status {
+        header.extend(["Open", "Draft", "Merged", "Closed"]);
+    }
+    let mut builder = Builder::default();
+    builder.push_record(header);
+
+    let overall = counters
+        .iter()
+        .fold(StatusCounters::new(), |total, (_, c)| total.merge(c));
+    for (name, c) in counters.iter().chain([&("All".to_string(), overall)]) {
+        let mut row = vec![
+            name.clone(),
+            c.total().to_string(),
+            c.clean.to_string(),
+            c.dirty.to_string(),
+            c.staged.to_string(),
+            c.conflicted.to_string(),
+            c.missing.to_string(),
+        ];
+        if show_pr_status {
+            row.extend([c.open, c.draft, c.merged, c.closed].map(|n| n.to_string()));
         }
+        builder.push_record(row);
+    }
 
-        Table::new(rows).with(Style::psql()).to_string()
-    } else {
-        let mut rows = Vec::new();
+    let mut table = builder.build();
+    match style {
+        TableStyle::Psql => table.with(Style::psql()),
+        TableStyle::Rounded => table.with(Style::rounded()),
+        TableStyle::Markdown => table.with(Style::markdown()),
+        TableStyle::Blank => table.with(Style::blank()),
+    };
+    table.to_string()
+}
 
-        for repo_result in repo_results {
-            for worktree in &repo_result.worktrees {
-                rows.push(TableRowWithoutPr::from_worktree(
-                    &repo_result.name,
-                    worktree,
-                    use_emoji,
-                ));
+/// Render rows of (repository name, worktree) as a table, or None when there are no rows
+fn render<'a>(
+    rows: impl Iterator<Item = (&'a String, &'a WorktreeResult)>,
+    columns: &[Column],
+    options: &TableOptions,
+    max_width: Option<usize>,
+) -> Option<String> {
+    let headers: Vec<&str> = columns
+        .iter()
+        .map(|column| match column {
+            // A column of dates isn't an age
+            Column::Age if options.dates != DateStyle::Relative => "Date",
+            column => column.header(),
+        })
+        .collect();
+
+    let mut rows: Vec<_> = rows
+        .map(|(repo_name, worktree)| {
+            let cells: Vec<String> = columns
+                .iter()
+                .map(|column| {
+                    let cell = column.cell(repo_name, worktree, options);
+                    fit_column(&options.widths, column.key(), &cell)
+                })
+                .collect();
+            (repo_name, worktree, cells)
+        })
+        .collect();
+    if rows.is_empty() {
+        return None;
+    }
+
+    if let Some(max_width) = max_width {
+        let natural: Vec<usize> = headers
+            .iter()
+            .enumerate()
+            .map(|(i, header)| {
+                rows.iter()
+                    .map(|(_, _, cells)| cells[i].width())
+                    .fold(header.width(), usize::max)
+            })
+            .collect();
+        let available = max_width.saturating_sub(frame_width(&headers, options.style));
+        let limits = shrink_to_fit(columns, &natural, available);
+        for (_, _, cells) in &mut rows {
+            for ((cell, column), limit) in cells.iter_mut().zip(columns).zip(&limits) {
+                if cell.width() > *limit {
+                    *cell = truncate(cell, *limit, column_strategy(&options.widths, column.key()));
+                }
             }
         }
+    }
+
+    let mut builder = Builder::default();
+    builder.push_record(headers);
+    for (repo_name, worktree, cells) in rows {
+        builder.push_record(cells.into_iter().zip(columns).map(|(cell, column)| {
+            // Color after fitting, so escape codes never count towards the width limit
+            let cell = column.paint(cell, worktree, &options.widths.colors);
+            // Clicking the PR status opens the pull request, in terminals that support it
+            // and the ticket its tracker page
+            let cell = match (column, &worktree.status.pr_url) {
+                (Column::PrStatus, Some(url)) => output::hyperlink(&cell, url),
+                (Column::Ticket, _) => {
+                    let tickets = &options.widths.tickets;
+                    match tickets
+                        .ticket_id(&worktree.branch)
+                        .and_then(|ticket| tickets.ticket_url(&ticket))
+                    {
+                        Some(url) => output::hyperlink(&cell, &url),
+                        None => cell,
+                    }
+                }
+                _ => cell,
+            };
+            if *column == Column::Branch
+                && options
+                    .highlight
+                    .contains(&format!("{}/{}", repo_name, worktree.branch))
+            {
+                output::paint(&cell, Tone::Changed)
+            } else {
+                cell
+            }
+        }));
+    }
+
+    let mut table = builder.build();
+    apply_style(&mut table, options.style);
 
-        if rows.is_empty() {
-            return "No work in progress branches found.".to_string();
+    Some(match options.widths.emoji_width {
+        EmojiWidth::Wide => table.to_string(),
+        EmojiWidth::Narrow => pad_narrow_emoji(&table.to_string()),
+    })
+}
+
+fn apply_style(table: &mut Table, style: TableStyle) {
+    match style {
+        TableStyle::Psql => table.with(Style::psql()),
+        TableStyle::Rounded => table.with(Style::rounded()),
+        TableStyle::Markdown => table.with(Style::markdown()),
+        TableStyle::Blank => table.with(Style::blank()),
+    };
+}
+
+/// Columns taken up by borders and padding rather than cell text
+fn frame_width(headers: &[&str], style: TableStyle) -> usize {
+    let mut builder = Builder::default();
+    builder.push_record(headers.iter().copied());
+    let mut table = builder.build();
+    apply_style(&mut table, style);
+    let text: usize = headers.iter().map(|header| header.width()).sum();
+    table.total_width().saturating_sub(text)
+}
+
+/// Pure function choosing column widths that fit in `available` columns of text
+/// The commit summary gives way first, then the path, the branch and the repository, each down to
+/// `MIN_FLEXIBLE_WIDTH`; every other column keeps its natural width
+fn shrink_to_fit(columns: &[Column], natural: &[usize], available: usize) -> Vec<usize> {
+    let mut widths = natural.to_vec();
+    let mut excess = widths.iter().sum::<usize>().saturating_sub(available);
+    for flexible in [
+        Column::LastCommit,
+        Column::Path,
+        Column::Branch,
+        Column::Repository,
+    ] {
+        if excess == 0 {
+            break;
+        }
+        if let Some(i) = columns.iter().position(|column| *column == flexible) {
+            let cut = excess.min(widths[i].saturating_sub(MIN_FLEXIBLE_WIDTH));
+            widths[i] -= cut;
+            excess -= cut;
         }
+    }
+    widths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pr_status_option_hides_pr_column() {
+        let options = TableOptions::new().pr_status(false);
+        assert!(!options.visible_columns().contains(&Column::PrStatus));
+        assert_eq!(options.visible_columns().len(), Column::ALL.len() - 1);
+    }
+
+    #[test]
+    fn path_column_goes_last_and_keeps_its_end_when_cut() {
+        let options = TableOptions::new().path(true).activity(true);
+        assert_eq!(options.visible_columns().last(), Some(&Column::Path));
+        assert_eq!(
+            column_strategy(&TableConfig::default(), Column::Path.key()),
+            TruncateStrategy::Head
+        );
+        assert_eq!(
+            column_strategy(&TableConfig::default(), Column::Branch.key()),
+            TruncateStrategy::Tail
+        );
+    }
+
+    #[test]
+    fn ticket_column_follows_the_branch() {
+        let options = TableOptions::new().ticket(true).path(true);
+        let columns = options.visible_columns();
+        let branch = columns.iter().position(|c| *c == Column::Branch).unwrap();
+        assert_eq!(columns[branch + 1], Column::Ticket);
+    }
+
+    #[test]
+    fn configured_icons_replace_the_icon_set() {
+        let mut config = TableConfig {
+            icon_set: IconSet::Ascii,
+            ..Default::default()
+        };
+        assert_eq!(local_icon(&config, &LocalStatus::Dirty), "*");
+        assert_eq!(lock_icon(&config), "#");
+
+        config.icons.dirty = Some("~".to_string());
+        assert_eq!(local_icon(&config, &LocalStatus::Dirty), "~");
+        assert_eq!(local_icon(&config, &LocalStatus::Clean), "=");
+        assert_eq!(
+            local_icon(&TableConfig::default(), &LocalStatus::Clean),
+            "✅"
+        );
+    }
+
+    #[test]
+    fn pr_status_shows_the_number_from_the_url() {
+        let url = "https://github.com/acme/proj/pull/42";
+        assert_eq!(
+            format_pr_status(&Some(PrStatus::Open), Some(url), None),
+            "Open #42"
+        );
+        assert_eq!(
+            format_pr_status(&Some(PrStatus::Merged), None, None),
+            "Merged"
+        );
+        assert_eq!(format_pr_status(&None, Some(url), None), "-");
+        assert_eq!(
+            format_pr_status(
+                &Some(PrStatus::Open),
+                Some(url),
+                Some(ReviewDecision::ChangesRequested)
+            ),
+            "Open #42, changes requested"
+        );
+        assert_eq!(
+            format_pr_status(
+                &Some(PrStatus::Merged),
+                Some(url),
+                Some(ReviewDecision::Approved)
+            ),
+            "Merged #42"
+        );
+    }
+
+    #[test]
+    fn diffstat_column_follows_the_pr_status() {
+        let options = TableOptions::new().diffstat(true);
+        let columns = options.visible_columns();
+        let pr = columns.iter().position(|c| *c == Column::PrStatus).unwrap();
+        assert_eq!(columns[pr + 1], Column::Diffstat);
+
+        let stat = DiffStat {
+            files_changed: 1,
+            insertions: 12,
+            deletions: 3,
+        };
+        assert_eq!(format_diffstat(Some(stat)), "+12 −3 (1 file)");
+        assert_eq!(format_diffstat(Some(DiffStat::default())), "-");
+    }
+
+    #[test]
+    fn format_size_uses_binary_units() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024), "3.0 GB");
+    }
+
+    #[test]
+    fn dates_can_be_shown_absolute() {
+        let timestamp = 1_700_000_000;
+        let iso = format_date(timestamp, DateStyle::Iso);
+        assert_eq!(
+            chrono::DateTime::parse_from_rfc3339(&iso)
+                .unwrap()
+                .timestamp(),
+            timestamp
+        );
+        assert_eq!(format_date(timestamp, DateStyle::Absolute).len(), 16);
+        assert_eq!(format_date(0, DateStyle::Absolute), "Unknown");
+        assert_eq!(
+            format_date(timestamp, DateStyle::Relative),
+            format_age(timestamp)
+        );
+    }
+
+    #[test]
+    fn truncate_leaves_short_text_alone() {
+        assert_eq!(truncate("feature", 10, TruncateStrategy::Tail), "feature");
+    }
+
+    #[test]
+    fn truncate_places_ellipsis_by_strategy() {
+        let branch = "PROJ-1234-fix-login";
+        assert_eq!(truncate(branch, 10, TruncateStrategy::Tail), "PROJ-1234…");
+        assert_eq!(truncate(branch, 10, TruncateStrategy::Head), "…fix-login");
+        assert_eq!(truncate(branch, 10, TruncateStrategy::Middle), "PROJ-…ogin");
+    }
+
+    #[test]
+    fn truncate_counts_wide_characters_as_two_columns() {
+        let summary = "修复登录页面的错误";
+        assert_eq!(truncate(summary, 7, TruncateStrategy::Tail), "修复登…");
+        assert_eq!(truncate(summary, 7, TruncateStrategy::Head), "…的错误");
+        assert_eq!(
+            truncate("🔧 fix build", 6, TruncateStrategy::Tail),
+            "🔧 fi…"
+        );
+        assert!(truncate(summary, 8, TruncateStrategy::Middle).width() <= 8);
+    }
+
+    #[test]
+    fn shrinking_cuts_the_summary_before_the_branch() {
+        let columns = [Column::Branch, Column::Local, Column::LastCommit];
+        assert_eq!(shrink_to_fit(&columns, &[30, 8, 60], 100), [30, 8, 60]);
+        assert_eq!(shrink_to_fit(&columns, &[30, 8, 60], 80), [30, 8, 42]);
+        assert_eq!(shrink_to_fit(&columns, &[30, 8, 60], 40), [16, 8, 16]);
+    }
+
+    #[test]
+    fn max_width_keeps_rows_within_the_terminal() {
+        use crate::core::WorktreeStatus;
+        use std::path::PathBuf;
+
+        let results = vec![RepoResult {
+            name: "api".to_string(),
+            path: PathBuf::from("/repos/api"),
+            worktrees: vec![WorktreeResult {
+                branch: "PROJ-1234-rework-the-session-token-refresh".to_string(),
+                path: PathBuf::from("/repos/api/PROJ-1234"),
+                status: WorktreeStatus {
+                    commit_summary: "Refresh session tokens in the background so that long \
+                                     uploads don't fail halfway"
+                        .to_string(),
+                    ..Default::default()
+                },
+            }],
+        }];
+        let options = TableOptions::new().emoji(false).pr_status(false);
+
+        let wide = create_table(&results, &options.clone());
+        assert!(wide.contains("halfway"));
+
+        let narrow = create_table(&results, &options.max_width(Some(100)));
+        assert!(narrow.lines().all(|line| line.width() <= 100));
+        assert!(narrow.contains("Refresh session token…"));
+        assert!(!narrow.contains("halfway"));
+        assert!(narrow.contains("PROJ-1234-rework-the-session-token-refresh"));
+    }
+
+    #[test]
+    fn narrow_emoji_padding_keeps_rows_aligned() {
+        assert_eq!(pad_narrow_emoji("| ✅ Clean |"), "| ✅  Clean |");
+        assert_eq!(
+            pad_narrow_emoji("| ⬆\u{FE0F} Ahead |"),
+            "| ⬆\u{FE0F}  Ahead |"
+        );
+        assert_eq!(pad_narrow_emoji("| 修复 |"), "| 修复 |");
+    }
+
+    #[test]
+    fn group_by_repo_names_each_repository_once() {
+        use crate::core::WorktreeStatus;
+        use std::path::PathBuf;
+
+        let repo = |name: &str, branches: &[&str]| RepoResult {
+            name: name.to_string(),
+            path: PathBuf::from(format!("/repos/{}", name)),
+            worktrees: branches
+                .iter()
+                .map(|branch| WorktreeResult {
+                    branch: branch.to_string(),
+                    path: PathBuf::from(format!("/repos/{}/{}", name, branch)),
+                    status: WorktreeStatus {
+                        commit_summary: "wip".to_string(),
+                        ..Default::default()
+                    },
+                })
+                .collect(),
+        };
+        let results = vec![repo("api", &["fix-auth", "add-cache"]), repo("web", &[])];
+
+        let output = create_table(
+            &results,
+            &TableOptions::new()
+                .emoji(false)
+                .pr_status(false)
+                .group_by(GroupBy::Repo),
+        );
+        assert!(output.starts_with("api (2)\n  "));
+        assert!(!output.contains("Repository"));
+        assert!(!output.contains("web"));
+        assert_eq!(output.matches("api").count(), 1);
+    }
+
+    #[test]
+    fn stashes_column_appears_only_with_stashes() {
+        use crate::core::WorktreeStatus;
+        use std::path::PathBuf;
+
+        let results = |stash_count| {
+            vec![RepoResult {
+                name: "api".to_string(),
+                path: PathBuf::from("/repos/api"),
+                worktrees: vec![WorktreeResult {
+                    branch: "fix-auth".to_string(),
+                    path: PathBuf::from("/repos/api/fix-auth"),
+                    status: WorktreeStatus {
+                        commit_summary: "wip".to_string(),
+                        stash_count,
+                        ..Default::default()
+                    },
+                }],
+            }]
+        };
+        let options = TableOptions::new().emoji(false).pr_status(false);
 
-        Table::new(rows).with(Style::psql()).to_string()
+        assert!(!create_table(&results(0), &options).contains("Stashes"));
+        let output = create_table(&results(2), &options);
+        let header = output.lines().next().unwrap();
+        assert!(header.find("Local").unwrap() < header.find("Stashes").unwrap());
     }
 }
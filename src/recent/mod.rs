@@ -0,0 +1,104 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::config;
+use crate::state::StateFile;
+
+/// Only this many worktrees are remembered
+const MAX_VISITS: usize = 50;
+
+/// Worktrees most recently visited with `switch` or `add`, for `switch -` and `gwm recent`
+/// Stored as JSON in the gwm data directory, newest first
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Visit {
+    pub repo: String,
+    pub branch: String,
+    pub path: PathBuf,
+    pub visited_at: i64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecentWorktrees {
+    /// Newest first, one entry per worktree directory
+    pub visits: Vec<Visit>,
+}
+
+impl RecentWorktrees {
+    /// Move the worktree to the front, adding it if it wasn't visited before
+    pub fn record(&mut self, visit: Visit) {
+        self.visits.retain(|v| v.path != visit.path);
+        self.visits.insert(0, visit);
+        self.visits.truncate(MAX_VISITS);
+    }
+
+    /// The worktree to return to with `switch -`: the newest one the current directory isn't in
+    pub fn previous(&self, current_dir: &Path) -> Option<&Visit> {
+        self.visits
+            .iter()
+            .find(|visit| !current_dir.starts_with(&visit.path))
+    }
+
+    pub fn load(path: PathBuf) -> Result<Self> {
+        StateFile::new(path).load()
+    }
+
+    /// Location of the recent worktrees file
+    pub fn path() -> Option<PathBuf> {
+        config::data_dir().map(|dir| dir.join("recent.json"))
+    }
+}
+
+/// Remember a visit to a worktree
+/// Failures are ignored so that the history can never break a command
+pub fn record(repo: &str, branch: &str, path: &Path) {
+    let Some(state_path) = RecentWorktrees::path() else {
+        return;
+    };
+    let visit = Visit {
+        repo: repo.to_string(),
+        branch: branch.to_string(),
+        path: path.canonicalize().unwrap_or_else(|_| path.to_path_buf()),
+        visited_at: chrono::Utc::now().timestamp(),
+    };
+    let _ = StateFile::new(state_path).update(|recent: &mut RecentWorktrees| recent.record(visit));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn visit(branch: &str, visited_at: i64) -> Visit {
+        Visit {
+            repo: "api".to_string(),
+            branch: branch.to_string(),
+            path: PathBuf::from("/repos/api").join(branch),
+            visited_at,
+        }
+    }
+
+    #[test]
+    fn revisited_worktrees_move_to_the_front() {
+        let mut recent = RecentWorktrees::default();
+        recent.record(visit("a", 1));
+        recent.record(visit("b", 2));
+        recent.record(visit("a", 3));
+
+        let branches: Vec<_> = recent.visits.iter().map(|v| v.branch.as_str()).collect();
+        assert_eq!(branches, ["a", "b"]);
+        assert_eq!(recent.visits[0].visited_at, 3);
+    }
+
+    #[test]
+    fn previous_skips_the_worktree_we_are_in() {
+        let mut recent = RecentWorktrees::default();
+        recent.record(visit("a", 1));
+        recent.record(visit("b", 2));
+
+        let inside_b = Path::new("/repos/api/b/src");
+        assert_eq!(recent.previous(inside_b).unwrap().branch, "a");
+        assert_eq!(recent.previous(Path::new("/home")).unwrap().branch, "b");
+    }
+}
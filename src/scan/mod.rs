@@ -0,0 +1,662 @@
+//! Repository discovery and worktree status engine
+//! Repositories are the directories directly under a search path that contain a `.git`,
+//! and only bare repositories (the gwm layout) contribute worktrees
+
+use anyhow::{Result, anyhow};
+use futures::future::{join_all, try_join_all};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, mpsc};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::cache::{CachedStatus, StatusCache};
+use crate::core::{RepoResult, WorktreeResult, WorktreeStatus};
+use crate::forge::{Forge, Forges, PrAuthor, PrInfo};
+use crate::git::{
+    Backend, BackendGitClient, GitRepository, LocalStatus, SystemGitClient, WorktreeInfo,
+    is_detached_label,
+};
+use crate::github;
+use crate::output::prompt::PromptStatus;
+
+/// Settings for a scan, built like `ScanOptions::new().pr_status(true)`
+#[derive(Clone, Default)]
+pub struct ScanOptions {
+    fetch_pr_status: bool,
+    backend: Backend,
+    cache: bool,
+    status_cache: Option<Arc<Mutex<StatusCache>>>,
+    include_slow: bool,
+    include_main: bool,
+    diffstat: bool,
+    size: bool,
+    pr_author: PrAuthor,
+    forges: Forges,
+}
+
+impl ScanOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Look up each branch's pull request on GitHub, Bitbucket or Azure DevOps (requires a token
+    /// for the forge)
+    pub fn pr_status(mut self, fetch_pr_status: bool) -> Self {
+        self.fetch_pr_status = fetch_pr_status;
+        self
+    }
+
+    /// Whose pull requests to match to branches
+    pub fn pr_author(mut self, pr_author: PrAuthor) -> Self {
+        self.pr_author = pr_author;
+        self
+    }
+
+    /// Forge settings and tokens to look pull requests up with, per repository remote
+    pub fn forges(mut self, forges: Forges) -> Self {
+        self.forges = forges;
+        self
+    }
+
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Wait for repositories that are slow to answer instead of skipping them
+    pub fn include_slow(mut self, include_slow: bool) -> Self {
+        self.include_slow = include_slow;
+        self
+    }
+
+    /// Also report main and master worktrees, including the working copy of non-bare
+    /// repositories, to check that they are clean and up to date
+    pub fn include_main(mut self, include_main: bool) -> Self {
+        self.include_main = include_main;
+        self
+    }
+
+    /// Also measure each branch's changes since it forked from the base branch, which costs a
+    /// tree diff per worktree
+    pub fn diffstat(mut self, diffstat: bool) -> Self {
+        self.diffstat = diffstat;
+        self
+    }
+
+    /// Also add up the size of each worktree directory, which reads every file's metadata
+    pub fn size(mut self, size: bool) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Reuse and update cached status for worktrees whose HEAD and index are unchanged, loading
+    /// the cache file before the scan and saving it after
+    pub fn cache(mut self, cache: bool) -> Self {
+        self.cache = cache;
+        self
+    }
+}
+
+/// Scan every repository under `search_path` concurrently
+/// Repositories that fail to scan are skipped with a warning
+pub async fn scan(search_path: &Path, options: &ScanOptions) -> Result<Vec<RepoResult>> {
+    // Without a token every repository would fail the same way, so fail once up front
+    if options.fetch_pr_status && !options.forges.any_available() {
+        return Err(anyhow!(
+            "GITHUB_TOKEN environment variable not set, gh is not logged in, and no Bitbucket or Azure DevOps token is set"
+        ));
+    }
+
+    let repo_paths = discover_repositories(search_path, options.include_slow)?;
+    let mut options = options.clone();
+    if options.cache {
+        options.status_cache = Some(Arc::new(Mutex::new(StatusCache::load())));
+    }
+    let repo_tasks = repo_paths.iter().cloned().map(|repo_path| {
+        let options = options.clone();
+        tokio::spawn(async move { scan_repository(&repo_path, &options).await })
+    });
+
+    // One unreadable repository (or a panic while scanning it) shouldn't hide all the others
+    let mut repos = Vec::new();
+    for (repo_path, outcome) in repo_paths.iter().zip(join_all(repo_tasks).await) {
+        match outcome {
+            Ok(Ok(repo)) => repos.push(repo),
+            Ok(Err(e)) => warn!("Skipping {}: {:#}", repo_name(repo_path), e),
+            Err(e) => warn!("Skipping {}: scan failed: {}", repo_name(repo_path), e),
+        }
+    }
+
+    // A stale or unwritable cache only costs speed, so ignore save failures
+    if let Some(cache) = &options.status_cache {
+        let _ = cache.lock().unwrap().save();
+    }
+    Ok(repos)
+}
+
+/// Scan a single repository, computing the status of each work-in-progress worktree
+pub async fn scan_repository(repo_path: &Path, options: &ScanOptions) -> Result<RepoResult> {
+    let repo_path_str = repo_path.to_string_lossy().to_string();
+
+    let discovery_path = repo_path_str.clone();
+    let backend = options.backend;
+    let include_main = options.include_main;
+    let worktrees = tokio::task::spawn_blocking(move || {
+        list_wip_worktrees(&discovery_path, backend, include_main)
+    })
+    .await??;
+
+    if worktrees.is_empty() {
+        return Ok(RepoResult {
+            name: repo_name(repo_path),
+            path: repo_path.to_path_buf(),
+            worktrees: Vec::new(),
+        });
+    }
+
+    let pr_matches: HashMap<String, PrInfo> = if options.fetch_pr_status {
+        fetch_pull_requests(
+            &repo_path_str,
+            &worktrees,
+            options.pr_author,
+            &options.forges,
+        )
+        .await?
+    } else {
+        HashMap::new()
+    };
+
+    // Stashes and the base branch belong to the repository rather than to any one worktree
+    let diffstat = options.diffstat;
+    let size = options.size;
+    let repo_dir = repo_path_str.clone();
+    let (base_branch, stash_counts) = tokio::task::spawn_blocking(move || {
+        let repo = GitRepository::new(&repo_dir, SystemGitClient)?;
+        let base_branch = if diffstat { repo.base_branch() } else { None };
+        let stash_counts = repo.stash_counts().unwrap_or_default();
+        anyhow::Ok((base_branch, stash_counts))
+    })
+    .await??;
+
+    // Process all worktrees for this repo concurrently on the blocking pool
+    let worktree_tasks = worktrees.into_iter().map(|worktree| {
+        let repo_path = repo_path_str.clone();
+        let cache = options.status_cache.clone();
+        let pr = pr_matches.get(&worktree.branch).cloned();
+        let base_branch = base_branch.clone();
+        let stash_count = stash_counts.get(&worktree.branch).copied().unwrap_or(0);
+        tokio::task::spawn_blocking(move || -> Result<WorktreeResult> {
+            let mut status = worktree_status(&repo_path, &worktree, backend, cache.as_deref())?;
+            status.stash_count = stash_count;
+            if size && status.local_status != LocalStatus::Missing {
+                status.size_bytes = Some(dir_size(Path::new(&worktree.path)));
+            }
+            if let Some(base_branch) = base_branch {
+                status.diffstat = GitRepository::new(&repo_path, SystemGitClient)?
+                    .diff_stat(worktree.revision(), &base_branch)
+                    .ok();
+            }
+            if let Some(pr) = pr {
+                status.pr_status = Some(pr.status);
+                status.pr_closed_at = pr.closed_at;
+                status.pr_url = pr.url;
+                status.pr_review = pr.review;
+            }
+            Ok(WorktreeResult {
+                branch: worktree.branch,
+                path: PathBuf::from(worktree.path),
+                status,
+            })
+        })
+    });
+    let worktree_results = try_join_all(worktree_tasks)
+        .await?
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(RepoResult {
+        name: repo_name(repo_path),
+        path: repo_path.to_path_buf(),
+        worktrees: worktree_results,
+    })
+}
+
+/// How long a directory may take to answer a stat before discovery gives up on it
+pub const STAT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Directories directly under `search_path` that look like git repositories
+/// Unless `include_slow` is set, directories that don't answer a stat within STAT_TIMEOUT,
+/// such as unreachable network mounts, are skipped with a warning instead of hanging the scan
+pub fn discover_repositories(search_path: &Path, include_slow: bool) -> Result<Vec<PathBuf>> {
+    let entries = fs::read_dir(search_path).map_err(|e| {
+        anyhow!(
+            "Failed to read directory '{}': {}",
+            search_path.display(),
+            e
+        )
+    })?;
+
+    let mut candidates = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        // File type comes from the directory listing itself, so this never blocks on a mount
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() || file_type.is_symlink() {
+            candidates.push(entry.path());
+        }
+    }
+
+    if include_slow {
+        return Ok(candidates
+            .into_iter()
+            .filter(|path| path.is_dir() && path.join(".git").exists())
+            .collect());
+    }
+
+    // Probe every candidate at once, so a dead mount costs one timeout rather than one each
+    let probes: Vec<_> = candidates
+        .into_iter()
+        .map(|path| {
+            let (sender, receiver) = mpsc::channel();
+            let probe_path = path.clone();
+            std::thread::spawn(move || {
+                let _ = sender.send(probe_path.is_dir() && probe_path.join(".git").exists());
+            });
+            (path, receiver)
+        })
+        .collect();
+
+    let deadline = Instant::now() + STAT_TIMEOUT;
+    let mut repos = Vec::new();
+    for (path, receiver) in probes {
+        match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(true) => repos.push(path),
+            Ok(false) => {}
+            Err(_) => warn!(
+                "Skipping {}: no response within {}s, is it on an unreachable network mount? \
+                 Use --include-slow to wait for it",
+                path.display(),
+                STAT_TIMEOUT.as_secs()
+            ),
+        }
+    }
+    Ok(repos)
+}
+
+/// Total size of the files under `path`, without following symlinks
+/// Blocking: call from a blocking thread in async code
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => dir_size(&entry.path()),
+            Ok(file_type) if file_type.is_file() => entry.metadata().map_or(0, |m| m.len()),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Repository name as shown to the user: the directory's file name
+pub fn repo_name(repo_path: &Path) -> String {
+    repo_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown")
+        .to_string()
+}
+
+/// Worktree directories inside a repository directory, where `gwm add` creates them: those
+/// with a `.git` file, nested under branch name prefixes like `feature/`
+pub fn worktree_dirs(repo_path: &Path) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let Ok(entries) = fs::read_dir(repo_path) else {
+        return found;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if entry.file_name() == ".git" || !entry.file_type().is_ok_and(|t| t.is_dir()) {
+            continue;
+        }
+        let dot_git = path.join(".git");
+        if dot_git.is_file() {
+            found.push(path);
+        } else if !dot_git.exists() {
+            found.extend(worktree_dirs(&path));
+        }
+    }
+    found.sort();
+    found
+}
+
+/// Repository that `dir` belongs to, directly or through one of its worktrees wherever that
+/// lives, as the directory [`discover_repositories`] would report
+pub fn repository_containing(dir: &Path) -> Option<PathBuf> {
+    let repo = git2::Repository::discover(dir).ok()?;
+    // A worktree's git directory names the main one, shared by all worktrees, in its
+    // `commondir` file; for gwm repositories that is `<repo>/.git`
+    let git_dir = repo.path();
+    let common_dir = match fs::read_to_string(git_dir.join("commondir")) {
+        Ok(common_dir) => git_dir.join(common_dir.trim()),
+        Err(_) => git_dir.to_path_buf(),
+    };
+    let common_dir = common_dir.canonicalize().ok()?;
+    if common_dir.file_name()? != ".git" {
+        return None;
+    }
+    Some(common_dir.parent()?.to_path_buf())
+}
+
+/// Work-in-progress worktrees of a repository, or none if it isn't bare
+/// With `include_main`, every worktree of any repository, main and master included
+/// Blocking: call from a blocking thread in async code
+pub fn list_wip_worktrees(
+    repo_path: &str,
+    backend: Backend,
+    include_main: bool,
+) -> Result<Vec<WorktreeInfo>> {
+    let repo = GitRepository::new(repo_path, backend.client()?)?;
+    if include_main {
+        return repo.list_all_worktrees();
+    }
+    if !repo.is_bare().unwrap_or(false) {
+        return Ok(Vec::new());
+    }
+    repo.list_worktrees()
+}
+
+/// Local status and last commit of a worktree, without PR information
+/// Blocking: call from a blocking thread in async code
+pub fn worktree_status(
+    repo_path: &str,
+    worktree: &WorktreeInfo,
+    backend: Backend,
+    cache: Option<&Mutex<StatusCache>>,
+) -> Result<WorktreeStatus> {
+    let repo = GitRepository::new(repo_path, backend.client()?)?;
+
+    let (local_status, commit_timestamp, commit_summary) =
+        local_and_commit_status(&repo, worktree, cache)?;
+    let directory_mtime = repo.get_directory_mtime(&worktree.path).unwrap_or(0);
+    let locked = repo.worktree_lock(&worktree.path);
+    let operation = repo.get_operation(&worktree.path);
+    let activity_timestamp = repo
+        .get_activity_time(&worktree.path)
+        .unwrap_or(0)
+        .max(commit_timestamp);
+
+    Ok(WorktreeStatus {
+        local_status,
+        commit_timestamp,
+        directory_mtime,
+        commit_summary,
+        pr_status: None,
+        pr_closed_at: None,
+        pr_url: None,
+        pr_review: None,
+        locked,
+        diffstat: None,
+        stash_count: 0,
+        size_bytes: None,
+        operation,
+        activity_timestamp,
+    })
+}
+
+/// Status of the worktree containing `dir` for a shell prompt, `None` outside gwm repositories
+/// Cheap enough to run on every prompt: local status comes from the status cache while HEAD
+/// and the index are unchanged, and ahead/behind counts compare with the last fetch
+pub fn prompt_status(
+    dir: &Path,
+    backend: Backend,
+    cache: Option<&Mutex<StatusCache>>,
+) -> Result<Option<PromptStatus>> {
+    let Some(repo_path) = repository_containing(dir) else {
+        return Ok(None);
+    };
+    // Inside a `.git` directory there is no worktree to describe
+    let Some(workdir) = git2::Repository::discover(dir)
+        .ok()
+        .and_then(|repo| repo.workdir().map(Path::to_path_buf))
+    else {
+        return Ok(None);
+    };
+    let path = workdir.to_string_lossy().trim_end_matches('/').to_string();
+    let repo = GitRepository::new(&path, backend.client()?)?;
+
+    let worktree = match repo.head_branch() {
+        Some(branch) => WorktreeInfo {
+            path,
+            branch,
+            detached: None,
+        },
+        None => {
+            // An unborn branch has nothing to show yet
+            let Ok(oid) = repo.get_head_oid(&path) else {
+                return Ok(None);
+            };
+            WorktreeInfo {
+                path,
+                branch: format!("(detached {})", &oid[..oid.len().min(7)]),
+                detached: Some(oid),
+            }
+        }
+    };
+
+    let (local_status, _, _) = local_and_commit_status(&repo, &worktree, cache)?;
+    let ahead_behind = match worktree.detached {
+        Some(_) => None,
+        None => repo.ahead_behind(&worktree.branch),
+    };
+
+    Ok(Some(PromptStatus {
+        repo: repo_name(&repo_path),
+        local_status: local_status.to_string().to_lowercase(),
+        operation: repo
+            .get_operation(&worktree.path)
+            .map(|operation| operation.to_string().to_lowercase()),
+        ahead: ahead_behind.map(|(ahead, _)| ahead),
+        behind: ahead_behind.map(|(_, behind)| behind),
+        branch: worktree.branch,
+        path: worktree.path,
+    }))
+}
+
+/// Compute local status and commit info, consulting the status cache when enabled
+fn local_and_commit_status(
+    repo: &GitRepository<BackendGitClient>,
+    worktree: &WorktreeInfo,
+    cache: Option<&Mutex<StatusCache>>,
+) -> Result<(LocalStatus, i64, String)> {
+    let fingerprint = cache.and_then(|_| {
+        let head_oid = repo.get_head_oid(&worktree.path).ok()?;
+        let index_mtime = repo.get_index_mtime(&worktree.path).ok()?;
+        Some((head_oid, index_mtime))
+    });
+
+    if let (Some(cache), Some((head_oid, index_mtime))) = (cache, &fingerprint)
+        && let Some(cached) = cache
+            .lock()
+            .unwrap()
+            .get(&worktree.path, head_oid, *index_mtime)
+    {
+        return Ok((
+            cached.local_status.clone(),
+            cached.commit_timestamp,
+            cached.commit_summary.clone(),
+        ));
+    }
+
+    let local_status = repo.get_local_status(&worktree.path)?;
+    let commit_timestamp = repo
+        .get_last_commit_timestamp(&worktree.path, worktree.revision())
+        .unwrap_or(0);
+    let commit_summary = repo
+        .get_commit_summary(&worktree.path, worktree.revision())
+        .unwrap_or_else(|_| "<no commit>".to_string());
+
+    if let (Some(cache), Some((head_oid, index_mtime))) = (cache, fingerprint) {
+        cache.lock().unwrap().insert(
+            &worktree.path,
+            CachedStatus {
+                head_oid,
+                index_mtime,
+                local_status: local_status.clone(),
+                commit_timestamp,
+                commit_summary: commit_summary.clone(),
+            },
+        );
+    }
+
+    Ok((local_status, commit_timestamp, commit_summary))
+}
+
+/// Match a repository's worktrees to pull requests on its forge by `author`, keyed by branch
+pub async fn fetch_pull_requests(
+    repo_path: &str,
+    worktrees: &[WorktreeInfo],
+    author: PrAuthor,
+    forges: &Forges,
+) -> Result<HashMap<String, PrInfo>> {
+    // Get upstream remote URL
+    let remote_url = GitRepository::new(repo_path, SystemGitClient)?
+        .get_upstream_remote_url()?
+        .ok_or_else(|| anyhow!("No upstream or origin remote found"))?;
+
+    // Pick the forge from the URL
+    let forge = forges.detect(&remote_url)?;
+
+    info!(
+        "Fetching PRs for {} ({})",
+        repo_name(Path::new(repo_path)),
+        remote_url
+    );
+
+    // Determine the earliest worktree creation time
+    let since_timestamp = earliest_worktree_time(repo_path, worktrees).await?;
+
+    let since_date = chrono::DateTime::from_timestamp(since_timestamp, 0)
+        .map(|dt| dt.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    info!("Looking for PRs created since {}", since_date);
+
+    // Extract branch names from worktrees; detached worktrees have no branch to look up
+    let branch_names: Vec<String> = worktrees
+        .iter()
+        .map(|wt| wt.branch.clone())
+        .filter(|branch| !is_detached_label(branch))
+        .collect();
+
+    // Fetch PRs for this repository
+    let prs = forge
+        .fetch_prs(&branch_names, since_timestamp, author)
+        .await?;
+
+    // Match worktrees to PRs
+    let matches = github::match_worktrees_to_pr_info(&branch_names, &prs);
+    info!("Matched {} worktrees to PRs", matches.len());
+
+    Ok(matches)
+}
+
+/// Creation time of the oldest worktree, falling back to one week ago
+async fn earliest_worktree_time(repo_path: &str, worktrees: &[WorktreeInfo]) -> Result<i64> {
+    let repo_path = repo_path.to_string();
+    let worktree_paths: Vec<String> = worktrees.iter().map(|w| w.path.clone()).collect();
+
+    let earliest_time = tokio::task::spawn_blocking(move || -> Result<Option<i64>> {
+        let repo = GitRepository::new(&repo_path, SystemGitClient)?;
+        Ok(worktree_paths
+            .iter()
+            .filter_map(|path| repo.get_worktree_birth_time(path).ok().flatten())
+            .min())
+    })
+    .await??;
+
+    // If we have a birth time, use it; otherwise fall back to 1 week ago
+    Ok(earliest_time.unwrap_or_else(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64
+            - (7 * 24 * 60 * 60)
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn dir_size_adds_up_nested_files() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("src").join("deep")).unwrap();
+        fs::write(dir.path().join("README.md"), "12345").unwrap();
+        fs::write(dir.path().join("src").join("deep").join("lib.rs"), "123").unwrap();
+
+        assert_eq!(dir_size(dir.path()), 8);
+        assert_eq!(dir_size(&dir.path().join("gone")), 0);
+    }
+
+    #[test]
+    fn worktree_dirs_follow_branch_prefixes() {
+        let dir = TempDir::new().unwrap();
+        let repo = dir.path().join("proj");
+        fs::create_dir_all(repo.join(".git").join("worktrees")).unwrap();
+        for worktree in ["main", "feature/login"] {
+            fs::create_dir_all(repo.join(worktree)).unwrap();
+            fs::write(repo.join(worktree).join(".git"), "gitdir: elsewhere\n").unwrap();
+        }
+        fs::create_dir_all(repo.join("feature").join("login").join("src")).unwrap();
+        fs::create_dir_all(repo.join("clone").join(".git")).unwrap();
+
+        assert_eq!(
+            worktree_dirs(&repo),
+            vec![repo.join("feature").join("login"), repo.join("main")]
+        );
+    }
+
+    #[test]
+    fn discovers_only_directories_with_a_git_dir() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("proj").join(".git")).unwrap();
+        fs::create_dir_all(dir.path().join("notes")).unwrap();
+        fs::write(dir.path().join("README.md"), "").unwrap();
+
+        let repos = discover_repositories(dir.path(), false).unwrap();
+        assert_eq!(repos, vec![dir.path().join("proj")]);
+        assert_eq!(repo_name(&repos[0]), "proj");
+        assert_eq!(discover_repositories(dir.path(), true).unwrap(), repos);
+    }
+
+    #[test]
+    fn finds_the_repository_of_an_outside_worktree() {
+        let dir = TempDir::new().unwrap();
+        let repo_path = dir.path().join("proj");
+        let repo = git2::Repository::init_bare(repo_path.join(".git")).unwrap();
+        let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+        let tree = repo.find_tree(repo.treebuilder(None).unwrap().write().unwrap());
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "init",
+            &tree.unwrap(),
+            &[],
+        )
+        .unwrap();
+        let outside = dir.path().join("elsewhere");
+        repo.worktree("feature", &outside, None).unwrap();
+        fs::create_dir(outside.join("src")).unwrap();
+
+        let expected = repo_path.canonicalize().unwrap();
+        assert_eq!(repository_containing(&repo_path), Some(expected.clone()));
+        assert_eq!(repository_containing(&outside.join("src")), Some(expected));
+    }
+}
@@ -0,0 +1,74 @@
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::config;
+use crate::core::RepoResult;
+use crate::state::StateFile;
+
+/// Latest scan results written by `gwm daemon`, so `gwm list` can take pull request
+/// statuses from it instead of waiting on GitHub
+/// Snapshots are keyed by the canonical search path the daemon scans
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Unix time the scan finished
+    pub taken_at: i64,
+    /// Seconds between daemon refreshes, to judge when the snapshot has gone stale
+    pub interval_secs: u64,
+    pub repos: Vec<RepoResult>,
+}
+
+impl Snapshot {
+    /// Pure check: a snapshot is fresh until the daemon has missed a refresh
+    pub fn is_fresh(&self, now: i64) -> bool {
+        let max_age = 2 * self.interval_secs as i64 + 60;
+        now - self.taken_at <= max_age
+    }
+
+    /// The snapshot for a search path, if the daemon has written one
+    pub fn load(search_path: &Path) -> Option<Snapshot> {
+        let snapshots: HashMap<String, Snapshot> = StateFile::new(Self::path()?).load().ok()?;
+        snapshots.get(&key(search_path)).cloned()
+    }
+
+    /// Replace the snapshot for a search path, keeping those of other paths
+    pub fn save(&self, search_path: &Path) -> Result<()> {
+        let path = Self::path().ok_or_else(|| anyhow!("Could not determine cache directory"))?;
+        let key = key(search_path);
+        StateFile::new(path).update(|snapshots: &mut HashMap<String, Snapshot>| {
+            snapshots.insert(key, self.clone());
+        })
+    }
+
+    pub fn path() -> Option<PathBuf> {
+        config::cache_dir().map(|dir| dir.join("snapshot.json"))
+    }
+}
+
+/// Canonical form of a search path, so "." and its absolute path share a snapshot
+fn key(search_path: &Path) -> String {
+    search_path
+        .canonicalize()
+        .unwrap_or_else(|_| search_path.to_path_buf())
+        .to_string_lossy()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_goes_stale_after_a_missed_refresh() {
+        let snapshot = Snapshot {
+            taken_at: 1_000,
+            interval_secs: 300,
+            repos: Vec::new(),
+        };
+        assert!(snapshot.is_fresh(1_000 + 300));
+        assert!(snapshot.is_fresh(1_000 + 660));
+        assert!(!snapshot.is_fresh(1_000 + 661));
+    }
+}
@@ -0,0 +1,142 @@
+use anyhow::{Result, anyhow};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Shared store for gwm's JSON state files (status cache, usage statistics, ...)
+/// Several gwm processes may touch the same file at once (a shell prompt, `list`, `gc`), so:
+/// - writes go to a temporary file that is renamed into place, so readers never see a torn file
+/// - read-modify-write cycles hold an exclusive lock on a sidecar `.lock` file, so no update is lost
+pub struct StateFile {
+    path: PathBuf,
+}
+
+impl StateFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Read the current contents, treating a missing file as the default value
+    /// Needs no lock, since writers only ever replace the file whole
+    pub fn load<T: DeserializeOwned + Default>(&self) -> Result<T> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(T::default()),
+            Err(e) => {
+                return Err(anyhow!("Failed to read '{}': {}", self.path.display(), e));
+            }
+        };
+        serde_json::from_str(&contents)
+            .map_err(|e| anyhow!("Invalid state file '{}': {}", self.path.display(), e))
+    }
+
+    /// Load, modify and save the contents while holding the lock, so concurrent updates compose
+    /// A file that can't be parsed is started over from the default value
+    pub fn update<T, R>(&self, update: impl FnOnce(&mut T) -> R) -> Result<R>
+    where
+        T: DeserializeOwned + Serialize + Default,
+    {
+        let _lock = self.lock()?;
+        let mut value = self.load().unwrap_or_default();
+        let result = update(&mut value);
+        self.write_atomic(&value)?;
+        Ok(result)
+    }
+
+    /// Take the exclusive lock, released when the returned file is dropped
+    fn lock(&self) -> Result<File> {
+        self.create_parent()?;
+        let lock_path = self.sidecar("lock");
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .map_err(|e| anyhow!("Failed to open lock file '{}': {}", lock_path.display(), e))?;
+        file.lock()
+            .map_err(|e| anyhow!("Failed to lock '{}': {}", lock_path.display(), e))?;
+        Ok(file)
+    }
+
+    fn write_atomic<T: Serialize>(&self, value: &T) -> Result<()> {
+        self.create_parent()?;
+        let tmp_path = self.sidecar(&format!("tmp.{}", std::process::id()));
+
+        let write = || -> std::io::Result<()> {
+            let mut file = File::create(&tmp_path)?;
+            file.write_all(serde_json::to_string_pretty(value)?.as_bytes())?;
+            file.sync_all()?;
+            fs::rename(&tmp_path, &self.path)
+        };
+
+        write().map_err(|e| {
+            let _ = fs::remove_file(&tmp_path);
+            anyhow!("Failed to write '{}': {}", self.path.display(), e)
+        })
+    }
+
+    fn create_parent(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| anyhow!("Failed to create '{}': {}", parent.display(), e))?;
+        }
+        Ok(())
+    }
+
+    /// A file next to the state file, e.g. `usage.json.lock`
+    fn sidecar(&self, suffix: &str) -> PathBuf {
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(".");
+        name.push(suffix);
+        self.path.with_file_name(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    #[test]
+    fn missing_file_loads_as_default_and_update_creates_it() {
+        let dir = TempDir::new().unwrap();
+        let state = StateFile::new(dir.path().join("nested").join("state.json"));
+
+        let value: HashMap<String, u64> = state.load().unwrap();
+        assert!(value.is_empty());
+
+        state
+            .update(|value: &mut HashMap<String, u64>| value.insert("a".to_string(), 1))
+            .unwrap();
+        let value: HashMap<String, u64> = state.load().unwrap();
+        assert_eq!(value["a"], 1);
+    }
+
+    #[test]
+    fn concurrent_updates_are_not_lost() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("counter.json");
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..25 {
+                        StateFile::new(&path)
+                            .update(|count: &mut u64| *count += 1)
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let count: u64 = StateFile::new(&path).load().unwrap();
+        assert_eq!(count, 200);
+    }
+}
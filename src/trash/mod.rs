@@ -0,0 +1,244 @@
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config;
+use crate::git::{GitRepository, SystemGitClient, is_detached_label};
+use crate::state::StateFile;
+
+/// Removed worktree directories, kept in the gwm data directory so `gwm undo` can restore them
+/// Each removal moves the directory to `<trash>/<repo>/<branch>-<timestamp>`, with `/` in the
+/// branch escaped, and its git index to the same path plus `.index`; it is recorded, with the
+/// commit that was checked out, in `<trash>/index.json`
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrashEntry {
+    pub repo: String,
+    pub repo_path: PathBuf,
+    pub branch: String,
+    /// Commit the branch pointed at, to recreate it if it is deleted later, or the detached HEAD
+    pub oid: String,
+    pub worktree_path: PathBuf,
+    pub trash_path: PathBuf,
+    /// Saved git index, so staged changes come back staged
+    #[serde(default)]
+    pub index_path: Option<PathBuf>,
+    pub removed_at: i64,
+}
+
+pub struct Trash {
+    dir: PathBuf,
+}
+
+impl Trash {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The trash in the gwm data directory
+    pub fn open() -> Result<Self> {
+        config::data_dir()
+            .map(|dir| Self::new(dir.join("trash")))
+            .ok_or_else(|| anyhow!("Could not determine the gwm data directory"))
+    }
+
+    /// Move a worktree's directory into the trash, then unregister the worktree
+    pub fn remove_worktree(
+        &self,
+        repo_path: &Path,
+        repo_name: &str,
+        branch: &str,
+        worktree_path: &Path,
+    ) -> Result<TrashEntry> {
+        let removed_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        let repo = GitRepository::new(&repo_path.to_string_lossy(), SystemGitClient)?;
+        let path = worktree_path.to_string_lossy();
+        let oid = if is_detached_label(branch) {
+            repo.get_head_oid(&path)?
+        } else {
+            repo.branch_tip(branch)?
+        };
+        // One directory per removal, however many `/` the branch name has
+        let name = format!(
+            "{}-{}",
+            branch.replace('%', "%25").replace('/', "%2F"),
+            removed_at
+        );
+        let trash_path = self.dir.join(repo_name).join(&name);
+        let mut entry = TrashEntry {
+            repo: repo_name.to_string(),
+            repo_path: repo_path.to_path_buf(),
+            branch: branch.to_string(),
+            oid,
+            worktree_path: worktree_path.to_path_buf(),
+            index_path: None,
+            trash_path,
+            removed_at,
+        };
+
+        // Removing the worktree deletes its git directory, index included
+        let index_path = self.dir.join(repo_name).join(format!("{}.index", name));
+        if let Ok(index) = repo.index_path(&path)
+            && index.exists()
+        {
+            fs::create_dir_all(self.dir.join(repo_name))?;
+            fs::copy(&index, &index_path)?;
+            entry.index_path = Some(index_path);
+        }
+
+        // Once the directory is gone the worktree can only be found by its registration
+        move_dir(&entry.worktree_path, &entry.trash_path)?;
+        if let Err(e) = repo.remove_worktree_at(&path) {
+            // Put the directory back so the worktree is left as it was
+            let _ = move_dir(&entry.trash_path, &entry.worktree_path);
+            if let Some(index_path) = &entry.index_path {
+                let _ = fs::remove_file(index_path);
+            }
+            return Err(e);
+        }
+
+        self.index()
+            .update(|entries: &mut Vec<TrashEntry>| entries.push(entry.clone()))?;
+        Ok(entry)
+    }
+
+    /// The most recent removal, if any
+    pub fn last(&self) -> Result<Option<TrashEntry>> {
+        let entries: Vec<TrashEntry> = self.index().load()?;
+        Ok(entries.last().cloned())
+    }
+
+    /// Put the most recently removed worktree back where it was, recreating its branch if needed,
+    /// or detaching HEAD at the same commit if it had no branch
+    pub fn restore_last(&self) -> Result<Option<TrashEntry>> {
+        let Some(entry) = self.last()? else {
+            return Ok(None);
+        };
+        if entry.worktree_path.exists() {
+            return Err(anyhow!(
+                "Cannot restore {}/{}: '{}' already exists",
+                entry.repo,
+                entry.branch,
+                entry.worktree_path.display()
+            ));
+        }
+
+        let repo = GitRepository::new(&entry.repo_path.to_string_lossy(), SystemGitClient)?;
+        let path = entry.worktree_path.to_string_lossy();
+        if is_detached_label(&entry.branch) {
+            repo.add_detached_worktree(&path, &entry.oid)?;
+        } else {
+            if repo.branch_tip(&entry.branch).is_err() {
+                repo.create_branch_at(&entry.branch, &entry.oid)?;
+            }
+            repo.add_worktree(&entry.branch, &path, None, true)?;
+        }
+
+        // The fresh checkout provides the worktree's registration in its .git file; everything
+        // else comes from the trash, including uncommitted and untracked changes
+        for item in fs::read_dir(&entry.worktree_path)? {
+            let item = item?;
+            if item.file_name() != ".git" {
+                remove_path(&item.path())?;
+            }
+        }
+        for item in fs::read_dir(&entry.trash_path)? {
+            let item = item?;
+            if item.file_name() != ".git" {
+                move_dir(&item.path(), &entry.worktree_path.join(item.file_name()))?;
+            }
+        }
+        fs::remove_dir_all(&entry.trash_path)?;
+        // Git rereads the restored files, since their timestamps no longer match the index
+        if let Some(index_path) = &entry.index_path {
+            fs::copy(index_path, repo.index_path(&path)?)?;
+            fs::remove_file(index_path)?;
+        }
+
+        self.index().update(|entries: &mut Vec<TrashEntry>| {
+            entries.retain(|e| e.trash_path != entry.trash_path)
+        })?;
+        Ok(Some(entry))
+    }
+
+    fn index(&self) -> StateFile {
+        StateFile::new(self.dir.join("index.json"))
+    }
+}
+
+/// Rename a file or directory, copying it when the destination is on another filesystem
+pub fn move_dir(from: &Path, to: &Path) -> Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    copy_path(from, to)
+        .and_then(|()| remove_path(from))
+        .map_err(|e| {
+            anyhow!(
+                "Failed to move '{}' to '{}': {}",
+                from.display(),
+                to.display(),
+                e
+            )
+        })
+}
+
+/// Copy a file or directory tree, recreating symlinks rather than following them
+pub fn copy_path(from: &Path, to: &Path) -> Result<()> {
+    let file_type = fs::symlink_metadata(from)?.file_type();
+    if file_type.is_dir() {
+        fs::create_dir_all(to)?;
+        for item in fs::read_dir(from)? {
+            let item = item?;
+            copy_path(&item.path(), &to.join(item.file_name()))?;
+        }
+    } else if file_type.is_symlink() {
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(fs::read_link(from)?, to)?;
+        #[cfg(not(unix))]
+        fs::copy(from, to)?;
+    } else {
+        fs::copy(from, to)?;
+    }
+    Ok(())
+}
+
+fn remove_path(path: &Path) -> Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)?;
+    } else {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn copy_fallback_keeps_nested_files() {
+        let dir = TempDir::new().unwrap();
+        let from = dir.path().join("from");
+        fs::create_dir_all(from.join("nested")).unwrap();
+        fs::write(from.join("nested").join("file.txt"), "kept").unwrap();
+
+        let to = dir.path().join("trash").join("to");
+        copy_path(&from, &to).unwrap();
+        remove_path(&from).unwrap();
+
+        assert!(!from.exists());
+        assert_eq!(
+            fs::read_to_string(to.join("nested").join("file.txt")).unwrap(),
+            "kept"
+        );
+    }
+}
@@ -0,0 +1,206 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use crate::config::{self, Config};
+use crate::core::{PrStatus, RepoResult};
+use crate::git::LocalStatus;
+use crate::state::StateFile;
+
+/// Samples taken within this long of the previous one are merged into it, so a `gwm list` in a
+/// shell prompt doesn't grow the log with every command
+const SAMPLE_INTERVAL_SECS: i64 = 60 * 60;
+
+/// Samples older than this are dropped
+const MAX_AGE_SECS: i64 = 365 * 24 * 60 * 60;
+
+/// Work-in-progress counts over time, recorded by `list` and the daemon
+/// when `[usage] enabled = true`
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WipHistory {
+    /// Oldest first
+    pub samples: Vec<WipSample>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WipSample {
+    pub taken_at: i64,
+    pub repos: BTreeMap<String, RepoSample>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RepoSample {
+    pub wip: u32,
+    /// Worktrees with uncommitted, staged or conflicted changes
+    pub dirty: u32,
+    /// Sum of the branches' ages since their last commit, to average over `wip`
+    pub total_age_secs: i64,
+    /// Branches whose pull request is merged
+    pub merged: Vec<String>,
+}
+
+impl WipSample {
+    pub fn from_results(repo_results: &[RepoResult], now: i64) -> Self {
+        let repos = repo_results
+            .iter()
+            .map(|repo_result| {
+                let mut sample = RepoSample::default();
+                for worktree in &repo_result.worktrees {
+                    let status = &worktree.status;
+                    sample.wip += 1;
+                    if matches!(
+                        status.local_status,
+                        LocalStatus::Dirty | LocalStatus::Staged | LocalStatus::Conflicted
+                    ) {
+                        sample.dirty += 1;
+                    }
+                    sample.total_age_secs += (now - status.commit_timestamp).max(0);
+                    if status.pr_status == Some(PrStatus::Merged) {
+                        sample.merged.push(worktree.branch.clone());
+                    }
+                }
+                (repo_result.name.clone(), sample)
+            })
+            .collect();
+        Self {
+            taken_at: now,
+            repos,
+        }
+    }
+
+    pub fn wip(&self) -> u32 {
+        self.repos.values().map(|repo| repo.wip).sum()
+    }
+
+    pub fn dirty(&self) -> u32 {
+        self.repos.values().map(|repo| repo.dirty).sum()
+    }
+
+    /// Average time since each branch's last commit
+    pub fn average_age_secs(&self) -> Option<i64> {
+        let wip = self.wip();
+        if wip == 0 {
+            return None;
+        }
+        Some(
+            self.repos
+                .values()
+                .map(|repo| repo.total_age_secs)
+                .sum::<i64>()
+                / wip as i64,
+        )
+    }
+}
+
+impl WipHistory {
+    /// Add a sample, merging it into the previous one if that is recent
+    /// Merging keeps repositories scanned from another directory within the hour
+    pub fn record(&mut self, sample: WipSample) {
+        match self.samples.last_mut() {
+            Some(last) if sample.taken_at - last.taken_at < SAMPLE_INTERVAL_SECS => {
+                last.taken_at = sample.taken_at;
+                last.repos.extend(sample.repos);
+            }
+            _ => self.samples.push(sample),
+        }
+        let now = self.samples.last().map_or(0, |last| last.taken_at);
+        self.samples
+            .retain(|sample| now - sample.taken_at <= MAX_AGE_SECS);
+    }
+
+    /// Pull requests per repository seen merged for the first time at or after `since`
+    /// Those already merged in the very first sample predate the history and aren't counted
+    pub fn merges_since(&self, since: i64) -> BTreeMap<String, usize> {
+        let mut seen: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        let mut merges = BTreeMap::new();
+        for (index, sample) in self.samples.iter().enumerate() {
+            for (repo, repo_sample) in &sample.repos {
+                let seen = seen.entry(repo).or_default();
+                for branch in &repo_sample.merged {
+                    if seen.insert(branch) && index > 0 && sample.taken_at >= since {
+                        *merges.entry(repo.clone()).or_default() += 1;
+                    }
+                }
+            }
+        }
+        merges
+    }
+
+    pub fn load(path: PathBuf) -> Result<Self> {
+        StateFile::new(path).load()
+    }
+
+    /// Location of the history file
+    pub fn path() -> Option<PathBuf> {
+        config::data_dir().map(|dir| dir.join("history.json"))
+    }
+}
+
+/// Add a sample of the scanned worktrees to the history if usage statistics are enabled
+/// Failures are ignored so that statistics can never break a command
+pub fn record(config: &Config, repo_results: &[RepoResult]) {
+    if !config.usage.enabled {
+        return;
+    }
+    let Some(path) = WipHistory::path() else {
+        return;
+    };
+
+    let sample = WipSample::from_results(repo_results, chrono::Utc::now().timestamp());
+    let _ = StateFile::new(path).update(|history: &mut WipHistory| history.record(sample));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(taken_at: i64, repo: &str, wip: u32, merged: &[&str]) -> WipSample {
+        WipSample {
+            taken_at,
+            repos: BTreeMap::from([(
+                repo.to_string(),
+                RepoSample {
+                    wip,
+                    merged: merged.iter().map(|branch| branch.to_string()).collect(),
+                    ..RepoSample::default()
+                },
+            )]),
+        }
+    }
+
+    #[test]
+    fn samples_within_the_hour_are_merged() {
+        let mut history = WipHistory::default();
+        history.record(sample(0, "api", 2, &[]));
+        history.record(sample(600, "web", 3, &[]));
+        history.record(sample(600 + SAMPLE_INTERVAL_SECS, "api", 1, &[]));
+
+        assert_eq!(history.samples.len(), 2);
+        assert_eq!(history.samples[0].taken_at, 600);
+        assert_eq!(history.samples[0].wip(), 5);
+        assert_eq!(history.samples[1].wip(), 1);
+    }
+
+    #[test]
+    fn merges_are_counted_once_when_first_seen() {
+        let day = 24 * 60 * 60;
+        let mut history = WipHistory::default();
+        history.record(sample(0, "api", 2, &["old"]));
+        history.record(sample(day, "api", 2, &["old", "a"]));
+        history.record(sample(2 * day, "api", 1, &["a", "b"]));
+
+        assert_eq!(
+            history.merges_since(0),
+            BTreeMap::from([("api".to_string(), 2)])
+        );
+        assert_eq!(
+            history.merges_since(2 * day),
+            BTreeMap::from([("api".to_string(), 1)])
+        );
+    }
+}
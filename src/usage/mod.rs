@@ -0,0 +1,114 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::config::{self, Config};
+use crate::state::StateFile;
+
+pub mod history;
+
+/// Purely local usage statistics - nothing here ever leaves the machine
+/// Stored as JSON in the gwm data directory when `[usage] enabled = true`
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageStats {
+    pub adds: u64,
+    pub removes: u64,
+    pub gc_runs: u64,
+    pub gc_removed: u64,
+    pub lifetime_total_secs: i64,
+    pub lifetime_samples: u64,
+    /// Creation time of worktrees added by gwm, keyed by "repo/branch"
+    pub created: HashMap<String, i64>,
+}
+
+impl UsageStats {
+    pub fn record_add(&mut self, repo: &str, branch: &str, now: i64) {
+        self.adds += 1;
+        self.created.insert(format!("{}/{}", repo, branch), now);
+    }
+
+    pub fn record_remove(&mut self, repo: &str, branch: &str, now: i64) {
+        self.removes += 1;
+        self.record_lifetime(repo, branch, now);
+    }
+
+    pub fn record_gc(&mut self, removed: &[(String, String)], now: i64) {
+        self.gc_runs += 1;
+        self.gc_removed += removed.len() as u64;
+        for (repo, branch) in removed {
+            self.record_lifetime(repo, branch, now);
+        }
+    }
+
+    /// Average time between `gwm add` and removal, for worktrees gwm saw created
+    pub fn average_lifetime_secs(&self) -> Option<i64> {
+        if self.lifetime_samples == 0 {
+            return None;
+        }
+        Some(self.lifetime_total_secs / self.lifetime_samples as i64)
+    }
+
+    fn record_lifetime(&mut self, repo: &str, branch: &str, now: i64) {
+        if let Some(created) = self.created.remove(&format!("{}/{}", repo, branch)) {
+            self.lifetime_total_secs += (now - created).max(0);
+            self.lifetime_samples += 1;
+        }
+    }
+
+    pub fn load(path: PathBuf) -> Result<Self> {
+        StateFile::new(path).load()
+    }
+
+    /// Location of the usage statistics file
+    pub fn path() -> Option<PathBuf> {
+        config::data_dir().map(|dir| dir.join("usage.json"))
+    }
+}
+
+/// Apply an update to the stats file if usage statistics are enabled
+/// Failures are ignored so that statistics can never break a command
+pub fn record(config: &Config, update: impl FnOnce(&mut UsageStats, i64)) {
+    if !config.usage.enabled {
+        return;
+    }
+    let Some(path) = UsageStats::path() else {
+        return;
+    };
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+
+    let _ = StateFile::new(path).update(|stats: &mut UsageStats| update(stats, now));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lifetime_is_measured_from_add_to_remove() {
+        let mut stats = UsageStats::default();
+        stats.record_add("api", "feature", 1_000);
+        stats.record_remove("api", "feature", 4_000);
+
+        assert_eq!(stats.adds, 1);
+        assert_eq!(stats.removes, 1);
+        assert_eq!(stats.average_lifetime_secs(), Some(3_000));
+        assert!(stats.created.is_empty());
+    }
+
+    #[test]
+    fn removals_of_unknown_worktrees_do_not_skew_lifetime() {
+        let mut stats = UsageStats::default();
+        stats.record_gc(&[("api".to_string(), "old".to_string())], 5_000);
+
+        assert_eq!(stats.gc_runs, 1);
+        assert_eq!(stats.gc_removed, 1);
+        assert_eq!(stats.average_lifetime_secs(), None);
+    }
+}
@@ -1,5 +1,8 @@
 use git2::{Repository, Signature};
-use gwm::git::{GitRepository, SystemGitClient};
+use gwm::core::PrStatus;
+use gwm::git::{Backend, DiffStat, GitRepository, Operation, SystemGitClient};
+use gwm::scan::{self, ScanOptions};
+use gwm::trash::Trash;
 use std::fs;
 use std::path::Path;
 use tempfile::TempDir;
@@ -101,6 +104,247 @@ fn test_add_worktree_creates_branch_successfully() {
     }
 }
 
+#[test]
+fn test_add_worktree_from_remote_tracking_base_branch() {
+    let (_temp_dir, repo_path) = setup_bare_repo_with_commit();
+    let repo = Repository::open(&repo_path).expect("Failed to open repo");
+    let head = repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .expect("Failed to find HEAD commit");
+    repo.reference("refs/remotes/origin/release", head.id(), false, "test")
+        .expect("Failed to create remote ref");
+
+    let git_repo =
+        GitRepository::new(&repo_path, SystemGitClient).expect("Failed to open repository");
+    let worktree_dir = TempDir::new().expect("Failed to create worktree temp dir");
+    let worktree_path = worktree_dir.path().join("hotfix");
+
+    git_repo
+        .add_worktree(
+            "hotfix",
+            worktree_path.to_str().unwrap(),
+            Some("origin/release"),
+            false,
+        )
+        .expect("add_worktree should accept a remote-tracking base branch");
+
+    let worktree_repo = Repository::open(&worktree_path).expect("Failed to open worktree");
+    let worktree_head = worktree_repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .expect("Worktree should have a commit");
+    assert_eq!(worktree_head.id(), head.id());
+}
+
+#[test]
+fn test_add_worktree_from_tag_or_commit() {
+    let (_temp_dir, repo_path) = setup_bare_repo_with_commit();
+    let repo = Repository::open(&repo_path).expect("Failed to open repo");
+    let head = repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .expect("Failed to find HEAD commit");
+    let signature =
+        Signature::now("Test User", "test@example.com").expect("Failed to create signature");
+    repo.tag("v1.0", head.as_object(), &signature, "Release 1.0", false)
+        .expect("Failed to create tag");
+
+    let git_repo =
+        GitRepository::new(&repo_path, SystemGitClient).expect("Failed to open repository");
+    let worktree_dir = TempDir::new().expect("Failed to create worktree temp dir");
+    let short_id = head.id().to_string()[..7].to_string();
+    for (branch, base) in [("from-tag", "v1.0"), ("from-commit", short_id.as_str())] {
+        let worktree_path = worktree_dir.path().join(branch);
+        git_repo
+            .add_worktree(branch, worktree_path.to_str().unwrap(), Some(base), false)
+            .unwrap_or_else(|e| panic!("add_worktree from {} failed: {}", base, e));
+
+        let worktree_repo = Repository::open(&worktree_path).expect("Failed to open worktree");
+        let worktree_head = worktree_repo.head().expect("Worktree should have a HEAD");
+        assert_eq!(worktree_head.shorthand(), Some(branch));
+        assert_eq!(worktree_head.peel_to_commit().unwrap().id(), head.id());
+    }
+}
+
+#[test]
+fn test_create_tracking_branch_follows_remote_branch() {
+    let (_temp_dir, repo_path) = setup_bare_repo_with_commit();
+    let repo = Repository::open(&repo_path).expect("Failed to open repo");
+    let head = repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .expect("Failed to find HEAD commit");
+    repo.remote("origin", "https://github.com/jml/git-worktree-manager.git")
+        .expect("Failed to add remote");
+    repo.reference("refs/remotes/origin/shared", head.id(), false, "test")
+        .expect("Failed to create remote ref");
+
+    let git_repo =
+        GitRepository::new(&repo_path, SystemGitClient).expect("Failed to open repository");
+    assert!(git_repo.has_remote_branch("origin/shared"));
+    assert!(!git_repo.has_remote_branch("origin/missing"));
+    git_repo
+        .create_tracking_branch("shared", "origin/shared")
+        .expect("Failed to create tracking branch");
+
+    let branch = repo
+        .find_branch("shared", git2::BranchType::Local)
+        .expect("Local branch should exist");
+    let upstream = branch
+        .upstream()
+        .expect("Branch should track origin/shared");
+    assert_eq!(upstream.name().unwrap(), Some("origin/shared"));
+
+    // A second attempt would clobber the local branch, so it must fail
+    assert!(
+        git_repo
+            .create_tracking_branch("shared", "origin/shared")
+            .is_err()
+    );
+}
+
+#[test]
+fn test_delete_remote_branch_removes_it_from_the_remote() {
+    let (_temp_dir, repo_path) = setup_bare_repo_with_commit();
+    let origin_dir = TempDir::new().expect("Failed to create origin directory");
+    let origin = Repository::init_bare(origin_dir.path()).expect("Failed to init origin");
+    let repo = Repository::open(&repo_path).expect("Failed to open repo");
+    let head = repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .expect("Failed to find HEAD commit");
+    repo.remote("origin", origin_dir.path().to_str().unwrap())
+        .expect("Failed to add remote");
+    repo.branch("shared", &head, false)
+        .expect("Failed to create branch");
+
+    let git_repo =
+        GitRepository::new(&repo_path, SystemGitClient).expect("Failed to open repository");
+    git_repo
+        .push_upstream("shared", "origin")
+        .expect("Failed to push branch");
+    assert!(git_repo.has_remote_branch("origin/shared"));
+
+    git_repo
+        .delete_remote_branch("shared", "origin")
+        .expect("Failed to delete remote branch");
+    assert!(origin.find_reference("refs/heads/shared").is_err());
+    assert!(!git_repo.has_remote_branch("origin/shared"));
+}
+
+#[test]
+fn test_only_merged_branches_are_deleted_on_the_remote() {
+    let (_temp_dir, repo_path) = setup_bare_repo_with_commit();
+    let origin_dir = TempDir::new().expect("Failed to create origin directory");
+    let origin = Repository::init_bare(origin_dir.path()).expect("Failed to init origin");
+    let repo = Repository::open(&repo_path).expect("Failed to open repo");
+    let head = repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .expect("Failed to find HEAD commit");
+    repo.remote("origin", origin_dir.path().to_str().unwrap())
+        .expect("Failed to add remote");
+
+    let git_repo =
+        GitRepository::new(&repo_path, SystemGitClient).expect("Failed to open repository");
+    for branch in ["abandoned", "shipped"] {
+        repo.branch(branch, &head, false)
+            .expect("Failed to create branch");
+        git_repo
+            .push_upstream(branch, "origin")
+            .expect("Failed to push branch");
+    }
+
+    // A PR closed without merging, as `gc --closed-older-than` collects, keeps its remote branch
+    let deleted = git_repo
+        .delete_merged_remote_branch("abandoned", "origin", Some(&PrStatus::Closed))
+        .expect("Failed to check remote branch");
+    assert!(!deleted);
+    assert!(origin.find_reference("refs/heads/abandoned").is_ok());
+    assert!(
+        !git_repo
+            .delete_merged_remote_branch("abandoned", "origin", None)
+            .unwrap()
+    );
+
+    let deleted = git_repo
+        .delete_merged_remote_branch("shipped", "origin", Some(&PrStatus::Merged))
+        .expect("Failed to delete remote branch");
+    assert!(deleted);
+    assert!(origin.find_reference("refs/heads/shipped").is_err());
+}
+
+#[test]
+fn test_archive_branch_tags_the_tip_once_per_commit() {
+    let (_temp_dir, repo_path) = setup_bare_repo_with_commit();
+    let repo = Repository::open(&repo_path).expect("Failed to open repo");
+    let head = repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .expect("Failed to find HEAD commit");
+    repo.branch("feature", &head, false)
+        .expect("Failed to create branch");
+
+    let git_repo =
+        GitRepository::new(&repo_path, SystemGitClient).expect("Failed to open repository");
+    let tag = git_repo
+        .archive_branch("feature", "2024-05-01")
+        .expect("Failed to archive branch");
+    assert_eq!(tag, "archive/feature/2024-05-01");
+    let tagged = repo
+        .find_reference("refs/tags/archive/feature/2024-05-01")
+        .expect("Tag should exist");
+    assert_eq!(tagged.target(), Some(head.id()));
+
+    // Archiving the same commit again reuses the tag
+    assert_eq!(
+        git_repo.archive_branch("feature", "2024-05-01").unwrap(),
+        "archive/feature/2024-05-01"
+    );
+
+    // A new commit on the same day gets its own tag
+    let signature =
+        Signature::now("Test User", "test@example.com").expect("Failed to create signature");
+    repo.commit(
+        Some("refs/heads/feature"),
+        &signature,
+        &signature,
+        "More work",
+        &head.tree().unwrap(),
+        &[&head],
+    )
+    .expect("Failed to commit");
+    assert_eq!(
+        git_repo.archive_branch("feature", "2024-05-01").unwrap(),
+        "archive/feature/2024-05-01-2"
+    );
+}
+
+#[test]
+fn test_delete_branch_with_backup_keeps_the_tip() {
+    let (_temp_dir, repo_path) = setup_bare_repo_with_commit();
+    let repo = Repository::open(&repo_path).expect("Failed to open repo");
+    let head = repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .expect("Failed to find HEAD commit");
+    repo.branch("merged", &head, false)
+        .expect("Failed to create branch");
+
+    let git_repo =
+        GitRepository::new(&repo_path, SystemGitClient).expect("Failed to open repository");
+    let backup = git_repo
+        .delete_branch_with_backup("merged")
+        .expect("Failed to delete branch");
+    assert_eq!(backup, "refs/gwm/backup/merged");
+    assert!(repo.find_branch("merged", git2::BranchType::Local).is_err());
+    assert_eq!(
+        repo.find_reference(&backup).unwrap().target(),
+        Some(head.id())
+    );
+}
+
 #[test]
 fn test_add_worktree_with_existing_branch() {
     let (_temp_dir, repo_path) = setup_bare_repo_with_commit();
@@ -321,3 +565,730 @@ fn test_reuse_flag_prevents_failure_with_existing_branch() {
         "Should be on the feature-branch branch"
     );
 }
+
+#[test]
+fn test_upstream_remote_url_applies_instead_of_rewrites() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let repo_path = temp_dir.path().join("rewritten");
+    let repo = Repository::init(&repo_path).expect("Failed to init repo");
+    repo.remote("origin", "gh:jml/git-worktree-manager")
+        .expect("Failed to add remote");
+    repo.config()
+        .expect("Failed to open config")
+        .set_str("url.git@github.com:.insteadOf", "gh:")
+        .expect("Failed to set insteadOf");
+
+    let git_repo = GitRepository::new(repo_path.to_str().unwrap(), SystemGitClient)
+        .expect("Failed to open repo");
+    let url = git_repo
+        .get_upstream_remote_url()
+        .expect("Failed to read remote url");
+
+    assert_eq!(
+        url.as_deref(),
+        Some("git@github.com:jml/git-worktree-manager")
+    );
+}
+
+#[test]
+fn test_branch_names_lists_local_then_remote_branches() {
+    let (_temp_dir, repo_path) = setup_bare_repo_with_commit();
+    let repo = Repository::open(&repo_path).expect("Failed to open repo");
+    let head = repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .expect("Failed to find HEAD commit");
+    repo.branch("feature", &head, false)
+        .expect("Failed to create branch");
+    for name in ["refs/remotes/origin/main", "refs/remotes/origin/shared"] {
+        repo.reference(name, head.id(), false, "test")
+            .expect("Failed to create remote ref");
+    }
+    repo.reference_symbolic(
+        "refs/remotes/origin/HEAD",
+        "refs/remotes/origin/main",
+        false,
+        "test",
+    )
+    .expect("Failed to create origin/HEAD");
+
+    let git_repo = GitRepository::new(&repo_path, SystemGitClient).expect("Failed to open repo");
+    assert_eq!(
+        git_repo.branch_names(false).unwrap(),
+        vec!["feature", "main"]
+    );
+    assert_eq!(
+        git_repo.branch_names(true).unwrap(),
+        vec!["feature", "main", "origin/main", "origin/shared"]
+    );
+}
+
+#[cfg(feature = "gitoxide")]
+#[test]
+fn test_gitoxide_backend_matches_libgit2_status() {
+    use gwm::git::{GixGitClient, LocalStatus};
+
+    let (_temp_dir, repo_path) = setup_bare_repo_with_commit();
+    let git_repo =
+        GitRepository::new(&repo_path, SystemGitClient).expect("Failed to open repository");
+    let gix_repo = GitRepository::new(&repo_path, GixGitClient).expect("Failed to open repository");
+
+    let worktree_dir = TempDir::new().expect("Failed to create worktree temp dir");
+    let worktree_path = worktree_dir.path().join("feature");
+    let worktree = worktree_path.to_str().unwrap();
+    git_repo
+        .add_worktree("feature", worktree, Some("main"), false)
+        .expect("Failed to add worktree");
+
+    let assert_same_status = |expected: LocalStatus| {
+        assert_eq!(git_repo.get_local_status(worktree).unwrap(), expected);
+        assert_eq!(gix_repo.get_local_status(worktree).unwrap(), expected);
+    };
+
+    assert_same_status(LocalStatus::Clean);
+
+    fs::write(worktree_path.join("notes.txt"), "wip\n").expect("Failed to write file");
+    assert_same_status(LocalStatus::Dirty);
+
+    let worktree_repo = Repository::open(&worktree_path).expect("Failed to open worktree");
+    let mut index = worktree_repo.index().expect("Failed to get index");
+    index
+        .add_path(Path::new("notes.txt"))
+        .expect("Failed to stage file");
+    index.write().expect("Failed to write index");
+    assert_same_status(LocalStatus::Staged);
+
+    assert_eq!(
+        gix_repo.get_commit_summary(worktree, "feature").unwrap(),
+        git_repo.get_commit_summary(worktree, "feature").unwrap()
+    );
+    assert_eq!(
+        gix_repo.get_head_oid(worktree).unwrap(),
+        git_repo.get_head_oid(worktree).unwrap()
+    );
+}
+
+#[test]
+fn test_remove_worktree_at_recovers_registration_without_branch() {
+    let (_temp_dir, repo_path) = setup_bare_repo_with_commit();
+    let git_repo =
+        GitRepository::new(&repo_path, SystemGitClient).expect("Failed to open repository");
+
+    let worktree_dir = TempDir::new().expect("Failed to create worktree temp dir");
+    let worktree_path = worktree_dir.path().join("orphaned");
+    let worktree = worktree_path.to_str().unwrap();
+    git_repo
+        .add_worktree("orphaned", worktree, Some("main"), false)
+        .expect("Failed to add worktree");
+
+    // Delete the branch and the directory behind gwm's back
+    let bare = Repository::open(&repo_path).expect("Failed to open bare repo");
+    bare.find_reference("refs/heads/orphaned")
+        .expect("Failed to find branch")
+        .delete()
+        .expect("Failed to delete branch");
+    fs::remove_dir_all(&worktree_path).expect("Failed to remove worktree directory");
+
+    assert!(git_repo.remove_worktree("orphaned").is_err());
+    assert!(git_repo.find_registered_worktree(worktree).is_some());
+
+    git_repo
+        .remove_worktree_at(worktree)
+        .expect("Failed to remove worktree by path");
+    assert!(git_repo.find_registered_worktree(worktree).is_none());
+    assert!(git_repo.remove_worktree_at(worktree).is_err());
+}
+
+#[test]
+fn test_prune_worktrees_cleans_up_deleted_directories() {
+    let (_temp_dir, repo_path) = setup_bare_repo_with_commit();
+    let git_repo =
+        GitRepository::new(&repo_path, SystemGitClient).expect("Failed to open repository");
+
+    let worktree_dir = TempDir::new().expect("Failed to create worktree temp dir");
+    let deleted = worktree_dir.path().join("deleted");
+    let kept = worktree_dir.path().join("kept");
+    git_repo
+        .add_worktree("deleted", deleted.to_str().unwrap(), Some("main"), false)
+        .expect("Failed to add worktree");
+    git_repo
+        .add_worktree("kept", kept.to_str().unwrap(), Some("main"), false)
+        .expect("Failed to add worktree");
+    assert!(git_repo.stale_worktrees().unwrap().is_empty());
+
+    fs::remove_dir_all(&deleted).expect("Failed to remove worktree directory");
+    assert_eq!(git_repo.stale_worktrees().unwrap(), vec!["deleted"]);
+
+    assert_eq!(git_repo.prune_worktrees().unwrap(), vec!["deleted"]);
+    assert!(git_repo.stale_worktrees().unwrap().is_empty());
+    assert!(git_repo.find_registered_worktree("kept").is_some());
+
+    // The branch is free to be checked out again
+    git_repo
+        .add_worktree("deleted", deleted.to_str().unwrap(), None, true)
+        .expect("Failed to re-add worktree after pruning");
+}
+
+#[test]
+fn test_attach_worktree_adopts_a_clone_with_its_changes() {
+    let (_temp_dir, repo_path) = setup_bare_repo_with_commit();
+    let git_repo =
+        GitRepository::new(&repo_path, SystemGitClient).expect("Failed to open repository");
+
+    let clone_dir = TempDir::new().expect("Failed to create clone temp dir");
+    let clone_path = clone_dir.path().join("hotfix");
+    let clone = Repository::clone(&repo_path, &clone_path).expect("Failed to clone");
+    let head = clone.head().unwrap().peel_to_commit().unwrap();
+    clone.branch("hotfix", &head, false).unwrap();
+    clone.set_head("refs/heads/hotfix").unwrap();
+    let tip = commit_file(&clone, &head, "refs/heads/hotfix", "fix.txt", "fixed\n");
+    fs::write(clone_path.join("fix.txt"), "fixed\n").expect("Failed to write file");
+    fs::write(clone_path.join("README.md"), "# Edited\n").expect("Failed to edit file");
+    let clone_repo = GitRepository::new(clone_path.to_str().unwrap(), SystemGitClient)
+        .expect("Failed to open clone");
+    assert_eq!(clone_repo.head_branch().as_deref(), Some("hotfix"));
+
+    git_repo
+        .fetch_branch_from(&clone_path, "hotfix")
+        .expect("Failed to fetch branch");
+    assert_eq!(git_repo.branch_tip("hotfix").unwrap(), tip.to_string());
+
+    fs::rename(clone_path.join(".git"), clone_dir.path().join("old.git"))
+        .expect("Failed to move .git aside");
+    git_repo
+        .attach_worktree(&clone_path, "hotfix")
+        .expect("Failed to attach worktree");
+
+    let worktrees = git_repo.list_worktrees().unwrap();
+    assert_eq!(worktrees.len(), 1);
+    assert_eq!(worktrees[0].branch, "hotfix");
+    assert_eq!(
+        fs::canonicalize(&worktrees[0].path).unwrap(),
+        fs::canonicalize(&clone_path).unwrap()
+    );
+    // The edit survives as an uncommitted change against the adopted branch
+    let worktree = Repository::open(&clone_path).expect("Worktree should open");
+    assert_eq!(worktree.head().unwrap().shorthand(), Some("hotfix"));
+    let statuses = worktree.statuses(None).unwrap();
+    assert_eq!(statuses.len(), 1);
+    assert_eq!(statuses.get(0).unwrap().path(), Some("README.md"));
+}
+
+#[test]
+fn test_repair_worktrees_reconnects_a_moved_repository() {
+    let (temp_dir, repo_path) = setup_bare_repo_with_commit();
+    let git_repo =
+        GitRepository::new(&repo_path, SystemGitClient).expect("Failed to open repository");
+    let worktree_dir = TempDir::new().expect("Failed to create worktree temp dir");
+    let worktree_path = worktree_dir.path().join("feature");
+    git_repo
+        .add_worktree(
+            "feature",
+            worktree_path.to_str().unwrap(),
+            Some("main"),
+            false,
+        )
+        .expect("Failed to add worktree");
+
+    let moved_path = temp_dir.path().join("moved-repo");
+    let moved_worktree = worktree_dir.path().join("moved-feature");
+    fs::rename(&repo_path, &moved_path).expect("Failed to move repository");
+    fs::rename(&worktree_path, &moved_worktree).expect("Failed to move worktree");
+    assert!(Repository::open(&moved_worktree).is_err());
+
+    let moved_repo = GitRepository::new(moved_path.to_str().unwrap(), SystemGitClient)
+        .expect("Failed to open moved repository");
+    let repaired = moved_repo
+        .repair_worktrees(std::slice::from_ref(&moved_worktree))
+        .expect("Failed to repair");
+    assert!(!repaired.is_empty());
+
+    let worktree = Repository::open(&moved_worktree).expect("Worktree should open again");
+    assert_eq!(worktree.head().unwrap().shorthand(), Some("feature"));
+    let worktrees = moved_repo.list_worktrees().unwrap();
+    assert_eq!(worktrees[0].branch, "feature");
+    assert_eq!(
+        fs::canonicalize(&worktrees[0].path).unwrap(),
+        fs::canonicalize(&moved_worktree).unwrap()
+    );
+    assert!(moved_repo.repair_worktrees(&[]).unwrap().is_empty());
+}
+
+#[test]
+fn test_cli_backend_manages_worktrees_like_libgit2() {
+    use gwm::git::{CliGitClient, LocalStatus};
+
+    let (_temp_dir, repo_path) = setup_bare_repo_with_commit();
+    let git_repo =
+        GitRepository::new(&repo_path, SystemGitClient).expect("Failed to open repository");
+    let cli_repo = GitRepository::new(&repo_path, CliGitClient).expect("Failed to open repository");
+
+    let worktree_dir = TempDir::new().expect("Failed to create worktree temp dir");
+    let worktree_path = worktree_dir.path().join("feature");
+    let worktree = worktree_path.to_str().unwrap();
+    cli_repo
+        .add_worktree("feature", worktree, Some("main"), false)
+        .expect("Failed to add worktree with git CLI");
+
+    assert!(cli_repo.is_bare().unwrap());
+    let cli_worktrees = cli_repo.list_worktrees().unwrap();
+    let git_worktrees = git_repo.list_worktrees().unwrap();
+    assert_eq!(cli_worktrees.len(), 1);
+    assert_eq!(cli_worktrees[0].branch, "feature");
+    assert_eq!(cli_worktrees[0].branch, git_worktrees[0].branch);
+
+    assert_eq!(
+        cli_repo.get_local_status(worktree).unwrap(),
+        LocalStatus::Clean
+    );
+    fs::write(worktree_path.join("notes.txt"), "wip\n").expect("Failed to write file");
+    assert_eq!(
+        cli_repo.get_local_status(worktree).unwrap(),
+        LocalStatus::Dirty
+    );
+
+    assert_eq!(
+        cli_repo.get_commit_summary(worktree, "feature").unwrap(),
+        "Initial commit"
+    );
+    assert_eq!(
+        cli_repo.get_head_oid(worktree).unwrap(),
+        git_repo.get_head_oid(worktree).unwrap()
+    );
+
+    let existing = cli_repo.add_worktree("feature", "/nonexistent/other", None, false);
+    assert!(existing.unwrap_err().to_string().contains("--reuse"));
+
+    cli_repo
+        .remove_worktree("feature")
+        .expect("Failed to remove worktree with git CLI");
+    assert!(!worktree_path.exists());
+    assert!(cli_repo.list_worktrees().unwrap().is_empty());
+}
+
+#[test]
+fn test_detached_worktrees_are_listed_and_removed_by_path() {
+    use gwm::git::CliGitClient;
+
+    let (_temp_dir, repo_path) = setup_bare_repo_with_commit();
+    let git_repo =
+        GitRepository::new(&repo_path, SystemGitClient).expect("Failed to open repository");
+    let cli_repo = GitRepository::new(&repo_path, CliGitClient).expect("Failed to open repository");
+
+    let worktree_dir = TempDir::new().expect("Failed to create worktree temp dir");
+    let worktree_path = worktree_dir.path().join("bisect");
+    let worktree = worktree_path.to_str().unwrap();
+    let status = std::process::Command::new("git")
+        .args([
+            "-C", &repo_path, "worktree", "add", "--quiet", "--detach", worktree, "main",
+        ])
+        .status()
+        .expect("Failed to run git");
+    assert!(status.success());
+
+    let head = git_repo.get_head_oid(worktree).unwrap();
+    for repo_worktrees in [git_repo.list_worktrees(), cli_repo.list_worktrees()] {
+        let worktrees = repo_worktrees.unwrap();
+        assert_eq!(worktrees.len(), 1);
+        assert_eq!(worktrees[0].branch, format!("(detached {})", &head[..7]));
+        assert_eq!(worktrees[0].detached.as_deref(), Some(head.as_str()));
+    }
+    assert_eq!(
+        git_repo.get_commit_summary(worktree, &head).unwrap(),
+        "Initial commit"
+    );
+
+    git_repo
+        .remove_worktree_at(worktree)
+        .expect("Failed to remove detached worktree by path");
+    assert!(git_repo.list_worktrees().unwrap().is_empty());
+}
+
+#[test]
+fn test_locked_worktrees_report_their_reason_and_survive_prune() {
+    let (_temp_dir, repo_path) = setup_bare_repo_with_commit();
+    let git_repo =
+        GitRepository::new(&repo_path, SystemGitClient).expect("Failed to open repository");
+    let worktree_dir = TempDir::new().expect("Failed to create worktree temp dir");
+    let worktree_path = worktree_dir.path().join("usb");
+    let worktree = worktree_path.to_str().unwrap();
+    git_repo
+        .add_worktree("usb", worktree, Some("main"), false)
+        .expect("Failed to add worktree");
+    assert_eq!(git_repo.worktree_lock(worktree), None);
+
+    git_repo
+        .lock_worktree("usb", Some("on a removable drive"))
+        .expect("Failed to lock worktree");
+    assert_eq!(
+        git_repo.worktree_lock(worktree).as_deref(),
+        Some("on a removable drive")
+    );
+
+    // A locked worktree whose directory is gone is kept, as git does
+    fs::remove_dir_all(&worktree_path).expect("Failed to delete worktree directory");
+    assert!(git_repo.prune_worktrees().unwrap().is_empty());
+    assert!(git_repo.find_registered_worktree("usb").is_some());
+
+    git_repo
+        .unlock_worktree(worktree)
+        .expect("Failed to unlock worktree");
+    assert_eq!(git_repo.worktree_lock(worktree), None);
+    assert_eq!(git_repo.prune_worktrees().unwrap(), vec!["usb".to_string()]);
+}
+
+#[test]
+fn test_operation_in_progress_is_detected() {
+    let (_temp_dir, repo_path) = setup_bare_repo_with_commit();
+    let git_repo =
+        GitRepository::new(&repo_path, SystemGitClient).expect("Failed to open repository");
+    let worktree_dir = TempDir::new().expect("Failed to create worktree temp dir");
+    let worktree_path = worktree_dir.path().join("merging");
+    let worktree = worktree_path.to_str().unwrap();
+    git_repo
+        .add_worktree("merging", worktree, Some("main"), false)
+        .expect("Failed to add worktree");
+    assert_eq!(git_repo.get_operation(worktree), None);
+    // Checking the branch out moved HEAD, which counts as activity
+    assert!(git_repo.get_activity_time(worktree).is_some());
+
+    // What `git merge` leaves behind when it stops for conflicts
+    let head = git_repo.get_head_oid(worktree).unwrap();
+    let worktree_git_dir = Repository::open(worktree).unwrap().path().to_path_buf();
+    fs::write(worktree_git_dir.join("MERGE_HEAD"), format!("{}\n", head))
+        .expect("Failed to write MERGE_HEAD");
+    assert_eq!(git_repo.get_operation(worktree), Some(Operation::Merging));
+
+    fs::create_dir_all(worktree_git_dir.join("rebase-merge")).unwrap();
+    fs::remove_file(worktree_git_dir.join("MERGE_HEAD")).unwrap();
+    assert_eq!(git_repo.get_operation(worktree), Some(Operation::Rebasing));
+}
+
+#[test]
+fn test_merge_conflicts_are_reported_as_conflicted() {
+    use gwm::git::{CliGitClient, LocalStatus};
+
+    let (_temp_dir, repo_path) = setup_bare_repo_with_commit();
+    let repo = Repository::open(&repo_path).expect("Failed to open repo");
+    let root = repo.head().unwrap().peel_to_commit().unwrap();
+    commit_file(&repo, &root, "refs/heads/left", "notes.txt", "left\n");
+    commit_file(&repo, &root, "refs/heads/right", "notes.txt", "right\n");
+
+    let git_repo =
+        GitRepository::new(&repo_path, SystemGitClient).expect("Failed to open repository");
+    let cli_repo = GitRepository::new(&repo_path, CliGitClient).expect("Failed to open repository");
+    let worktree_dir = TempDir::new().expect("Failed to create worktree temp dir");
+    let worktree_path = worktree_dir.path().join("left");
+    let worktree = worktree_path.to_str().unwrap();
+    git_repo
+        .add_worktree("left", worktree, None, true)
+        .expect("Failed to add worktree");
+
+    let status = std::process::Command::new("git")
+        .args([
+            "-C",
+            worktree,
+            "-c",
+            "user.name=Test User",
+            "-c",
+            "user.email=test@example.com",
+        ])
+        .args(["merge", "--quiet", "right"])
+        .stdout(std::process::Stdio::null())
+        .status()
+        .expect("Failed to run git");
+    assert!(!status.success(), "merge should stop for conflicts");
+
+    for local_status in [
+        git_repo.get_local_status(worktree),
+        cli_repo.get_local_status(worktree),
+    ] {
+        assert_eq!(local_status.unwrap(), LocalStatus::Conflicted);
+    }
+    assert_eq!(git_repo.get_operation(worktree), Some(Operation::Merging));
+}
+
+#[test]
+fn test_trash_restores_removed_worktree_with_local_changes() {
+    let (_temp_dir, repo_path) = setup_bare_repo_with_commit();
+    let git_repo =
+        GitRepository::new(&repo_path, SystemGitClient).expect("Failed to open repository");
+    let worktree_dir = TempDir::new().expect("Failed to create worktree temp dir");
+    let worktree_path = worktree_dir.path().join("feature");
+    git_repo
+        .add_worktree(
+            "feature",
+            worktree_path.to_str().unwrap(),
+            Some("main"),
+            false,
+        )
+        .expect("Failed to add worktree");
+    fs::write(worktree_path.join("README.md"), "# Edited\n").expect("Failed to edit file");
+    fs::write(worktree_path.join("notes.txt"), "untracked\n").expect("Failed to write file");
+
+    let trash_dir = TempDir::new().expect("Failed to create trash dir");
+    let trash = Trash::new(trash_dir.path());
+    let entry = trash
+        .remove_worktree(
+            Path::new(&repo_path),
+            "test-repo",
+            "feature",
+            &worktree_path,
+        )
+        .expect("Failed to trash worktree");
+    assert!(!worktree_path.exists());
+    assert!(entry.trash_path.join("notes.txt").exists());
+    assert!(git_repo.list_worktrees().unwrap().is_empty());
+
+    // The branch is deleted after removal; undo brings it back at the recorded commit
+    git_repo
+        .delete_branch("feature")
+        .expect("Failed to delete branch");
+    let restored = trash
+        .restore_last()
+        .expect("Failed to restore worktree")
+        .expect("Trash should have an entry");
+    assert_eq!(restored, entry);
+    assert_eq!(git_repo.branch_tip("feature").unwrap(), entry.oid);
+    assert_eq!(
+        fs::read_to_string(worktree_path.join("README.md")).unwrap(),
+        "# Edited\n"
+    );
+    assert!(worktree_path.join("notes.txt").exists());
+    let worktree_repo = Repository::open(&worktree_path).expect("Worktree should open");
+    assert_eq!(worktree_repo.head().unwrap().shorthand(), Some("feature"));
+    assert!(!entry.trash_path.exists());
+    assert_eq!(trash.last().unwrap(), None);
+}
+
+#[test]
+fn test_trash_keeps_staged_changes_nested_branches_and_detached_heads() {
+    let (_temp_dir, repo_path) = setup_bare_repo_with_commit();
+    let git_repo =
+        GitRepository::new(&repo_path, SystemGitClient).expect("Failed to open repository");
+    let worktree_dir = TempDir::new().expect("Failed to create worktree temp dir");
+    let trash_dir = TempDir::new().expect("Failed to create trash dir");
+    let trash = Trash::new(trash_dir.path());
+
+    // A branch name with a slash and a staged change
+    let oid = git_repo.branch_tip("main").unwrap();
+    let nested_path = worktree_dir.path().join("feature-nested");
+    git_repo.create_branch_at("feature/nested", &oid).unwrap();
+    git_repo
+        .add_worktree("feature/nested", nested_path.to_str().unwrap(), None, true)
+        .expect("Failed to add worktree");
+    fs::write(nested_path.join("staged.txt"), "staged\n").expect("Failed to write file");
+    let worktree_repo = Repository::open(&nested_path).expect("Worktree should open");
+    let mut index = worktree_repo.index().unwrap();
+    index.add_path(Path::new("staged.txt")).unwrap();
+    index.write().unwrap();
+
+    let entry = trash
+        .remove_worktree(
+            Path::new(&repo_path),
+            "test-repo",
+            "feature/nested",
+            &nested_path,
+        )
+        .expect("Failed to trash worktree");
+    assert_eq!(
+        entry.trash_path.parent(),
+        Some(trash_dir.path().join("test-repo").as_path())
+    );
+    trash
+        .restore_last()
+        .expect("Failed to restore worktree")
+        .expect("Trash should have an entry");
+    let worktree_repo = Repository::open(&nested_path).expect("Worktree should open");
+    let staged = worktree_repo
+        .index()
+        .unwrap()
+        .get_path(Path::new("staged.txt"), 0);
+    assert!(staged.is_some(), "the staged file should still be staged");
+    assert!(!entry.index_path.unwrap().exists());
+
+    // A detached HEAD comes back detached at the same commit
+    let detached_path = worktree_dir.path().join("bisect");
+    git_repo
+        .add_detached_worktree(detached_path.to_str().unwrap(), &oid)
+        .expect("Failed to add detached worktree");
+    let entry = trash
+        .remove_worktree(
+            Path::new(&repo_path),
+            "test-repo",
+            &format!("(detached {})", &oid[..7]),
+            &detached_path,
+        )
+        .expect("Failed to trash detached worktree");
+    assert_eq!(entry.oid, oid);
+    trash
+        .restore_last()
+        .expect("Failed to restore worktree")
+        .expect("Trash should have an entry");
+    let worktree_repo = Repository::open(&detached_path).expect("Worktree should open");
+    assert!(worktree_repo.head_detached().unwrap());
+    assert_eq!(
+        worktree_repo.head().unwrap().target().unwrap().to_string(),
+        oid
+    );
+}
+
+/// Commit a file on top of `parent` in a bare repository, moving `update_ref` to the new commit
+fn commit_file(
+    repo: &Repository,
+    parent: &git2::Commit,
+    update_ref: &str,
+    file: &str,
+    content: &str,
+) -> git2::Oid {
+    let blob = repo.blob(content.as_bytes()).expect("Failed to write blob");
+    let mut tree = repo
+        .treebuilder(Some(&parent.tree().unwrap()))
+        .expect("Failed to build tree");
+    tree.insert(file, blob, 0o100644)
+        .expect("Failed to insert file");
+    let tree = repo.find_tree(tree.write().unwrap()).unwrap();
+    let signature =
+        Signature::now("Test User", "test@example.com").expect("Failed to create signature");
+    repo.commit(
+        Some(update_ref),
+        &signature,
+        &signature,
+        file,
+        &tree,
+        &[parent],
+    )
+    .expect("Failed to commit")
+}
+
+#[test]
+fn test_is_merged_into_detects_squash_merges() {
+    let (_temp_dir, repo_path) = setup_bare_repo_with_commit();
+    let repo = Repository::open(&repo_path).expect("Failed to open repo");
+    let root = repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .expect("Failed to find HEAD commit");
+    repo.branch("old", &root, false)
+        .expect("Failed to create branch");
+
+    // Two commits on the feature branch, squashed into one on main after unrelated work
+    repo.branch("feature", &root, false)
+        .expect("Failed to create branch");
+    let first = commit_file(&repo, &root, "refs/heads/feature", "a.txt", "one\n");
+    let first = repo.find_commit(first).unwrap();
+    commit_file(&repo, &first, "refs/heads/feature", "a.txt", "one\ntwo\n");
+    let other = commit_file(&repo, &root, "refs/heads/main", "other.txt", "other\n");
+    let other = repo.find_commit(other).unwrap();
+    commit_file(&repo, &other, "refs/heads/main", "a.txt", "one\ntwo\n");
+
+    repo.branch("unmerged", &root, false)
+        .expect("Failed to create branch");
+    commit_file(&repo, &root, "refs/heads/unmerged", "b.txt", "b\n");
+
+    let git_repo =
+        GitRepository::new(&repo_path, SystemGitClient).expect("Failed to open repository");
+    assert!(git_repo.is_merged_into("feature", "main").unwrap());
+    assert!(git_repo.is_merged_into("old", "main").unwrap());
+    assert!(!git_repo.is_merged_into("unmerged", "main").unwrap());
+}
+
+#[test]
+fn test_diff_stat_counts_changes_since_the_fork_point() {
+    let (_temp_dir, repo_path) = setup_bare_repo_with_commit();
+    let repo = Repository::open(&repo_path).expect("Failed to open repo");
+    let root = repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .expect("Failed to find HEAD commit");
+
+    repo.branch("feature", &root, false)
+        .expect("Failed to create branch");
+    let first = commit_file(&repo, &root, "refs/heads/feature", "a.txt", "one\ntwo\n");
+    let first = repo.find_commit(first).unwrap();
+    commit_file(&repo, &first, "refs/heads/feature", "b.txt", "b\n");
+    // Work landing on main after the fork doesn't count towards the branch
+    commit_file(&repo, &root, "refs/heads/main", "other.txt", "other\n");
+
+    let git_repo =
+        GitRepository::new(&repo_path, SystemGitClient).expect("Failed to open repository");
+    assert_eq!(git_repo.base_branch().as_deref(), Some("main"));
+    assert_eq!(
+        git_repo.diff_stat("feature", "main").unwrap(),
+        DiffStat {
+            files_changed: 2,
+            insertions: 3,
+            deletions: 0,
+        }
+    );
+    assert_eq!(
+        git_repo.diff_stat("main", "main").unwrap(),
+        DiffStat::default()
+    );
+}
+
+#[tokio::test]
+async fn test_scan_skips_repositories_that_fail() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    Repository::init(temp_dir.path().join("good")).expect("Failed to init repo");
+    // Looks like a repository to discovery, but git can't open it
+    fs::create_dir_all(temp_dir.path().join("broken").join(".git")).unwrap();
+
+    let repos = scan::scan(temp_dir.path(), &ScanOptions::new().pr_status(false))
+        .await
+        .expect("One broken repository shouldn't fail the scan");
+    let names: Vec<_> = repos.iter().map(|repo| repo.name.as_str()).collect();
+    assert_eq!(names, vec!["good"]);
+}
+
+#[test]
+fn test_prompt_status_describes_the_worktree_of_a_directory() {
+    let (temp_dir, repo_path) = setup_bare_repo_with_commit();
+    // gwm repositories keep the bare repository in `<name>/.git`
+    let repo_dir = temp_dir.path().join("proj");
+    fs::create_dir(&repo_dir).unwrap();
+    fs::rename(&repo_path, repo_dir.join(".git")).expect("Failed to move repository");
+    let git_repo = GitRepository::new(repo_dir.join(".git").to_str().unwrap(), SystemGitClient)
+        .expect("Failed to open repository");
+    let worktree_path = repo_dir.join("feature");
+    git_repo
+        .add_worktree(
+            "feature",
+            worktree_path.to_str().unwrap(),
+            Some("main"),
+            false,
+        )
+        .expect("Failed to add worktree");
+
+    // Track main and fall one commit behind it, with an edit in the worktree
+    let repo = Repository::open(repo_dir.join(".git")).unwrap();
+    repo.find_branch("feature", git2::BranchType::Local)
+        .unwrap()
+        .set_upstream(Some("main"))
+        .unwrap();
+    let head = repo.find_branch("main", git2::BranchType::Local).unwrap();
+    commit_file(
+        &repo,
+        &head.get().peel_to_commit().unwrap(),
+        "refs/heads/main",
+        "upstream.txt",
+        "upstream",
+    );
+    fs::write(worktree_path.join("README.md"), "edited").unwrap();
+    fs::create_dir(worktree_path.join("src")).unwrap();
+
+    let cache = std::sync::Mutex::new(gwm::cache::StatusCache::default());
+    let status = scan::prompt_status(&worktree_path.join("src"), Backend::Libgit2, Some(&cache))
+        .expect("Failed to get prompt status")
+        .expect("Directory should belong to a worktree");
+    assert_eq!(status.text(), "proj@feature (dirty, behind 1)");
+    assert_eq!(status.ahead, Some(0));
+    assert!(cache.lock().unwrap().is_changed());
+
+    let outside = TempDir::new().unwrap();
+    assert!(
+        scan::prompt_status(outside.path(), Backend::Libgit2, None)
+            .unwrap()
+            .is_none()
+    );
+}